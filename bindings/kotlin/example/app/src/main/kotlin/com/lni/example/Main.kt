@@ -187,7 +187,7 @@ suspend fun blinkExample() {
         println("Decoding invoice...")
         val testInvoice = "lnbc1..."  // Replace with a real invoice
         val decoded = node.decode(testInvoice)
-        println("  Decoded: ${decoded.take(100)}...")
+        println("  Decoded: ${decoded.amountMsats} msats, hash ${decoded.paymentHash}")
         
     } catch (e: ApiException) {
         println("  API Error: ${e.message}")
@@ -207,7 +207,7 @@ suspend fun payInvoiceExample(node: StrikeNode, invoice: String) {
         invoice = invoice,
         feeLimitMsat = 1000L,  // Max 1 sat fee
         feeLimitPercentage = null,
-        timeoutSeconds = 60L,
+        timeoutSeconds = null,  // Strike has no payment timeout
         amountMsats = null,  // Use amount from invoice
         maxParts = null,
         firstHopPubkey = null,
@@ -264,8 +264,8 @@ suspend fun onInvoiceEventsExample(node: StrikeNode, paymentHash: String) {
     )
     
     // This will poll for invoice status and call the appropriate callback method
-    node.onInvoiceEvents(params, callback)
-    println("  Invoice event monitoring complete.")
+    val outcome = node.onInvoiceEvents(params, callback).wait()
+    println("  Invoice event monitoring complete: $outcome")
 }
 
 /**
@@ -862,7 +862,7 @@ suspend fun createAndMonitorInvoice(
             maxPollingSec = 300L // 5 minutes timeout
         )
 
-        node.onInvoiceEvents(params, callback)
+        node.onInvoiceEvents(params, callback).wait()
 
     } catch (e: ApiException) {
         withContext(Dispatchers.Main) {
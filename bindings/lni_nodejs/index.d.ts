@@ -119,8 +119,22 @@ export interface NodeInfo {
   pendingOpenSendBalance: number
   pendingOpenReceiveBalance: number
 }
+/** Lifecycle state of an invoice or payment, normalized across backends */
+export const enum TransactionStatus {
+  Pending = 'Pending',
+  Settled = 'Settled',
+  Failed = 'Failed',
+  Expired = 'Expired',
+  Cancelled = 'Cancelled'
+}
+export const enum Direction {
+  Incoming = 'Incoming',
+  Outgoing = 'Outgoing'
+}
 export interface Transaction {
   type: string
+  status: TransactionStatus
+  direction: Direction
   invoice: string
   description: string
   descriptionHash: string
@@ -134,6 +148,55 @@ export interface Transaction {
   payerNote?: string
   externalId?: string
 }
+export interface DecodedPaymentRequest {
+  invoiceType: InvoiceType
+  invoice: string
+  network: string
+  amountMsats?: number
+  paymentHash: string
+  paymentSecret?: string
+  description?: string
+  descriptionHash?: string
+  payeePubkey: string
+  createdAt: number
+  expiry: number
+  expiresAt: number
+  minFinalCltvExpiryDelta: number
+  routeHints: Array<RouteHint>
+  features: Array<number>
+  payerNote?: string
+  offer?: Bolt12Offer
+}
+export interface Bolt12Offer {
+  offer: string
+  network: string
+  amountMsats?: number
+  amount?: number
+  currency?: string
+  description?: string
+  issuer?: string
+  absoluteExpiry?: number
+  quantityMax?: number
+  issuerId?: string
+  paths: Array<BlindedPath>
+  features: Array<number>
+}
+export interface BlindedPath {
+  introductionNode: string
+  numHops: number
+}
+export interface RouteHint {
+  hops: Array<RouteHintHop>
+}
+export interface RouteHintHop {
+  pubkey: string
+  shortChannelId: string
+  feeBaseMsat: number
+  feeProportionalMillionths: number
+  cltvExpiryDelta: number
+  htlcMinimumMsat?: number
+  htlcMaximumMsat?: number
+}
 export interface NodeConnectionInfo {
   pubkey: string
   address: string
@@ -143,7 +206,9 @@ export interface Channel {
   localBalance: number
   localSpendableBalance: number
   remoteBalance: number
+  capacity: number
   id: string
+  shortChannelId?: string
   remotePubkey: string
   fundingTxId: string
   fundingTxVout: number
@@ -171,23 +236,34 @@ export interface OpenChannelRequest {
   pubkey: string
   amountMsats: number
   public: boolean
+  host?: string
+  pushMsats?: number
+  satPerVbyte?: number
 }
 export interface OpenChannelResponse {
   fundingTxId: string
+  fundingTxVout: number
+  channelId?: string
 }
 export interface CloseChannelRequest {
   channelId: string
   nodeId: string
   force: boolean
+  address?: string
+  satPerVbyte?: number
 }
 export interface UpdateChannelRequest {
   channelId: string
   nodeId: string
   forwardingFeeBaseMsat: number
   maxDustHtlcExposureFromFeeRateMultiplier: number
+  feeRatePpm?: number
+  cltvExpiryDelta?: number
+  minHtlcMsat?: number
+  maxHtlcMsat?: number
 }
 export interface CloseChannelResponse {
-  
+  closingTxId?: string
 }
 export interface PendingBalanceDetails {
   channelId: string
@@ -204,6 +280,21 @@ export interface OnchainBalanceResponse {
   pendingBalancesDetails: Array<PendingBalanceDetails>
   internalBalances: string
 }
+export interface OnchainTransaction {
+  txid: string
+  direction: Direction
+  amountSats: number
+  feeSats: number
+  confirmations: number
+  blockHeight: number
+  createdAt: number
+  address?: string
+}
+export interface SendOnchainResponse {
+  txid?: string
+  externalId?: string
+  feeSats?: number
+}
 export interface PeerDetails {
   nodeId: string
   address: string
@@ -222,9 +313,90 @@ export interface PayInvoiceResponse {
   paymentHash: string
   preimage: string
   feeMsats: number
+  status: TransactionStatus
+}
+/** Options for `LightningNode::pay`, the fee and timeout fields work as in `PayInvoiceParams` */
+export interface PayOptions {
+  feeLimitMsat?: number
+  feeLimitPercentage?: number
+  timeoutSeconds?: number
+  payerNote?: string
+  payerData?: PayerData
+  wait?: boolean
+}
+/** Result of `LightningNode::pay`, the same whatever the destination was */
+export interface PayResponse {
+  destinationType: string
+  invoice: string
+  paymentHash: string
+  preimage: string
+  feeMsats: number
+  status: TransactionStatus
+  successAction?: SuccessAction
+}
+/** The NIP-57 zap request `LightningNode::zap` signs and sends along with the payment */
+export interface ZapRequestParams {
+  recipientPubkey: string
+  relays: Array<string>
+  comment?: string
+  eventId?: string
+  eventCoordinate?: string
+  secretKey?: string
+}
+/** Result of `LightningNode::zap` */
+export interface ZapResponse {
+  zapRequest: string
+  zapperPubkey: string
+  payment: PayResponse
+}
+/** Payer details to send when a service asks for them (LUD-18), fields it didn't ask for are not sent */
+export interface PayerData {
+  name?: string
+  pubkey?: string
+  identifier?: string
+  email?: string
+}
+/** What to show the payer once an LNURL payment went through */
+export interface SuccessAction {
+  tag: string
+  message?: string
+  description?: string
+  url?: string
+}
+/** A login the service accepted */
+export interface LnurlAuthResponse {
+  domain: string
+  linkingKey: string
+  action?: string
+}
+/**
+ * Fee the node expects to pay for an invoice, see `LightningNode::estimate_payment_fee`.
+ * Every backend quotes a single fee for now, so this is a point estimate with min == max.
+ */
+export interface PaymentFeeEstimate {
+  minFeeMsats: number
+  maxFeeMsats: number
+  routeFound: boolean
+}
+/**
+ * An outgoing payment as the node reports it right now, see `LightningNode::lookup_payment`.
+ * Use it to find out how a `pay_invoice` that timed out or errored actually ended.
+ */
+export interface OutgoingPayment {
+  paymentHash: string
+  status: TransactionStatus
+  amountMsats: number
+  feeMsats: number
+  preimage: string
+  failureReason?: string
+  invoice: string
+  createdAt: number
+  settledAt: number
 }
 export interface PayKeysendResponse {
-  fee: number
+  paymentHash: string
+  preimage: string
+  feeMsats: number
 }
 export interface BalancesResponse {
   onchain: OnchainBalanceResponse
@@ -239,6 +411,13 @@ export interface ListTransactionsParams {
   limit: number
   paymentHash?: string
   search?: string
+  createdAfter?: number
+  createdBefore?: number
+  cursor?: string
+}
+export interface TransactionPage {
+  transactions: Array<Transaction>
+  nextCursor?: string
 }
 export interface LookupInvoiceParams {
   paymentHash?: string
@@ -271,6 +450,24 @@ export interface CreateOfferParams {
   description?: string
   amountMsats?: number
 }
+export interface NodeCapabilities {
+  bolt11Receive: boolean
+  bolt11Send: boolean
+  bolt12Receive: boolean
+  bolt12Send: boolean
+  keysend: boolean
+  onchainReceive: boolean
+  onchainSend: boolean
+  onchainBalance: boolean
+  holdInvoices: boolean
+  descriptionHashInvoices: boolean
+  zeroAmountInvoices: boolean
+  lookupInvoice: boolean
+  listTransactions: boolean
+  channelManagement: boolean
+  nativeEventStreaming: boolean
+  fiat: boolean
+}
 export interface PayInvoiceParams {
   invoice: string
   feeLimitMsat?: number
@@ -282,6 +479,8 @@ export interface PayInvoiceParams {
   lastHopPubkey?: string
   allowSelfPayment?: boolean
   isAmp?: boolean
+  customRecords?: Array<TlvRecord>
+  wait?: boolean
 }
 export interface OnInvoiceEventParams {
   paymentHash?: string
@@ -289,6 +488,36 @@ export interface OnInvoiceEventParams {
   pollingDelaySec: number
   maxPollingSec: number
 }
+/** How a subscription ended, the callbacks already carried the details */
+export const enum SubscriptionOutcome {
+  Success = 'Success',
+  Failure = 'Failure',
+  Cancelled = 'Cancelled',
+  TimedOut = 'TimedOut'
+}
+export interface OnPaymentEventParams {
+  paymentHash: string
+  pollingDelaySec: number
+  maxPollingSec: number
+}
+export interface OnAccountEventParams {
+  pollingDelaySec: number
+  maxPollingSec: number
+}
+export const enum AccountEventType {
+  InvoiceSettled = 'InvoiceSettled',
+  PaymentSent = 'PaymentSent',
+  PaymentFailed = 'PaymentFailed'
+}
+export interface AccountEvent {
+  eventType: AccountEventType
+  transaction: Transaction
+  resumeIndex: string
+}
+export interface SubscribeAllParams {
+  resumeFrom?: string
+  pollingDelaySec: number
+}
 export interface Payment {
   paymentId: string
   circId: string
@@ -308,7 +537,7 @@ export interface PaymentInfo {
 }
 /**
  * Check what type of payment destination this is
- * Returns: "bolt11", "bolt12", "lnurl", or "lightning_address"
+ * Returns: "bolt11", "bolt12", "lnurl", "lnurl_withdraw", or "lightning_address"
  */
 export declare function detectPaymentType(destination: string): string
 /**
@@ -329,6 +558,10 @@ export declare function resolveToBolt11(destination: string, amountMsats?: numbe
  * Fetches LNURL metadata if needed to get min/max amounts
  */
 export declare function getPaymentInfo(destination: string, amountMsats?: number | undefined | null): Promise<PaymentInfo>
+/** Log in with an LNURL-auth link, the linking key is derived from a BIP39 mnemonic (LUD-05) */
+export declare function lnurlAuthWithMnemonic(lnurl: string, mnemonic: string, passphrase?: string | undefined | null): Promise<LnurlAuthResponse>
+/** Log in with an LNURL-auth link, the linking key is derived from `signMessage` (LUD-13) */
+export declare function lnurlAuthWithSigner(lnurl: string, signMessage: (message: string) => Promise<string>): Promise<LnurlAuthResponse>
 /**
  * Generate a BIP39 mnemonic phrase
  *
@@ -336,6 +569,10 @@ export declare function getPaymentInfo(destination: string, amountMsats?: number
  * @returns A space-separated mnemonic phrase
  */
 export declare function generateMnemonic(wordCount?: number | undefined | null): string
+/** Decode a BOLT11 invoice, BOLT12 offer or BOLT12 invoice locally, without a node */
+export declare function decodePaymentRequest(input: string): DecodedPaymentRequest
+/** Decode a BOLT12 offer locally, without a node */
+export declare function decodeOffer(offer: string): Bolt12Offer
 export declare function sayAfterWithTokio(ms: number, who: string, url: string, socks5Proxy?: string | undefined | null, headerKey?: string | undefined | null, headerValue?: string | undefined | null): Promise<string>
 export declare class PhoenixdNode {
   constructor(config: PhoenixdConfig)
@@ -343,14 +580,31 @@ export declare class PhoenixdNode {
   getPassword(): string
   getConfig(): PhoenixdConfig
   getInfo(): Promise<NodeInfo>
+  capabilities(): Promise<NodeCapabilities>
   createInvoice(params: CreateInvoiceParams): Promise<Transaction>
   payInvoice(params: PayInvoiceParams): Promise<PayInvoiceResponse>
+  estimatePaymentFee(invoice: string, amountMsats?: number | undefined | null): Promise<PaymentFeeEstimate>
+  pay(destination: string, amountMsats?: number | undefined | null, options?: PayOptions | undefined | null): Promise<PayResponse>
+  zap(destination: string, amountMsats: number, params: ZapRequestParams): Promise<ZapResponse>
   createOffer(params: CreateOfferParams): Promise<Offer>
   getOffer(): Promise<Offer>
   lookupInvoice(params: LookupInvoiceParams): Promise<Transaction>
+  lookupPayment(paymentHash: string): Promise<OutgoingPayment>
   payOffer(offer: string, amountMsats: number, payerNote?: string | undefined | null): Promise<PayInvoiceResponse>
   listTransactions(params: ListTransactionsParams): Promise<Array<Transaction>>
-  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): void
+  listTransactionsPage(params: ListTransactionsParams): Promise<TransactionPage>
+  decode(str: string): DecodedPaymentRequest
+  listChannels(): Promise<Array<Channel>>
+  openChannel(params: OpenChannelRequest): Promise<OpenChannelResponse>
+  closeChannel(params: CloseChannelRequest): Promise<CloseChannelResponse>
+  updateChannelPolicy(params: UpdateChannelRequest): Promise<void>
+  getOnchainBalance(): Promise<OnchainBalanceResponse>
+  newOnchainAddress(): Promise<string>
+  sendOnchain(address: string, amountSats: number, satPerVbyte?: number | undefined | null): Promise<SendOnchainResponse>
+  listOnchainTransactions(): Promise<Array<OnchainTransaction>>
+  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): Subscription
+  onPaymentEvents(params: OnPaymentEventParams, callback: (arg0: string, arg1?: OutgoingPayment | undefined | null) => void): Subscription
+  subscribeAll(params: SubscribeAllParams, callback: (err: Error | null, arg: AccountEvent) => void): Subscription
 }
 export declare class ClnNode {
   constructor(config: ClnConfig)
@@ -358,16 +612,34 @@ export declare class ClnNode {
   getRune(): string
   getConfig(): ClnConfig
   getInfo(): Promise<NodeInfo>
+  capabilities(): Promise<NodeCapabilities>
   createInvoice(params: CreateInvoiceParams): Promise<Transaction>
   payInvoice(params: PayInvoiceParams): Promise<PayInvoiceResponse>
+  estimatePaymentFee(invoice: string, amountMsats?: number | undefined | null): Promise<PaymentFeeEstimate>
+  signMessage(message: string): Promise<string>
+  pay(destination: string, amountMsats?: number | undefined | null, options?: PayOptions | undefined | null): Promise<PayResponse>
+  zap(destination: string, amountMsats: number, params: ZapRequestParams): Promise<ZapResponse>
+  payKeysend(destinationPubkey: string, amountMsats: number, customRecords: Array<TlvRecord>): Promise<PayKeysendResponse>
   createOffer(params: CreateOfferParams): Promise<Offer>
   getOffer(search?: string | undefined | null): Promise<Offer>
   listOffers(search?: string | undefined | null): Promise<Array<Offer>>
   payOffer(offer: string, amountMsats: number, payerNote?: string | undefined | null): Promise<PayInvoiceResponse>
   lookupInvoice(params: LookupInvoiceParams): Promise<Transaction>
+  lookupPayment(paymentHash: string): Promise<OutgoingPayment>
   listTransactions(params: ListTransactionsParams): Promise<Array<Transaction>>
-  decode(str: string): Promise<string>
-  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): void
+  listTransactionsPage(params: ListTransactionsParams): Promise<TransactionPage>
+  decode(str: string): Promise<DecodedPaymentRequest>
+  listChannels(): Promise<Array<Channel>>
+  openChannel(params: OpenChannelRequest): Promise<OpenChannelResponse>
+  closeChannel(params: CloseChannelRequest): Promise<CloseChannelResponse>
+  updateChannelPolicy(params: UpdateChannelRequest): Promise<void>
+  getOnchainBalance(): Promise<OnchainBalanceResponse>
+  newOnchainAddress(): Promise<string>
+  sendOnchain(address: string, amountSats: number, satPerVbyte?: number | undefined | null): Promise<SendOnchainResponse>
+  listOnchainTransactions(): Promise<Array<OnchainTransaction>>
+  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): Subscription
+  onPaymentEvents(params: OnPaymentEventParams, callback: (arg0: string, arg1?: OutgoingPayment | undefined | null) => void): Subscription
+  subscribeAll(params: SubscribeAllParams, callback: (err: Error | null, arg: AccountEvent) => void): Subscription
 }
 export declare class LndNode {
   constructor(config: LndConfig)
@@ -379,12 +651,30 @@ export declare class LndNode {
   listOffers(search?: string | undefined | null): Promise<Array<Offer>>
   payOffer(offer: string, amountMsats: number, payerNote?: string | undefined | null): Promise<PayInvoiceResponse>
   getInfo(): Promise<NodeInfo>
+  capabilities(): Promise<NodeCapabilities>
   createInvoice(params: CreateInvoiceParams): Promise<Transaction>
   payInvoice(params: PayInvoiceParams): Promise<PayInvoiceResponse>
+  estimatePaymentFee(invoice: string, amountMsats?: number | undefined | null): Promise<PaymentFeeEstimate>
+  signMessage(message: string): Promise<string>
+  pay(destination: string, amountMsats?: number | undefined | null, options?: PayOptions | undefined | null): Promise<PayResponse>
+  zap(destination: string, amountMsats: number, params: ZapRequestParams): Promise<ZapResponse>
+  payKeysend(destinationPubkey: string, amountMsats: number, customRecords: Array<TlvRecord>): Promise<PayKeysendResponse>
   lookupInvoice(params: LookupInvoiceParams): Promise<Transaction>
+  lookupPayment(paymentHash: string): Promise<OutgoingPayment>
   listTransactions(params: ListTransactionsParams): Promise<Array<Transaction>>
-  decode(invoiceStr: string): Promise<string>
-  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): void
+  listTransactionsPage(params: ListTransactionsParams): Promise<TransactionPage>
+  decode(invoiceStr: string): Promise<DecodedPaymentRequest>
+  listChannels(): Promise<Array<Channel>>
+  openChannel(params: OpenChannelRequest): Promise<OpenChannelResponse>
+  closeChannel(params: CloseChannelRequest): Promise<CloseChannelResponse>
+  updateChannelPolicy(params: UpdateChannelRequest): Promise<void>
+  getOnchainBalance(): Promise<OnchainBalanceResponse>
+  newOnchainAddress(): Promise<string>
+  sendOnchain(address: string, amountSats: number, satPerVbyte?: number | undefined | null): Promise<SendOnchainResponse>
+  listOnchainTransactions(): Promise<Array<OnchainTransaction>>
+  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): Subscription
+  onPaymentEvents(params: OnPaymentEventParams, callback: (arg0: string, arg1?: OutgoingPayment | undefined | null) => void): Subscription
+  subscribeAll(params: SubscribeAllParams, callback: (err: Error | null, arg: AccountEvent) => void): Subscription
   getOfferAsync(search?: string | undefined | null): Promise<Offer>
   listOffersAsync(search?: string | undefined | null): Promise<Array<Offer>>
   payOfferAsync(offer: string, amountMsats: number, payerNote?: string | undefined | null): Promise<PayInvoiceResponse>
@@ -397,16 +687,28 @@ export declare class BlinkNode {
   getApiKey(): string
   getConfig(): BlinkConfig
   getInfo(): Promise<NodeInfo>
+  capabilities(): Promise<NodeCapabilities>
   createInvoice(params: CreateInvoiceParams): Promise<Transaction>
   payInvoice(params: PayInvoiceParams): Promise<PayInvoiceResponse>
+  estimatePaymentFee(invoice: string, amountMsats?: number | undefined | null): Promise<PaymentFeeEstimate>
+  pay(destination: string, amountMsats?: number | undefined | null, options?: PayOptions | undefined | null): Promise<PayResponse>
+  zap(destination: string, amountMsats: number, params: ZapRequestParams): Promise<ZapResponse>
   createOffer(params: CreateOfferParams): Promise<Offer>
   getOffer(search?: string | undefined | null): Promise<Offer>
   listOffers(search?: string | undefined | null): Promise<Array<Offer>>
   lookupInvoice(params: LookupInvoiceParams): Promise<Transaction>
+  lookupPayment(paymentHash: string): Promise<OutgoingPayment>
   payOffer(offer: string, amountMsats: number, payerNote?: string | undefined | null): Promise<PayInvoiceResponse>
   listTransactions(params: ListTransactionsParams): Promise<Array<Transaction>>
-  decode(str: string): Promise<string>
-  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): void
+  listTransactionsPage(params: ListTransactionsParams): Promise<TransactionPage>
+  decode(str: string): Promise<DecodedPaymentRequest>
+  getOnchainBalance(): Promise<OnchainBalanceResponse>
+  newOnchainAddress(): Promise<string>
+  sendOnchain(address: string, amountSats: number, satPerVbyte?: number | undefined | null): Promise<SendOnchainResponse>
+  listOnchainTransactions(): Promise<Array<OnchainTransaction>>
+  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): Subscription
+  onPaymentEvents(params: OnPaymentEventParams, callback: (arg0: string, arg1?: OutgoingPayment | undefined | null) => void): Subscription
+  subscribeAll(params: SubscribeAllParams, callback: (err: Error | null, arg: AccountEvent) => void): Subscription
 }
 export declare class NwcNode {
   constructor(config: NwcConfig)
@@ -414,16 +716,25 @@ export declare class NwcNode {
   getSocks5Proxy(): string | null
   getConfig(): NwcConfig
   getInfo(): Promise<NodeInfo>
+  capabilities(): Promise<NodeCapabilities>
   createInvoice(params: CreateInvoiceParams): Promise<Transaction>
   payInvoice(params: PayInvoiceParams): Promise<PayInvoiceResponse>
+  estimatePaymentFee(invoice: string, amountMsats?: number | undefined | null): Promise<PaymentFeeEstimate>
+  pay(destination: string, amountMsats?: number | undefined | null, options?: PayOptions | undefined | null): Promise<PayResponse>
+  zap(destination: string, amountMsats: number, params: ZapRequestParams): Promise<ZapResponse>
+  payKeysend(destinationPubkey: string, amountMsats: number, customRecords: Array<TlvRecord>): Promise<PayKeysendResponse>
   createOffer(params: CreateOfferParams): Promise<Offer>
   getOffer(search?: string | undefined | null): Promise<Offer>
   listOffers(search?: string | undefined | null): Promise<Array<Offer>>
   lookupInvoice(params: LookupInvoiceParams): Promise<Transaction>
+  lookupPayment(paymentHash: string): Promise<OutgoingPayment>
   payOffer(offer: string, amountMsats: number, payerNote?: string | undefined | null): Promise<PayInvoiceResponse>
   listTransactions(params: ListTransactionsParams): Promise<Array<Transaction>>
-  decode(str: string): Promise<string>
-  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): void
+  listTransactionsPage(params: ListTransactionsParams): Promise<TransactionPage>
+  decode(str: string): Promise<DecodedPaymentRequest>
+  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): Subscription
+  onPaymentEvents(params: OnPaymentEventParams, callback: (arg0: string, arg1?: OutgoingPayment | undefined | null) => void): Subscription
+  subscribeAll(params: SubscribeAllParams, callback: (err: Error | null, arg: AccountEvent) => void): Subscription
 }
 export declare class StrikeNode {
   constructor(config: StrikeConfig)
@@ -431,16 +742,28 @@ export declare class StrikeNode {
   getApiKey(): string
   getConfig(): StrikeConfig
   getInfo(): Promise<NodeInfo>
+  capabilities(): Promise<NodeCapabilities>
   createInvoice(params: CreateInvoiceParams): Promise<Transaction>
   payInvoice(params: PayInvoiceParams): Promise<PayInvoiceResponse>
+  estimatePaymentFee(invoice: string, amountMsats?: number | undefined | null): Promise<PaymentFeeEstimate>
+  pay(destination: string, amountMsats?: number | undefined | null, options?: PayOptions | undefined | null): Promise<PayResponse>
+  zap(destination: string, amountMsats: number, params: ZapRequestParams): Promise<ZapResponse>
   createOffer(params: CreateOfferParams): Offer
   lookupInvoice(params: LookupInvoiceParams): Promise<Transaction>
+  lookupPayment(paymentHash: string): Promise<OutgoingPayment>
   listTransactions(params: ListTransactionsParams): Promise<Array<Transaction>>
+  listTransactionsPage(params: ListTransactionsParams): Promise<TransactionPage>
   getOffer(search?: string | undefined | null): Offer
   listOffers(search?: string | undefined | null): Promise<Array<Offer>>
   payOffer(offer: string, amountMsats: number, payerNote?: string | undefined | null): PayInvoiceResponse
-  decode(str: string): string
-  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): void
+  decode(str: string): DecodedPaymentRequest
+  getOnchainBalance(): Promise<OnchainBalanceResponse>
+  newOnchainAddress(): Promise<string>
+  sendOnchain(address: string, amountSats: number, satPerVbyte?: number | undefined | null): Promise<SendOnchainResponse>
+  listOnchainTransactions(): Promise<Array<OnchainTransaction>>
+  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): Subscription
+  onPaymentEvents(params: OnPaymentEventParams, callback: (arg0: string, arg1?: OutgoingPayment | undefined | null) => void): Subscription
+  subscribeAll(params: SubscribeAllParams, callback: (err: Error | null, arg: AccountEvent) => void): Subscription
 }
 export declare class SpeedNode {
   constructor(config: SpeedConfig)
@@ -448,16 +771,28 @@ export declare class SpeedNode {
   getApiKey(): string
   getConfig(): SpeedConfig
   getInfo(): Promise<NodeInfo>
+  capabilities(): Promise<NodeCapabilities>
   createInvoice(params: CreateInvoiceParams): Promise<Transaction>
   payInvoice(params: PayInvoiceParams): Promise<PayInvoiceResponse>
+  estimatePaymentFee(invoice: string, amountMsats?: number | undefined | null): Promise<PaymentFeeEstimate>
+  pay(destination: string, amountMsats?: number | undefined | null, options?: PayOptions | undefined | null): Promise<PayResponse>
+  zap(destination: string, amountMsats: number, params: ZapRequestParams): Promise<ZapResponse>
   createOffer(params: CreateOfferParams): Promise<Offer>
   getOffer(search?: string | undefined | null): Promise<Offer>
   listOffers(search?: string | undefined | null): Promise<Array<Offer>>
   lookupInvoice(params: LookupInvoiceParams): Promise<Transaction>
+  lookupPayment(paymentHash: string): Promise<OutgoingPayment>
   payOffer(offer: string, amountMsats: number, payerNote?: string | undefined | null): Promise<PayInvoiceResponse>
   listTransactions(params: ListTransactionsParams): Promise<Array<Transaction>>
-  decode(str: string): Promise<string>
-  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): void
+  listTransactionsPage(params: ListTransactionsParams): Promise<TransactionPage>
+  decode(str: string): Promise<DecodedPaymentRequest>
+  getOnchainBalance(): Promise<OnchainBalanceResponse>
+  newOnchainAddress(): Promise<string>
+  sendOnchain(address: string, amountSats: number, satPerVbyte?: number | undefined | null): Promise<SendOnchainResponse>
+  listOnchainTransactions(): Promise<Array<OnchainTransaction>>
+  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): Subscription
+  onPaymentEvents(params: OnPaymentEventParams, callback: (arg0: string, arg1?: OutgoingPayment | undefined | null) => void): Subscription
+  subscribeAll(params: SubscribeAllParams, callback: (err: Error | null, arg: AccountEvent) => void): Subscription
 }
 /**
  * Spark Node wrapper for napi-rs
@@ -478,14 +813,28 @@ export declare class SparkNode {
   /** Get a Bitcoin address for on-chain deposits */
   getDepositAddress(): Promise<string>
   getInfo(): Promise<NodeInfo>
+  capabilities(): Promise<NodeCapabilities>
   createInvoice(params: CreateInvoiceParams): Promise<Transaction>
   payInvoice(params: PayInvoiceParams): Promise<PayInvoiceResponse>
+  estimatePaymentFee(invoice: string, amountMsats?: number | undefined | null): Promise<PaymentFeeEstimate>
+  pay(destination: string, amountMsats?: number | undefined | null, options?: PayOptions | undefined | null): Promise<PayResponse>
+  zap(destination: string, amountMsats: number, params: ZapRequestParams): Promise<ZapResponse>
   createOffer(params: CreateOfferParams): Promise<Offer>
   getOffer(search?: string | undefined | null): Promise<Offer>
   listOffers(search?: string | undefined | null): Promise<Array<Offer>>
   lookupInvoice(params: LookupInvoiceParams): Promise<Transaction>
+  lookupPayment(paymentHash: string): Promise<OutgoingPayment>
   payOffer(offer: string, amountMsats: number, payerNote?: string | undefined | null): Promise<PayInvoiceResponse>
   listTransactions(params: ListTransactionsParams): Promise<Array<Transaction>>
-  decode(str: string): Promise<string>
-  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): void
+  listTransactionsPage(params: ListTransactionsParams): Promise<TransactionPage>
+  decode(str: string): Promise<DecodedPaymentRequest>
+  onInvoiceEvents(params: OnInvoiceEventParams, callback: (arg0: string, arg1?: Transaction | undefined | null) => void): Promise<Subscription>
+  onPaymentEvents(params: OnPaymentEventParams, callback: (arg0: string, arg1?: OutgoingPayment | undefined | null) => void): Promise<Subscription>
+  subscribeAll(params: SubscribeAllParams, callback: (err: Error | null, arg: AccountEvent) => void): Promise<Subscription>
+}
+/** Handle returned by `onInvoiceEvents`, `onPaymentEvents` and `subscribeAll`, the watcher runs until it is done or cancelled */
+export declare class Subscription {
+  cancel(): void
+  isActive(): boolean
+  wait(): Promise<SubscriptionOutcome>
 }
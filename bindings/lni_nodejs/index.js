@@ -310,9 +310,13 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { InvoiceType, PhoenixdNode, ClnNode, LndNode, BlinkNode, NwcNode, StrikeNode, SpeedNode, SparkNode, detectPaymentType, needsResolution, resolveToBolt11, getPaymentInfo, generateMnemonic, sayAfterWithTokio } = nativeBinding
+const { InvoiceType, TransactionStatus, Direction, SubscriptionOutcome, AccountEventType, PhoenixdNode, ClnNode, LndNode, BlinkNode, NwcNode, StrikeNode, SpeedNode, SparkNode, Subscription, detectPaymentType, needsResolution, resolveToBolt11, getPaymentInfo, generateMnemonic, decodePaymentRequest, decodeOffer, sayAfterWithTokio } = nativeBinding
 
 module.exports.InvoiceType = InvoiceType
+module.exports.TransactionStatus = TransactionStatus
+module.exports.Direction = Direction
+module.exports.SubscriptionOutcome = SubscriptionOutcome
+module.exports.AccountEventType = AccountEventType
 module.exports.PhoenixdNode = PhoenixdNode
 module.exports.ClnNode = ClnNode
 module.exports.LndNode = LndNode
@@ -321,9 +325,12 @@ module.exports.NwcNode = NwcNode
 module.exports.StrikeNode = StrikeNode
 module.exports.SpeedNode = SpeedNode
 module.exports.SparkNode = SparkNode
+module.exports.Subscription = Subscription
 module.exports.detectPaymentType = detectPaymentType
 module.exports.needsResolution = needsResolution
 module.exports.resolveToBolt11 = resolveToBolt11
 module.exports.getPaymentInfo = getPaymentInfo
 module.exports.generateMnemonic = generateMnemonic
+module.exports.decodePaymentRequest = decodePaymentRequest
+module.exports.decodeOffer = decodeOffer
 module.exports.sayAfterWithTokio = sayAfterWithTokio
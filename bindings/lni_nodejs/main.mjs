@@ -65,11 +65,13 @@ async function testAsyncNode(nodeName, node, testInvoiceHash) {
       };
 
       console.log(`${nodeName} - Starting onInvoiceEvents with callback...`);
-      await node.onInvoiceEvents(params, (status, transaction) => {
+      const subscription = await node.onInvoiceEvents(params, (status, transaction) => {
         console.log(`${nodeName} - Invoice event: ${status}`, transaction);
       });
       console.log(`${nodeName} - onInvoiceEvents started successfully`);
       await new Promise(resolve => setTimeout(resolve, 2000));
+      subscription.cancel();
+      console.log(`${nodeName} - onInvoiceEvents ended: ${await subscription.wait()}`);
 
     } catch (error) {
       console.log(`${nodeName} - onInvoiceEvents test failed:`, error.message);
@@ -35,6 +35,9 @@ pub use spark::SparkNode;
 mod lnurl;
 pub use lnurl::*;
 
+mod subscription;
+pub use subscription::Subscription;
+
 use std::time::Duration;
 
 /// Generate a BIP39 mnemonic phrase
@@ -61,6 +64,18 @@ pub fn generate_mnemonic(word_count: Option<u8>) -> napi::Result<String> {
     }
 }
 
+/// Decode a BOLT11 invoice, BOLT12 offer or BOLT12 invoice locally, without a node
+#[napi]
+pub fn decode_payment_request(input: String) -> napi::Result<lni::DecodedPaymentRequest> {
+    lni::decode_payment_request(input).map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
+/// Decode a BOLT12 offer locally, without a node
+#[napi]
+pub fn decode_offer(offer: String) -> napi::Result<lni::bolt12::Bolt12Offer> {
+    lni::bolt12::decode_offer(offer).map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
 // Make an HTTP request to get IP address and simulate latency with optional SOCKS5 proxy
 #[napi]
 pub async fn say_after_with_tokio(ms: u16, who: String, url: String, socks5_proxy: Option<String>, header_key: Option<String>, header_value: Option<String>) -> napi::Result<String> {
@@ -3,6 +3,8 @@ use lni::{
 };
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
+
+use crate::subscription::{AccountEventCallback, InvoiceEventCallback, PaymentEventCallback, Subscription};
 #[napi]
 pub struct LndNode {
   inner: LndConfig,
@@ -81,6 +83,13 @@ impl LndNode {
     Ok(info)
   }
 
+  #[napi]
+  pub async fn capabilities(&self) -> napi::Result<lni::NodeCapabilities> {
+    lni::lnd::api::capabilities(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
   #[napi]
   pub async fn create_invoice(
     &self,
@@ -103,6 +112,65 @@ impl LndNode {
     Ok(invoice)
   }
 
+  #[napi]
+  pub async fn estimate_payment_fee(
+    &self,
+    invoice: String,
+    amount_msats: Option<i64>,
+  ) -> napi::Result<lni::PaymentFeeEstimate> {
+    let estimate = lni::lnd::api::estimate_payment_fee(self.inner.clone(), invoice, amount_msats)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(estimate)
+  }
+
+  #[napi]
+  pub async fn sign_message(&self, message: String) -> napi::Result<String> {
+    lni::lnd::api::sign_message(self.inner.clone(), message)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn pay(
+    &self,
+    destination: String,
+    amount_msats: Option<i64>,
+    options: Option<lni::PayOptions>,
+  ) -> napi::Result<lni::PayResponse> {
+    let response = lni::lnd::LndNode::new(self.inner.clone())
+      .pay(destination, amount_msats, options.unwrap_or_default())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
+  #[napi]
+  pub async fn zap(
+    &self,
+    destination: String,
+    amount_msats: i64,
+    params: lni::ZapRequestParams,
+  ) -> napi::Result<lni::ZapResponse> {
+    let response = lni::lnd::LndNode::new(self.inner.clone())
+      .zap(destination, amount_msats, params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
+  #[napi]
+  pub async fn pay_keysend(
+    &self,
+    destination_pubkey: String,
+    amount_msats: i64,
+    custom_records: Vec<lni::TLVRecord>,
+  ) -> napi::Result<lni::PayKeysendResponse> {
+    lni::lnd::api::pay_keysend(self.inner.clone(), destination_pubkey, amount_msats, custom_records)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
   #[napi]
   pub async fn lookup_invoice(
     &self,
@@ -120,6 +188,14 @@ impl LndNode {
     Ok(txn)
   }
 
+  #[napi]
+  pub async fn lookup_payment(&self, payment_hash: String) -> napi::Result<lni::OutgoingPayment> {
+    let payment = lni::lnd::api::lookup_payment(self.inner.clone(), payment_hash)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(payment)
+  }
+
   #[napi]
   pub async fn list_transactions(
     &self,
@@ -137,7 +213,17 @@ impl LndNode {
   }
 
   #[napi]
-  pub async fn decode(&self, invoice_str: String) -> Result<String> {
+  pub async fn list_transactions_page(
+    &self,
+    params: lni::types::ListTransactionsParams,
+  ) -> napi::Result<lni::TransactionPage> {
+    lni::lnd::api::list_transactions_page(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn decode(&self, invoice_str: String) -> Result<lni::DecodedPaymentRequest> {
     let decoded = lni::lnd::api::decode(self.inner.clone(), invoice_str)
       .await
       .map_err(|e| napi::Error::from_reason(e.to_string()))?;
@@ -145,23 +231,105 @@ impl LndNode {
   }
 
   #[napi]
-  pub fn on_invoice_events<T: Fn(String, Option<lni::Transaction>) -> Result<()>>(
+  pub async fn list_channels(&self) -> napi::Result<Vec<lni::Channel>> {
+    let channels = lni::lnd::api::list_channels(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(channels)
+  }
+
+  #[napi]
+  pub async fn open_channel(
+    &self,
+    params: lni::OpenChannelRequest,
+  ) -> napi::Result<lni::OpenChannelResponse> {
+    let channel = lni::lnd::api::open_channel(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(channel)
+  }
+
+  #[napi]
+  pub async fn close_channel(
+    &self,
+    params: lni::CloseChannelRequest,
+  ) -> napi::Result<lni::CloseChannelResponse> {
+    let closed = lni::lnd::api::close_channel(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(closed)
+  }
+
+  #[napi]
+  pub async fn update_channel_policy(&self, params: lni::UpdateChannelRequest) -> napi::Result<()> {
+    lni::lnd::api::update_channel_policy(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn get_onchain_balance(&self) -> napi::Result<lni::OnchainBalanceResponse> {
+    lni::lnd::api::get_onchain_balance(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn new_onchain_address(&self) -> napi::Result<String> {
+    lni::lnd::api::new_onchain_address(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn send_onchain(
+    &self,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+  ) -> napi::Result<lni::SendOnchainResponse> {
+    lni::lnd::api::send_onchain(self.inner.clone(), address, amount_sats, sat_per_vbyte)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn list_onchain_transactions(&self) -> napi::Result<Vec<lni::OnchainTransaction>> {
+    lni::lnd::api::list_onchain_transactions(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub fn on_invoice_events(
     &self,
     params: lni::types::OnInvoiceEventParams,
-    callback: T,
-  ) -> Result<()> {
+    callback: InvoiceEventCallback,
+  ) -> Subscription {
     let config = self.inner.clone();
+    Subscription::invoice_events(callback, |callback| {
+      lni::lnd::api::on_invoice_events(config, params, callback)
+    })
+  }
 
-    // Block on the async function in the current thread, similar to CLN's sync approach
-    tokio::runtime::Runtime::new().unwrap().block_on(async {
-      lni::lnd::api::poll_invoice_events(&config, params, move |status, tx| {
-        let _ = callback(status.clone(), tx.clone())
-          .map_err(|err| napi::Error::from_reason(err.to_string()));
-      })
-      .await;
-    });
+  #[napi]
+  pub fn on_payment_events(
+    &self,
+    params: lni::types::OnPaymentEventParams,
+    callback: PaymentEventCallback,
+  ) -> Subscription {
+    let node = lni::lnd::LndNode::new(self.inner.clone());
+    Subscription::payment_events(callback, |callback| node.on_payment_events(params, callback))
+  }
 
-    Ok(())
+  #[napi]
+  pub fn subscribe_all(
+    &self,
+    params: lni::types::SubscribeAllParams,
+    callback: AccountEventCallback,
+  ) -> Subscription {
+    let node = lni::lnd::LndNode::new(self.inner.clone());
+    Subscription::subscribe_all(callback, |callback| node.subscribe_all(params, callback))
   }
 
   #[napi]
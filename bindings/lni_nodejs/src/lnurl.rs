@@ -2,6 +2,10 @@
 //!
 //! Exposes LNURL resolution functions to JavaScript
 
+use std::sync::Arc;
+
+use napi::bindgen_prelude::Promise;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction};
 use napi_derive::napi;
 
 /// Payment destination info for confirmation flows
@@ -16,7 +20,7 @@ pub struct PaymentInfo {
 }
 
 /// Check what type of payment destination this is
-/// Returns: "bolt11", "bolt12", "lnurl", or "lightning_address"
+/// Returns: "bolt11", "bolt12", "lnurl", "lnurl_withdraw", or "lightning_address"
 #[napi]
 pub fn detect_payment_type(destination: String) -> napi::Result<String> {
     match lni::lnurl::PaymentDestination::parse(&destination) {
@@ -24,6 +28,7 @@ pub fn detect_payment_type(destination: String) -> napi::Result<String> {
             lni::lnurl::PaymentDestination::Bolt11(_) => Ok("bolt11".to_string()),
             lni::lnurl::PaymentDestination::Bolt12(_) => Ok("bolt12".to_string()),
             lni::lnurl::PaymentDestination::LnurlPay(_) => Ok("lnurl".to_string()),
+            lni::lnurl::PaymentDestination::LnurlWithdraw(_) => Ok("lnurl_withdraw".to_string()),
             lni::lnurl::PaymentDestination::LightningAddress { .. } => Ok("lightning_address".to_string()),
         },
         Err(e) => Err(napi::Error::from_reason(format!("{}", e))),
@@ -66,3 +71,42 @@ pub async fn get_payment_info(destination: String, amount_msats: Option<i64>) ->
         description: info.description,
     })
 }
+
+/// JS `signMessage(message) => Promise<string>` resolving to a zbase32 signature, e.g. a node's `signMessage`
+pub type SignMessageCallback = ThreadsafeFunction<String, ErrorStrategy::Fatal>;
+
+/// Log in with an LNURL-auth link, the linking key is derived from a BIP39 mnemonic (LUD-05)
+#[napi]
+pub async fn lnurl_auth_with_mnemonic(
+    lnurl: String,
+    mnemonic: String,
+    passphrase: Option<String>,
+) -> napi::Result<lni::lnurl::LnurlAuthResponse> {
+    lni::lnurl::auth::lnurl_auth_with_mnemonic(lnurl, mnemonic, passphrase)
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("{}", e)))
+}
+
+/// Log in with an LNURL-auth link, the linking key is derived from `signMessage` (LUD-13)
+#[napi]
+pub async fn lnurl_auth_with_signer(
+    lnurl: String,
+    sign_message: SignMessageCallback,
+) -> napi::Result<lni::lnurl::LnurlAuthResponse> {
+    lni::lnurl::auth::lnurl_auth_with_signer(lnurl, Arc::new(JsMessageSigner(sign_message)))
+        .await
+        .map_err(|e| napi::Error::from_reason(format!("{}", e)))
+}
+
+struct JsMessageSigner(SignMessageCallback);
+
+#[async_trait::async_trait]
+impl lni::lnurl::MessageSigner for JsMessageSigner {
+    async fn sign_message(&self, message: String) -> Result<String, lni::ApiError> {
+        let failed = |e: napi::Error| lni::ApiError::Api {
+            reason: format!("signMessage failed: {}", e.reason),
+        };
+        let signature = self.0.call_async::<Promise<String>>(message).await.map_err(failed)?;
+        signature.await.map_err(failed)
+    }
+}
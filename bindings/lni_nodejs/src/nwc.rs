@@ -4,6 +4,8 @@ use lni::{
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
+use crate::subscription::{AccountEventCallback, InvoiceEventCallback, PaymentEventCallback, Subscription};
+
 #[napi]
 pub struct NwcNode {
   inner: NwcConfig,
@@ -39,6 +41,13 @@ impl NwcNode {
     Ok(info)
   }
 
+  #[napi]
+  pub async fn capabilities(&self) -> napi::Result<lni::NodeCapabilities> {
+    lni::nwc::api::capabilities(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
   #[napi]
   pub async fn create_invoice(
     &self,
@@ -61,6 +70,58 @@ impl NwcNode {
     Ok(invoice)
   }
 
+  #[napi]
+  pub async fn estimate_payment_fee(
+    &self,
+    invoice: String,
+    amount_msats: Option<i64>,
+  ) -> napi::Result<lni::PaymentFeeEstimate> {
+    let estimate = lni::nwc::NwcNode::new(self.inner.clone()).estimate_payment_fee(invoice, amount_msats)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(estimate)
+  }
+
+  #[napi]
+  pub async fn pay(
+    &self,
+    destination: String,
+    amount_msats: Option<i64>,
+    options: Option<lni::PayOptions>,
+  ) -> napi::Result<lni::PayResponse> {
+    let response = lni::nwc::NwcNode::new(self.inner.clone())
+      .pay(destination, amount_msats, options.unwrap_or_default())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
+  #[napi]
+  pub async fn zap(
+    &self,
+    destination: String,
+    amount_msats: i64,
+    params: lni::ZapRequestParams,
+  ) -> napi::Result<lni::ZapResponse> {
+    let response = lni::nwc::NwcNode::new(self.inner.clone())
+      .zap(destination, amount_msats, params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
+  #[napi]
+  pub async fn pay_keysend(
+    &self,
+    destination_pubkey: String,
+    amount_msats: i64,
+    custom_records: Vec<lni::TLVRecord>,
+  ) -> napi::Result<lni::PayKeysendResponse> {
+    lni::nwc::api::pay_keysend(self.inner.clone(), destination_pubkey, amount_msats, custom_records)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
   #[napi]
   pub async fn create_offer(&self, _params: CreateOfferParams) -> Result<lni::Offer> {
     Err(napi::Error::from_reason("NWC does not support offers (BOLT12) yet".to_string()))
@@ -93,6 +154,14 @@ impl NwcNode {
     Ok(txn)
   }
 
+  #[napi]
+  pub async fn lookup_payment(&self, payment_hash: String) -> napi::Result<lni::OutgoingPayment> {
+    let payment = lni::nwc::api::lookup_payment(self.inner.clone(), payment_hash)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(payment)
+  }
+
   #[napi]
   pub async fn pay_offer(
     &self,
@@ -118,6 +187,7 @@ impl NwcNode {
       search: params.search,
       created_after: params.created_after,
       created_before: params.created_before,
+      cursor: params.cursor,
     };
     let txns = lni::nwc::api::list_transactions(self.inner.clone(), nwc_params)
       .await
@@ -126,29 +196,51 @@ impl NwcNode {
   }
 
   #[napi]
-  pub async fn decode(&self, str: String) -> Result<String> {
+  pub async fn list_transactions_page(
+    &self,
+    params: crate::ListTransactionsParams,
+  ) -> napi::Result<lni::TransactionPage> {
+    lni::nwc::api::list_transactions_page(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn decode(&self, str: String) -> Result<lni::DecodedPaymentRequest> {
     let decoded = lni::nwc::api::decode(self.inner.clone(), str)
       .await
       .map_err(|e| napi::Error::from_reason(e.to_string()))?;
     Ok(decoded)
   }
   #[napi]
-  pub fn on_invoice_events<T: Fn(String, Option<lni::Transaction>) -> Result<()>>(
+  pub fn on_invoice_events(
     &self,
     params: lni::types::OnInvoiceEventParams,
-    callback: T,
-  ) -> Result<()> {
+    callback: InvoiceEventCallback,
+  ) -> Subscription {
     let config = self.inner.clone();
+    Subscription::invoice_events(callback, |callback| {
+      lni::nwc::api::on_invoice_events(config, params, callback)
+    })
+  }
 
-    // Block on the async function in the current thread, similar to CLN's sync approach
-    tokio::runtime::Runtime::new().unwrap().block_on(async {
-      lni::nwc::api::poll_invoice_events(&config, params, move |status, tx| {
-        let _ = callback(status.clone(), tx.clone())
-          .map_err(|err| napi::Error::from_reason(err.to_string()));
-      })
-      .await;
-    });
+  #[napi]
+  pub fn on_payment_events(
+    &self,
+    params: lni::types::OnPaymentEventParams,
+    callback: PaymentEventCallback,
+  ) -> Subscription {
+    let node = lni::nwc::NwcNode::new(self.inner.clone());
+    Subscription::payment_events(callback, |callback| node.on_payment_events(params, callback))
+  }
 
-    Ok(())
+  #[napi]
+  pub fn subscribe_all(
+    &self,
+    params: lni::types::SubscribeAllParams,
+    callback: AccountEventCallback,
+  ) -> Subscription {
+    let node = lni::nwc::NwcNode::new(self.inner.clone());
+    Subscription::subscribe_all(callback, |callback| node.subscribe_all(params, callback))
   }
 }
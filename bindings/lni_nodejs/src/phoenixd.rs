@@ -4,6 +4,8 @@ use lni::{
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
+use crate::subscription::{AccountEventCallback, InvoiceEventCallback, PaymentEventCallback, Subscription};
+
 #[napi]
 pub struct PhoenixdNode {
   inner: PhoenixdConfig,
@@ -38,6 +40,13 @@ impl PhoenixdNode {
     Ok(info)
   }
 
+  #[napi]
+  pub async fn capabilities(&self) -> napi::Result<lni::NodeCapabilities> {
+    lni::phoenixd::api::capabilities(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
   #[napi]
   pub async fn create_invoice(&self, params: CreateInvoiceParams) -> napi::Result<lni::Transaction> {
     let txn = lni::phoenixd::api::create_invoice(
@@ -59,6 +68,46 @@ impl PhoenixdNode {
     Ok(invoice)
   }
 
+  #[napi]
+  pub async fn estimate_payment_fee(
+    &self,
+    invoice: String,
+    amount_msats: Option<i64>,
+  ) -> napi::Result<lni::PaymentFeeEstimate> {
+    let estimate = lni::phoenixd::PhoenixdNode::new(self.inner.clone()).estimate_payment_fee(invoice, amount_msats)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(estimate)
+  }
+
+  #[napi]
+  pub async fn pay(
+    &self,
+    destination: String,
+    amount_msats: Option<i64>,
+    options: Option<lni::PayOptions>,
+  ) -> napi::Result<lni::PayResponse> {
+    let response = lni::phoenixd::PhoenixdNode::new(self.inner.clone())
+      .pay(destination, amount_msats, options.unwrap_or_default())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
+  #[napi]
+  pub async fn zap(
+    &self,
+    destination: String,
+    amount_msats: i64,
+    params: lni::ZapRequestParams,
+  ) -> napi::Result<lni::ZapResponse> {
+    let response = lni::phoenixd::PhoenixdNode::new(self.inner.clone())
+      .zap(destination, amount_msats, params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
   #[napi]
   pub async fn create_offer(&self, params: CreateOfferParams) -> Result<lni::Offer> {
     let offer = lni::phoenixd::api::create_offer(self.inner.clone(), params)
@@ -86,6 +135,14 @@ impl PhoenixdNode {
     Ok(txn)
   }
 
+  #[napi]
+  pub async fn lookup_payment(&self, payment_hash: String) -> napi::Result<lni::OutgoingPayment> {
+    let payment = lni::phoenixd::api::lookup_payment(self.inner.clone(), payment_hash)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(payment)
+  }
+
   #[napi]
   pub async fn pay_offer(
     &self,
@@ -109,21 +166,120 @@ impl PhoenixdNode {
   }
 
   #[napi]
-  pub fn on_invoice_events<T: Fn(String, Option<lni::Transaction>) -> Result<()>>(
+  pub async fn list_transactions_page(
+    &self,
+    params: crate::ListTransactionsParams,
+  ) -> napi::Result<lni::TransactionPage> {
+    lni::phoenixd::api::list_transactions_page(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub fn decode(&self, str: String) -> napi::Result<lni::DecodedPaymentRequest> {
+    lni::phoenixd::api::decode(str).map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn list_channels(&self) -> napi::Result<Vec<lni::Channel>> {
+    let channels = lni::phoenixd::api::list_channels(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(channels)
+  }
+
+  #[napi]
+  pub async fn open_channel(
+    &self,
+    params: lni::OpenChannelRequest,
+  ) -> napi::Result<lni::OpenChannelResponse> {
+    let channel = lni::phoenixd::api::open_channel(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(channel)
+  }
+
+  #[napi]
+  pub async fn close_channel(
+    &self,
+    params: lni::CloseChannelRequest,
+  ) -> napi::Result<lni::CloseChannelResponse> {
+    let closed = lni::phoenixd::api::close_channel(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(closed)
+  }
+
+  #[napi]
+  pub async fn update_channel_policy(&self, params: lni::UpdateChannelRequest) -> napi::Result<()> {
+    lni::phoenixd::api::update_channel_policy(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn get_onchain_balance(&self) -> napi::Result<lni::OnchainBalanceResponse> {
+    lni::phoenixd::api::get_onchain_balance(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn new_onchain_address(&self) -> napi::Result<String> {
+    lni::phoenixd::api::new_onchain_address(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn send_onchain(
+    &self,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+  ) -> napi::Result<lni::SendOnchainResponse> {
+    lni::phoenixd::api::send_onchain(self.inner.clone(), address, amount_sats, sat_per_vbyte)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn list_onchain_transactions(&self) -> napi::Result<Vec<lni::OnchainTransaction>> {
+    lni::phoenixd::api::list_onchain_transactions(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub fn on_invoice_events(
     &self,
     params: lni::types::OnInvoiceEventParams,
-    callback: T,
-  ) -> Result<()> {
+    callback: InvoiceEventCallback,
+  ) -> Subscription {
     let config = self.inner.clone();
-    
-    // Block on the async function in the current thread, similar to LND's sync approach
-    tokio::runtime::Runtime::new().unwrap().block_on(async {
-      lni::phoenixd::api::poll_invoice_events(config, params, move |status, tx| {
-        let _ = callback(status.clone(), tx.clone()).map_err(|err| napi::Error::from_reason(err.to_string()));
-      }).await;
-    });
-    
-    Ok(())
+    Subscription::invoice_events(callback, |callback| {
+      lni::phoenixd::api::on_invoice_events(config, params, callback)
+    })
+  }
+
+  #[napi]
+  pub fn on_payment_events(
+    &self,
+    params: lni::types::OnPaymentEventParams,
+    callback: PaymentEventCallback,
+  ) -> Subscription {
+    let node = lni::phoenixd::PhoenixdNode::new(self.inner.clone());
+    Subscription::payment_events(callback, |callback| node.on_payment_events(params, callback))
+  }
+
+  #[napi]
+  pub fn subscribe_all(
+    &self,
+    params: lni::types::SubscribeAllParams,
+    callback: AccountEventCallback,
+  ) -> Subscription {
+    let node = lni::phoenixd::PhoenixdNode::new(self.inner.clone());
+    Subscription::subscribe_all(callback, |callback| node.subscribe_all(params, callback))
   }
 }
 
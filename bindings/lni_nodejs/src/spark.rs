@@ -5,6 +5,8 @@ use napi_derive::napi;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::subscription::{AccountEventCallback, InvoiceEventCallback, PaymentEventCallback, Subscription};
+
 /// Spark Node wrapper for napi-rs
 /// Note: SparkNode requires async initialization, so we use a builder pattern
 #[napi]
@@ -102,6 +104,18 @@ impl SparkNode {
             .map_err(|e| napi::Error::from_reason(e.to_string()))
     }
 
+    #[napi]
+    pub async fn capabilities(&self) -> napi::Result<lni::NodeCapabilities> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+
+        node.capabilities()
+            .await
+            .map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
     #[napi]
     pub async fn create_invoice(
         &self,
@@ -132,6 +146,56 @@ impl SparkNode {
             .map_err(|e| napi::Error::from_reason(e.to_string()))
     }
 
+    #[napi]
+    pub async fn estimate_payment_fee(
+        &self,
+        invoice: String,
+        amount_msats: Option<i64>,
+    ) -> napi::Result<lni::PaymentFeeEstimate> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+
+        node.estimate_payment_fee(invoice, amount_msats)
+            .await
+            .map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    #[napi]
+    pub async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: Option<lni::PayOptions>,
+    ) -> napi::Result<lni::PayResponse> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+
+        node.pay(destination, amount_msats, options.unwrap_or_default())
+            .await
+            .map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    #[napi]
+    pub async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: lni::ZapRequestParams,
+    ) -> napi::Result<lni::ZapResponse> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+
+        node.zap(destination, amount_msats, params)
+            .await
+            .map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
     #[napi]
     pub async fn create_offer(&self, params: CreateOfferParams) -> Result<lni::Offer> {
         let inner = self.inner.read().await;
@@ -183,6 +247,18 @@ impl SparkNode {
             .map_err(|e| napi::Error::from_reason(e.to_string()))
     }
 
+    #[napi]
+    pub async fn lookup_payment(&self, payment_hash: String) -> napi::Result<lni::OutgoingPayment> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+
+        node.lookup_payment(payment_hash)
+            .await
+            .map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
     #[napi]
     pub async fn pay_offer(
         &self,
@@ -217,6 +293,7 @@ impl SparkNode {
             payment_hash: params.payment_hash,
             created_after: params.created_after,
             created_before: params.created_before,
+            cursor: params.cursor,
         };
         
         node.list_transactions(lni_params)
@@ -225,7 +302,22 @@ impl SparkNode {
     }
 
     #[napi]
-    pub async fn decode(&self, str: String) -> napi::Result<String> {
+    pub async fn list_transactions_page(
+        &self,
+        params: crate::ListTransactionsParams,
+    ) -> napi::Result<lni::TransactionPage> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+
+        node.list_transactions_page(params)
+            .await
+            .map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
+    #[napi]
+    pub async fn decode(&self, str: String) -> napi::Result<lni::DecodedPaymentRequest> {
         let inner = self.inner.read().await;
         let node = inner
             .as_ref()
@@ -237,26 +329,41 @@ impl SparkNode {
     }
 
     #[napi]
-    pub fn on_invoice_events<T: Fn(String, Option<lni::Transaction>) -> Result<()>>(
+    pub async fn on_invoice_events(
         &self,
         params: lni::types::OnInvoiceEventParams,
-        callback: T,
-    ) -> Result<()> {
-        let inner = self.inner.clone();
-
-        // Block on the async function in the current thread, similar to Blink's sync approach
-        tokio::runtime::Runtime::new().unwrap().block_on(async {
-            let guard = inner.read().await;
-            if let Some(node) = guard.as_ref() {
-                let sdk = node.get_sdk();
-                lni::spark::api::poll_invoice_events(sdk, params, move |status, tx| {
-                    let _ = callback(status.clone(), tx.clone())
-                        .map_err(|err| napi::Error::from_reason(err.to_string()));
-                })
-                .await;
-            }
-        });
+        callback: InvoiceEventCallback,
+    ) -> napi::Result<Subscription> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+        Ok(Subscription::attach(callback, |callback| node.on_invoice_events(params, callback)))
+    }
 
-        Ok(())
+    #[napi]
+    pub async fn on_payment_events(
+        &self,
+        params: lni::types::OnPaymentEventParams,
+        callback: PaymentEventCallback,
+    ) -> napi::Result<Subscription> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+        Ok(Subscription::payment_events(callback, |callback| node.on_payment_events(params, callback)))
+    }
+
+    #[napi]
+    pub async fn subscribe_all(
+        &self,
+        params: lni::types::SubscribeAllParams,
+        callback: AccountEventCallback,
+    ) -> napi::Result<Subscription> {
+        let inner = self.inner.read().await;
+        let node = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("SparkNode not connected. Call connect() first.".to_string()))?;
+        Ok(Subscription::subscribe_all(callback, |callback| node.subscribe_all(params, callback)))
     }
 }
@@ -2,6 +2,8 @@ use lni::{speed::lib::SpeedConfig, CreateInvoiceParams, CreateOfferParams, Looku
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
+use crate::subscription::{AccountEventCallback, InvoiceEventCallback, PaymentEventCallback, Subscription};
+
 #[napi]
 pub struct SpeedNode {
   inner: SpeedConfig,
@@ -37,6 +39,13 @@ impl SpeedNode {
     Ok(info)
   }
 
+  #[napi]
+  pub async fn capabilities(&self) -> napi::Result<lni::NodeCapabilities> {
+    lni::speed::api::capabilities(&self.inner)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
   #[napi]
   pub async fn create_invoice(
     &self,
@@ -59,6 +68,46 @@ impl SpeedNode {
     Ok(invoice)
   }
 
+  #[napi]
+  pub async fn estimate_payment_fee(
+    &self,
+    invoice: String,
+    amount_msats: Option<i64>,
+  ) -> napi::Result<lni::PaymentFeeEstimate> {
+    let estimate = lni::speed::SpeedNode::new(self.inner.clone()).estimate_payment_fee(invoice, amount_msats)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(estimate)
+  }
+
+  #[napi]
+  pub async fn pay(
+    &self,
+    destination: String,
+    amount_msats: Option<i64>,
+    options: Option<lni::PayOptions>,
+  ) -> napi::Result<lni::PayResponse> {
+    let response = lni::speed::SpeedNode::new(self.inner.clone())
+      .pay(destination, amount_msats, options.unwrap_or_default())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
+  #[napi]
+  pub async fn zap(
+    &self,
+    destination: String,
+    amount_msats: i64,
+    params: lni::ZapRequestParams,
+  ) -> napi::Result<lni::ZapResponse> {
+    let response = lni::speed::SpeedNode::new(self.inner.clone())
+      .zap(destination, amount_msats, params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
   #[napi]
   pub async fn create_offer(&self, _params: CreateOfferParams) -> Result<lni::Offer> {
     Err(napi::Error::from_reason("Bolt12 not implemented for Speed".to_string()))
@@ -92,6 +141,14 @@ impl SpeedNode {
     Ok(txn)
   }
 
+  #[napi]
+  pub async fn lookup_payment(&self, payment_hash: String) -> napi::Result<lni::OutgoingPayment> {
+    let payment = lni::speed::api::lookup_payment(&self.inner, payment_hash)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(payment)
+  }
+
   #[napi]
   pub async fn pay_offer(
     &self,
@@ -118,7 +175,17 @@ impl SpeedNode {
   }
 
   #[napi]
-  pub async fn decode(&self, str: String) -> napi::Result<String> {
+  pub async fn list_transactions_page(
+    &self,
+    params: crate::ListTransactionsParams,
+  ) -> napi::Result<lni::TransactionPage> {
+    lni::speed::api::list_transactions_page(&self.inner, params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn decode(&self, str: String) -> napi::Result<lni::DecodedPaymentRequest> {
     let decoded = lni::speed::api::decode(&self.inner, str)
       .await
       .map_err(|e| napi::Error::from_reason(e.to_string()))?;
@@ -126,22 +193,67 @@ impl SpeedNode {
   }
 
   #[napi]
-  pub fn on_invoice_events<T: Fn(String, Option<lni::Transaction>) -> Result<()>>(
+  pub async fn get_onchain_balance(&self) -> napi::Result<lni::OnchainBalanceResponse> {
+    lni::speed::api::get_onchain_balance(&self.inner)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn new_onchain_address(&self) -> napi::Result<String> {
+    lni::speed::api::new_onchain_address(&self.inner)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn send_onchain(
+    &self,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+  ) -> napi::Result<lni::SendOnchainResponse> {
+    lni::speed::api::send_onchain(&self.inner, address, amount_sats, sat_per_vbyte)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn list_onchain_transactions(&self) -> napi::Result<Vec<lni::OnchainTransaction>> {
+    lni::speed::api::list_onchain_transactions(&self.inner)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub fn on_invoice_events(
     &self,
     params: lni::types::OnInvoiceEventParams,
-    callback: T,
-  ) -> Result<()> {
+    callback: InvoiceEventCallback,
+  ) -> Subscription {
     let config = self.inner.clone();
+    Subscription::invoice_events(callback, |callback| {
+      lni::speed::api::on_invoice_events(config, params, callback)
+    })
+  }
 
-    // Block on the async function in the current thread, similar to CLN's sync approach
-    tokio::runtime::Runtime::new().unwrap().block_on(async {
-      lni::speed::api::poll_invoice_events(&config, params, move |status, tx| {
-        let _ = callback(status.clone(), tx.clone())
-          .map_err(|err| napi::Error::from_reason(err.to_string()));
-      })
-      .await;
-    });
+  #[napi]
+  pub fn on_payment_events(
+    &self,
+    params: lni::types::OnPaymentEventParams,
+    callback: PaymentEventCallback,
+  ) -> Subscription {
+    let node = lni::speed::SpeedNode::new(self.inner.clone());
+    Subscription::payment_events(callback, |callback| node.on_payment_events(params, callback))
+  }
 
-    Ok(())
+  #[napi]
+  pub fn subscribe_all(
+    &self,
+    params: lni::types::SubscribeAllParams,
+    callback: AccountEventCallback,
+  ) -> Subscription {
+    let node = lni::speed::SpeedNode::new(self.inner.clone());
+    Subscription::subscribe_all(callback, |callback| node.subscribe_all(params, callback))
   }
 }
@@ -7,6 +7,8 @@ use lni::{
 };
 use napi_derive::napi;
 
+use crate::subscription::{AccountEventCallback, InvoiceEventCallback, PaymentEventCallback, Subscription};
+
 #[napi]
 pub struct StrikeNode {
   inner: StrikeConfig,
@@ -42,6 +44,13 @@ impl StrikeNode {
     Ok(info)
   }
 
+  #[napi]
+  pub async fn capabilities(&self) -> napi::Result<lni::NodeCapabilities> {
+    lni::strike::api::capabilities(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
   #[napi]
   pub async fn create_invoice(&self, params: CreateInvoiceParams) -> napi::Result<lni::Transaction> {
     let txn = lni::strike::api::create_invoice(self.inner.clone(), params).await
@@ -56,6 +65,46 @@ impl StrikeNode {
     Ok(invoice)
   }
 
+  #[napi]
+  pub async fn estimate_payment_fee(
+    &self,
+    invoice: String,
+    amount_msats: Option<i64>,
+  ) -> napi::Result<lni::PaymentFeeEstimate> {
+    let estimate = lni::strike::api::estimate_payment_fee(self.inner.clone(), invoice, amount_msats)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(estimate)
+  }
+
+  #[napi]
+  pub async fn pay(
+    &self,
+    destination: String,
+    amount_msats: Option<i64>,
+    options: Option<lni::PayOptions>,
+  ) -> napi::Result<lni::PayResponse> {
+    let response = lni::strike::StrikeNode::new(self.inner.clone())
+      .pay(destination, amount_msats, options.unwrap_or_default())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
+  #[napi]
+  pub async fn zap(
+    &self,
+    destination: String,
+    amount_msats: i64,
+    params: lni::ZapRequestParams,
+  ) -> napi::Result<lni::ZapResponse> {
+    let response = lni::strike::StrikeNode::new(self.inner.clone())
+      .zap(destination, amount_msats, params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(response)
+  }
+
   #[napi]
   pub fn create_offer(&self, _params: CreateOfferParams) -> napi::Result<lni::Offer> {
     Err(napi::Error::from_reason("Bolt12 not implemented for Strike".to_string()))
@@ -74,6 +123,14 @@ impl StrikeNode {
     Ok(txn)
   }
 
+  #[napi]
+  pub async fn lookup_payment(&self, payment_hash: String) -> napi::Result<lni::OutgoingPayment> {
+    let payment = lni::strike::api::lookup_payment(self.inner.clone(), payment_hash)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+    Ok(payment)
+  }
+
   #[napi]
   pub async fn list_transactions(
     &self,
@@ -84,6 +141,16 @@ impl StrikeNode {
     Ok(txns)
   }
 
+  #[napi]
+  pub async fn list_transactions_page(
+    &self,
+    params: crate::ListTransactionsParams,
+  ) -> napi::Result<lni::TransactionPage> {
+    lni::strike::api::list_transactions_page(self.inner.clone(), params)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
   #[napi]
   pub fn get_offer(&self, search: Option<String>) -> napi::Result<lni::Offer> {
     let offer = lni::strike::api::get_offer(&self.inner, search)
@@ -111,27 +178,74 @@ impl StrikeNode {
   }
 
   #[napi]
-  pub fn decode(&self, str: String) -> napi::Result<String> {
+  pub fn decode(&self, str: String) -> napi::Result<lni::DecodedPaymentRequest> {
     let decoded = lni::strike::api::decode(&self.inner, str)
       .map_err(|e| napi::Error::from_reason(e.to_string()))?;
     Ok(decoded)
   }
 
   #[napi]
-  pub fn on_invoice_events<T: Fn(String, Option<lni::Transaction>) -> napi::Result<()>>(
+  pub async fn get_onchain_balance(&self) -> napi::Result<lni::OnchainBalanceResponse> {
+    lni::strike::api::get_onchain_balance(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn new_onchain_address(&self) -> napi::Result<String> {
+    lni::strike::api::new_onchain_address(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn send_onchain(
+    &self,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+  ) -> napi::Result<lni::SendOnchainResponse> {
+    lni::strike::api::send_onchain(self.inner.clone(), address, amount_sats, sat_per_vbyte)
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub async fn list_onchain_transactions(&self) -> napi::Result<Vec<lni::OnchainTransaction>> {
+    lni::strike::api::list_onchain_transactions(self.inner.clone())
+      .await
+      .map_err(|e| napi::Error::from_reason(e.to_string()))
+  }
+
+  #[napi]
+  pub fn on_invoice_events(
     &self,
     params: lni::types::OnInvoiceEventParams,
-    callback: T,
-  ) -> napi::Result<()> {
+    callback: InvoiceEventCallback,
+  ) -> Subscription {
     let config = self.inner.clone();
-    let rt = tokio::runtime::Runtime::new().unwrap();
-    
-    rt.block_on(async move {
-      lni::strike::api::poll_invoice_events(config, params, move |status, transaction| {
-        let _ = callback(status, transaction);
-      }).await;
-    });
-    
-    Ok(())
+    Subscription::invoice_events(callback, |callback| {
+      lni::strike::api::on_invoice_events(config, params, callback)
+    })
+  }
+
+  #[napi]
+  pub fn on_payment_events(
+    &self,
+    params: lni::types::OnPaymentEventParams,
+    callback: PaymentEventCallback,
+  ) -> Subscription {
+    let node = lni::strike::StrikeNode::new(self.inner.clone());
+    Subscription::payment_events(callback, |callback| node.on_payment_events(params, callback))
+  }
+
+  #[napi]
+  pub fn subscribe_all(
+    &self,
+    params: lni::types::SubscribeAllParams,
+    callback: AccountEventCallback,
+  ) -> Subscription {
+    let node = lni::strike::StrikeNode::new(self.inner.clone());
+    Subscription::subscribe_all(callback, |callback| node.subscribe_all(params, callback))
   }
 }
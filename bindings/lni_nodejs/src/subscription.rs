@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use lni::types::{OnInvoiceEventCallback, OnPaymentEventCallback, SubscribeAllCallback};
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+
+/// JS callback for invoice events, called with (status, transaction) from the lni runtime
+pub type InvoiceEventCallback = ThreadsafeFunction<(String, Option<lni::Transaction>), ErrorStrategy::Fatal>;
+
+/// JS callback for payment events, called with (status, payment) from the lni runtime
+pub type PaymentEventCallback = ThreadsafeFunction<(String, Option<lni::OutgoingPayment>), ErrorStrategy::Fatal>;
+
+/// Node style JS callback for `subscribeAll`, (err, event) where err means the feed gave up
+pub type AccountEventCallback = ThreadsafeFunction<lni::AccountEvent, ErrorStrategy::CalleeHandled>;
+
+/// Handle returned by `onInvoiceEvents`, `onPaymentEvents` and `subscribeAll`, the watcher runs until it is done or cancelled
+#[napi]
+pub struct Subscription {
+  inner: Arc<lni::Subscription>,
+}
+
+#[napi]
+impl Subscription {
+  #[napi]
+  pub fn cancel(&self) {
+    self.inner.cancel();
+  }
+
+  #[napi]
+  pub fn is_active(&self) -> bool {
+    self.inner.is_active()
+  }
+
+  #[napi]
+  pub async fn wait(&self) -> lni::SubscriptionOutcome {
+    self.inner.wait().await
+  }
+}
+
+impl Subscription {
+  /// Spawns `watch` with the JS callback adapted to lni's callback trait
+  pub fn invoice_events<F, Fut>(callback: InvoiceEventCallback, watch: F) -> Self
+  where
+    F: FnOnce(Arc<dyn OnInvoiceEventCallback>) -> Fut,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+  {
+    let callback: Arc<dyn OnInvoiceEventCallback> = Arc::new(JsInvoiceCallback(callback));
+    Self {
+      inner: lni::Subscription::invoice_events(callback, watch),
+    }
+  }
+
+  /// For nodes whose own on_invoice_events already hands back a subscription
+  pub fn attach<F>(callback: InvoiceEventCallback, subscribe: F) -> Self
+  where
+    F: FnOnce(Arc<dyn OnInvoiceEventCallback>) -> Arc<lni::Subscription>,
+  {
+    Self {
+      inner: subscribe(Arc::new(JsInvoiceCallback(callback))),
+    }
+  }
+
+  /// Same as `attach` for a node's on_payment_events
+  pub fn payment_events<F>(callback: PaymentEventCallback, subscribe: F) -> Self
+  where
+    F: FnOnce(Arc<dyn OnPaymentEventCallback>) -> Arc<lni::Subscription>,
+  {
+    Self {
+      inner: subscribe(Arc::new(JsPaymentCallback(callback))),
+    }
+  }
+
+  /// Same as `attach` for a node's subscribe_all
+  pub fn subscribe_all<F>(callback: AccountEventCallback, subscribe: F) -> Self
+  where
+    F: FnOnce(Arc<dyn SubscribeAllCallback>) -> Arc<lni::Subscription>,
+  {
+    Self {
+      inner: subscribe(Arc::new(JsAccountCallback(callback))),
+    }
+  }
+}
+
+struct JsInvoiceCallback(InvoiceEventCallback);
+
+impl JsInvoiceCallback {
+  fn call(&self, status: &str, transaction: Option<lni::Transaction>) {
+    self.0.call(
+      (status.to_string(), transaction),
+      ThreadsafeFunctionCallMode::NonBlocking,
+    );
+  }
+}
+
+impl OnInvoiceEventCallback for JsInvoiceCallback {
+  fn success(&self, transaction: Option<lni::Transaction>) {
+    self.call("success", transaction);
+  }
+
+  fn pending(&self, transaction: Option<lni::Transaction>) {
+    self.call("pending", transaction);
+  }
+
+  fn failure(&self, transaction: Option<lni::Transaction>) {
+    self.call("failure", transaction);
+  }
+}
+
+struct JsPaymentCallback(PaymentEventCallback);
+
+impl JsPaymentCallback {
+  fn call(&self, status: &str, payment: Option<lni::OutgoingPayment>) {
+    self.0.call(
+      (status.to_string(), payment),
+      ThreadsafeFunctionCallMode::NonBlocking,
+    );
+  }
+}
+
+impl OnPaymentEventCallback for JsPaymentCallback {
+  fn success(&self, payment: Option<lni::OutgoingPayment>) {
+    self.call("success", payment);
+  }
+
+  fn pending(&self, payment: Option<lni::OutgoingPayment>) {
+    self.call("pending", payment);
+  }
+
+  fn failure(&self, payment: Option<lni::OutgoingPayment>) {
+    self.call("failure", payment);
+  }
+}
+
+struct JsAccountCallback(AccountEventCallback);
+
+impl SubscribeAllCallback for JsAccountCallback {
+  fn event(&self, event: lni::AccountEvent) {
+    self.0.call(Ok(event), ThreadsafeFunctionCallMode::NonBlocking);
+  }
+
+  fn error(&self, reason: String) {
+    self.0.call(
+      Err(napi::Error::from_reason(reason)),
+      ThreadsafeFunctionCallMode::NonBlocking,
+    );
+  }
+}
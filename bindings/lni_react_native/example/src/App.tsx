@@ -162,7 +162,7 @@ export default function App() {
         addOutput(nodeName, '(5) Testing decode...');
         try {
           const decoded = await node.decode(invoice.invoice);
-          addOutput(nodeName, `Decode success: ${decoded.substring(0, 50)}...`);
+          addOutput(nodeName, `Decode success: ${decoded.amountMsats} msats, hash ${decoded.paymentHash}`);
         } catch (error) {
           addOutput(nodeName, `Decode failed: ${error}`);
         }
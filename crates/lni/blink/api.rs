@@ -7,9 +7,11 @@ use super::types::*;
 use super::BlinkConfig;
 use crate::types::NodeInfo;
 use crate::{
-    ApiError, CreateInvoiceParams, InvoiceType, Offer, OnInvoiceEventCallback, OnInvoiceEventParams,
-    PayInvoiceParams, PayInvoiceResponse, Transaction,
+    retry_after_secs, ApiError, DecodedPaymentRequest, CreateInvoiceParams, Direction, InvoiceType, NodeCapabilities, Offer, OnInvoiceEventCallback,
+    OnInvoiceEventParams, OnchainBalanceResponse, OnchainTransaction, OutgoingPayment, PayInvoiceParams, PayInvoiceResponse,
+    PaymentFeeEstimate, ListTransactionsParams, SendOnchainResponse, Transaction, TransactionPage, TransactionStatus,
 };
+use crate::websocket;
 use reqwest::header;
 
 // Docs: https://dev.blink.sv/
@@ -72,6 +74,32 @@ fn client(config: &BlinkConfig) -> reqwest::Client {
     client_builder.build().unwrap_or_else(|_| reqwest::Client::new())
 }
 
+// Map Blink GraphQL error codes into structured errors, the first error decides the variant
+fn blink_error(context: &str, errors: &[GraphQLError]) -> ApiError {
+    let reason = format!(
+        "{}: {}",
+        context,
+        errors
+            .iter()
+            .map(|e| e.message.clone())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let code = errors
+        .iter()
+        .find_map(|e| e.code.clone())
+        .unwrap_or_default();
+    match code.as_str() {
+        "INSUFFICIENT_BALANCE" => ApiError::InsufficientBalance { reason },
+        "INVOICE_EXPIRED" | "PAYMENT_REQUEST_EXPIRED" => ApiError::InvoiceExpired { reason },
+        "ROUTE_FINDING_ERROR" | "NO_ROUTE" => ApiError::NoRoute { reason },
+        "ALREADY_PAID" => ApiError::AlreadyPaid { reason },
+        "NOT_AUTHORIZED" | "UNAUTHENTICATED" | "INVALID_AUTHORIZATION" => ApiError::Unauthorized { reason },
+        "TOO_MANY_REQUEST" | "RATE_LIMIT_EXCEEDED" => ApiError::RateLimited { retry_after: None, reason },
+        _ => ApiError::from_message(reason.clone()).unwrap_or(ApiError::Api { reason }),
+    }
+}
+
 async fn execute_graphql_query<T>(
     config: &BlinkConfig,
     query: &str,
@@ -91,16 +119,13 @@ where
         .json(&request)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Blink GraphQL request"))?;
 
     if !response.status().is_success() {
         let status = response.status();
+        let retry_after = retry_after_secs(response.headers());
         let error_text = response.text().await.unwrap_or_default();
-        return Err(ApiError::Http {
-            reason: format!("HTTP {} - {}", status, error_text),
-        });
+        return Err(ApiError::from_http(status, retry_after, error_text));
     }
 
     let response_text = response.text().await.unwrap();
@@ -110,16 +135,7 @@ where
         })?;
 
     if let Some(errors) = graphql_response.errors {
-        return Err(ApiError::Api {
-            reason: format!(
-                "GraphQL errors: {}",
-                errors
-                    .iter()
-                    .map(|e| e.message.clone())
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            ),
-        });
+        return Err(blink_error("GraphQL errors", &errors));
     }
 
     graphql_response.data.ok_or_else(|| ApiError::Json {
@@ -239,16 +255,7 @@ pub async fn create_invoice(
 
             if let Some(errors) = &response.ln_invoice_create.errors {
                 if !errors.is_empty() {
-                    return Err(ApiError::Api {
-                        reason: format!(
-                            "Invoice creation errors: {}",
-                            errors
-                                .iter()
-                                .map(|e| e.message.clone())
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        ),
-                    });
+                    return Err(blink_error("Invoice creation errors", errors));
                 }
             }
 
@@ -266,6 +273,8 @@ pub async fn create_invoice(
 
             Ok(Transaction {
                 type_: "incoming".to_string(),
+                status: TransactionStatus::Pending,
+                direction: Direction::Incoming,
                 invoice: invoice.payment_request,
                 preimage: "".to_string(),
                 payment_hash: invoice.payment_hash,
@@ -280,57 +289,51 @@ pub async fn create_invoice(
                 external_id: Some("".to_string()),
             })
         }
-        InvoiceType::Bolt12 => Err(ApiError::Json {
+        InvoiceType::Bolt12 => Err(ApiError::NotSupported {
             reason: "Bolt12 not implemented for Blink".to_string(),
         }),
     }
 }
 
+// lnInvoicePaymentSend takes just the invoice, the fee probe that runs first lets us
+// hold back payments above the fee limit
+pub(crate) const PAY_PARAMS: crate::PayParamSupport = crate::PayParamSupport {
+    fee_limit: true,
+    ..crate::PayParamSupport::NONE
+};
+
 pub async fn pay_invoice(
     config: &BlinkConfig,
     invoice_params: PayInvoiceParams,
 ) -> Result<PayInvoiceResponse, ApiError> {
-    let wallet_id = get_btc_wallet_id(config).await?;
-
-    // First probe the fee
-    let fee_probe_query = r#"
-        mutation lnInvoiceFeeProbe($input: LnInvoiceFeeProbeInput!) {
-            lnInvoiceFeeProbe(input: $input) {
-                errors {
-                    message
-                }
-                amount
-            }
-        }
-    "#;
-
-    let fee_probe_variables = serde_json::json!({
-        "input": {
-            "paymentRequest": invoice_params.invoice,
-            "walletId": wallet_id
-        }
-    });
+    let fee_limit_msat = crate::check_pay_params(&invoice_params, "Blink", PAY_PARAMS)?;
+    if invoice_params.wait == Some(false) {
+        // lnInvoicePaymentSend only answers once the payment is final or Blink gave up waiting
+        let invoice = invoice_params.invoice.clone();
+        let timeout_seconds = invoice_params.timeout_seconds;
+        let background = config.clone();
+        let pay = async move { send_payment(&background, invoice_params, fee_limit_msat).await };
+        return crate::dispatch_payment(&invoice, timeout_seconds, pay, |payment_hash| lookup_payment(config, payment_hash)).await;
+    }
+    send_payment(config, invoice_params, fee_limit_msat).await
+}
 
-    let fee_response: LnInvoiceFeeProbeResponse = execute_graphql_query(config, fee_probe_query, Some(fee_probe_variables)).await?;
+async fn send_payment(
+    config: &BlinkConfig,
+    invoice_params: PayInvoiceParams,
+    fee_limit_msat: Option<i64>,
+) -> Result<PayInvoiceResponse, ApiError> {
+    let wallet_id = get_btc_wallet_id(config).await?;
 
-    let fee_msats = if let Some(errors) = &fee_response.ln_invoice_fee_probe.errors {
-        if !errors.is_empty() {
-            return Err(ApiError::Api {
-                reason: format!(
-                    "Fee probe errors: {}",
-                    errors
-                        .iter()
-                        .map(|e| e.message.clone())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                ),
-            });
-        } else {
-            fee_response.ln_invoice_fee_probe.amount.unwrap_or(0) * 1000
-        }
-    } else {
-        fee_response.ln_invoice_fee_probe.amount.unwrap_or(0) * 1000
-    };
+    let fee_msats = probe_fee(config, &wallet_id, &invoice_params.invoice, None).await?;
+    if let Some(fee_limit_msat) = fee_limit_msat.filter(|limit| fee_msats > *limit) {
+        return Err(ApiError::NoRoute {
+            reason: format!(
+                "Blink's fee probe came back at {} msats, above the {} msat fee limit",
+                fee_msats, fee_limit_msat
+            ),
+        });
+    }
 
     // Now send the payment
     let payment_query = r#"
@@ -357,27 +360,32 @@ pub async fn pay_invoice(
 
     if let Some(errors) = &payment_response.ln_invoice_payment_send.errors {
         if !errors.is_empty() {
-            return Err(ApiError::Api {
-                reason: format!(
-                    "Payment errors: {}",
-                    errors
-                        .iter()
-                        .map(|e| e.message.clone())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                ),
-            });
+            return Err(blink_error("Payment errors", errors));
         }
     }
 
-    if payment_response.ln_invoice_payment_send.status != "SUCCESS" {
-        return Err(ApiError::Api {
+    // PENDING means Blink stopped waiting, the payment goes on and can be looked up. Only a
+    // caller that asked not to wait gets it back as a result.
+    let status = match payment_response.ln_invoice_payment_send.status.as_str() {
+        "SUCCESS" => TransactionStatus::Settled,
+        "PENDING" if invoice_params.wait == Some(false) => TransactionStatus::Pending,
+        "PENDING" => {
+            return Err(ApiError::PaymentInFlight {
+                reason: "Blink stopped waiting, the payment is still in flight".to_string(),
+            })
+        }
+        "ALREADY_PAID" => {
+            return Err(ApiError::AlreadyPaid {
+                reason: "Invoice was already paid".to_string(),
+            })
+        }
+        _ => return Err(ApiError::Api {
             reason: format!(
                 "Payment failed with status: {}",
                 payment_response.ln_invoice_payment_send.status
             ),
-        });
-    }
+        }),
+    };
 
     // Extract payment hash from the BOLT11 invoice
     let payment_hash = match Bolt11Invoice::from_str(&invoice_params.invoice) {
@@ -389,16 +397,83 @@ pub async fn pay_invoice(
         payment_hash,
         preimage: "".to_string(), // Blink doesn't expose preimage
         fee_msats,
+        status,
     })
 }
 
-pub async fn decode(_config: &BlinkConfig, str: String) -> Result<String, ApiError> {
-    // Blink doesn't have a decode endpoint, return raw string
-    Ok(str)
+// What Blink will charge to route the invoice in msats, zero amount invoices are probed
+// through lnNoAmountInvoiceFeeProbe with `amount_msats`
+async fn probe_fee(
+    config: &BlinkConfig,
+    wallet_id: &str,
+    invoice: &str,
+    amount_msats: Option<i64>,
+) -> Result<i64, ApiError> {
+    let fee_probe_query = match amount_msats {
+        None => r#"
+            mutation lnInvoiceFeeProbe($input: LnInvoiceFeeProbeInput!) {
+                lnInvoiceFeeProbe(input: $input) {
+                    errors {
+                        message
+                        code
+                    }
+                    amount
+                }
+            }
+        "#,
+        // aliased so both probes answer in the same shape
+        Some(_) => r#"
+            mutation lnNoAmountInvoiceFeeProbe($input: LnNoAmountInvoiceFeeProbeInput!) {
+                lnInvoiceFeeProbe: lnNoAmountInvoiceFeeProbe(input: $input) {
+                    errors {
+                        message
+                        code
+                    }
+                    amount
+                }
+            }
+        "#,
+    };
+    let mut fee_probe_variables = serde_json::json!({
+        "input": {
+            "paymentRequest": invoice,
+            "walletId": wallet_id
+        }
+    });
+    if let Some(amount_msats) = amount_msats {
+        fee_probe_variables["input"]["amount"] = serde_json::json!(amount_msats / 1000);
+    }
+
+    let fee_response: LnInvoiceFeeProbeResponse = execute_graphql_query(config, fee_probe_query, Some(fee_probe_variables)).await?;
+    if let Some(errors) = fee_response.ln_invoice_fee_probe.errors.as_ref().filter(|errors| !errors.is_empty()) {
+        return Err(blink_error("Fee probe errors", errors));
+    }
+    Ok(fee_response.ln_invoice_fee_probe.amount.unwrap_or(0) * 1000)
+}
+
+pub async fn estimate_payment_fee(
+    config: &BlinkConfig,
+    invoice: String,
+    amount_msats: Option<i64>,
+) -> Result<PaymentFeeEstimate, ApiError> {
+    let (decoded, amount) = crate::estimate_target(&invoice, amount_msats)?;
+    let wallet_id = get_btc_wallet_id(config).await?;
+    let probe_amount = decoded.amount_msats.is_none().then_some(amount);
+    crate::no_route_estimate(probe_fee(config, &wallet_id, &invoice, probe_amount).await.map(|fee_msats| {
+        PaymentFeeEstimate {
+            min_fee_msats: fee_msats,
+            max_fee_msats: fee_msats,
+            route_found: true,
+        }
+    }))
+}
+
+pub async fn decode(_config: &BlinkConfig, str: String) -> Result<DecodedPaymentRequest, ApiError> {
+    crate::decode_payment_request(str)
 }
 
 pub async fn get_offer(_config: &BlinkConfig, _search: Option<String>) -> Result<Offer, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Blink".to_string(),
     })
 }
@@ -407,7 +482,7 @@ pub async fn list_offers(
     _config: &BlinkConfig,
     _search: Option<String>,
 ) -> Result<Vec<Offer>, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Blink".to_string(),
     })
 }
@@ -418,7 +493,7 @@ pub async fn create_offer(
     _description: Option<String>,
     _expiry: Option<i64>,
 ) -> Result<Transaction, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Blink".to_string(),
     })
 }
@@ -429,7 +504,7 @@ pub async fn fetch_invoice_from_offer(
     _amount_msats: i64,
     _payer_note: Option<String>,
 ) -> Result<crate::cln::types::FetchInvoiceResponse, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Blink".to_string(),
     })
 }
@@ -440,7 +515,7 @@ pub async fn pay_offer(
     _amount_msats: i64,
     _payer_note: Option<String>,
 ) -> Result<PayInvoiceResponse, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Blink".to_string(),
     })
 }
@@ -472,13 +547,71 @@ pub async fn lookup_invoice(
     Ok(transaction)
 }
 
-pub async fn list_transactions(
-    config: &BlinkConfig,
-    from: i64,
-    limit: i64,
-    search: Option<String>,
-) -> Result<Vec<Transaction>, ApiError> {
-    let query = r#"
+const TRANSACTIONS_BY_PAYMENT_HASH_QUERY: &str = r#"
+        query TransactionsByPaymentHash($walletId: WalletId!, $paymentHash: PaymentHash!) {
+            me {
+                defaultAccount {
+                    walletById(walletId: $walletId) {
+                        ... on BTCWallet {
+                            transactionsByPaymentHash(paymentHash: $paymentHash) {
+                                id
+                                createdAt
+                                direction
+                                status
+                                memo
+                                settlementAmount
+                                settlementCurrency
+                                settlementFee
+                                initiationVia {
+                                    __typename
+                                    ... on InitiationViaLn {
+                                        paymentHash
+                                    }
+                                }
+                                settlementVia {
+                                    __typename
+                                    ... on SettlementViaLn {
+                                        preImage
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    "#;
+
+pub async fn lookup_payment(config: &BlinkConfig, payment_hash: String) -> Result<OutgoingPayment, ApiError> {
+    let wallet_id = get_btc_wallet_id(config).await?;
+    let variables = serde_json::json!({
+        "walletId": wallet_id,
+        "paymentHash": payment_hash,
+    });
+    let response: TransactionsByPaymentHashQuery =
+        execute_graphql_query(config, TRANSACTIONS_BY_PAYMENT_HASH_QUERY, Some(variables)).await?;
+
+    // the same hash also lists the receive when paying an invoice of another Blink wallet
+    let payment = response
+        .me
+        .default_account
+        .wallet_by_id
+        .transactions_by_payment_hash
+        .into_iter()
+        .filter(|node| node.direction == "SEND")
+        .map(blink_transaction)
+        .max_by_key(|txn| match txn.status {
+            TransactionStatus::Settled => 2,
+            TransactionStatus::Pending => 1,
+            _ => 0,
+        })
+        .ok_or_else(|| ApiError::Api {
+            reason: format!("Payment not found for payment hash {}", payment_hash),
+        })?;
+    Ok(OutgoingPayment::from_transaction(payment, None))
+}
+
+const TRANSACTIONS_QUERY: &str = r#"
         query TransactionsQuery($first: Int, $last: Int, $after: String, $before: String) {
             me {
                 defaultAccount {
@@ -528,8 +661,14 @@ pub async fn list_transactions(
         }
     "#;
 
-    // Simple approach: map limit directly to $first, handle from with client-side skip
-    // This is cleaner than trying to convert integer offsets to opaque cursors
+pub async fn list_transactions(
+    config: &BlinkConfig,
+    from: i64,
+    limit: i64,
+    search: Option<String>,
+) -> Result<Vec<Transaction>, ApiError> {
+    // Simple approach: map limit directly to $first, handle from with client-side skip.
+    // list_transactions_page uses Blink's cursors instead.
     let variables = serde_json::json!({
         "first": (from + limit) as i32,  // Fetch enough to skip 'from' records
         "last": serde_json::Value::Null,
@@ -537,69 +676,17 @@ pub async fn list_transactions(
         "before": serde_json::Value::Null
     });
 
-    let response: TransactionsQuery = execute_graphql_query(config, query, Some(variables)).await?;
-    
-    let mut all_transactions = Vec::new();
-    
-    for edge in response.me.default_account.transactions.edges {
-        let node = edge.node;
-        
-        // Extract Lightning-specific information
-        let payment_hash = match node.initiation_via {
-            Some(InitiationVia::InitiationViaLn { payment_hash }) => payment_hash,
-            _ => "".to_string(),
-        };
-
-        let preimage = match node.settlement_via {
-            Some(SettlementVia::SettlementViaLn { pre_image }) => pre_image.unwrap_or_default(),
-            _ => "".to_string(),
-        };
-
-        // Handle amount conversion based on settlement currency
-        let (amount_msats, fees_paid) = if let Some(currency) = &node.settlement_currency {
-            if currency == "BTC" {
-                // BTC amounts are in satoshis, convert to millisatoshis
-                let amount = (node.settlement_amount.unwrap_or(0).abs()) * 1000;
-                let fees = (node.settlement_fee.unwrap_or(0).abs()) * 1000;
-                (amount, fees)
-            } else if currency == "USD" {
-                // USD amounts - for now return 0 as we can't meaningfully convert to satoshis
-                // without current exchange rate data
-                (0, 0)
-            } else {
-                // Other currencies
-                (0, 0)
-            }
-        } else {
-            // No settlement currency available
-            (0, 0)
-        };
-        
-        // Use the timestamp directly since it's already a Unix timestamp
-        let created_at = node.created_at;
+    let response: TransactionsQuery =
+        execute_graphql_query(config, TRANSACTIONS_QUERY, Some(variables)).await?;
 
-        let settled_at = if node.status == "SUCCESS" {
-            created_at
-        } else {
-            0
-        };
-
-        all_transactions.push(Transaction {
-            type_: if node.direction == "SEND" { "outgoing" } else { "incoming" }.to_string(),
-            invoice: "".to_string(), // Not available from this query
-            preimage,
-            payment_hash,
-            amount_msats,
-            fees_paid,
-            created_at,
-            expires_at: 0, // Not available from this query
-            settled_at,
-            description: node.memo.unwrap_or_default(),
-            description_hash: "".to_string(),
-            payer_note: Some("".to_string()),
-            external_id: Some(node.id),
-        });
-    }
+    let mut all_transactions: Vec<Transaction> = response
+        .me
+        .default_account
+        .transactions
+        .edges
+        .into_iter()
+        .map(|edge| blink_transaction(edge.node))
+        .collect();
 
     // Apply client-side search filtering if search term is provided
     if let Some(search_term) = search {
@@ -625,6 +712,116 @@ pub async fn list_transactions(
     Ok(all_transactions)
 }
 
+pub async fn list_transactions_page(
+    config: &BlinkConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    crate::search_page(params, |params| transactions_page(config, params)).await
+}
+
+// Blink's own connection cursors are stable, so they are passed through as is
+async fn transactions_page(
+    config: &BlinkConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    let limit = crate::page_limit(params.limit)?;
+    let variables = serde_json::json!({
+        "first": limit as i32,
+        "last": serde_json::Value::Null,
+        "after": params.cursor,
+        "before": serde_json::Value::Null
+    });
+    let response: TransactionsQuery =
+        execute_graphql_query(config, TRANSACTIONS_QUERY, Some(variables)).await?;
+    let connection = response.me.default_account.transactions;
+
+    let transactions: Vec<Transaction> = connection
+        .edges
+        .into_iter()
+        .map(|edge| blink_transaction(edge.node))
+        .collect();
+    Ok(TransactionPage {
+        transactions,
+        next_cursor: if connection.page_info.has_next_page {
+            connection.page_info.end_cursor
+        } else {
+            None
+        },
+    })
+}
+
+fn blink_transaction(node: super::types::Transaction) -> Transaction {
+    // Extract Lightning-specific information
+    let payment_hash = match node.initiation_via {
+        Some(InitiationVia::InitiationViaLn { payment_hash }) => payment_hash,
+        _ => "".to_string(),
+    };
+
+    let preimage = match node.settlement_via {
+        Some(SettlementVia::SettlementViaLn { pre_image }) => pre_image.unwrap_or_default(),
+        _ => "".to_string(),
+    };
+
+    // Handle amount conversion based on settlement currency
+    let (amount_msats, fees_paid) = if let Some(currency) = &node.settlement_currency {
+        if currency == "BTC" {
+            // BTC amounts are in satoshis, convert to millisatoshis
+            let amount = (node.settlement_amount.unwrap_or(0).abs()) * 1000;
+            let fees = (node.settlement_fee.unwrap_or(0).abs()) * 1000;
+            (amount, fees)
+        } else if currency == "USD" {
+            // USD amounts - for now return 0 as we can't meaningfully convert to satoshis
+            // without current exchange rate data
+            (0, 0)
+        } else {
+            // Other currencies
+            (0, 0)
+        }
+    } else {
+        // No settlement currency available
+        (0, 0)
+    };
+    
+    // Use the timestamp directly since it's already a Unix timestamp
+    let created_at = node.created_at;
+
+    let settled_at = if node.status == "SUCCESS" {
+        created_at
+    } else {
+        0
+    };
+
+    // Blink transaction status is SUCCESS, FAILURE or PENDING
+    let status = match node.status.as_str() {
+        "SUCCESS" => TransactionStatus::Settled,
+        "FAILURE" => TransactionStatus::Failed,
+        _ => TransactionStatus::Pending,
+    };
+    let direction = if node.direction == "SEND" {
+        Direction::Outgoing
+    } else {
+        Direction::Incoming
+    };
+
+    Transaction {
+        type_: direction.as_str().to_string(),
+        status,
+        direction,
+        invoice: "".to_string(), // Not available from this query
+        preimage,
+        payment_hash,
+        amount_msats,
+        fees_paid,
+        created_at,
+        expires_at: 0, // Not available from this query
+        settled_at,
+        description: node.memo.unwrap_or_default(),
+        description_hash: "".to_string(),
+        payer_note: Some("".to_string()),
+        external_id: Some(node.id),
+    }
+}
+
 // Core logic shared by both implementations  
 pub async fn poll_invoice_events<F>(config: &BlinkConfig, params: OnInvoiceEventParams, mut callback: F)
 where
@@ -646,7 +843,7 @@ where
             params.search.clone(),
         ).await {
             Ok(transaction) => {
-                if transaction.settled_at > 0 {
+                if transaction.status == TransactionStatus::Settled {
                     ("success".to_string(), Some(transaction))
                 } else {
                     ("pending".to_string(), Some(transaction))
@@ -665,14 +862,378 @@ where
     }
 }
 
+const INVOICE_STATUS_SUBSCRIPTION: &str = r#"
+    subscription LnInvoicePaymentStatusByHash($input: LnInvoicePaymentStatusByHashInput!) {
+        lnInvoicePaymentStatusByHash(input: $input) {
+            paymentHash
+            status
+        }
+    }
+"#;
+
+const MY_UPDATES_SUBSCRIPTION: &str = r#"
+    subscription MyUpdates {
+        myUpdates {
+            update {
+                ... on LnUpdate {
+                    paymentHash
+                    status
+                }
+            }
+        }
+    }
+"#;
+
+// api.blink.sv/graphql subscribes on wss://ws.blink.sv/graphql, likewise for staging
+fn subscription_url(config: &BlinkConfig) -> String {
+    let base_url = config.base_url.as_deref().unwrap_or("https://api.blink.sv/graphql");
+    websocket::ws_url(base_url).replacen("://api.", "://ws.", 1)
+}
+
+// Settlements are pushed over a graphql-ws subscription instead of listing transactions
+// every few seconds, which is what eats into Blink's API quota. lnInvoicePaymentStatusByHash
+// watches a single invoice; myUpdates covers lookups by search and servers without it.
+// Polling remains the fallback when no subscription can be opened.
+pub async fn invoice_events<F>(config: &BlinkConfig, params: OnInvoiceEventParams, mut callback: F)
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(params.max_polling_sec.max(0) as u64);
+
+    let proxied = config.socks5_proxy.as_ref().is_some_and(|p| !p.is_empty());
+    if !proxied && stream_invoice_events(config, &params, deadline, &mut callback).await.is_ok() {
+        return;
+    }
+
+    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+    let params = OnInvoiceEventParams {
+        max_polling_sec: remaining.as_secs() as i64,
+        ..params
+    };
+    poll_invoice_events(config, params, callback).await;
+}
+
+// Ok once a final status was reported, Err hands over to polling
+async fn stream_invoice_events<F>(
+    config: &BlinkConfig,
+    params: &OnInvoiceEventParams,
+    deadline: tokio::time::Instant,
+    callback: &mut F,
+) -> Result<(), ApiError>
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let url = subscription_url(config);
+    let mut by_hash = params.payment_hash.is_some();
+    let mut connected = false;
+    let mut attempts = 0;
+    loop {
+        let mut ws = match open_subscription(config, &url, params, by_hash).await {
+            Ok(ws) => ws,
+            Err(e) => {
+                attempts += 1;
+                if !connected || attempts > websocket::MAX_RECONNECTS {
+                    return Err(e);
+                }
+                if tokio::time::Instant::now() + websocket::reconnect_delay(attempts) >= deadline {
+                    callback("failure".to_string(), None);
+                    return Ok(());
+                }
+                tokio::time::sleep(websocket::reconnect_delay(attempts)).await;
+                continue;
+            }
+        };
+        connected = true;
+        attempts = 0;
+
+        // Checked on every (re)connect so a payment received while disconnected isn't missed
+        if report_invoice_state(config, params, callback).await? {
+            return Ok(());
+        }
+        loop {
+            let text = match tokio::time::timeout_at(deadline, websocket::next_text(&mut ws)).await {
+                Err(_) => {
+                    callback("failure".to_string(), None);
+                    return Ok(());
+                }
+                Ok(Ok(Some(text))) => text,
+                Ok(_) => break,
+            };
+            let frame: WsFrame = match serde_json::from_str(&text) {
+                Ok(frame) => frame,
+                Err(_) => continue,
+            };
+            match frame.type_.as_str() {
+                "ping" if websocket::send_json(&mut ws, &serde_json::json!({ "type": "pong" })).await.is_err() => break,
+                "next" => {
+                    let Some(update) = frame.payload.and_then(status_update) else {
+                        continue;
+                    };
+                    if let (Some(wanted), Some(got)) = (&params.payment_hash, &update.payment_hash) {
+                        if wanted != got {
+                            continue;
+                        }
+                    }
+                    match update.status.as_deref() {
+                        // The transaction list can trail the event, so a matched hash is final
+                        Some("PAID") if params.payment_hash.is_some() => {
+                            let txn = lookup_invoice(config, params.payment_hash.clone(), None, None, params.search.clone())
+                                .await
+                                .ok()
+                                .map(|mut txn| {
+                                    txn.status = TransactionStatus::Settled;
+                                    txn
+                                });
+                            callback("success".to_string(), txn);
+                            return Ok(());
+                        }
+                        Some("PAID") if report_invoice_state(config, params, callback).await? => return Ok(()),
+                        Some("EXPIRED") if params.payment_hash.is_some() => {
+                            callback("failure".to_string(), None);
+                            return Ok(());
+                        }
+                        _ => {}
+                    }
+                }
+                // Servers without lnInvoicePaymentStatusByHash reject it, myUpdates is the fallback
+                "error" if by_hash => {
+                    by_hash = false;
+                    subscribe(&mut ws, params, by_hash).await?;
+                }
+                "error" => {
+                    return Err(ApiError::Api {
+                        reason: format!("Blink subscription rejected: {}", frame.payload.unwrap_or_default()),
+                    });
+                }
+                "complete" => break,
+                _ => {}
+            }
+        }
+    }
+}
+
+async fn open_subscription(
+    config: &BlinkConfig,
+    url: &str,
+    params: &OnInvoiceEventParams,
+    by_hash: bool,
+) -> Result<websocket::WsStream, ApiError> {
+    let mut ws = websocket::connect(url, &[("sec-websocket-protocol", "graphql-transport-ws".to_string())]).await?;
+    websocket::send_json(
+        &mut ws,
+        &serde_json::json!({ "type": "connection_init", "payload": { "X-API-KEY": config.api_key } }),
+    )
+    .await?;
+
+    let ack = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(text) = websocket::next_text(&mut ws).await? {
+            if let Ok(frame) = serde_json::from_str::<WsFrame>(&text) {
+                if frame.type_ == "connection_ack" {
+                    return Ok(());
+                }
+            }
+        }
+        Err(ApiError::Unauthorized {
+            reason: "Blink closed the subscription socket before connection_ack".to_string(),
+        })
+    })
+    .await
+    .map_err(|_| ApiError::Timeout {
+        reason: "Blink subscription connection_ack".to_string(),
+    })?;
+    ack?;
+
+    subscribe(&mut ws, params, by_hash).await?;
+    Ok(ws)
+}
+
+async fn subscribe(
+    ws: &mut websocket::WsStream,
+    params: &OnInvoiceEventParams,
+    by_hash: bool,
+) -> Result<(), ApiError> {
+    let payload = if by_hash {
+        serde_json::json!({
+            "query": INVOICE_STATUS_SUBSCRIPTION,
+            "variables": { "input": { "paymentHash": params.payment_hash } },
+        })
+    } else {
+        serde_json::json!({ "query": MY_UPDATES_SUBSCRIPTION })
+    };
+    let id = if by_hash { "invoice" } else { "updates" };
+    websocket::send_json(ws, &serde_json::json!({ "id": id, "type": "subscribe", "payload": payload })).await
+}
+
+fn status_update(payload: serde_json::Value) -> Option<InvoiceStatusUpdate> {
+    let data = serde_json::from_value::<GraphQLResponse<serde_json::Value>>(payload).ok()?.data?;
+    if let Ok(s) = serde_json::from_value::<LnInvoicePaymentStatusSubscription>(data.clone()) {
+        return Some(s.ln_invoice_payment_status);
+    }
+    serde_json::from_value::<MyUpdatesSubscription>(data).ok()?.my_updates.update
+}
+
+// Reports success (true) once the invoice is settled, pending otherwise
+async fn report_invoice_state<F>(
+    config: &BlinkConfig,
+    params: &OnInvoiceEventParams,
+    callback: &mut F,
+) -> Result<bool, ApiError>
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let txn = lookup_invoice(config, params.payment_hash.clone(), None, None, params.search.clone()).await?;
+    if txn.status == TransactionStatus::Settled {
+        callback("success".to_string(), Some(txn));
+        Ok(true)
+    } else {
+        callback("pending".to_string(), Some(txn));
+        Ok(false)
+    }
+}
+
 pub async fn on_invoice_events(
     config: BlinkConfig,
     params: OnInvoiceEventParams,
     callback: std::sync::Arc<dyn OnInvoiceEventCallback>,
 ) {
-    poll_invoice_events(&config, params, move |status, tx| match status.as_str() {
+    invoice_events(&config, params, move |status, tx| match status.as_str() {
         "success" => callback.success(tx),
         "pending" => callback.pending(tx),
         "failure" | _ => callback.failure(tx),
     }).await;
-}
\ No newline at end of file
+}
+
+// On-chain funds land in the same BTC wallet as lightning, see get_info
+pub async fn get_onchain_balance(_config: &BlinkConfig) -> Result<OnchainBalanceResponse, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Blink does not keep a separate onchain balance".to_string(),
+    })
+}
+
+pub async fn new_onchain_address(config: &BlinkConfig) -> Result<String, ApiError> {
+    let wallet_id = get_btc_wallet_id(config).await?;
+
+    let query = r#"
+        mutation OnChainAddressCreate($input: OnChainAddressCreateInput!) {
+            onChainAddressCreate(input: $input) {
+                address
+                errors {
+                    message
+                    code
+                }
+            }
+        }
+    "#;
+    let variables = serde_json::json!({
+        "input": {
+            "walletId": wallet_id
+        }
+    });
+
+    let response: OnChainAddressCreateResponse = execute_graphql_query(config, query, Some(variables)).await?;
+    if let Some(errors) = &response.on_chain_address_create.errors {
+        if !errors.is_empty() {
+            return Err(blink_error("Onchain address errors", errors));
+        }
+    }
+    response.on_chain_address_create.address.ok_or_else(|| ApiError::Json {
+        reason: "No address in onChainAddressCreate response".to_string(),
+    })
+}
+
+// Blink picks the fee rate, `sat_per_vbyte` is ignored
+pub async fn send_onchain(
+    config: &BlinkConfig,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+) -> Result<SendOnchainResponse, ApiError> {
+    crate::refuse_fee_rate(sat_per_vbyte, "Blink")?;
+    let wallet_id = get_btc_wallet_id(config).await?;
+
+    let fee_query = r#"
+        query OnChainTxFee($walletId: WalletId!, $address: OnChainAddress!, $amount: SatAmount!) {
+            onChainTxFee(walletId: $walletId, address: $address, amount: $amount) {
+                amount
+            }
+        }
+    "#;
+    let fee_variables = serde_json::json!({
+        "walletId": wallet_id,
+        "address": address,
+        "amount": amount_sats,
+    });
+    let fee_response: OnChainTxFeeResponse = execute_graphql_query(config, fee_query, Some(fee_variables)).await?;
+
+    let payment_query = r#"
+        mutation OnChainPaymentSend($input: OnChainPaymentSendInput!) {
+            onChainPaymentSend(input: $input) {
+                status
+                errors {
+                    message
+                    path
+                    code
+                }
+            }
+        }
+    "#;
+    let payment_variables = serde_json::json!({
+        "input": {
+            "walletId": wallet_id,
+            "address": address,
+            "amount": amount_sats,
+        }
+    });
+    let payment_response: OnChainPaymentSendResponse = execute_graphql_query(config, payment_query, Some(payment_variables)).await?;
+
+    if let Some(errors) = &payment_response.on_chain_payment_send.errors {
+        if !errors.is_empty() {
+            return Err(blink_error("Onchain payment errors", errors));
+        }
+    }
+    match payment_response.on_chain_payment_send.status.as_str() {
+        "SUCCESS" | "PENDING" => {}
+        status => {
+            return Err(ApiError::Api {
+                reason: format!("Onchain payment failed with status: {}", status),
+            })
+        }
+    }
+
+    Ok(SendOnchainResponse {
+        // Blink batches onchain payouts, the txid is only known once broadcast
+        txid: None,
+        external_id: None,
+        fee_sats: Some(fee_response.on_chain_tx_fee.amount),
+    })
+}
+
+pub async fn list_onchain_transactions(
+    _config: &BlinkConfig,
+) -> Result<Vec<OnchainTransaction>, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Onchain transaction history not implemented for Blink".to_string(),
+    })
+}
+
+// `fiat` because Blink accounts also carry a USD wallet
+pub async fn capabilities(_config: &BlinkConfig) -> Result<NodeCapabilities, ApiError> {
+    Ok(NodeCapabilities {
+        bolt11_receive: true,
+        bolt11_send: true,
+        bolt12_receive: false,
+        bolt12_send: false,
+        keysend: false,
+        onchain_receive: true,
+        onchain_send: true,
+        onchain_balance: false,
+        hold_invoices: false,
+        description_hash_invoices: false,
+        zero_amount_invoices: false,
+        lookup_invoice: true,
+        list_transactions: true,
+        channel_management: false,
+        native_event_streaming: true,
+        fiat: true,
+    })
+}
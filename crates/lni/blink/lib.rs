@@ -4,7 +4,10 @@ use napi_derive::napi;
 use crate::types::NodeInfo;
 use crate::{
     ApiError, CreateInvoiceParams, CreateOfferParams, ListTransactionsParams, LookupInvoiceParams,
-    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction,
+    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction, Channel, CloseChannelRequest,
+    CloseChannelResponse, OpenChannelRequest, OpenChannelResponse, UpdateChannelRequest,
+    OnchainBalanceResponse, OnchainTransaction, SendOnchainResponse, PayKeysendResponse, TLVRecord,
+    NodeCapabilities,
 };
 #[cfg(not(feature = "uniffi"))]
 use crate::LightningNode;
@@ -59,6 +62,10 @@ impl BlinkNode {
         crate::blink::api::get_info(&self.config).await
     }
 
+    pub async fn capabilities(&self) -> Result<NodeCapabilities, ApiError> {
+        crate::blink::api::capabilities(&self.config).await
+    }
+
     pub async fn create_invoice(&self, params: CreateInvoiceParams) -> Result<Transaction, ApiError> {
         crate::blink::api::create_invoice(&self.config, params).await
     }
@@ -67,8 +74,43 @@ impl BlinkNode {
         crate::blink::api::pay_invoice(&self.config, params).await
     }
 
+    pub async fn estimate_payment_fee(
+        &self,
+        invoice: String,
+        amount_msats: Option<i64>,
+    ) -> Result<crate::PaymentFeeEstimate, ApiError> {
+        crate::blink::api::estimate_payment_fee(&self.config, invoice, amount_msats).await
+    }
+
+    pub async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: crate::PayOptions,
+    ) -> Result<crate::PayResponse, ApiError> {
+        crate::lnurl::pay(self, destination, amount_msats, options).await
+    }
+
+    pub async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: crate::ZapRequestParams,
+    ) -> Result<crate::ZapResponse, ApiError> {
+        crate::lnurl::zap(self, destination, amount_msats, params).await
+    }
+
+    pub async fn pay_keysend(
+        &self,
+        _destination_pubkey: String,
+        _amount_msats: i64,
+        _custom_records: Vec<TLVRecord>,
+    ) -> Result<PayKeysendResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Keysend is not available for custodial BlinkNode".to_string() })
+    }
+
     pub async fn create_offer(&self, _params: CreateOfferParams) -> Result<Offer, ApiError> {
-        Err(ApiError::Api { reason: "create_offer not implemented for BlinkNode".to_string() })
+        Err(ApiError::NotSupported { reason: "create_offer not implemented for BlinkNode".to_string() })
     }
 
     pub async fn get_offer(&self, search: Option<String>) -> Result<Offer, ApiError> {
@@ -98,6 +140,10 @@ impl BlinkNode {
         ).await
     }
 
+    pub async fn lookup_payment(&self, payment_hash: String) -> Result<crate::OutgoingPayment, ApiError> {
+        crate::blink::api::lookup_payment(&self.config, payment_hash).await
+    }
+
     pub async fn list_transactions(
         &self,
         params: ListTransactionsParams,
@@ -105,22 +151,95 @@ impl BlinkNode {
         crate::blink::api::list_transactions(&self.config, params.from, params.limit, params.search).await
     }
 
-    pub async fn decode(&self, str: String) -> Result<String, ApiError> {
+    pub async fn list_transactions_page(
+        &self,
+        params: ListTransactionsParams,
+    ) -> Result<crate::TransactionPage, ApiError> {
+        crate::blink::api::list_transactions_page(&self.config, params).await
+    }
+
+    pub async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, ApiError> {
         crate::blink::api::decode(&self.config, str).await
     }
 
-    pub async fn on_invoice_events(
+    pub fn on_invoice_events(
         &self,
         params: crate::types::OnInvoiceEventParams,
         callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-    ) {
-        crate::blink::api::on_invoice_events(self.config.clone(), params, callback).await
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::invoice_events(callback, |callback| {
+            crate::blink::api::on_invoice_events(config, params, callback)
+        })
+    }
+
+    pub fn on_payment_events(
+        &self,
+        params: crate::types::OnPaymentEventParams,
+        callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No payment feed, the payment is looked up every polling_delay_sec
+        let node = self.clone();
+        crate::Subscription::payment_events(callback, |callback| async move {
+            let payment_hash = params.payment_hash.clone();
+            crate::poll_payment_events(|| node.lookup_payment(payment_hash.clone()), params, callback.as_ref()).await
+        })
+    }
+
+    pub fn subscribe_all(
+        &self,
+        params: crate::types::SubscribeAllParams,
+        callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No account feed, changes are picked up by diffing list_transactions
+        let node = self.clone();
+        crate::Subscription::subscribe_all(callback, |callback| async move {
+            crate::events::poll_all(&node, params, callback.as_ref()).await
+        })
+    }
+
+    pub async fn list_channels(&self) -> Result<Vec<Channel>, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for custodial BlinkNode".to_string() })
+    }
+
+    pub async fn open_channel(&self, _params: OpenChannelRequest) -> Result<OpenChannelResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for custodial BlinkNode".to_string() })
+    }
+
+    pub async fn close_channel(&self, _params: CloseChannelRequest) -> Result<CloseChannelResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for custodial BlinkNode".to_string() })
+    }
+
+    pub async fn update_channel_policy(&self, _params: UpdateChannelRequest) -> Result<(), ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for custodial BlinkNode".to_string() })
+    }
+
+    pub async fn get_onchain_balance(&self) -> Result<OnchainBalanceResponse, ApiError> {
+        crate::blink::api::get_onchain_balance(&self.config).await
+    }
+
+    pub async fn new_onchain_address(&self) -> Result<String, ApiError> {
+        crate::blink::api::new_onchain_address(&self.config).await
+    }
+
+    pub async fn send_onchain(
+        &self,
+        address: String,
+        amount_sats: i64,
+        sat_per_vbyte: Option<i64>,
+    ) -> Result<SendOnchainResponse, ApiError> {
+        crate::blink::api::send_onchain(&self.config, address, amount_sats, sat_per_vbyte).await
+    }
+
+    pub async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, ApiError> {
+        crate::blink::api::list_onchain_transactions(&self.config).await
     }
 }
 
 // Trait implementation for Rust consumers - uses the impl_lightning_node macro
 // Trait implementation for polymorphic access via Arc<dyn LightningNode>
 crate::impl_lightning_node!(BlinkNode);
+impl crate::LightningNodeEvents for BlinkNode {}
 
 #[cfg(test)]
 mod tests {
@@ -262,6 +381,7 @@ mod tests {
             search: None,
             created_after: None,
             created_before: None,
+            cursor: None,
         };
 
         match NODE.list_transactions(params).await {
@@ -317,7 +437,7 @@ mod tests {
             ..Default::default()
         };
 
-        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).await;
+        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).wait().await;
         
         // Check that some events were captured
         let events_guard = events.lock().unwrap();
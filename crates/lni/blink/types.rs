@@ -134,6 +134,36 @@ pub struct LnInvoiceFeeProbeResult {
     pub errors: Option<Vec<GraphQLError>>,
 }
 
+// On-chain structures
+#[derive(Debug, Deserialize)]
+pub struct OnChainAddressCreateResponse {
+    #[serde(rename = "onChainAddressCreate")]
+    pub on_chain_address_create: OnChainAddressResult,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnChainAddressResult {
+    pub address: Option<String>,
+    pub errors: Option<Vec<GraphQLError>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnChainTxFeeResponse {
+    #[serde(rename = "onChainTxFee")]
+    pub on_chain_tx_fee: OnChainTxFee,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnChainTxFee {
+    pub amount: i64, // Fee amount in satoshis
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnChainPaymentSendResponse {
+    #[serde(rename = "onChainPaymentSend")]
+    pub on_chain_payment_send: LnInvoicePaymentResult,
+}
+
 // Transaction structures
 #[derive(Debug, Deserialize)]
 pub struct TransactionConnection {
@@ -265,4 +295,61 @@ pub struct UserWithTransactions {
 #[derive(Debug, Deserialize)]
 pub struct AccountWithTransactions {
     pub transactions: TransactionConnection,
-}
\ No newline at end of file
+}
+// transactionsByPaymentHash is a field of the wallet
+#[derive(Debug, Deserialize)]
+pub struct TransactionsByPaymentHashQuery {
+    pub me: UserWithWallet,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserWithWallet {
+    #[serde(rename = "defaultAccount")]
+    pub default_account: AccountWithWallet,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountWithWallet {
+    #[serde(rename = "walletById")]
+    pub wallet_by_id: WalletTransactions,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletTransactions {
+    #[serde(rename = "transactionsByPaymentHash", default)]
+    pub transactions_by_payment_hash: Vec<Transaction>,
+}
+
+// Subscription structures, frames follow the graphql-transport-ws protocol
+#[derive(Debug, Deserialize)]
+pub struct WsFrame {
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvoiceStatusUpdate {
+    #[serde(rename = "paymentHash")]
+    pub payment_hash: Option<String>,
+    pub status: Option<String>, // "PENDING", "PAID", "EXPIRED"
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LnInvoicePaymentStatusSubscription {
+    #[serde(rename = "lnInvoicePaymentStatusByHash")]
+    pub ln_invoice_payment_status: InvoiceStatusUpdate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MyUpdatesSubscription {
+    #[serde(rename = "myUpdates")]
+    pub my_updates: MyUpdatesPayload,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MyUpdatesPayload {
+    // Only LnUpdate is selected, other update kinds arrive as empty objects
+    pub update: Option<InvoiceStatusUpdate>,
+}
@@ -0,0 +1,527 @@
+//! BOLT12 offer (`lno1...`) and invoice (`lni1...`) parsing
+//!
+//! Decodes the bech32 (no checksum) string and walks the TLV stream locally,
+//! so an offer can be previewed without asking a node.
+//! https://github.com/lightning/bolts/blob/master/12-offer-encoding.md
+//!
+//! Signatures are not checked, the node that pays the offer does that.
+
+#[cfg(feature = "napi_rs")]
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+
+use crate::{ApiError, DecodedPaymentRequest, InvoiceType};
+
+// Offer TLV types
+const OFFER_CHAINS: u64 = 2;
+const OFFER_CURRENCY: u64 = 6;
+const OFFER_AMOUNT: u64 = 8;
+const OFFER_DESCRIPTION: u64 = 10;
+const OFFER_FEATURES: u64 = 12;
+const OFFER_ABSOLUTE_EXPIRY: u64 = 14;
+const OFFER_PATHS: u64 = 16;
+const OFFER_ISSUER: u64 = 18;
+const OFFER_QUANTITY_MAX: u64 = 20;
+const OFFER_ISSUER_ID: u64 = 22;
+// Offer fields are 1..80 and the experimental 1_000_000_000..2_000_000_000, type 0 is the
+// invoice request's invreq_metadata
+const OFFER_RANGES: [std::ops::Range<u64>; 2] = [1..80, 1_000_000_000..2_000_000_000];
+// Invoice request TLV types (echoed in the invoice)
+const INVREQ_PAYER_NOTE: u64 = 89;
+// Invoice TLV types
+const INVOICE_CREATED_AT: u64 = 164;
+const INVOICE_RELATIVE_EXPIRY: u64 = 166;
+const INVOICE_PAYMENT_HASH: u64 = 168;
+const INVOICE_AMOUNT: u64 = 170;
+const INVOICE_FEATURES: u64 = 174;
+const INVOICE_NODE_ID: u64 = 176;
+
+// Default invoice_relative_expiry when the field is missing
+const DEFAULT_INVOICE_EXPIRY: i64 = 7200;
+
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bolt12Offer {
+    pub offer: String,
+    pub network: String,           // "bitcoin", "testnet", "signet", "regtest" or the chain hash
+    pub amount_msats: Option<i64>, // only set when the amount is in bitcoin
+    pub amount: Option<i64>,       // in `currency` minor units when set, otherwise msats
+    pub currency: Option<String>,  // ISO 4217 code, None means bitcoin
+    pub description: Option<String>,
+    pub issuer: Option<String>,
+    pub absolute_expiry: Option<i64>, // seconds since epoch
+    pub quantity_max: Option<i64>,    // Some(0) means any quantity
+    pub issuer_id: Option<String>,    // signing pubkey, may be absent when paths are set
+    pub paths: Vec<BlindedPath>,
+    pub features: Vec<i64>,
+}
+
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BlindedPath {
+    pub introduction_node: String, // pubkey, or "blockxtxxoutput/direction" for an sciddir
+    pub num_hops: i64,
+}
+
+/// True for strings that look like a BOLT12 offer or invoice
+pub fn is_bolt12(input: &str) -> bool {
+    let lower = input.trim().to_lowercase();
+    lower.starts_with("lno1") || lower.starts_with("lni1")
+}
+
+/// Decode a BOLT12 offer (`lno1...`)
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn decode_offer(offer: String) -> Result<Bolt12Offer, ApiError> {
+    let records = decode_tlv_string(&offer, "lno")?;
+    parse_offer(&offer, &records)
+}
+
+/// Decode a BOLT12 offer or invoice into the same shape as a BOLT11 invoice
+pub fn decode(input: &str) -> Result<DecodedPaymentRequest, ApiError> {
+    let lower = input.trim().to_lowercase();
+    if lower.starts_with("lni1") {
+        return decode_invoice(input);
+    }
+    let offer = decode_offer(input.to_string())?;
+    Ok(DecodedPaymentRequest {
+        invoice_type: InvoiceType::Bolt12,
+        invoice: input.trim().to_string(),
+        network: offer.network.clone(),
+        amount_msats: offer.amount_msats,
+        payment_hash: "".to_string(), // offers don't have one until an invoice is fetched
+        payment_secret: None,
+        description: offer.description.clone(),
+        description_hash: None,
+        payee_pubkey: offer.issuer_id.clone().unwrap_or_default(),
+        created_at: 0,
+        expiry: 0,
+        expires_at: offer.absolute_expiry.unwrap_or(0),
+        min_final_cltv_expiry_delta: 0,
+        route_hints: vec![],
+        features: offer.features.clone(),
+        payer_note: None,
+        offer: Some(offer),
+    })
+}
+
+fn decode_invoice(invoice: &str) -> Result<DecodedPaymentRequest, ApiError> {
+    let records = decode_tlv_string(invoice, "lni")?;
+    // Offer fields are copied verbatim into the invoice, so the original offer can be rebuilt
+    let offer_records: Vec<_> = records
+        .iter()
+        .filter(|(type_, _)| OFFER_RANGES.iter().any(|range| range.contains(type_)))
+        .cloned()
+        .collect();
+    let offer = parse_offer(&encode_tlv_string("lno", &offer_records)?, &offer_records)?;
+
+    let mut payment_hash = None;
+    let mut amount_msats = None;
+    let mut created_at = None;
+    let mut expiry = DEFAULT_INVOICE_EXPIRY;
+    let mut features = vec![];
+    let mut node_id = None;
+    let mut payer_note = None;
+    for (type_, value) in &records {
+        match *type_ {
+            INVOICE_PAYMENT_HASH => payment_hash = Some(hex::encode(fixed::<32>(value, "invoice_payment_hash")?)),
+            INVOICE_AMOUNT => amount_msats = Some(tu64(value)? as i64),
+            INVOICE_CREATED_AT => created_at = Some(tu64(value)? as i64),
+            INVOICE_RELATIVE_EXPIRY => expiry = tu64(value)? as i64,
+            INVOICE_FEATURES => features = feature_bits(value),
+            INVOICE_NODE_ID => node_id = Some(hex::encode(fixed::<33>(value, "invoice_node_id")?)),
+            INVREQ_PAYER_NOTE => payer_note = Some(utf8(value, "invreq_payer_note")?),
+            _ => {}
+        }
+    }
+
+    let missing = |field: &str| ApiError::InvalidInput(format!("BOLT12 invoice is missing {}", field));
+    let created_at = created_at.ok_or_else(|| missing("invoice_created_at"))?;
+
+    Ok(DecodedPaymentRequest {
+        invoice_type: InvoiceType::Bolt12,
+        invoice: invoice.trim().to_string(),
+        network: offer.network.clone(),
+        amount_msats: Some(amount_msats.ok_or_else(|| missing("invoice_amount"))?),
+        payment_hash: payment_hash.ok_or_else(|| missing("invoice_payment_hash"))?,
+        payment_secret: None,
+        description: offer.description.clone(),
+        description_hash: None,
+        payee_pubkey: node_id.ok_or_else(|| missing("invoice_node_id"))?,
+        created_at,
+        expiry,
+        expires_at: created_at + expiry,
+        min_final_cltv_expiry_delta: 0,
+        route_hints: vec![],
+        features,
+        payer_note,
+        offer: Some(offer),
+    })
+}
+
+fn parse_offer(input: &str, records: &[(u64, Vec<u8>)]) -> Result<Bolt12Offer, ApiError> {
+    let mut offer = Bolt12Offer {
+        offer: input.trim().to_string(),
+        network: "bitcoin".to_string(),
+        amount_msats: None,
+        amount: None,
+        currency: None,
+        description: None,
+        issuer: None,
+        absolute_expiry: None,
+        quantity_max: None,
+        issuer_id: None,
+        paths: vec![],
+        features: vec![],
+    };
+
+    for (type_, value) in records {
+        match *type_ {
+            OFFER_CHAINS => {
+                // An offer can list several chains, the first one is the one we show
+                let chain = value.get(..32).ok_or_else(|| invalid("offer_chains"))?;
+                offer.network = network_name(chain);
+            }
+            OFFER_CURRENCY => offer.currency = Some(utf8(value, "offer_currency")?),
+            OFFER_AMOUNT => offer.amount = Some(tu64(value)? as i64),
+            OFFER_DESCRIPTION => offer.description = Some(utf8(value, "offer_description")?),
+            OFFER_FEATURES => offer.features = feature_bits(value),
+            OFFER_ABSOLUTE_EXPIRY => offer.absolute_expiry = Some(tu64(value)? as i64),
+            OFFER_PATHS => offer.paths = blinded_paths(value)?,
+            OFFER_ISSUER => offer.issuer = Some(utf8(value, "offer_issuer")?),
+            OFFER_QUANTITY_MAX => offer.quantity_max = Some(tu64(value)? as i64),
+            OFFER_ISSUER_ID => offer.issuer_id = Some(hex::encode(fixed::<33>(value, "offer_issuer_id")?)),
+            _ => {}
+        }
+    }
+
+    if offer.currency.is_none() {
+        offer.amount_msats = offer.amount;
+    }
+    Ok(offer)
+}
+
+// Strip the optional `lightning:` prefix and `+` line breaks, check the hrp and
+// return the TLV records in stream order
+fn decode_tlv_string(input: &str, expected_hrp: &str) -> Result<Vec<(u64, Vec<u8>)>, ApiError> {
+    let lower = input.trim().to_lowercase();
+    let lower = lower.strip_prefix("lightning:").unwrap_or(&lower);
+    let joined: String = lower
+        .split('+')
+        .map(|part| part.trim())
+        .collect::<Vec<_>>()
+        .join("");
+
+    let parsed = bech32::primitives::decode::CheckedHrpstring::new::<bech32::NoChecksum>(&joined)
+        .map_err(|e| ApiError::InvalidInput(format!("Invalid BOLT12 encoding: {}", e)))?;
+    let hrp = parsed.hrp().to_string().to_lowercase();
+    if hrp != expected_hrp {
+        return Err(ApiError::InvalidInput(format!(
+            "Expected a BOLT12 string starting with {}1, got {}1",
+            expected_hrp, hrp
+        )));
+    }
+    let bytes: Vec<u8> = parsed.byte_iter().collect();
+    parse_tlv_stream(&bytes)
+}
+
+fn encode_tlv_string(hrp: &str, records: &[(u64, Vec<u8>)]) -> Result<String, ApiError> {
+    let mut bytes = vec![];
+    for (type_, value) in records {
+        write_bigsize(&mut bytes, *type_);
+        write_bigsize(&mut bytes, value.len() as u64);
+        bytes.extend_from_slice(value);
+    }
+    let hrp = bech32::Hrp::parse(hrp).map_err(|e| ApiError::InvalidInput(e.to_string()))?;
+    bech32::encode::<bech32::NoChecksum>(hrp, &bytes)
+        .map_err(|e| ApiError::InvalidInput(format!("Failed to encode BOLT12 string: {}", e)))
+}
+
+fn parse_tlv_stream(bytes: &[u8]) -> Result<Vec<(u64, Vec<u8>)>, ApiError> {
+    let mut records = vec![];
+    let mut cursor = bytes;
+    let mut last_type: Option<u64> = None;
+    while !cursor.is_empty() {
+        let type_ = read_bigsize(&mut cursor)?;
+        let len = read_bigsize(&mut cursor)? as usize;
+        if last_type.is_some_and(|last| type_ <= last) {
+            return Err(ApiError::InvalidInput("BOLT12 TLV types are not in ascending order".to_string()));
+        }
+        if cursor.len() < len {
+            return Err(ApiError::InvalidInput("BOLT12 TLV record is truncated".to_string()));
+        }
+        let (value, rest) = cursor.split_at(len);
+        records.push((type_, value.to_vec()));
+        cursor = rest;
+        last_type = Some(type_);
+    }
+    Ok(records)
+}
+
+// BigSize: https://github.com/lightning/bolts/blob/master/01-messaging.md#appendix-a-bigsize-test-vectors
+fn read_bigsize(cursor: &mut &[u8]) -> Result<u64, ApiError> {
+    let (first, rest) = cursor
+        .split_first()
+        .ok_or_else(|| ApiError::InvalidInput("BOLT12 TLV stream is truncated".to_string()))?;
+    let width = match first {
+        0xfd => 2,
+        0xfe => 4,
+        0xff => 8,
+        n => {
+            *cursor = rest;
+            return Ok(*n as u64);
+        }
+    };
+    if rest.len() < width {
+        return Err(ApiError::InvalidInput("BOLT12 TLV stream is truncated".to_string()));
+    }
+    let value = rest[..width].iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+    *cursor = &rest[width..];
+    Ok(value)
+}
+
+fn write_bigsize(bytes: &mut Vec<u8>, value: u64) {
+    match value {
+        0..=0xfc => bytes.push(value as u8),
+        0xfd..=0xffff => {
+            bytes.push(0xfd);
+            bytes.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            bytes.push(0xfe);
+            bytes.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            bytes.push(0xff);
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+// Truncated big-endian u64
+fn tu64(value: &[u8]) -> Result<u64, ApiError> {
+    if value.len() > 8 {
+        return Err(ApiError::InvalidInput("BOLT12 integer is longer than 8 bytes".to_string()));
+    }
+    Ok(value.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+}
+
+fn fixed<const N: usize>(value: &[u8], field: &str) -> Result<[u8; N], ApiError> {
+    value.try_into().map_err(|_| invalid(field))
+}
+
+fn utf8(value: &[u8], field: &str) -> Result<String, ApiError> {
+    String::from_utf8(value.to_vec()).map_err(|_| invalid(field))
+}
+
+fn invalid(field: &str) -> ApiError {
+    ApiError::InvalidInput(format!("Invalid BOLT12 field {}", field))
+}
+
+// Feature bitfields are big endian, bit 0 is the lowest bit of the last byte
+fn feature_bits(value: &[u8]) -> Vec<i64> {
+    let mut bits: Vec<i64> = value
+        .iter()
+        .rev()
+        .enumerate()
+        .flat_map(|(i, byte)| {
+            (0..8)
+                .filter(move |bit| byte & (1 << bit) != 0)
+                .map(move |bit| (i * 8 + bit) as i64)
+        })
+        .collect();
+    bits.sort();
+    bits
+}
+
+// Chain hashes are the genesis block hash in internal byte order
+fn network_name(chain: &[u8]) -> String {
+    match hex::encode(chain).as_str() {
+        "6fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000" => "bitcoin".to_string(),
+        "43497fd7f826957108f4a30fd9cec3aeba79972084e90ead01ea330900000000" => "testnet".to_string(),
+        "f61eee3b63a380a477a063af32b2bbc97c9ff9f01f2c4225e973988108000000" => "signet".to_string(),
+        "06226e46111a0b59caaf126043eb5bbf28c34f3a5e332a1fc7b2b73cf188910f" => "regtest".to_string(),
+        other => other.to_string(),
+    }
+}
+
+// blinded_path: first_node_id (sciddir_or_pubkey), first_path_key (point), num_hops (byte),
+// then num_hops x { blinded_node_id (point), enclen (u16), encrypted_recipient_data }
+fn blinded_paths(value: &[u8]) -> Result<Vec<BlindedPath>, ApiError> {
+    fn take(cursor: &mut &[u8], n: usize) -> Result<Vec<u8>, ApiError> {
+        if cursor.len() < n {
+            return Err(invalid("offer_paths"));
+        }
+        let (head, rest) = cursor.split_at(n);
+        *cursor = rest;
+        Ok(head.to_vec())
+    }
+
+    let mut paths = vec![];
+    let mut cursor = value;
+    while !cursor.is_empty() {
+        let introduction_node = match cursor[0] {
+            0 | 1 => {
+                let sciddir = take(&mut cursor, 9)?;
+                let scid = sciddir[1..].iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+                format!("{}/{}", crate::utils::format_short_channel_id(scid), sciddir[0])
+            }
+            _ => hex::encode(take(&mut cursor, 33)?),
+        };
+        take(&mut cursor, 33)?; // first_path_key
+        let num_hops = take(&mut cursor, 1)?[0];
+        for _ in 0..num_hops {
+            take(&mut cursor, 33)?; // blinded_node_id
+            let enclen = take(&mut cursor, 2)?;
+            take(&mut cursor, u16::from_be_bytes([enclen[0], enclen[1]]) as usize)?;
+        }
+        paths.push(BlindedPath {
+            introduction_node,
+            num_hops: num_hops as i64,
+        });
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(hrp: &str, records: &[(u64, Vec<u8>)]) -> String {
+        encode_tlv_string(hrp, records).unwrap()
+    }
+
+    #[test]
+    fn test_decode_minimal_offer() {
+        // BOLT12 test vector "Minimal bolt12 offer"
+        let offer = decode_offer("lno1zcss9mk8y3wkklfvevcrszlmu23kfrxh49px20665dqwmn4p72pksese".to_string()).unwrap();
+        assert_eq!(
+            offer.issuer_id.as_deref(),
+            Some("02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619")
+        );
+        assert_eq!(offer.network, "bitcoin");
+        assert_eq!(offer.amount_msats, None);
+        assert!(offer.paths.is_empty());
+    }
+
+    #[test]
+    fn test_decode_offer_fields() {
+        let issuer_id = hex::decode("02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619").unwrap();
+        let raw = encode(
+            "lno",
+            &[
+                (OFFER_AMOUNT, vec![0x03, 0xe8]),
+                (OFFER_DESCRIPTION, b"coffee".to_vec()),
+                (OFFER_ABSOLUTE_EXPIRY, vec![0x65, 0x00, 0x00, 0x00]),
+                (OFFER_ISSUER, b"lni".to_vec()),
+                (OFFER_QUANTITY_MAX, vec![]),
+                (OFFER_ISSUER_ID, issuer_id),
+            ],
+        );
+        // long offers are often split with `+` and whitespace
+        let split = format!("{}+\n {}", &raw[..20], &raw[20..]).to_uppercase();
+        let offer = decode_offer(split).unwrap();
+        assert_eq!(offer.amount_msats, Some(1000));
+        assert_eq!(offer.currency, None);
+        assert_eq!(offer.description.as_deref(), Some("coffee"));
+        assert_eq!(offer.absolute_expiry, Some(0x65000000));
+        assert_eq!(offer.issuer.as_deref(), Some("lni"));
+        assert_eq!(offer.quantity_max, Some(0));
+
+        let decoded = decode(&raw).unwrap();
+        assert!(matches!(decoded.invoice_type, InvoiceType::Bolt12));
+        assert_eq!(decoded.amount_msats, Some(1000));
+        assert!(decoded.offer.is_some());
+    }
+
+    #[test]
+    fn test_decode_offer_currency_and_paths() {
+        let point = |b: u8| {
+            let mut p = vec![0x02];
+            p.extend_from_slice(&[b; 32]);
+            p
+        };
+        let mut path = point(1); // introduction node
+        path.extend(point(2)); // first_path_key
+        path.push(1); // one hop
+        path.extend(point(3));
+        path.extend_from_slice(&[0x00, 0x02, 0xaa, 0xbb]);
+        let raw = encode(
+            "lno",
+            &[
+                (OFFER_CURRENCY, b"USD".to_vec()),
+                (OFFER_AMOUNT, vec![0x01, 0xf4]),
+                (OFFER_DESCRIPTION, b"tip".to_vec()),
+                (OFFER_PATHS, path),
+            ],
+        );
+        let offer = decode_offer(raw).unwrap();
+        assert_eq!(offer.currency.as_deref(), Some("USD"));
+        assert_eq!(offer.amount, Some(500));
+        assert_eq!(offer.amount_msats, None);
+        assert_eq!(offer.paths.len(), 1);
+        assert_eq!(offer.paths[0].num_hops, 1);
+        assert_eq!(offer.paths[0].introduction_node, hex::encode(point(1)));
+        assert_eq!(offer.issuer_id, None);
+    }
+
+    #[test]
+    fn test_decode_invoice() {
+        let node_id = hex::decode("02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619").unwrap();
+        let raw = encode(
+            "lni",
+            &[
+            (0, vec![0x42; 8]), // invreq_metadata, not part of the offer
+            (OFFER_DESCRIPTION, b"coffee".to_vec()),
+            (INVREQ_PAYER_NOTE, b"thanks".to_vec()),
+            (INVOICE_CREATED_AT, vec![0x65, 0x00, 0x00, 0x00]),
+            (INVOICE_RELATIVE_EXPIRY, vec![0x0e, 0x10]),
+            (INVOICE_PAYMENT_HASH, vec![0xab; 32]),
+            (INVOICE_AMOUNT, vec![0x03, 0xe8]),
+            (INVOICE_FEATURES, vec![0x01, 0x00]),
+            (INVOICE_NODE_ID, node_id),
+            (1_000_000_001, b"experimental".to_vec()),
+            ],
+        );
+
+        let decoded = decode(&raw).unwrap();
+        assert_eq!(decoded.payment_hash, "ab".repeat(32));
+        assert_eq!(decoded.amount_msats, Some(1000));
+        assert_eq!(decoded.created_at, 0x65000000);
+        assert_eq!(decoded.expiry, 3600);
+        assert_eq!(decoded.expires_at, 0x65000000 + 3600);
+        assert_eq!(decoded.description.as_deref(), Some("coffee"));
+        assert_eq!(decoded.payer_note.as_deref(), Some("thanks"));
+        assert_eq!(decoded.features, vec![8]);
+        let offer = decoded.offer.unwrap();
+        let original = encode(
+            "lno",
+            &[(OFFER_DESCRIPTION, b"coffee".to_vec()), (1_000_000_001, b"experimental".to_vec())],
+        );
+        assert_eq!(offer.offer, original);
+        assert_eq!(decode_offer(offer.offer).unwrap().description.as_deref(), Some("coffee"));
+    }
+
+    #[test]
+    fn test_bigsize_roundtrip() {
+        for value in [0u64, 0xfc, 0xfd, 0xffff, 0x10000, 0xffff_ffff, 0x1_0000_0000] {
+            let mut bytes = vec![];
+            write_bigsize(&mut bytes, value);
+            let mut cursor = bytes.as_slice();
+            assert_eq!(read_bigsize(&mut cursor).unwrap(), value);
+            assert!(cursor.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_input() {
+        assert!(decode_offer("lno1qtest".to_string()).is_err());
+        // an invoice is not an offer
+        let raw = encode("lni", &[(OFFER_DESCRIPTION, b"x".to_vec())]);
+        assert!(decode_offer(raw).is_err());
+        // types must be ascending
+        let raw = encode("lno", &[(OFFER_ISSUER, b"a".to_vec()), (OFFER_DESCRIPTION, b"b".to_vec())]);
+        assert!(decode_offer(raw).is_err());
+    }
+}
@@ -1,15 +1,23 @@
 use super::types::{
-    Bolt11Resp, Bolt12Resp, ChannelWrapper, FetchInvoiceResponse, InfoResponse, InvoicesResponse,
-    ListOffersResponse, PayResponse,
+    Bolt11Resp, Bolt12Resp, ChannelWrapper, ClnError, CloseResponse, FetchInvoiceResponse,
+    FundChannelResponse, FundsResponse, GetRouteResponse, InfoResponse, InvoicesResponse, ListOffersResponse, ListPaysResponse,
+    ListSendpaysResponse, NewAddrResponse, PayResponse, PeerChannelsResponse, SendPay, SignMessageResponse, WaitIndexResponse,
+    WalletTransactionsResponse, WithdrawResponse,
 };
+use super::rune::Rune;
 use super::ClnConfig;
 use crate::cln::types::Invoice;
 use crate::types::NodeInfo;
 use crate::{
-    calculate_fee_msats, ApiError, CreateOfferParams, InvoiceType, Offer, OnInvoiceEventCallback, OnInvoiceEventParams,
-    PayInvoiceParams, PayInvoiceResponse, Transaction,
+    retry_after_secs, AccountEvent, AccountEventType, ApiError, Channel, CloseChannelRequest, CloseChannelResponse, DecodedPaymentRequest, CreateOfferParams, Direction, InvoiceType, ListTransactionsParams, NodeCapabilities, Offer, OnInvoiceEventCallback,
+    OnInvoiceEventParams, OnchainBalanceResponse, OnchainTransaction, OpenChannelRequest, OutgoingPayment, OpenChannelResponse, PayInvoiceParams, PayInvoiceResponse,
+    PaymentFeeEstimate, PayKeysendResponse, PendingBalanceDetails, SendOnchainResponse, SubscribeAllCallback, SubscribeAllParams, TLVRecord, Transaction, TransactionPage, TransactionStatus,
+    UpdateChannelRequest,
 };
 use reqwest::header;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -56,6 +64,32 @@ fn clnrest_client(config: &ClnConfig) -> reqwest::Client {
         .unwrap_or_else(|_| reqwest::Client::new())
 }
 
+// Map lightningd error codes into structured errors
+// https://github.com/ElementsProject/lightning/blob/master/common/jsonrpc_errors.h
+fn cln_error(status: reqwest::StatusCode, retry_after: Option<i64>, body: String) -> ApiError {
+    let (code, reason) = match serde_json::from_str::<ClnError>(&body) {
+        Ok(ClnError { code, message: Some(message) }) => (code.unwrap_or_default(), message),
+        _ => (0, body),
+    };
+    match code {
+        200 => ApiError::PaymentInFlight { reason },      // PAY_IN_PROGRESS
+        201 => ApiError::AlreadyPaid { reason },          // PAY_RHASH_ALREADY_USED
+        205 => ApiError::NoRoute { reason },              // PAY_ROUTE_NOT_FOUND
+        207 => ApiError::InvoiceExpired { reason },       // PAY_INVOICE_EXPIRED
+        301 => ApiError::InsufficientBalance { reason },  // FUND_CANNOT_AFFORD
+        -32601 => ApiError::NotSupported { reason },      // JSONRPC2_METHOD_NOT_FOUND
+        1501 | 1502 => ApiError::Unauthorized { reason }, // rune rejected
+        _ => ApiError::from_http(status, retry_after, reason),
+    }
+}
+
+async fn error_from_response(response: reqwest::Response) -> ApiError {
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    cln_error(status, retry_after, body)
+}
+
 pub async fn get_info(config: ClnConfig) -> Result<NodeInfo, ApiError> {
     let req_url = format!("{}/v1/getinfo", config.url);
     let client = clnrest_client(&config);
@@ -64,9 +98,10 @@ pub async fn get_info(config: ClnConfig) -> Result<NodeInfo, ApiError> {
         .header("Content-Type", "application/json")
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to get node info: {}", e),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to get node info"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     let response_text = response.text().await.map_err(|e| ApiError::Http {
         reason: format!("Failed to read node info response: {}", e),
     })?;
@@ -79,9 +114,10 @@ pub async fn get_info(config: ClnConfig) -> Result<NodeInfo, ApiError> {
         .header("Content-Type", "application/json")
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to get funds info: {}", e),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to get funds info"))?;
+    if !funds_response.status().is_success() {
+        return Err(error_from_response(funds_response).await);
+    }
     let funds_response_text = funds_response.text().await.map_err(|e| ApiError::Http {
         reason: format!("Failed to read funds response: {}", e),
     })?;
@@ -159,6 +195,17 @@ pub async fn create_invoice(
     ));
     match invoice_type {
         InvoiceType::Bolt11 => {
+            // invoice can only put the hash of the description it gets in place of the description
+            if let Some(description_hash) = description_hash.as_deref() {
+                let hash = crate::description_hash_bytes(description_hash)?;
+                let described = Sha256::digest(description.as_deref().unwrap_or_default().as_bytes());
+                if described.as_slice() != hash.as_slice() {
+                    return Err(ApiError::InvalidInput(
+                        "CLN needs the description that description_hash is the hash of".to_string(),
+                    ));
+                }
+                params.push(("deschashonly", Some("true".to_string())));
+            }
             let req_url = format!("{}/v1/invoice", config.url);
             let response = client
                 .post(&req_url)
@@ -169,9 +216,10 @@ pub async fn create_invoice(
                     .collect::<serde_json::Value>()))
                 .send()
                 .await
-                .map_err(|e| ApiError::Http {
-                    reason: format!("Failed to create invoice: {}", e),
-                })?;
+                .map_err(|e| ApiError::from_reqwest(e, "Failed to create invoice"))?;
+            if !response.status().is_success() {
+                return Err(error_from_response(response).await);
+            }
 
             let invoice_str = response.text().await.map_err(|e| ApiError::Http {
                 reason: format!("Failed to read invoice response: {}", e),
@@ -184,6 +232,8 @@ pub async fn create_invoice(
 
             Ok(Transaction {
                 type_: "incoming".to_string(),
+                status: TransactionStatus::Pending,
+                direction: Direction::Incoming,
                 invoice: bolt11_resp.bolt11,
                 preimage: "".to_string(),
                 payment_hash: bolt11_resp.payment_hash,
@@ -213,6 +263,8 @@ pub async fn create_invoice(
             .await?;
             Ok(Transaction {
                 type_: "incoming".to_string(),
+                status: TransactionStatus::Pending,
+                direction: Direction::Incoming,
                 invoice: fetch_invoice_resp.invoice,
                 preimage: "".to_string(),
                 payment_hash: "".to_string(),
@@ -230,9 +282,37 @@ pub async fn create_invoice(
     }
 }
 
+// pay has maxfee and retry_for but no say in how the route is built
+pub(crate) const PAY_PARAMS: crate::PayParamSupport = crate::PayParamSupport {
+    amount: true,
+    fee_limit: true,
+    timeout: true,
+    ..crate::PayParamSupport::NONE
+};
+
 pub async fn pay_invoice(
     config: ClnConfig,
     invoice_params: PayInvoiceParams,
+) -> Result<PayInvoiceResponse, ApiError> {
+    let fee_limit_msat = crate::check_pay_params(&invoice_params, "CLN", PAY_PARAMS)?;
+    if invoice_params.wait == Some(false) {
+        // pay only answers once the payment is final or retry_for ran out
+        let invoice = invoice_params.invoice.clone();
+        let timeout_seconds = invoice_params.timeout_seconds;
+        let lookup_config = config.clone();
+        let pay = send_payment(config, invoice_params, fee_limit_msat);
+        return crate::dispatch_payment(&invoice, timeout_seconds, pay, |payment_hash| {
+            lookup_payment(lookup_config.clone(), payment_hash)
+        })
+        .await;
+    }
+    send_payment(config, invoice_params, fee_limit_msat).await
+}
+
+async fn send_payment(
+    config: ClnConfig,
+    invoice_params: PayInvoiceParams,
+    fee_limit_msat: Option<i64>,
 ) -> Result<PayInvoiceResponse, ApiError> {
     let client = clnrest_client(&config);
     let pay_url = format!("{}/v1/pay", config.url);
@@ -251,27 +331,9 @@ pub async fn pay_invoice(
         ))
     });
 
-    // calculate fee limit
-    if invoice_params.fee_limit_msat.is_some() && invoice_params.fee_limit_percentage.is_some() {
-        return Err(ApiError::Json {
-            reason: "Cannot set both fee_limit_msat and fee_limit_percentage".to_string(),
-        });
-    }
-    invoice_params.fee_limit_msat.map(|amt| {
+    if let Some(amt) = fee_limit_msat {
         params.push(("maxfee", Some(serde_json::Value::String(amt.to_string()))));
-    });
-    invoice_params.fee_limit_percentage.map(|fee_percentage| {
-        let fee_msats = calculate_fee_msats(
-            invoice_params.invoice.as_str(),
-            fee_percentage,
-            invoice_params.amount_msats.map(|v| v as u64),
-        )
-        .unwrap();
-        params.push((
-            "maxfee",
-            Some(serde_json::Value::String(fee_msats.to_string())),
-        ));
-    });
+    }
     invoice_params.timeout_seconds.map(|timeout| {
         params.push((
             "retry_for",
@@ -291,9 +353,10 @@ pub async fn pay_invoice(
         .json(&params_json)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to pay invoice: {}", e),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to pay invoice"))?;
+    if !pay_response.status().is_success() {
+        return Err(error_from_response(pay_response).await);
+    }
     let pay_response_text = pay_response.text().await.map_err(|e| ApiError::Http {
         reason: format!("Failed to read pay response: {}", e),
     })?;
@@ -308,32 +371,120 @@ pub async fn pay_invoice(
     };
 
     Ok(PayInvoiceResponse {
+        status: pay_status(&pay_resp.status),
         payment_hash: pay_resp.payment_hash,
         preimage: pay_resp.payment_preimage,
         fee_msats: pay_resp.amount_sent_msat - pay_resp.amount_msat,
     })
 }
 
-// decode - bolt11 invoice (lnbc) bolt12 invoice (lni) or bolt12 offer (lno)
-pub async fn decode(config: ClnConfig, str: String) -> Result<String, ApiError> {
+// getroute finds the cheapest route through the public graph, it doesn't see the invoice's
+// route hints so payees behind private channels come back without a route
+pub async fn estimate_payment_fee(
+    config: ClnConfig,
+    invoice: String,
+    amount_msats: Option<i64>,
+) -> Result<PaymentFeeEstimate, ApiError> {
+    let (decoded, amount) = crate::estimate_target(&invoice, amount_msats)?;
     let client = clnrest_client(&config);
-    let req_url = format!("{}/v1/decode", config.url);
+    let getroute_url = format!("{}/v1/getroute", config.url);
     let response = client
-        .post(&req_url)
+        .post(&getroute_url)
         .header("Content-Type", "application/json")
         .json(&serde_json::json!({
-            "string": str,
+            "id": decoded.payee_pubkey,
+            "amount_msat": amount,
+            "riskfactor": 10, // pay's default
         }))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to decode: {}", e),
-        })?;
-    // TODO parse JSON response
-    let decoded = response.text().await.map_err(|e| ApiError::Http {
-        reason: format!("Failed to read decode response: {}", e),
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to get route"))?;
+    if !response.status().is_success() {
+        return crate::no_route_estimate(Err(error_from_response(response).await));
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read getroute response: {}", e),
+    })?;
+    let route: GetRouteResponse = serde_json::from_str(&response_text)?;
+    let fee_msats = route.route.first().map_or(0, |hop| hop.amount_msat - amount);
+    Ok(PaymentFeeEstimate {
+        min_fee_msats: fee_msats,
+        max_fee_msats: fee_msats,
+        route_found: !route.route.is_empty(),
+    })
+}
+
+// Signs with the node identity key, returns the zbase32 form lnd also uses
+pub async fn sign_message(config: ClnConfig, message: String) -> Result<String, ApiError> {
+    let client = clnrest_client(&config);
+    let signmessage_url = format!("{}/v1/signmessage", config.url);
+    let response = client
+        .post(&signmessage_url)
+        .header("Content-Type", "application/json")
+        .json(&serde_json::json!({ "message": message }))
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to sign message"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read signmessage response: {}", e),
+    })?;
+    let signed: SignMessageResponse = serde_json::from_str(&response_text)?;
+    Ok(signed.zbase)
+}
+
+// keysend generates the preimage itself, custom records go in extratlvs keyed by type
+pub async fn pay_keysend(
+    config: ClnConfig,
+    destination_pubkey: String,
+    amount_msats: i64,
+    custom_records: Vec<TLVRecord>,
+) -> Result<PayKeysendResponse, ApiError> {
+    crate::validate_tlv_records(&custom_records)?;
+    let client = clnrest_client(&config);
+    let keysend_url = format!("{}/v1/keysend", config.url);
+
+    let mut params = serde_json::json!({
+        "destination": destination_pubkey,
+        "amount_msat": amount_msats,
+    });
+    if !custom_records.is_empty() {
+        params["extratlvs"] = custom_records
+            .iter()
+            .map(|r| (r.type_.to_string(), serde_json::Value::String(r.value.clone())))
+            .collect::<serde_json::Map<String, _>>()
+            .into();
+    }
+
+    let response = client
+        .post(&keysend_url)
+        .header("Content-Type", "application/json")
+        .json(&params)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to send keysend"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read keysend response: {}", e),
+    })?;
+    let pay_resp: PayResponse = serde_json::from_str(&response_text).map_err(|_| ApiError::Json {
+        reason: response_text.to_string(),
     })?;
-    Ok(decoded)
+
+    Ok(PayKeysendResponse {
+        payment_hash: pay_resp.payment_hash,
+        preimage: pay_resp.payment_preimage,
+        fee_msats: pay_resp.amount_sent_msat - pay_resp.amount_msat,
+    })
+}
+
+// Decoded locally rather than with clnrest /v1/decode so it also works offline
+pub async fn decode(_config: ClnConfig, str: String) -> Result<DecodedPaymentRequest, ApiError> {
+    crate::decode_payment_request(str)
 }
 
 // get the one with the offer_id or label or get the first offer in the list
@@ -372,9 +523,10 @@ pub async fn list_offers(
             .collect::<serde_json::Value>()))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to list offers: {}", e),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to list offers"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     let offers = response.text().await.map_err(|e| ApiError::Http {
         reason: format!("Failed to read offers response: {}", e),
     })?;
@@ -415,9 +567,10 @@ pub async fn create_offer(
         .json(&json_params)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to create offer: {}", e),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to create offer"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
         
     let offer_str = response.text().await.map_err(|e| ApiError::Http {
         reason: format!("Failed to read offer response: {}", e),
@@ -458,9 +611,10 @@ async fn fetch_invoice_from_offer(
         }))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to fetch invoice: {}", e),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to fetch invoice"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     let response_text = response.text().await.map_err(|e| ApiError::Http {
         reason: format!("Failed to read fetch invoice response: {}", e),
     })?;
@@ -503,9 +657,10 @@ pub async fn pay_offer(
         }))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to pay offer: {}", e),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to pay offer"))?;
+    if !pay_response.status().is_success() {
+        return Err(error_from_response(pay_response).await);
+    }
     let pay_response_text = pay_response.text().await.map_err(|e| ApiError::Http {
         reason: format!("Failed to read pay offer response: {}", e),
     })?;
@@ -520,12 +675,22 @@ pub async fn pay_offer(
     };
 
     Ok(PayInvoiceResponse {
+        status: pay_status(&pay_resp.status),
         payment_hash: pay_resp.payment_hash,
         preimage: pay_resp.payment_preimage,
         fee_msats: pay_resp.amount_sent_msat - pay_resp.amount_msat,
     })
 }
 
+// pay reports "pending" when retry_for ran out with parts still in flight
+fn pay_status(status: &str) -> TransactionStatus {
+    match status {
+        "complete" => TransactionStatus::Settled,
+        "failed" => TransactionStatus::Failed,
+        _ => TransactionStatus::Pending,
+    }
+}
+
 // Looks up invoice by payment_hash or search field, or returns latest invoice
 pub async fn lookup_invoice(
     config: ClnConfig,
@@ -548,6 +713,23 @@ pub async fn lookup_invoice(
     }
 }
 
+// listpays keeps every attempt at a payment hash, the one that went through (or is still
+// going) is what counts
+pub async fn lookup_payment(config: ClnConfig, payment_hash: String) -> Result<OutgoingPayment, ApiError> {
+    let attempts = list_pays(&config, Some(payment_hash.clone())).await?;
+    let payment = attempts
+        .into_iter()
+        .max_by_key(|txn| match txn.status {
+            TransactionStatus::Settled => 2,
+            TransactionStatus::Pending => 1,
+            _ => 0,
+        })
+        .ok_or_else(|| ApiError::Api {
+            reason: format!("Payment not found for payment hash {}", payment_hash),
+        })?;
+    Ok(OutgoingPayment::from_transaction(payment, None))
+}
+
 async fn lookup_invoices(
     config: &ClnConfig,
     payment_hash: Option<String>,
@@ -583,9 +765,10 @@ async fn lookup_invoices(
             }))
             .send()
             .await
-            .map_err(|e| ApiError::Http {
-                reason: format!("Failed to query invoices: {}", e),
-            })?;
+            .map_err(|e| ApiError::from_reqwest(e, "Failed to query invoices"))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response).await);
+        }
         let response_text = response.text().await.map_err(|e| ApiError::Http {
             reason: format!("Failed to read invoices response: {}", e),
         })?;
@@ -640,23 +823,28 @@ async fn lookup_invoices(
             let mut transactions: Vec<Transaction> = incoming_payments
                 .invoices
                 .into_iter()
-                .map(|inv| Transaction {
-                    type_: "incoming".to_string(),
-                    invoice: inv
+                .map(|inv| {
+                    let invoice = inv
                         .bolt11
                         .clone()
-                        .unwrap_or_else(|| inv.bolt12.clone().unwrap_or_default()),
-                    preimage: inv.payment_preimage.unwrap_or_default(),
-                    payment_hash: inv.payment_hash,
-                    amount_msats: inv.amount_received_msat.unwrap_or(0),
-                    fees_paid: 0,
-                    created_at: 0, // TODO: parse if available
-                    expires_at: inv.expires_at,
-                    settled_at: inv.paid_at.unwrap_or(0),
-                    description: inv.description.unwrap_or_default(),
-                    description_hash: "".to_string(),
-                    payer_note: Some(inv.invreq_payer_note.unwrap_or_default()),
-                    external_id: Some(inv.label),
+                        .unwrap_or_else(|| inv.bolt12.clone().unwrap_or_default());
+                    Transaction {
+                        type_: "incoming".to_string(),
+                        status: invoice_status(&inv.status),
+                        direction: Direction::Incoming,
+                        created_at: invoice_created_at(&invoice),
+                        invoice,
+                        preimage: inv.payment_preimage.unwrap_or_default(),
+                        payment_hash: inv.payment_hash,
+                        amount_msats: inv.amount_received_msat.unwrap_or(0),
+                        fees_paid: 0,
+                        expires_at: inv.expires_at,
+                        settled_at: inv.paid_at.unwrap_or(0),
+                        description: inv.description.unwrap_or_default(),
+                        description_hash: "".to_string(),
+                        payer_note: Some(inv.invreq_payer_note.unwrap_or_default()),
+                        external_id: Some(inv.label),
+                    }
                 })
                 .collect();
             transactions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
@@ -696,9 +884,10 @@ async fn lookup_invoices(
             .collect::<serde_json::Value>()))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: format!("Failed to list invoices: {}", e),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to list invoices"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     let response_text = response.text().await.map_err(|e| ApiError::Http {
         reason: format!("Failed to read list invoices response: {}", e),
     })?;
@@ -712,23 +901,7 @@ async fn lookup_invoices(
     let mut transactions: Vec<Transaction> = incoming_payments
         .invoices
         .into_iter()
-        .map(|inv| {
-            Transaction {
-                type_: "incoming".to_string(),
-                invoice: inv.bolt11.unwrap_or_else(|| inv.bolt12.unwrap_or_default()),
-                preimage: inv.payment_preimage.unwrap_or("".to_string()),
-                payment_hash: inv.payment_hash,
-                amount_msats: inv.amount_received_msat.unwrap_or(0),
-                fees_paid: 0,
-                created_at: 0, // TODO
-                expires_at: inv.expires_at,
-                settled_at: inv.paid_at.unwrap_or(0),
-                description: inv.description.unwrap_or("".to_string()),
-                description_hash: "".to_string(),
-                payer_note: Some(inv.invreq_payer_note.unwrap_or("".to_string())),
-                external_id: Some(inv.label),
-            }
-        })
+        .map(invoice_transaction)
         .collect();
 
     // Sort by created date descending
@@ -737,16 +910,460 @@ async fn lookup_invoices(
     Ok(transactions)
 }
 
+fn invoice_transaction(inv: Invoice) -> Transaction {
+    let invoice = inv.bolt11.unwrap_or_else(|| inv.bolt12.unwrap_or_default());
+    Transaction {
+        type_: "incoming".to_string(),
+        status: invoice_status(&inv.status),
+        direction: Direction::Incoming,
+        created_at: invoice_created_at(&invoice),
+        invoice,
+        preimage: inv.payment_preimage.unwrap_or("".to_string()),
+        payment_hash: inv.payment_hash,
+        amount_msats: inv.amount_received_msat.unwrap_or(0),
+        fees_paid: 0,
+        expires_at: inv.expires_at,
+        settled_at: inv.paid_at.unwrap_or(0),
+        description: inv.description.unwrap_or("".to_string()),
+        description_hash: "".to_string(),
+        payer_note: Some(inv.invreq_payer_note.unwrap_or("".to_string())),
+        external_id: Some(inv.label),
+    }
+}
+
+// listinvoices status is "unpaid", "paid" or "expired"
+fn invoice_status(status: &str) -> TransactionStatus {
+    match status {
+        "paid" => TransactionStatus::Settled,
+        "expired" => TransactionStatus::Expired,
+        _ => TransactionStatus::Pending,
+    }
+}
+
+// Invoices and payments merged newest first, read a page at a time down their
+// created_index until `from + limit` are in hand. A limit of 0 reads everything.
 pub async fn list_transactions(
     config: ClnConfig,
     from: i64,
     limit: i64,
     search: Option<String>,
 ) -> Result<Vec<Transaction>, ApiError> {
-    match lookup_invoices(&config, None, Some(from), Some(limit), search).await {
-        Ok(transactions) => Ok(transactions),
-        Err(e) => Err(e),
+    let from = from.max(0) as usize;
+    let wanted = (limit > 0).then(|| from + limit as usize);
+    let mut transactions: Vec<Transaction> = vec![];
+    let mut cursor = None;
+    loop {
+        let page = list_transactions_page(
+            config.clone(),
+            ListTransactionsParams {
+                from: 0,
+                limit: wanted.map_or(100, |wanted| wanted - transactions.len()) as i64,
+                payment_hash: None,
+                search: search.clone(),
+                created_after: None,
+                created_before: None,
+                cursor,
+            },
+        )
+        .await?;
+        transactions.extend(page.transactions);
+        cursor = page.next_cursor;
+        if cursor.is_none() || wanted.is_some_and(|wanted| transactions.len() >= wanted) {
+            break;
+        }
     }
+    Ok(transactions
+        .into_iter()
+        .skip(from)
+        .take(wanted.map_or(usize::MAX, |wanted| wanted - from))
+        .collect())
+}
+
+// Invoices and sendpays are both walked down their created_index, which only ever grows,
+// so new ones can't shift a page. 0 before the first page, 1 once a list is used up.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ClnCursor {
+    invoices_before: i64,
+    sendpays_before: i64,
+}
+
+pub async fn list_transactions_page(
+    config: ClnConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    crate::search_page(params, |params| transactions_page(config.clone(), params)).await
+}
+
+async fn transactions_page(
+    config: ClnConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    let limit = crate::page_limit(params.limit)?;
+    let mut cursor: ClnCursor = match params.cursor.as_deref() {
+        Some(cursor) => crate::decode_cursor(cursor)?,
+        None => ClnCursor {
+            invoices_before: created_index(&config, "invoices").await? + 1,
+            sendpays_before: created_index(&config, "sendpays").await? + 1,
+        },
+    };
+
+    let (invoices, invoices_exhausted) =
+        list_before::<Invoice>(&config, "listinvoices", cursor.invoices_before, limit).await?;
+    let (invoice_indexes, invoices): (Vec<i64>, Vec<Transaction>) = invoices
+        .into_iter()
+        .map(|(index, inv)| (index, invoice_transaction(inv)))
+        .unzip();
+    let (sendpays, sendpays_exhausted) =
+        list_before::<SendPay>(&config, "listsendpays", cursor.sendpays_before, limit).await?;
+    let lowest = sendpays.iter().map(|(index, _)| *index).min();
+    let sendpays = complete_payments(&config, sendpays).await?;
+    let (sendpay_indexes, sendpays): (Vec<i64>, Vec<Transaction>) = group_sendpays(sendpays)
+        .into_iter()
+        // a payment that started below the window is listed with the window it started in
+        .filter(|(index, _)| lowest.is_some_and(|lowest| *index >= lowest))
+        .unzip();
+
+    let (transactions, taken) = crate::merge_newest_first(vec![invoices, sendpays], limit);
+    cursor.invoices_before =
+        next_before(cursor.invoices_before, &invoice_indexes, taken[0], invoices_exhausted);
+    cursor.sendpays_before = match lowest {
+        // the window only held later parts of older payments
+        Some(lowest) if sendpay_indexes.is_empty() => lowest,
+        _ => next_before(cursor.sendpays_before, &sendpay_indexes, taken[1], sendpays_exhausted),
+    };
+
+    Ok(TransactionPage {
+        transactions,
+        next_cursor: if cursor.invoices_before <= 1 && cursor.sendpays_before <= 1 {
+            None
+        } else {
+            Some(crate::encode_cursor(&cursor))
+        },
+    })
+}
+
+// The latest created_index of a `wait` subsystem, 0 when nothing was created yet
+async fn created_index(config: &ClnConfig, subsystem: &str) -> Result<i64, ApiError> {
+    wait_index(config, subsystem, "created").await
+}
+
+// The latest "created" or "updated" index of a `wait` subsystem
+async fn wait_index(config: &ClnConfig, subsystem: &str, indexname: &str) -> Result<i64, ApiError> {
+    let client = clnrest_client(config);
+    let wait_url = format!("{}/v1/wait", config.url);
+    let response = client
+        .post(&wait_url)
+        .header("Content-Type", "application/json")
+        .json(&serde_json::json!({
+            "subsystem": subsystem,
+            "indexname": indexname,
+            "nextvalue": 0,
+        }))
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to read wait index"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read wait response: {}", e),
+    })?;
+    let wait: WaitIndexResponse = serde_json::from_str(&response_text).map_err(|e| ApiError::Json {
+        reason: e.to_string(),
+    })?;
+    let index = if indexname == "updated" { wait.updated } else { wait.created };
+    Ok(index.unwrap_or(0))
+}
+
+// At least `limit` entries with a created_index below `before` if there are that many,
+// newest first, and whether the start of the list was reached. The list methods only
+// page upwards, so this steps down a window at a time; deleted entries leave gaps,
+// hence the loop.
+async fn list_before<T: CreatedIndex>(
+    config: &ClnConfig,
+    method: &str,
+    before: i64,
+    limit: usize,
+) -> Result<(Vec<(i64, T)>, bool), ApiError> {
+    let client = clnrest_client(config);
+    let list_url = format!("{}/v1/{}", config.url, method);
+    let mut entries: Vec<(i64, T)> = vec![];
+    let mut before = before;
+    while entries.len() < limit && before > 1 {
+        let start = (before - limit as i64).max(1);
+        let response = client
+            .post(&list_url)
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({
+                "index": "created",
+                "start": start,
+                "limit": before - start,
+            }))
+            .send()
+            .await
+            .map_err(|e| ApiError::from_reqwest(e, "Failed to list transactions"))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response).await);
+        }
+        let response_text = response.text().await.map_err(|e| ApiError::Http {
+            reason: format!("Failed to read {} response: {}", method, e),
+        })?;
+        let mut window = T::from_list_response(&response_text)?
+            .into_iter()
+            .map(|entry| (entry.created_index(), entry))
+            .filter(|(index, _)| *index < before)
+            .collect::<Vec<_>>();
+        window.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+        entries.extend(window);
+        before = start;
+    }
+    Ok((entries, before <= 1))
+}
+
+trait CreatedIndex: Sized {
+    fn created_index(&self) -> i64;
+    fn from_list_response(response_text: &str) -> Result<Vec<Self>, ApiError>;
+}
+
+impl CreatedIndex for Invoice {
+    fn created_index(&self) -> i64 {
+        self.created_index as i64
+    }
+    fn from_list_response(response_text: &str) -> Result<Vec<Self>, ApiError> {
+        let response: InvoicesResponse = serde_json::from_str(response_text).map_err(|e| ApiError::Json {
+            reason: e.to_string(),
+        })?;
+        Ok(response.invoices)
+    }
+}
+
+impl CreatedIndex for SendPay {
+    fn created_index(&self) -> i64 {
+        self.created_index
+    }
+    fn from_list_response(response_text: &str) -> Result<Vec<Self>, ApiError> {
+        let response: ListSendpaysResponse =
+            serde_json::from_str(response_text).map_err(|e| ApiError::Json {
+                reason: e.to_string(),
+            })?;
+        Ok(response.payments)
+    }
+}
+
+// Where a list continues after `taken` of the `indexes` fetched for this page
+fn next_before(before: i64, indexes: &[i64], taken: usize, exhausted: bool) -> i64 {
+    if exhausted && taken == indexes.len() {
+        // nothing older was left
+        1
+    } else if taken > 0 {
+        indexes[taken - 1]
+    } else {
+        before
+    }
+}
+
+// The parts of a multi-part payment can sit on both sides of a window's edge, the rest of
+// them are looked up by payment hash so the payment isn't split into partial transactions
+async fn complete_payments(
+    config: &ClnConfig,
+    mut parts: Vec<(i64, SendPay)>,
+) -> Result<Vec<(i64, SendPay)>, ApiError> {
+    let split: HashSet<String> = parts
+        .iter()
+        .filter(|(_, part)| part.partid.unwrap_or(0) > 0)
+        .map(|(_, part)| part.payment_hash.clone())
+        .collect();
+    for payment_hash in split {
+        for part in list_sendpays(config, &payment_hash).await? {
+            let known = parts.iter().any(|(index, _)| *index == part.created_index);
+            let same_attempt = parts
+                .iter()
+                .any(|(_, p)| p.payment_hash == part.payment_hash && p.groupid == part.groupid);
+            if !known && same_attempt {
+                parts.push((part.created_index, part));
+            }
+        }
+    }
+    Ok(parts)
+}
+
+async fn list_sendpays(config: &ClnConfig, payment_hash: &str) -> Result<Vec<SendPay>, ApiError> {
+    let response = clnrest_client(config)
+        .post(format!("{}/v1/listsendpays", config.url))
+        .header("Content-Type", "application/json")
+        .json(&serde_json::json!({ "payment_hash": payment_hash }))
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to list payment parts"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read listsendpays response: {}", e),
+    })?;
+    SendPay::from_list_response(&response_text)
+}
+
+// Payment hash and groupid of each part by its created_index, what identifies its attempt
+fn sendpay_keys(parts: &[(i64, SendPay)]) -> HashMap<i64, (String, Option<i64>)> {
+    parts
+        .iter()
+        .map(|(index, part)| (*index, (part.payment_hash.clone(), part.groupid)))
+        .collect()
+}
+
+// Each attempt becomes one transaction at the index of its oldest part
+fn group_sendpays(parts: Vec<(i64, SendPay)>) -> Vec<(i64, Transaction)> {
+    let mut groups: Vec<(i64, Vec<SendPay>)> = vec![];
+    for (index, part) in parts {
+        let key = (part.payment_hash.clone(), part.groupid);
+        match groups
+            .iter_mut()
+            .find(|(_, g)| (g[0].payment_hash.clone(), g[0].groupid) == key)
+        {
+            Some(group) => {
+                group.0 = group.0.min(index);
+                group.1.push(part);
+            }
+            None => groups.push((index, vec![part])),
+        }
+    }
+    groups.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+    groups
+        .into_iter()
+        .map(|(index, parts)| (index, sendpay_transaction(parts)))
+        .collect()
+}
+
+fn sendpay_transaction(parts: Vec<SendPay>) -> Transaction {
+    let status = if parts.iter().any(|p| p.status == "complete") {
+        TransactionStatus::Settled
+    } else if parts.iter().any(|p| p.status == "pending") {
+        TransactionStatus::Pending
+    } else {
+        TransactionStatus::Failed
+    };
+    let settled = matches!(status, TransactionStatus::Settled);
+    let amount_msats: i64 = parts.iter().filter_map(|p| p.amount_msat).sum();
+    let amount_sent_msats: i64 = parts.iter().filter_map(|p| p.amount_sent_msat).sum();
+    let first = &parts[0];
+    let invoice = first.bolt11.clone().or_else(|| first.bolt12.clone()).unwrap_or_default();
+    let decoded = if invoice.is_empty() {
+        None
+    } else {
+        crate::decode_payment_request(invoice.clone()).ok()
+    };
+    Transaction {
+        type_: "outgoing".to_string(),
+        direction: Direction::Outgoing,
+        invoice,
+        preimage: parts
+            .iter()
+            .find_map(|p| p.payment_preimage.clone())
+            .unwrap_or_default(),
+        payment_hash: first.payment_hash.clone(),
+        amount_msats,
+        fees_paid: if settled { amount_sent_msats - amount_msats } else { 0 },
+        created_at: parts.iter().map(|p| p.created_at).min().unwrap_or(0),
+        expires_at: decoded.as_ref().map(|d| d.expires_at).unwrap_or_default(),
+        settled_at: if settled {
+            parts.iter().filter_map(|p| p.completed_at).max().unwrap_or(0)
+        } else {
+            0
+        },
+        description: first
+            .description
+            .clone()
+            .or_else(|| decoded.as_ref().and_then(|d| d.description.clone()))
+            .unwrap_or_default(),
+        description_hash: decoded
+            .and_then(|d| d.description_hash)
+            .unwrap_or_default(),
+        payer_note: Some("".to_string()),
+        external_id: Some(first.label.clone().unwrap_or_default()),
+        status,
+    }
+}
+
+async fn list_pays(config: &ClnConfig, payment_hash: Option<String>) -> Result<Vec<Transaction>, ApiError> {
+    let client = clnrest_client(config);
+    let list_pays_url = format!("{}/v1/listpays", config.url);
+    let mut params = serde_json::json!({});
+    if let Some(payment_hash) = payment_hash {
+        params["payment_hash"] = serde_json::Value::String(payment_hash);
+    }
+    let response = client
+        .post(&list_pays_url)
+        .header("Content-Type", "application/json")
+        .json(&params)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to list pays"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read list pays response: {}", e),
+    })?;
+    let pays: ListPaysResponse = serde_json::from_str(&response_text).map_err(|e| ApiError::Json {
+        reason: e.to_string(),
+    })?;
+
+    let transactions = pays
+        .pays
+        .into_iter()
+        .map(|pay| {
+            let invoice = pay.bolt11.or(pay.bolt12).unwrap_or_default();
+            let decoded = if invoice.is_empty() {
+                None
+            } else {
+                crate::decode_payment_request(invoice.clone()).ok()
+            };
+            let complete = pay.status == "complete";
+            let amount_msats = pay.amount_msat.or(pay.amount_sent_msat).unwrap_or(0);
+            Transaction {
+                type_: "outgoing".to_string(),
+                status: match pay.status.as_str() {
+                    "complete" => TransactionStatus::Settled,
+                    "failed" => TransactionStatus::Failed,
+                    _ => TransactionStatus::Pending,
+                },
+                direction: Direction::Outgoing,
+                invoice,
+                preimage: pay.preimage.unwrap_or_default(),
+                payment_hash: pay.payment_hash,
+                amount_msats,
+                fees_paid: if complete {
+                    pay.amount_sent_msat.map_or(0, |sent| sent - amount_msats)
+                } else {
+                    0
+                },
+                created_at: pay.created_at,
+                expires_at: decoded.as_ref().map(|d| d.expires_at).unwrap_or_default(),
+                settled_at: if complete { pay.completed_at.unwrap_or(0) } else { 0 },
+                description: pay
+                    .description
+                    .or_else(|| decoded.as_ref().and_then(|d| d.description.clone()))
+                    .unwrap_or_default(),
+                description_hash: decoded
+                    .and_then(|d| d.description_hash)
+                    .unwrap_or_default(),
+                payer_note: Some("".to_string()),
+                external_id: Some(pay.label.unwrap_or_default()),
+            }
+        })
+        .collect();
+    Ok(transactions)
+}
+
+// listinvoices has no creation time, the invoice itself carries one
+fn invoice_created_at(invoice: &str) -> i64 {
+    if invoice.is_empty() {
+        return 0;
+    }
+    crate::decode_payment_request(invoice.to_string())
+        .map(|decoded| decoded.created_at)
+        .unwrap_or(0)
 }
 
 // Core logic shared by both implementations
@@ -775,7 +1392,7 @@ pub async fn poll_invoice_events<F>(
         .await
         {
             Ok(transaction) => {
-                if transaction.settled_at > 0 {
+                if transaction.status == TransactionStatus::Settled {
                     ("settled".to_string(), Some(transaction))
                 } else {
                     ("pending".to_string(), Some(transaction))
@@ -802,12 +1419,106 @@ pub async fn poll_invoice_events<F>(
     }
 }
 
+// waitinvoice returns as soon as the invoice is paid or expires, so a settle is reported
+// right away instead of on the next poll. Polling is the fallback when it can't be used,
+// e.g. with a rune that doesn't allow it.
+pub async fn invoice_events<F>(config: ClnConfig, params: OnInvoiceEventParams, mut callback: F)
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(params.max_polling_sec.max(0) as u64);
+    if let Ok(txn) = lookup_invoice(
+        config.clone(),
+        params.payment_hash.clone(),
+        None,
+        None,
+        params.search.clone(),
+    )
+    .await
+    {
+        if wait_invoice(&config, txn, deadline, &mut callback).await.is_ok() {
+            return;
+        }
+    }
+
+    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+    let params = OnInvoiceEventParams {
+        max_polling_sec: remaining.as_secs() as i64,
+        ..params
+    };
+    poll_invoice_events(config, params, callback).await;
+}
+
+// Reports the invoice state and waits for it to change, Ok once the invoice is final or
+// the deadline passed
+async fn wait_invoice<F>(
+    config: &ClnConfig,
+    txn: Transaction,
+    deadline: tokio::time::Instant,
+    callback: &mut F,
+) -> Result<(), ApiError>
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    match txn.status {
+        TransactionStatus::Settled => {
+            callback("success".to_string(), Some(txn));
+            return Ok(());
+        }
+        TransactionStatus::Expired => {
+            callback("failure".to_string(), Some(txn));
+            return Ok(());
+        }
+        _ => {}
+    }
+    let payment_hash = txn.payment_hash.clone();
+    let label = txn.external_id.clone().unwrap_or_default();
+    callback("pending".to_string(), Some(txn));
+
+    let client = clnrest_client(config);
+    let wait_url = format!("{}/v1/waitinvoice", config.url);
+    let request = client
+        .post(&wait_url)
+        .header("Content-Type", "application/json")
+        .json(&serde_json::json!({ "label": label }))
+        // the wait has to outlive the client's http_timeout
+        .timeout(deadline.saturating_duration_since(tokio::time::Instant::now()) + Duration::from_secs(5))
+        .send();
+    let response = match tokio::time::timeout_at(deadline, request).await {
+        Ok(response) => response.map_err(|e| ApiError::from_reqwest(e, "Failed to wait for invoice"))?,
+        Err(_) => {
+            // timeout
+            callback("failure".to_string(), None);
+            return Ok(());
+        }
+    };
+    let waited = response.status().is_success();
+    if !waited && response.status() != reqwest::StatusCode::INTERNAL_SERVER_ERROR {
+        // not an answer about the invoice, e.g. the rune doesn't allow waitinvoice
+        return Err(error_from_response(response).await);
+    }
+
+    // waitinvoice also errors once the invoice expires or is deleted, read back which it was
+    let txn = lookup_invoice(config.clone(), Some(payment_hash), None, None, None).await?;
+    match txn.status {
+        TransactionStatus::Settled => callback("success".to_string(), Some(txn)),
+        TransactionStatus::Expired => callback("failure".to_string(), Some(txn)),
+        _ if waited => {
+            return Err(ApiError::Api {
+                reason: "Invoice still open after waitinvoice".to_string(),
+            })
+        }
+        _ => return Err(error_from_response(response).await),
+    }
+    Ok(())
+}
+
 pub async fn on_invoice_events(
     config: ClnConfig,
     params: OnInvoiceEventParams,
     callback: std::sync::Arc<dyn OnInvoiceEventCallback>,
 ) {
-    poll_invoice_events(config, params, move |status, tx| match status.as_str() {
+    invoice_events(config, params, move |status, tx| match status.as_str() {
         "success" => callback.success(tx),
         "pending" => callback.pending(tx),
         "failure" => callback.failure(tx),
@@ -815,3 +1526,679 @@ pub async fn on_invoice_events(
     })
     .await;
 }
+
+// Every paid invoice and finished payment on the node. waitanyinvoice hands out paid invoices
+// in pay_index order, in between sendpays are listed forward by created_index. The resume
+// index is "pay_index:created_index".
+pub async fn subscribe_all(
+    config: ClnConfig,
+    params: SubscribeAllParams,
+    callback: std::sync::Arc<dyn SubscribeAllCallback>,
+) -> Result<(), ApiError> {
+    let mut feed = match params.resume_from.as_deref() {
+        Some(index) => {
+            let [pay_index, sendpay_index] = crate::events::parse_resume_index::<2>(index)?;
+            ClnAccountFeed {
+                pay_index,
+                sendpay_index,
+                in_flight: BTreeMap::new(),
+            }
+        }
+        None => ClnAccountFeed::start(&config).await?,
+    };
+    let mut backoff = crate::events::Backoff::new();
+    loop {
+        let polled = match feed.poll_sendpays(&config, callback.as_ref()).await {
+            Ok(()) => feed.wait_invoice(&config, params.polling_delay_sec.max(1), callback.as_ref()).await,
+            Err(e) => Err(e),
+        };
+        match polled {
+            Ok(()) => backoff.reset(),
+            Err(e) => backoff.retry(e).await?,
+        }
+    }
+}
+
+struct ClnAccountFeed {
+    pay_index: i64,
+    sendpay_index: i64, // every payment created up to here was listed
+    in_flight: BTreeMap<i64, (String, Option<i64>)>, // payment hash and groupid of the listed payments still going
+}
+
+impl ClnAccountFeed {
+    // Starts from now: after the latest paid invoice and the latest payment, following the
+    // payments that are in flight
+    async fn start(config: &ClnConfig) -> Result<Self, ApiError> {
+        let pay_index = latest_pay_index(config).await?;
+        let sendpay_index = created_index(config, "sendpays").await?;
+
+        let response = clnrest_client(config)
+            .post(format!("{}/v1/listsendpays", config.url))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "status": "pending" }))
+            .send()
+            .await
+            .map_err(|e| ApiError::from_reqwest(e, "Failed to list pending payments"))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response).await);
+        }
+        let response_text = response.text().await.map_err(|e| ApiError::Http {
+            reason: format!("Failed to read listsendpays response: {}", e),
+        })?;
+        let parts: Vec<_> = SendPay::from_list_response(&response_text)?
+            .into_iter()
+            .map(|part| (part.created_index, part))
+            .collect();
+        let keys = sendpay_keys(&parts);
+        let in_flight = group_sendpays(parts)
+            .into_iter()
+            .map(|(index, _)| (index, keys[&index].clone()))
+            .collect();
+        Ok(Self {
+            pay_index,
+            sendpay_index,
+            in_flight,
+        })
+    }
+
+    // Resuming lists the payments that are still in flight again, ones that finished after
+    // the oldest of them may be reported twice
+    fn resume_index(&self) -> String {
+        let sendpay_index = self
+            .in_flight
+            .keys()
+            .next()
+            .map_or(self.sendpay_index, |index| index - 1);
+        format!("{}:{}", self.pay_index, sendpay_index)
+    }
+
+    // Ok(()) also when nothing was paid within `timeout_sec`
+    async fn wait_invoice(
+        &mut self,
+        config: &ClnConfig,
+        timeout_sec: i64,
+        callback: &dyn SubscribeAllCallback,
+    ) -> Result<(), ApiError> {
+        let response = clnrest_client(config)
+            .post(format!("{}/v1/waitanyinvoice", config.url))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({
+                "lastpay_index": self.pay_index,
+                "timeout": timeout_sec,
+            }))
+            // the wait has to outlive the client's http_timeout
+            .timeout(Duration::from_secs(timeout_sec as u64 + 30))
+            .send()
+            .await
+            .map_err(|e| ApiError::from_reqwest(e, "Failed to wait for invoices"))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return match serde_json::from_str::<ClnError>(&body) {
+                Ok(ClnError { code: Some(904), .. }) => Ok(()), // WAIT_TIMEOUT
+                _ => Err(cln_error(status, None, body)),
+            };
+        }
+        let response_text = response.text().await.map_err(|e| ApiError::Http {
+            reason: format!("Failed to read waitanyinvoice response: {}", e),
+        })?;
+        let inv: Invoice = serde_json::from_str(&response_text)?;
+        self.pay_index = self.pay_index.max(inv.pay_index.unwrap_or_default() as i64);
+        callback.event(AccountEvent {
+            event_type: AccountEventType::InvoiceSettled,
+            transaction: invoice_transaction(inv),
+            resume_index: self.resume_index(),
+        });
+        Ok(())
+    }
+
+    // The payments that were in flight are looked up again, then the new ones are listed
+    async fn poll_sendpays(&mut self, config: &ClnConfig, callback: &dyn SubscribeAllCallback) -> Result<(), ApiError> {
+        for (index, (payment_hash, groupid)) in self.in_flight.clone() {
+            let parts: Vec<SendPay> = list_sendpays(config, &payment_hash)
+                .await?
+                .into_iter()
+                .filter(|part| part.groupid == groupid)
+                .collect();
+            if parts.is_empty() {
+                // deleted in the meantime
+                self.in_flight.remove(&index);
+                continue;
+            }
+            let txn = sendpay_transaction(parts);
+            if txn.status != TransactionStatus::Pending {
+                self.in_flight.remove(&index);
+                self.report(txn, callback);
+            }
+        }
+
+        loop {
+            let response = clnrest_client(config)
+                .post(format!("{}/v1/listsendpays", config.url))
+                .header("Content-Type", "application/json")
+                .json(&serde_json::json!({
+                    "index": "created",
+                    "start": self.sendpay_index + 1,
+                    "limit": 200,
+                }))
+                .send()
+                .await
+                .map_err(|e| ApiError::from_reqwest(e, "Failed to list payments"))?;
+            if !response.status().is_success() {
+                return Err(error_from_response(response).await);
+            }
+            let response_text = response.text().await.map_err(|e| ApiError::Http {
+                reason: format!("Failed to read listsendpays response: {}", e),
+            })?;
+            let parts: Vec<_> = SendPay::from_list_response(&response_text)?
+                .into_iter()
+                .map(|part| (part.created_index, part))
+                .collect();
+            let full_page = parts.len() == 200;
+            let newest = parts.iter().map(|(index, _)| *index).max();
+            let parts = complete_payments(config, parts).await?;
+            let keys = sendpay_keys(&parts);
+            let mut payments = group_sendpays(parts);
+            payments.reverse();
+
+            let listed_before = self.sendpay_index;
+            self.sendpay_index = newest.unwrap_or(self.sendpay_index);
+            for (index, txn) in payments {
+                // later parts of a payment that was listed already
+                if index <= listed_before {
+                    continue;
+                }
+                if txn.status == TransactionStatus::Pending {
+                    self.in_flight.insert(index, keys[&index].clone());
+                } else {
+                    self.report(txn, callback);
+                }
+            }
+            if !full_page {
+                return Ok(());
+            }
+        }
+    }
+
+    fn report(&self, txn: Transaction, callback: &dyn SubscribeAllCallback) {
+        let event_type = match txn.status {
+            TransactionStatus::Settled => AccountEventType::PaymentSent,
+            _ => AccountEventType::PaymentFailed,
+        };
+        callback.event(AccountEvent {
+            event_type,
+            transaction: txn,
+            resume_index: self.resume_index(),
+        });
+    }
+}
+
+// The newest pay_index, 0 when nothing was paid yet. Being paid is the last change an
+// invoice sees, so walking down the updated_index the first paid invoices hold it.
+async fn latest_pay_index(config: &ClnConfig) -> Result<i64, ApiError> {
+    let client = clnrest_client(config);
+    let mut before = wait_index(config, "invoices", "updated").await? + 1;
+    while before > 1 {
+        let start = (before - 100).max(1);
+        let response = client
+            .post(format!("{}/v1/listinvoices", config.url))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({
+                "index": "updated",
+                "start": start,
+                "limit": before - start,
+            }))
+            .send()
+            .await
+            .map_err(|e| ApiError::from_reqwest(e, "Failed to list invoices"))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response).await);
+        }
+        let response_text = response.text().await.map_err(|e| ApiError::Http {
+            reason: format!("Failed to read listinvoices response: {}", e),
+        })?;
+        let latest = Invoice::from_list_response(&response_text)?
+            .into_iter()
+            .filter_map(|inv| inv.pay_index)
+            .max();
+        if let Some(pay_index) = latest {
+            return Ok(pay_index as i64);
+        }
+        before = start;
+    }
+    Ok(0)
+}
+
+pub async fn list_channels(config: ClnConfig) -> Result<Vec<Channel>, ApiError> {
+    let client = clnrest_client(&config);
+    let req_url = format!("{}/v1/listpeerchannels", config.url);
+    let response = client
+        .post(&req_url)
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to list channels"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read list channels response: {}", e),
+    })?;
+    let raw: serde_json::Value = serde_json::from_str(&response_text)?;
+    let peer_channels: PeerChannelsResponse = serde_json::from_value(raw.clone())?;
+    let raw_channels = raw["channels"].as_array().cloned().unwrap_or_default();
+
+    // closed channels stay listed until forgotten, only return the ones that are opening or open
+    // https://docs.corelightning.org/reference/listpeerchannels
+    let channels = peer_channels
+        .channels
+        .into_iter()
+        .zip(raw_channels)
+        .filter(|(c, _)| {
+            c.state == "CHANNELD_NORMAL"
+                || c.state == "CHANNELD_AWAITING_LOCKIN"
+                || c.state == "DUALOPEND_AWAITING_LOCKIN"
+                || c.state == "DUALOPEND_OPEN_INIT"
+                || c.state == "DUALOPEND_OPEN_COMMITTED"
+                || c.state == "DUALOPEND_OPEN_COMMIT_READY"
+                || c.state == "OPENINGD"
+        })
+        .map(|(c, raw)| Channel {
+            local_balance: c.to_us_msat,
+            local_spendable_balance: c.spendable_msat,
+            remote_balance: c.total_msat - c.to_us_msat,
+            capacity: c.total_msat,
+            id: c.channel_id.unwrap_or_default(),
+            short_channel_id: c.short_channel_id,
+            remote_pubkey: c.peer_id,
+            funding_tx_id: c.funding_txid.unwrap_or_default(),
+            funding_tx_vout: c.funding_outnum.unwrap_or_default(),
+            active: c.state == "CHANNELD_NORMAL" && c.peer_connected,
+            public: !c.private,
+            internal_channel: raw.to_string(),
+            confirmations: 0,
+            confirmations_required: c.minimum_depth.unwrap_or_default(),
+            forwarding_fee_base_msat: c.fee_base_msat,
+            unspendable_punishment_reserve: c.our_reserve_msat / 1000,
+            counterparty_unspendable_punishment_reserve: c.their_reserve_msat / 1000,
+            error: "".to_string(),
+            is_outbound: c.opener == "local",
+        })
+        .collect();
+    Ok(channels)
+}
+
+pub async fn open_channel(
+    config: ClnConfig,
+    params: OpenChannelRequest,
+) -> Result<OpenChannelResponse, ApiError> {
+    let client = clnrest_client(&config);
+
+    if let Some(host) = params.host.as_deref().filter(|h| !h.is_empty()) {
+        let connect_url = format!("{}/v1/connect", config.url);
+        let response = client
+            .post(&connect_url)
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "id": format!("{}@{}", params.pubkey, host) }))
+            .send()
+            .await
+            .map_err(|e| ApiError::from_reqwest(e, "Failed to connect peer"))?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response).await);
+        }
+    }
+
+    let mut body = serde_json::json!({
+        "id": params.pubkey,
+        "amount": params.amount_msats / 1000,
+        "announce": params.public,
+    });
+    if let Some(push_msats) = params.push_msats {
+        body["push_msat"] = serde_json::json!(push_msats);
+    }
+    if let Some(sat_per_vbyte) = params.sat_per_vbyte {
+        body["feerate"] = serde_json::json!(format!("{}perkb", sat_per_vbyte * 1000));
+    }
+
+    let req_url = format!("{}/v1/fundchannel", config.url);
+    let response = client
+        .post(&req_url)
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to open channel"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read open channel response: {}", e),
+    })?;
+    let fund: FundChannelResponse = serde_json::from_str(&response_text)?;
+    Ok(OpenChannelResponse {
+        funding_tx_id: fund.txid,
+        funding_tx_vout: fund.outnum,
+        channel_id: Some(fund.channel_id),
+    })
+}
+
+pub async fn close_channel(
+    config: ClnConfig,
+    params: CloseChannelRequest,
+) -> Result<CloseChannelResponse, ApiError> {
+    // close takes a channel id, short channel id or the peer id
+    let id = if params.channel_id.is_empty() {
+        params.node_id.clone()
+    } else {
+        params.channel_id.clone()
+    };
+    let mut body = serde_json::json!({ "id": id });
+    if params.force {
+        // fall back to a unilateral close after a second instead of waiting on the peer
+        body["unilateraltimeout"] = serde_json::json!(1);
+    }
+    if let Some(address) = params.address.clone() {
+        body["destination"] = serde_json::json!(address);
+    }
+    if let Some(sat_per_vbyte) = params.sat_per_vbyte {
+        let feerate = format!("{}perkb", sat_per_vbyte * 1000);
+        body["feerange"] = serde_json::json!([feerate, feerate]);
+    }
+
+    let client = clnrest_client(&config);
+    let req_url = format!("{}/v1/close", config.url);
+    let response = client
+        .post(&req_url)
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to close channel"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read close channel response: {}", e),
+    })?;
+    let close: CloseResponse = serde_json::from_str(&response_text)?;
+    Ok(CloseChannelResponse {
+        closing_tx_id: close.txid,
+    })
+}
+
+pub async fn update_channel_policy(
+    config: ClnConfig,
+    params: UpdateChannelRequest,
+) -> Result<(), ApiError> {
+    if params.cltv_expiry_delta.is_some() {
+        return Err(ApiError::NotSupported {
+            reason: "CLN sets cltv_expiry_delta node-wide with the cltv-delta config option"
+                .to_string(),
+        });
+    }
+    let id = if params.channel_id.is_empty() {
+        params.node_id.clone()
+    } else {
+        params.channel_id.clone()
+    };
+    let mut body = serde_json::json!({
+        "id": id,
+        "feebase": params.forwarding_fee_base_msat,
+    });
+    if let Some(fee_rate_ppm) = params.fee_rate_ppm {
+        body["feeppm"] = serde_json::json!(fee_rate_ppm);
+    }
+    if let Some(min_htlc_msat) = params.min_htlc_msat {
+        body["htlcmin"] = serde_json::json!(min_htlc_msat);
+    }
+    if let Some(max_htlc_msat) = params.max_htlc_msat {
+        body["htlcmax"] = serde_json::json!(max_htlc_msat);
+    }
+
+    let client = clnrest_client(&config);
+    let req_url = format!("{}/v1/setchannel", config.url);
+    let response = client
+        .post(&req_url)
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to update channel policy"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    Ok(())
+}
+
+async fn list_funds(config: &ClnConfig, spent: bool) -> Result<(FundsResponse, String), ApiError> {
+    let client = clnrest_client(config);
+    let req_url = format!("{}/v1/listfunds", config.url);
+    let response = client
+        .post(&req_url)
+        .header("Content-Type", "application/json")
+        .json(&serde_json::json!({ "spent": spent }))
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to list funds"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read funds response: {}", e),
+    })?;
+    let funds: FundsResponse = serde_json::from_str(&response_text)?;
+    Ok((funds, response_text))
+}
+
+pub async fn get_onchain_balance(config: ClnConfig) -> Result<OnchainBalanceResponse, ApiError> {
+    let (funds, response_text) = list_funds(&config, false).await?;
+
+    let mut spendable: i64 = 0;
+    let mut total: i64 = 0;
+    let mut reserved: i64 = 0;
+    for output in funds.outputs.iter() {
+        let amount_sats = output.amount_msat / 1000;
+        if output.status == "confirmed" || output.status == "unconfirmed" {
+            total += amount_sats;
+        }
+        if output.reserved {
+            reserved += amount_sats;
+        } else if output.status == "confirmed" {
+            spendable += amount_sats;
+        }
+    }
+
+    // our side of channels that are closing but not yet swept back into the wallet
+    let pending_balances_details: Vec<PendingBalanceDetails> = funds
+        .channels
+        .iter()
+        .filter(|c| {
+            c.state == "CHANNELD_SHUTTING_DOWN"
+                || c.state == "CLOSINGD_SIGEXCHANGE"
+                || c.state == "CLOSINGD_COMPLETE"
+                || c.state == "AWAITING_UNILATERAL"
+                || c.state == "FUNDING_SPEND_SEEN"
+                || c.state == "ONCHAIN"
+        })
+        .map(|c| PendingBalanceDetails {
+            channel_id: c.channel_id.clone(),
+            node_id: c.peer_id.clone(),
+            amount_msats: c.our_amount_msat,
+            funding_tx_id: c.funding_txid.clone(),
+            funding_tx_vout: c.funding_output as i64,
+        })
+        .collect();
+
+    Ok(OnchainBalanceResponse {
+        spendable,
+        total,
+        reserved,
+        pending_balances_from_channel_closures: pending_balances_details
+            .iter()
+            .map(|d| d.amount_msats / 1000)
+            .sum(),
+        pending_balances_details,
+        internal_balances: response_text,
+    })
+}
+
+pub async fn new_onchain_address(config: ClnConfig) -> Result<String, ApiError> {
+    let client = clnrest_client(&config);
+    let req_url = format!("{}/v1/newaddr", config.url);
+    let response = client
+        .post(&req_url)
+        .header("Content-Type", "application/json")
+        .json(&serde_json::json!({ "addresstype": "bech32" }))
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to create onchain address"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read new address response: {}", e),
+    })?;
+    let address: NewAddrResponse = serde_json::from_str(&response_text)?;
+    address.bech32.or(address.p2tr).ok_or_else(|| ApiError::Json {
+        reason: format!("No address in newaddr response: {}", response_text),
+    })
+}
+
+pub async fn send_onchain(
+    config: ClnConfig,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+) -> Result<SendOnchainResponse, ApiError> {
+    let mut body = serde_json::json!({
+        "destination": address,
+        "satoshi": amount_sats,
+    });
+    if let Some(sat_per_vbyte) = sat_per_vbyte {
+        body["feerate"] = serde_json::json!(format!("{}perkb", sat_per_vbyte * 1000));
+    }
+
+    let client = clnrest_client(&config);
+    let req_url = format!("{}/v1/withdraw", config.url);
+    let response = client
+        .post(&req_url)
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to send onchain"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read withdraw response: {}", e),
+    })?;
+    let withdraw: WithdrawResponse = serde_json::from_str(&response_text)?;
+    Ok(SendOnchainResponse {
+        txid: Some(withdraw.txid),
+        external_id: None,
+        fee_sats: None,
+    })
+}
+
+// listfunds only knows about our outputs, so this lists received funds including spent ones
+pub async fn list_onchain_transactions(config: ClnConfig) -> Result<Vec<OnchainTransaction>, ApiError> {
+    let (funds, _) = list_funds(&config, true).await?;
+    let block_height = get_info(config.clone()).await?.block_height;
+
+    let client = clnrest_client(&config);
+    let req_url = format!("{}/v1/listtransactions", config.url);
+    let response = client
+        .post(&req_url)
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to list wallet transactions"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read listtransactions response: {}", e),
+    })?;
+    let wallet: WalletTransactionsResponse = serde_json::from_str(&response_text)?;
+    Ok(wallet_transactions(&funds, wallet, block_height))
+}
+
+// A transaction spending any of our outputs is a send, whatever comes back to us is change.
+// Otherwise it is a receive of the outputs that are ours. Transactions touching none of our
+// outputs (e.g. channel opens by the peer) are left out.
+pub(crate) fn wallet_transactions(
+    funds: &FundsResponse,
+    wallet: WalletTransactionsResponse,
+    block_height: i64,
+) -> Vec<OnchainTransaction> {
+    let ours = |txid: &str, index: i64| funds.outputs.iter().find(|o| o.txid == txid && o.output == index);
+
+    let mut transactions: Vec<OnchainTransaction> = wallet
+        .transactions
+        .into_iter()
+        .filter_map(|tx| {
+            let inputs: Vec<_> = tx.inputs.iter().map(|input| ours(&input.txid, input.index)).collect();
+            let spent_msat: i64 = inputs.iter().flatten().map(|o| o.amount_msat).sum();
+            let received: Vec<_> = tx.outputs.iter().filter_map(|output| ours(&tx.hash, output.index)).collect();
+            let received_msat: i64 = received.iter().map(|o| o.amount_msat).sum();
+            let total_out_msat: i64 = tx.outputs.iter().map(|o| o.amount_msat).sum();
+
+            let (direction, amount_msat, fee_msat, address) = if spent_msat > 0 {
+                // the fee is only known when every input was ours
+                let fee = if inputs.iter().all(Option::is_some) { spent_msat - total_out_msat } else { 0 };
+                (Direction::Outgoing, total_out_msat - received_msat, fee, None)
+            } else if received_msat > 0 {
+                (Direction::Incoming, received_msat, 0, received[0].address.clone())
+            } else {
+                return None;
+            };
+            Some(OnchainTransaction {
+                txid: tx.hash,
+                direction,
+                amount_sats: amount_msat / 1000,
+                fee_sats: fee_msat.max(0) / 1000,
+                confirmations: if tx.blockheight > 0 {
+                    (block_height - tx.blockheight + 1).max(0)
+                } else {
+                    0
+                },
+                block_height: tx.blockheight,
+                created_at: 0,
+                address,
+            })
+        })
+        .collect();
+
+    transactions.sort_by_key(|t| std::cmp::Reverse(t.block_height));
+    transactions
+}
+
+// Narrowed down by the rune, an unreadable rune is treated as unrestricted and
+// any missing permission surfaces at call time instead
+pub async fn capabilities(config: ClnConfig) -> Result<NodeCapabilities, ApiError> {
+    let rune = Rune::parse(&config.rune).ok();
+    let can = |methods: &[&str]| {
+        rune.as_ref()
+            .is_none_or(|r| methods.iter().all(|m| r.allows_method(m)))
+    };
+    let receive = can(&["invoice"]);
+    Ok(NodeCapabilities {
+        bolt11_receive: receive,
+        bolt11_send: can(&["pay"]),
+        bolt12_receive: can(&["offer"]),
+        bolt12_send: can(&["fetchinvoice", "pay"]),
+        keysend: can(&["keysend"]),
+        onchain_receive: can(&["newaddr"]),
+        onchain_send: can(&["withdraw"]),
+        onchain_balance: can(&["listfunds"]),
+        hold_invoices: false,
+        description_hash_invoices: receive,
+        zero_amount_invoices: receive,
+        lookup_invoice: can(&["listinvoices"]),
+        list_transactions: can(&["listinvoices", "listsendpays", "wait"]),
+        channel_management: can(&["listpeerchannels", "connect", "fundchannel", "close", "setchannel"]),
+        native_event_streaming: true,
+        fiat: false,
+    })
+}
@@ -3,8 +3,11 @@ use napi_derive::napi;
 
 use crate::types::NodeInfo;
 use crate::{
-    ApiError, CreateInvoiceParams, CreateOfferParams, ListTransactionsParams, LookupInvoiceParams, Offer,
-    PayInvoiceParams, PayInvoiceResponse, Transaction,
+    ApiError, CreateInvoiceParams, CreateOfferParams, ListTransactionsParams, LookupInvoiceParams,
+    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction, Channel, CloseChannelRequest,
+    CloseChannelResponse, OpenChannelRequest, OpenChannelResponse, UpdateChannelRequest,
+    OnchainBalanceResponse, OnchainTransaction, SendOnchainResponse, PayKeysendResponse, TLVRecord,
+    NodeCapabilities,
 };
 #[cfg(not(feature = "uniffi"))]
 use crate::LightningNode;
@@ -57,6 +60,10 @@ impl ClnNode {
         crate::cln::api::get_info(self.config.clone()).await
     }
 
+    pub async fn capabilities(&self) -> Result<NodeCapabilities, ApiError> {
+        crate::cln::api::capabilities(self.config.clone()).await
+    }
+
     pub async fn create_invoice(
         &self,
         params: CreateInvoiceParams,
@@ -80,6 +87,46 @@ impl ClnNode {
         crate::cln::api::pay_invoice(self.config.clone(), params).await
     }
 
+    pub async fn estimate_payment_fee(
+        &self,
+        invoice: String,
+        amount_msats: Option<i64>,
+    ) -> Result<crate::PaymentFeeEstimate, ApiError> {
+        crate::cln::api::estimate_payment_fee(self.config.clone(), invoice, amount_msats).await
+    }
+
+    /// zbase32 signature of `message` by the node key, see `lnurl::MessageSigner`
+    pub async fn sign_message(&self, message: String) -> Result<String, ApiError> {
+        crate::cln::api::sign_message(self.config.clone(), message).await
+    }
+
+    pub async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: crate::PayOptions,
+    ) -> Result<crate::PayResponse, ApiError> {
+        crate::lnurl::pay(self, destination, amount_msats, options).await
+    }
+
+    pub async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: crate::ZapRequestParams,
+    ) -> Result<crate::ZapResponse, ApiError> {
+        crate::lnurl::zap(self, destination, amount_msats, params).await
+    }
+
+    pub async fn pay_keysend(
+        &self,
+        destination_pubkey: String,
+        amount_msats: i64,
+        custom_records: Vec<TLVRecord>,
+    ) -> Result<PayKeysendResponse, ApiError> {
+        crate::cln::api::pay_keysend(self.config.clone(), destination_pubkey, amount_msats, custom_records).await
+    }
+
     pub async fn create_offer(&self, params: CreateOfferParams) -> Result<Offer, ApiError> {
         crate::cln::api::create_offer(self.config.clone(), params).await
     }
@@ -115,6 +162,10 @@ impl ClnNode {
         .await
     }
 
+    pub async fn lookup_payment(&self, payment_hash: String) -> Result<crate::OutgoingPayment, ApiError> {
+        crate::cln::api::lookup_payment(self.config.clone(), payment_hash).await
+    }
+
     pub async fn list_transactions(
         &self,
         params: ListTransactionsParams,
@@ -128,22 +179,105 @@ impl ClnNode {
         .await
     }
 
-    pub async fn decode(&self, str: String) -> Result<String, ApiError> {
+    pub async fn list_transactions_page(
+        &self,
+        params: ListTransactionsParams,
+    ) -> Result<crate::TransactionPage, ApiError> {
+        crate::cln::api::list_transactions_page(self.config.clone(), params).await
+    }
+
+    pub async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, ApiError> {
         crate::cln::api::decode(self.config.clone(), str).await
     }
 
-    pub async fn on_invoice_events(
+    pub fn on_invoice_events(
         &self,
         params: crate::types::OnInvoiceEventParams,
         callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-    ) {
-        crate::cln::api::on_invoice_events(self.config.clone(), params, callback).await
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::invoice_events(callback, |callback| {
+            crate::cln::api::on_invoice_events(config, params, callback)
+        })
+    }
+
+    pub fn on_payment_events(
+        &self,
+        params: crate::types::OnPaymentEventParams,
+        callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No payment feed, the payment is looked up every polling_delay_sec
+        let node = self.clone();
+        crate::Subscription::payment_events(callback, |callback| async move {
+            let payment_hash = params.payment_hash.clone();
+            crate::poll_payment_events(|| node.lookup_payment(payment_hash.clone()), params, callback.as_ref()).await
+        })
+    }
+
+    pub fn subscribe_all(
+        &self,
+        params: crate::types::SubscribeAllParams,
+        callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::subscribe_all(callback, |callback| {
+            crate::cln::api::subscribe_all(config, params, callback)
+        })
+    }
+
+    pub async fn list_channels(&self) -> Result<Vec<Channel>, ApiError> {
+        crate::cln::api::list_channels(self.config.clone()).await
+    }
+
+    pub async fn open_channel(&self, params: OpenChannelRequest) -> Result<OpenChannelResponse, ApiError> {
+        crate::cln::api::open_channel(self.config.clone(), params).await
+    }
+
+    pub async fn close_channel(&self, params: CloseChannelRequest) -> Result<CloseChannelResponse, ApiError> {
+        crate::cln::api::close_channel(self.config.clone(), params).await
+    }
+
+    pub async fn update_channel_policy(&self, params: UpdateChannelRequest) -> Result<(), ApiError> {
+        crate::cln::api::update_channel_policy(self.config.clone(), params).await
+    }
+
+    pub async fn get_onchain_balance(&self) -> Result<OnchainBalanceResponse, ApiError> {
+        crate::cln::api::get_onchain_balance(self.config.clone()).await
+    }
+
+    pub async fn new_onchain_address(&self) -> Result<String, ApiError> {
+        crate::cln::api::new_onchain_address(self.config.clone()).await
+    }
+
+    pub async fn send_onchain(
+        &self,
+        address: String,
+        amount_sats: i64,
+        sat_per_vbyte: Option<i64>,
+    ) -> Result<SendOnchainResponse, ApiError> {
+        crate::cln::api::send_onchain(self.config.clone(), address, amount_sats, sat_per_vbyte).await
+    }
+
+    pub async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, ApiError> {
+        crate::cln::api::list_onchain_transactions(self.config.clone()).await
     }
 }
 
 // Trait implementation for Rust consumers - uses the impl_lightning_node macro
 // Trait implementation for polymorphic access via Arc<dyn LightningNode>
 crate::impl_lightning_node!(ClnNode);
+impl crate::LightningNodeEvents for ClnNode {}
+
+// Lets the node derive LNURL-auth keys (LUD-13)
+#[async_trait::async_trait]
+impl crate::lnurl::MessageSigner for ClnNode {
+    async fn sign_message(&self, message: String) -> Result<String, ApiError> {
+        let this = self.clone();
+        crate::TOKIO_RUNTIME.spawn(async move {
+            ClnNode::sign_message(&this, message).await
+        }).await.unwrap()
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -450,6 +584,7 @@ mod tests {
             search: None,
             created_after: None,
             created_before: None,
+            cursor: None,
         };
         match NODE.list_transactions(params).await {
             Ok(txns) => {
@@ -465,6 +600,42 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_list_transactions_page() {
+        // walk two pages and make sure nothing comes back twice
+        let mut params = ListTransactionsParams {
+            from: 0,
+            limit: 5,
+            payment_hash: None,
+            search: None,
+            created_after: None,
+            created_before: None,
+            cursor: None,
+        };
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..2 {
+            let page = match NODE.list_transactions_page(params).await {
+                Ok(page) => page,
+                Err(e) => panic!("Failed to list transactions page: {:?}", e),
+            };
+            dbg!(&page.next_cursor);
+            assert!(page.transactions.len() <= 5);
+            for txn in &page.transactions {
+                assert!(seen.insert((txn.payment_hash.clone(), txn.created_at)), "Transaction repeated across pages");
+            }
+            let Some(next_cursor) = page.next_cursor else { break };
+            params = ListTransactionsParams {
+                from: 0,
+                limit: 5,
+                payment_hash: None,
+                search: None,
+                created_after: None,
+                created_before: None,
+                cursor: Some(next_cursor),
+            };
+        }
+    }
+
     #[tokio::test]
     async fn test_decode() {
         match NODE.decode(PHOENIX_MOBILE_OFFER.to_string()).await {
@@ -499,6 +670,96 @@ mod tests {
             ..Default::default()
         };
         let callback = OnInvoiceEventCallback {};
-        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).await;
+        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).wait().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_channels() {
+        match NODE.list_channels().await {
+            Ok(channels) => {
+                println!("channels: {:?}", channels);
+                for channel in channels.iter() {
+                    assert!(
+                        channel.local_balance <= channel.capacity,
+                        "Local balance should not exceed channel capacity"
+                    );
+                }
+            }
+            Err(e) => {
+                panic!("Failed to list channels: {:?}", e);
+            }
+        }
+    }
+    #[tokio::test]
+    async fn test_get_onchain_balance() {
+        match NODE.get_onchain_balance().await {
+            Ok(balance) => {
+                println!("onchain balance: {:?}", balance);
+                assert!(balance.spendable <= balance.total, "Spendable should not exceed total");
+            }
+            Err(e) => {
+                panic!("Failed to get onchain balance: {:?}", e);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_new_onchain_address() {
+        match NODE.new_onchain_address().await {
+            Ok(address) => {
+                println!("onchain address: {}", address);
+                assert!(!address.is_empty(), "Address should not be empty");
+            }
+            Err(e) => {
+                panic!("Failed to create onchain address: {:?}", e);
+            }
+        }
+    }
+    #[tokio::test]
+    async fn test_capabilities() {
+        match NODE.capabilities().await {
+            Ok(capabilities) => {
+                println!("capabilities: {:?}", capabilities);
+                assert!(capabilities.lookup_invoice, "Test credentials should be able to look up invoices");
+            }
+            Err(e) => {
+                panic!("Failed to get capabilities: {:?}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wallet_transactions_direction() {
+        // we received 0.001 BTC in aa, then sent 40000 sats out of it in bb with our change at index 1
+        let funds: crate::cln::types::FundsResponse = serde_json::from_value(serde_json::json!({
+            "outputs": [
+                {"txid": "aa", "output": 0, "amount_msat": 100_000_000, "address": "bcrt1qin", "status": "spent", "blockheight": 100},
+                {"txid": "bb", "output": 1, "amount_msat": 59_000_000, "address": "bcrt1qchange", "status": "confirmed", "blockheight": 105}
+            ],
+            "channels": []
+        }))
+        .unwrap();
+        let wallet: crate::cln::types::WalletTransactionsResponse = serde_json::from_value(serde_json::json!({
+            "transactions": [
+                {"hash": "aa", "blockheight": 100, "inputs": [{"txid": "ff", "index": 3}],
+                 "outputs": [{"index": 0, "amount_msat": 100_000_000}, {"index": 1, "amount_msat": 7_000_000}]},
+                {"hash": "bb", "blockheight": 105, "inputs": [{"txid": "aa", "index": 0}],
+                 "outputs": [{"index": 0, "amount_msat": 40_000_000}, {"index": 1, "amount_msat": 59_000_000}]},
+                {"hash": "cc", "blockheight": 0, "inputs": [{"txid": "ee", "index": 0}],
+                 "outputs": [{"index": 0, "amount_msat": 5_000_000}]}
+            ]
+        }))
+        .unwrap();
+
+        let transactions = crate::cln::api::wallet_transactions(&funds, wallet, 110);
+        assert_eq!(transactions.len(), 2, "unrelated transactions are left out");
+        let sent = &transactions[0];
+        assert_eq!(sent.txid, "bb");
+        assert_eq!(sent.direction, crate::types::Direction::Outgoing);
+        assert_eq!((sent.amount_sats, sent.fee_sats, sent.confirmations), (40_000, 1_000, 6));
+        let received = &transactions[1];
+        assert_eq!(received.txid, "aa");
+        assert_eq!(received.direction, crate::types::Direction::Incoming);
+        assert_eq!((received.amount_sats, received.address.as_deref()), (100_000, Some("bcrt1qin")));
     }
 }
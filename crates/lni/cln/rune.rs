@@ -0,0 +1,183 @@
+//! Read-only view of a CLN rune's restrictions
+//!
+//! A rune is base64url(32 byte hash state || restrictions). Restrictions are
+//! separated by `&`, alternatives within a restriction by `|`, and `\` escapes.
+//! https://docs.corelightning.org/reference/lightning-commando-rune
+//!
+//! The hash is not checked, the node does that. We only use the restrictions to
+//! tell which commands a rune can call, conditions on anything other than the
+//! method (params, time, rate) are assumed to pass.
+
+use crate::ApiError;
+
+const RUNE_HASH_LEN: usize = 32;
+const OPERATORS: &[char] = &['!', '=', '/', '^', '$', '~', '<', '>', '{', '}', '#'];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    field: String,
+    op: char,
+    value: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rune {
+    restrictions: Vec<Vec<Condition>>,
+}
+
+impl Rune {
+    pub fn parse(rune: &str) -> Result<Rune, ApiError> {
+        let trimmed = rune.trim().trim_end_matches('=');
+        let bytes = base64::decode_config(trimmed, base64::URL_SAFE_NO_PAD)
+            .map_err(|e| ApiError::InvalidInput(format!("Invalid rune encoding: {}", e)))?;
+        if bytes.len() < RUNE_HASH_LEN {
+            return Err(ApiError::InvalidInput("Rune is too short".to_string()));
+        }
+        let restrictions = String::from_utf8(bytes[RUNE_HASH_LEN..].to_vec())
+            .map_err(|_| ApiError::InvalidInput("Rune restrictions are not valid UTF-8".to_string()))?;
+        Ok(Rune {
+            restrictions: parse_restrictions(&restrictions)?,
+        })
+    }
+
+    /// True when every restriction has an alternative that lets `method` through
+    pub fn allows_method(&self, method: &str) -> bool {
+        self.restrictions
+            .iter()
+            .all(|alternatives| alternatives.iter().any(|c| c.allows_method(method)))
+    }
+}
+
+impl Condition {
+    fn allows_method(&self, method: &str) -> bool {
+        // The unique id (empty field) and conditions on other fields can't be judged here
+        if self.field != "method" {
+            return true;
+        }
+        let value = self.value.as_str();
+        match self.op {
+            '=' => method == value,
+            '/' => method != value,
+            '^' => method.starts_with(value),
+            '$' => method.ends_with(value),
+            '~' => method.contains(value),
+            '{' => method < value,
+            '}' => method > value,
+            '#' => true,
+            // method is always present and never an integer
+            _ => false,
+        }
+    }
+}
+
+fn parse_restrictions(s: &str) -> Result<Vec<Vec<Condition>>, ApiError> {
+    let mut restrictions = vec![];
+    let mut alternatives = vec![];
+    let mut current = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // keep the escape so parse_condition doesn't treat the next char as an operator
+            '\\' => {
+                current.push(c);
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            '|' => alternatives.push(parse_condition(&std::mem::take(&mut current))?),
+            '&' => {
+                alternatives.push(parse_condition(&std::mem::take(&mut current))?);
+                restrictions.push(std::mem::take(&mut alternatives));
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() || !alternatives.is_empty() {
+        alternatives.push(parse_condition(&current)?);
+        restrictions.push(alternatives);
+    }
+    Ok(restrictions)
+}
+
+fn parse_condition(s: &str) -> Result<Condition, ApiError> {
+    let op_index = s
+        .find(|c: char| OPERATORS.contains(&c))
+        .ok_or_else(|| ApiError::InvalidInput(format!("Rune condition has no operator: {}", s)))?;
+    let op = s[op_index..].chars().next().unwrap_or('=');
+    Ok(Condition {
+        field: s[..op_index].to_string(),
+        op,
+        value: unescape(&s[op_index + op.len_utf8()..]),
+    })
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                out.push(escaped);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_rune(restrictions: &str) -> String {
+        let mut bytes = vec![0u8; RUNE_HASH_LEN];
+        bytes.extend_from_slice(restrictions.as_bytes());
+        base64::encode_config(bytes, base64::URL_SAFE)
+    }
+
+    #[test]
+    fn test_unrestricted_rune() {
+        let rune = Rune::parse(&make_rune("=0")).unwrap();
+        assert!(rune.allows_method("pay"));
+        assert!(rune.allows_method("withdraw"));
+    }
+
+    #[test]
+    fn test_method_allowlist() {
+        let rune = Rune::parse(&make_rune("=1&method=invoice|method=listinvoices")).unwrap();
+        assert!(rune.allows_method("invoice"));
+        assert!(rune.allows_method("listinvoices"));
+        assert!(!rune.allows_method("pay"));
+    }
+
+    #[test]
+    fn test_read_only_rune() {
+        // what `commando-rune restrictions=readonly` produces
+        let rune = Rune::parse(&make_rune("=2&method^list|method^get|method=summary&method/listdatastore")).unwrap();
+        assert!(rune.allows_method("listfunds"));
+        assert!(rune.allows_method("getinfo"));
+        assert!(!rune.allows_method("listdatastore"));
+        assert!(!rune.allows_method("invoice"));
+    }
+
+    #[test]
+    fn test_other_fields_are_assumed_to_pass() {
+        let rune = Rune::parse(&make_rune("=3&method=pay&pnamebolt11^lnbc&time<1700000000")).unwrap();
+        assert!(rune.allows_method("pay"));
+        assert!(!rune.allows_method("keysend"));
+    }
+
+    #[test]
+    fn test_escaped_separator() {
+        let rune = Rune::parse(&make_rune("=4&method=a\\|b")).unwrap();
+        assert!(rune.allows_method("a|b"));
+        assert!(!rune.allows_method("a"));
+    }
+
+    #[test]
+    fn test_invalid_rune() {
+        assert!(Rune::parse("not a rune!").is_err());
+        assert!(Rune::parse(&base64::encode_config(b"short", base64::URL_SAFE)).is_err());
+    }
+}
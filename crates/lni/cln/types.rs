@@ -30,6 +30,23 @@ pub struct PayResponse {
     pub status: String,
 }
 
+// https://docs.corelightning.org/reference/lightning-getroute
+#[derive(Debug, Deserialize)]
+pub struct GetRouteResponse {
+    pub route: Vec<RouteHop>,
+}
+
+// https://docs.corelightning.org/reference/lightning-signmessage
+#[derive(Debug, Deserialize)]
+pub struct SignMessageResponse {
+    pub zbase: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RouteHop {
+    pub amount_msat: i64, // what this hop receives, so the first one carries every fee
+}
+
 
 #[derive(Debug, Deserialize)]
 pub struct PaidOutpoint {
@@ -64,6 +81,58 @@ pub struct InvoicesResponse {
     pub invoices: Vec<Invoice>,
 }
 
+// `wait` with nextvalue 0 returns straight away with the current index values
+#[derive(Debug, Deserialize)]
+pub struct WaitIndexResponse {
+    pub created: Option<i64>,
+    pub updated: Option<i64>,
+}
+
+// One row per part, parts of the same attempt share a groupid
+#[derive(Debug, Deserialize)]
+pub struct ListSendpaysResponse {
+    pub payments: Vec<SendPay>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendPay {
+    pub created_index: i64,
+    pub payment_hash: String,
+    pub groupid: Option<i64>,
+    pub partid: Option<i64>, // above 0 for the parts of a multi-part payment
+    pub status: String, // "pending", "failed" or "complete"
+    pub amount_msat: Option<i64>,
+    pub amount_sent_msat: Option<i64>,
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+    pub label: Option<String>,
+    pub bolt11: Option<String>,
+    pub bolt12: Option<String>,
+    pub description: Option<String>,
+    pub payment_preimage: Option<String>,
+}
+
+// listpays groups the sendpay attempts of each payment
+#[derive(Debug, Deserialize)]
+pub struct ListPaysResponse {
+    pub pays: Vec<Pay>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Pay {
+    pub payment_hash: String,
+    pub status: String, // "pending", "failed" or "complete"
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+    pub label: Option<String>,
+    pub bolt11: Option<String>,
+    pub bolt12: Option<String>,
+    pub description: Option<String>,
+    pub amount_msat: Option<i64>,      // what the destination received
+    pub amount_sent_msat: Option<i64>, // including fees
+    pub preimage: Option<String>,
+}
+
 
 #[derive(Debug, Deserialize)]
 pub struct Bolt11Resp {
@@ -107,4 +176,117 @@ pub struct Channel {
     pub amount_msat: i64,
     pub funding_txid: String,
     pub funding_output: i32,
-}
\ No newline at end of file
+}
+// JSON-RPC style error returned by clnrest, e.g. {"code": 205, "message": "..."}
+#[derive(Debug, Deserialize)]
+pub struct ClnError {
+    pub code: Option<i64>,
+    pub message: Option<String>,
+}
+
+// https://docs.corelightning.org/reference/listpeerchannels
+#[derive(Debug, Deserialize)]
+pub struct PeerChannelsResponse {
+    pub channels: Vec<PeerChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PeerChannel {
+    pub peer_id: String,
+    pub peer_connected: bool,
+    pub state: String,
+    pub channel_id: Option<String>,
+    pub short_channel_id: Option<String>,
+    pub funding_txid: Option<String>,
+    pub funding_outnum: Option<i64>,
+    #[serde(default)]
+    pub private: bool,
+    pub opener: String,
+    #[serde(default)]
+    pub total_msat: i64,
+    #[serde(default)]
+    pub to_us_msat: i64,
+    #[serde(default)]
+    pub spendable_msat: i64,
+    #[serde(default)]
+    pub our_reserve_msat: i64,
+    #[serde(default)]
+    pub their_reserve_msat: i64,
+    #[serde(default)]
+    pub fee_base_msat: i64,
+    pub minimum_depth: Option<i64>,
+}
+
+// https://docs.corelightning.org/reference/fundchannel
+#[derive(Debug, Deserialize)]
+pub struct FundChannelResponse {
+    pub txid: String,
+    pub outnum: i64,
+    pub channel_id: String,
+}
+
+// https://docs.corelightning.org/reference/close
+#[derive(Debug, Deserialize)]
+pub struct CloseResponse {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub txid: Option<String>,
+}
+
+// https://docs.corelightning.org/reference/listfunds
+#[derive(Debug, Deserialize)]
+pub struct FundsResponse {
+    pub outputs: Vec<FundsOutput>,
+    pub channels: Vec<Channel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FundsOutput {
+    pub txid: String,
+    pub output: i64,
+    pub amount_msat: i64,
+    pub address: Option<String>,
+    pub status: String, // "unconfirmed", "confirmed", "spent" or "immature"
+    #[serde(default)]
+    pub reserved: bool,
+    pub blockheight: Option<i64>,
+}
+
+// https://docs.corelightning.org/reference/listtransactions
+#[derive(Debug, Deserialize)]
+pub struct WalletTransactionsResponse {
+    pub transactions: Vec<WalletTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletTransaction {
+    pub hash: String,
+    #[serde(default)]
+    pub blockheight: i64, // 0 while unconfirmed
+    pub inputs: Vec<WalletTxInput>,
+    pub outputs: Vec<WalletTxOutput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletTxInput {
+    pub txid: String,
+    pub index: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletTxOutput {
+    pub index: i64,
+    pub amount_msat: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewAddrResponse {
+    pub bech32: Option<String>,
+    pub p2tr: Option<String>,
+}
+
+// https://docs.corelightning.org/reference/withdraw
+#[derive(Debug, Deserialize)]
+pub struct WithdrawResponse {
+    pub txid: String,
+}
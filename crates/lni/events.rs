@@ -0,0 +1,413 @@
+//! Account-wide event feeds. `subscribe_all` helpers shared by the backends (the diffing
+//! poller for nodes without a feed of their own, retry backoff) and the `futures::Stream`
+//! views for Rust consumers. The streams are not exported over FFI, the bindings keep the
+//! callback + `Subscription` API. Each stream drives the same watcher as the callbacks and
+//! cancels it when dropped, so feeds from several nodes can be combined with `select!` or
+//! `stream::select_all`.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::Stream;
+use tokio::sync::mpsc;
+
+use crate::types::{
+    AccountEvent, AccountEventType, OnAccountEventParams, OnInvoiceEventCallback, OnInvoiceEventParams,
+    SubscribeAllCallback, SubscribeAllParams,
+};
+use crate::{
+    ApiError, Channel, Direction, LightningNode, ListTransactionsParams, Subscription, SubscriptionOutcome,
+    Transaction, TransactionStatus,
+};
+
+/// One status report for a watched invoice, the same sequence the callbacks see
+#[derive(Debug, Clone)]
+pub enum InvoiceEvent {
+    Pending(Option<Transaction>),
+    Settled(Option<Transaction>),
+    Failed(Option<Transaction>), // timed out or the backend gave up
+}
+
+/// Account-wide activity
+#[derive(Debug, Clone)]
+pub enum NodeEvent {
+    InvoiceSettled(Transaction),
+    PaymentSent(Transaction),
+    PaymentFailed(Transaction),
+    ChannelOpened(Channel),
+    ChannelUpdated(Channel), // went active/inactive or got confirmed
+    ChannelClosed(Channel),  // last state seen before it disappeared
+}
+
+impl From<AccountEvent> for NodeEvent {
+    fn from(event: AccountEvent) -> Self {
+        match event.event_type {
+            AccountEventType::InvoiceSettled => NodeEvent::InvoiceSettled(event.transaction),
+            AccountEventType::PaymentSent => NodeEvent::PaymentSent(event.transaction),
+            AccountEventType::PaymentFailed => NodeEvent::PaymentFailed(event.transaction),
+        }
+    }
+}
+
+/// Stream API on top of `LightningNode`. Streams end once `max_polling_sec` has passed
+/// (or the watched invoice reached a final state), dropping them stops the watcher.
+pub trait LightningNodeEvents: LightningNode + Clone + 'static {
+    fn invoice_events(&self, params: OnInvoiceEventParams) -> impl Stream<Item = InvoiceEvent> + Send + 'static {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let subscription = self.on_invoice_events(params, Arc::new(InvoiceEventSender(tx)));
+        receiver_stream(rx, subscription)
+    }
+
+    /// Payments come from `subscribe_all`, channels from diffing list_channels every
+    /// `polling_delay_sec`
+    fn account_events(&self, params: OnAccountEventParams) -> impl Stream<Item = NodeEvent> + Send + 'static {
+        let node = self.clone();
+        spawn_stream(move |tx| async move {
+            let feed = CancelOnDrop(node.subscribe_all(
+                SubscribeAllParams {
+                    resume_from: None,
+                    polling_delay_sec: params.polling_delay_sec,
+                },
+                Arc::new(AccountEventSender(tx.clone())),
+            ));
+            poll_channel_events(&node, params, |event| {
+                let _ = tx.send(event);
+            })
+            .await;
+            drop(feed);
+        })
+    }
+}
+
+/// Maps the event names of the backends' account callbacks
+pub(crate) fn account_event(event: &str, transaction: Transaction) -> Option<NodeEvent> {
+    match event {
+        "payment_received" => Some(NodeEvent::InvoiceSettled(transaction)),
+        "payment_sent" => Some(NodeEvent::PaymentSent(transaction)),
+        "payment_failed" => Some(NodeEvent::PaymentFailed(transaction)),
+        _ => None,
+    }
+}
+
+/// Runs `watch` on the shared runtime and streams whatever it sends
+pub(crate) fn spawn_stream<T, F, Fut>(watch: F) -> impl Stream<Item = T> + Send + 'static
+where
+    T: Send + 'static,
+    F: FnOnce(mpsc::UnboundedSender<T>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::unbounded_channel();
+    let watcher = watch(tx);
+    let subscription = Subscription::spawn(async move {
+        watcher.await;
+        SubscriptionOutcome::Success
+    });
+    receiver_stream(rx, subscription)
+}
+
+// The stream ends when the watcher drops its sender, i.e. once it finished
+fn receiver_stream<T: Send + 'static>(
+    rx: mpsc::UnboundedReceiver<T>,
+    subscription: Arc<Subscription>,
+) -> impl Stream<Item = T> + Send + 'static {
+    let watcher = CancelOnDrop(subscription);
+    futures_util::stream::unfold((rx, watcher), |(mut rx, watcher)| async move {
+        let item = rx.recv().await?;
+        Some((item, (rx, watcher)))
+    })
+}
+
+struct CancelOnDrop(Arc<Subscription>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+struct InvoiceEventSender(mpsc::UnboundedSender<InvoiceEvent>);
+
+impl OnInvoiceEventCallback for InvoiceEventSender {
+    fn success(&self, transaction: Option<Transaction>) {
+        let _ = self.0.send(InvoiceEvent::Settled(transaction));
+    }
+
+    fn pending(&self, transaction: Option<Transaction>) {
+        let _ = self.0.send(InvoiceEvent::Pending(transaction));
+    }
+
+    fn failure(&self, transaction: Option<Transaction>) {
+        let _ = self.0.send(InvoiceEvent::Failed(transaction));
+    }
+}
+
+struct AccountEventSender(mpsc::UnboundedSender<NodeEvent>);
+
+impl SubscribeAllCallback for AccountEventSender {
+    fn event(&self, event: AccountEvent) {
+        let _ = self.0.send(event.into());
+    }
+
+    // the stream just runs dry, there is no error item to put it in
+    fn error(&self, _reason: String) {}
+}
+
+/// Delay between retries of a feed after transient errors, 1s doubling up to a minute
+pub(crate) struct Backoff(u32);
+
+impl Backoff {
+    pub(crate) fn new() -> Self {
+        Self(0)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.0 = 0;
+    }
+
+    /// Waits before the next attempt, or hands back errors that retrying won't fix
+    pub(crate) async fn retry(&mut self, error: ApiError) -> Result<(), ApiError> {
+        if !error.is_retryable() {
+            return Err(error);
+        }
+        self.0 += 1;
+        tokio::time::sleep(Duration::from_secs((1u64 << (self.0 - 1).min(6)).min(60))).await;
+        Ok(())
+    }
+}
+
+pub(crate) fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Resume indexes are opaque to callers, but every backend builds them from numbers
+pub(crate) fn parse_resume_index<const N: usize>(index: &str) -> Result<[i64; N], ApiError> {
+    let invalid = || ApiError::InvalidInput(format!("Invalid resume index: {}", index));
+    let parts = index
+        .split(':')
+        .map(|part| part.parse::<i64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    parts.try_into().map_err(|_| invalid())
+}
+
+// Invoices look back this far on resume, anything settled later was created within it
+pub(crate) const RESUME_LOOKBACK_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// `subscribe_all` for backends without an account feed: diffs list_transactions every
+/// `polling_delay_sec`. The resume index is the unix time of the event.
+pub(crate) async fn poll_all<N>(
+    node: &N,
+    params: SubscribeAllParams,
+    callback: &dyn SubscribeAllCallback,
+) -> Result<(), ApiError>
+where
+    N: LightningNode + ?Sized,
+{
+    let since = match params.resume_from.as_deref() {
+        Some(index) => parse_resume_index::<1>(index)?[0],
+        None => now_secs(),
+    };
+    let delay = Duration::from_secs(params.polling_delay_sec.max(1) as u64);
+    let mut seen = HashMap::new();
+    let mut backoff = Backoff::new();
+    loop {
+        let listed = node
+            .list_transactions(ListTransactionsParams {
+                from: 0,
+                limit: 500,
+                payment_hash: None,
+                search: None,
+                created_after: Some(since - RESUME_LOOKBACK_SECS),
+                created_before: None,
+                cursor: None,
+            })
+            .await;
+        match listed {
+            Ok(txns) => {
+                backoff.reset();
+                for (event_type, txn) in account_transactions(&mut seen, txns, since) {
+                    callback.event(AccountEvent {
+                        event_type,
+                        resume_index: event_time(&txn).to_string(),
+                        transaction: txn,
+                    });
+                }
+            }
+            Err(e) => backoff.retry(e).await?,
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+// When the event happened, failed payments have no settle time
+pub(crate) fn event_time(txn: &Transaction) -> i64 {
+    if txn.settled_at > 0 {
+        txn.settled_at
+    } else {
+        txn.created_at
+    }
+}
+
+/// What changed since the last listing, oldest first. Transactions showing up for the first
+/// time only count if they finished at or after `since`. `seen` ends up holding the listing.
+pub(crate) fn account_transactions(
+    seen: &mut HashMap<String, TransactionStatus>,
+    txns: Vec<Transaction>,
+    since: i64,
+) -> Vec<(AccountEventType, Transaction)> {
+    let mut listed = HashMap::new();
+    let mut events = Vec::new();
+    for txn in txns {
+        if txn.payment_hash.is_empty() {
+            continue;
+        }
+        let previous = seen.get(&txn.payment_hash).copied();
+        listed.insert(txn.payment_hash.clone(), txn.status);
+        if previous == Some(txn.status) || (previous.is_none() && event_time(&txn) < since) {
+            continue;
+        }
+        let event_type = match (txn.direction, txn.status) {
+            (Direction::Incoming, TransactionStatus::Settled) => AccountEventType::InvoiceSettled,
+            (Direction::Outgoing, TransactionStatus::Settled) => AccountEventType::PaymentSent,
+            (Direction::Outgoing, TransactionStatus::Failed) => AccountEventType::PaymentFailed,
+            _ => continue,
+        };
+        events.push((event_type, txn));
+    }
+    *seen = listed;
+    events.sort_by_key(|(_, txn)| event_time(txn));
+    events
+}
+
+async fn poll_channel_events<N, F>(node: &N, params: OnAccountEventParams, mut emit: F)
+where
+    N: LightningNode + ?Sized,
+    F: FnMut(NodeEvent),
+{
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(params.max_polling_sec.max(0) as u64);
+    let delay = Duration::from_secs(params.polling_delay_sec.max(1) as u64);
+
+    // The first listing is the baseline, only changes after it are reported. It is retried
+    // while the node is unreachable; backends without channels answer NotSupported once and
+    // are not asked again.
+    let mut backoff = Backoff::new();
+    let mut known: HashMap<String, Channel> = loop {
+        match node.list_channels().await {
+            Ok(list) => break list.into_iter().map(|channel| (channel.id.clone(), channel)).collect(),
+            Err(e) => {
+                if !matches!(tokio::time::timeout_at(deadline, backoff.retry(e)).await, Ok(Ok(()))) {
+                    tokio::time::sleep_until(deadline).await;
+                    return;
+                }
+            }
+        }
+    };
+
+    while tokio::time::Instant::now() + delay < deadline {
+        tokio::time::sleep(delay).await;
+        if let Ok(list) = node.list_channels().await {
+            diff_channels(&mut known, list).into_iter().for_each(&mut emit);
+        }
+    }
+    tokio::time::sleep_until(deadline).await;
+}
+
+fn diff_channels(known: &mut HashMap<String, Channel>, list: Vec<Channel>) -> Vec<NodeEvent> {
+    let mut events = Vec::new();
+    let mut current = HashMap::new();
+    for channel in list {
+        match known.remove(&channel.id) {
+            None => events.push(NodeEvent::ChannelOpened(channel.clone())),
+            Some(previous)
+                if previous.active != channel.active || previous.short_channel_id != channel.short_channel_id =>
+            {
+                events.push(NodeEvent::ChannelUpdated(channel.clone()))
+            }
+            _ => {}
+        }
+        current.insert(channel.id.clone(), channel);
+    }
+    events.extend(known.drain().map(|(_, channel)| NodeEvent::ChannelClosed(channel)));
+    *known = current;
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn txn(hash: &str, direction: Direction, status: TransactionStatus, settled_at: i64) -> Transaction {
+        Transaction {
+            type_: direction.as_str().to_string(),
+            status,
+            direction,
+            invoice: String::new(),
+            description: String::new(),
+            description_hash: String::new(),
+            preimage: String::new(),
+            payment_hash: hash.to_string(),
+            amount_msats: 1000,
+            fees_paid: 0,
+            created_at: 100,
+            expires_at: 0,
+            settled_at,
+            payer_note: None,
+            external_id: None,
+        }
+    }
+
+    #[test]
+    fn test_account_transactions() {
+        let mut seen = HashMap::new();
+        // settled before the subscription started
+        let baseline = account_transactions(
+            &mut seen,
+            vec![
+                txn("a", Direction::Incoming, TransactionStatus::Pending, 0),
+                txn("old", Direction::Incoming, TransactionStatus::Settled, 150),
+            ],
+            200,
+        );
+        assert!(baseline.is_empty());
+
+        let events = account_transactions(
+            &mut seen,
+            vec![
+                txn("a", Direction::Incoming, TransactionStatus::Settled, 300),
+                txn("b", Direction::Outgoing, TransactionStatus::Failed, 0), // created before, failed now
+                txn("c", Direction::Outgoing, TransactionStatus::Pending, 0),
+                txn("old", Direction::Incoming, TransactionStatus::Settled, 150),
+            ],
+            200,
+        );
+        assert!(seen.contains_key("b"));
+        let kinds: Vec<(AccountEventType, &str)> =
+            events.iter().map(|(kind, txn)| (*kind, txn.payment_hash.as_str())).collect();
+        assert_eq!(kinds, vec![(AccountEventType::InvoiceSettled, "a")]);
+
+        // b was new in that listing and failed before `since`, a resumed poller skips it too
+        let again = account_transactions(
+            &mut seen,
+            vec![
+                txn("a", Direction::Incoming, TransactionStatus::Settled, 300),
+                txn("c", Direction::Outgoing, TransactionStatus::Settled, 400),
+            ],
+            200,
+        );
+        let kinds: Vec<(AccountEventType, &str)> =
+            again.iter().map(|(kind, txn)| (*kind, txn.payment_hash.as_str())).collect();
+        assert_eq!(kinds, vec![(AccountEventType::PaymentSent, "c")]);
+    }
+
+    #[test]
+    fn test_parse_resume_index() {
+        assert_eq!(parse_resume_index::<2>("12:34").unwrap(), [12, 34]);
+        assert_eq!(parse_resume_index::<1>("1700000000").unwrap(), [1_700_000_000]);
+        assert!(parse_resume_index::<2>("12").is_err());
+        assert!(parse_resume_index::<1>("abc").is_err());
+    }
+}
@@ -29,6 +29,31 @@ pub enum ApiError {
     InvalidInput(String),
     #[error("LnurlError: {0}")]
     LnurlError(String),
+    // Normalized backend errors, `reason` keeps the raw backend message
+    #[error("InsufficientBalance: {reason}")]
+    InsufficientBalance { reason: String },
+    #[error("NoRoute: {reason}")]
+    NoRoute { reason: String },
+    #[error("InvoiceExpired: {reason}")]
+    InvoiceExpired { reason: String },
+    #[error("AlreadyPaid: {reason}")]
+    AlreadyPaid { reason: String },
+    #[error("Unauthorized: {reason}")]
+    Unauthorized { reason: String },
+    #[error("RateLimited: {reason}")]
+    RateLimited {
+        retry_after: Option<i64>, // seconds, when the backend tells us
+        reason: String,
+    },
+    #[error("Timeout: {reason}")]
+    Timeout { reason: String },
+    #[error("PaymentInFlight: {reason}")]
+    PaymentInFlight { reason: String },
+    #[error("NotSupported: {reason}")]
+    NotSupported { reason: String },
+    // A PayInvoiceParams field the backend can't apply, rather than silently dropping it
+    #[error("UnsupportedParameter: {parameter}: {reason}")]
+    UnsupportedParameter { parameter: String, reason: String },
 }
 impl From<serde_json::Error> for ApiError {
     fn from(e: serde_json::Error) -> Self {
@@ -38,6 +63,79 @@ impl From<serde_json::Error> for ApiError {
     }
 }
 
+impl ApiError {
+    /// True for transient failures where retrying the same request later can succeed.
+    /// `PaymentInFlight` is deliberately excluded, retrying could pay twice.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ApiError::RateLimited { .. } | ApiError::Timeout { .. } | ApiError::NetworkError(_)
+        )
+    }
+
+    /// Best-effort classification of a free-form backend error message.
+    /// Backends try their native error codes first and fall back to this.
+    pub fn from_message(reason: String) -> Option<ApiError> {
+        let msg = reason.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| msg.contains(n));
+
+        if has(&["insufficient balance", "insufficient_balance", "insufficient funds", "not enough funds", "not enough balance", "cannot afford"]) {
+            Some(ApiError::InsufficientBalance { reason })
+        } else if has(&["no route", "no_route", "route not found", "route_not_found", "unable to find a path", "unable to route"]) {
+            Some(ApiError::NoRoute { reason })
+        } else if has(&["already paid", "already been paid", "already_paid", "already succeeded", "rhash already used"]) {
+            Some(ApiError::AlreadyPaid { reason })
+        } else if msg.contains("invoice") && msg.contains("expired") {
+            Some(ApiError::InvoiceExpired { reason })
+        } else if has(&["unauthorized", "unauthenticated", "not authorized", "permission denied", "verification failed", "invalid api key"]) {
+            Some(ApiError::Unauthorized { reason })
+        } else if has(&["rate limit", "rate_limit", "too many requests"]) {
+            Some(ApiError::RateLimited { retry_after: None, reason })
+        } else if has(&["timed out", "timeout", "deadline exceeded"]) {
+            Some(ApiError::Timeout { reason })
+        } else if has(&["in flight", "in_flight", "in progress", "in_progress"]) {
+            Some(ApiError::PaymentInFlight { reason })
+        } else if has(&["not implemented", "not supported", "unsupported", "unknown command", "method not found"]) {
+            Some(ApiError::NotSupported { reason })
+        } else {
+            None
+        }
+    }
+
+    /// Map an unsuccessful HTTP response, falling back to `Http` when neither the
+    /// status code nor the message says anything more specific.
+    pub fn from_http(status: reqwest::StatusCode, retry_after: Option<i64>, reason: String) -> ApiError {
+        match status.as_u16() {
+            401 | 403 => ApiError::Unauthorized { reason },
+            429 => ApiError::RateLimited { retry_after, reason },
+            408 | 504 => ApiError::Timeout { reason },
+            501 => ApiError::NotSupported { reason },
+            _ => ApiError::from_message(reason.clone())
+                .unwrap_or(ApiError::Http { reason: format!("{} - {}", status, reason) }),
+        }
+    }
+
+    /// Map a transport error from reqwest, `context` describes what we were doing
+    pub fn from_reqwest(e: reqwest::Error, context: &str) -> ApiError {
+        let reason = format!("{}: {}", context, e);
+        if e.is_timeout() {
+            ApiError::Timeout { reason }
+        } else if e.is_connect() {
+            ApiError::NetworkError(reason)
+        } else {
+            ApiError::Http { reason }
+        }
+    }
+}
+
+/// Seconds from a `Retry-After` header (only the delta-seconds form is supported)
+pub(crate) fn retry_after_secs(headers: &reqwest::header::HeaderMap) -> Option<i64> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok())
+}
+
 /// Generate a new BIP39 mnemonic phrase for wallet creation.
 /// Uses cryptographically secure randomness from the OS.
 /// 
@@ -46,8 +144,7 @@ impl From<serde_json::Error> for ApiError {
 /// 
 /// # Returns
 /// A space-separated mnemonic phrase
-#[cfg(feature = "uniffi")]
-#[uniffi::export]
+#[cfg_attr(feature = "uniffi", uniffi::export)]
 pub fn generate_mnemonic(word_count: Option<u8>) -> Result<String, ApiError> {
     use bip39::{Language, Mnemonic};
     use rand::rngs::OsRng;
@@ -87,6 +184,13 @@ macro_rules! impl_lightning_node {
                 }).await.unwrap()
             }
 
+            async fn capabilities(&self) -> Result<crate::NodeCapabilities, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::capabilities(&this).await
+                }).await.unwrap()
+            }
+
             async fn create_invoice(&self, params: crate::CreateInvoiceParams) -> Result<crate::Transaction, crate::ApiError> {
                 let this = self.clone();
                 crate::TOKIO_RUNTIME.spawn(async move {
@@ -101,6 +205,53 @@ macro_rules! impl_lightning_node {
                 }).await.unwrap()
             }
 
+            async fn pay(
+                &self,
+                destination: String,
+                amount_msats: Option<i64>,
+                options: crate::PayOptions,
+            ) -> Result<crate::PayResponse, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::pay(&this, destination, amount_msats, options).await
+                }).await.unwrap()
+            }
+
+            async fn zap(
+                &self,
+                destination: String,
+                amount_msats: i64,
+                params: crate::ZapRequestParams,
+            ) -> Result<crate::ZapResponse, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::zap(&this, destination, amount_msats, params).await
+                }).await.unwrap()
+            }
+
+            async fn estimate_payment_fee(
+                &self,
+                invoice: String,
+                amount_msats: Option<i64>,
+            ) -> Result<crate::PaymentFeeEstimate, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::estimate_payment_fee(&this, invoice, amount_msats).await
+                }).await.unwrap()
+            }
+
+            async fn pay_keysend(
+                &self,
+                destination_pubkey: String,
+                amount_msats: i64,
+                custom_records: Vec<crate::TLVRecord>,
+            ) -> Result<crate::PayKeysendResponse, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::pay_keysend(&this, destination_pubkey, amount_msats, custom_records).await
+                }).await.unwrap()
+            }
+
             async fn create_offer(&self, params: crate::CreateOfferParams) -> Result<crate::Offer, crate::ApiError> {
                 let this = self.clone();
                 crate::TOKIO_RUNTIME.spawn(async move {
@@ -141,6 +292,13 @@ macro_rules! impl_lightning_node {
                 }).await.unwrap()
             }
 
+            async fn lookup_payment(&self, payment_hash: String) -> Result<crate::OutgoingPayment, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::lookup_payment(&this, payment_hash).await
+                }).await.unwrap()
+            }
+
             async fn list_transactions(
                 &self,
                 params: crate::ListTransactionsParams,
@@ -151,21 +309,105 @@ macro_rules! impl_lightning_node {
                 }).await.unwrap()
             }
 
-            async fn decode(&self, str: String) -> Result<String, crate::ApiError> {
+            async fn list_transactions_page(
+                &self,
+                params: crate::ListTransactionsParams,
+            ) -> Result<crate::TransactionPage, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::list_transactions_page(&this, params).await
+                }).await.unwrap()
+            }
+
+            async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, crate::ApiError> {
                 let this = self.clone();
                 crate::TOKIO_RUNTIME.spawn(async move {
                     <$node_type>::decode(&this, str).await
                 }).await.unwrap()
             }
 
-            async fn on_invoice_events(
+            fn on_invoice_events(
                 &self,
                 params: crate::types::OnInvoiceEventParams,
                 callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-            ) {
+            ) -> std::sync::Arc<crate::Subscription> {
+                <$node_type>::on_invoice_events(self, params, callback)
+            }
+
+            fn on_payment_events(
+                &self,
+                params: crate::types::OnPaymentEventParams,
+                callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+            ) -> std::sync::Arc<crate::Subscription> {
+                <$node_type>::on_payment_events(self, params, callback)
+            }
+
+            fn subscribe_all(
+                &self,
+                params: crate::types::SubscribeAllParams,
+                callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+            ) -> std::sync::Arc<crate::Subscription> {
+                <$node_type>::subscribe_all(self, params, callback)
+            }
+
+            async fn list_channels(&self) -> Result<Vec<crate::Channel>, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::list_channels(&this).await
+                }).await.unwrap()
+            }
+
+            async fn open_channel(&self, params: crate::OpenChannelRequest) -> Result<crate::OpenChannelResponse, crate::ApiError> {
                 let this = self.clone();
                 crate::TOKIO_RUNTIME.spawn(async move {
-                    <$node_type>::on_invoice_events(&this, params, callback).await
+                    <$node_type>::open_channel(&this, params).await
+                }).await.unwrap()
+            }
+
+            async fn close_channel(&self, params: crate::CloseChannelRequest) -> Result<crate::CloseChannelResponse, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::close_channel(&this, params).await
+                }).await.unwrap()
+            }
+
+            async fn update_channel_policy(&self, params: crate::UpdateChannelRequest) -> Result<(), crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::update_channel_policy(&this, params).await
+                }).await.unwrap()
+            }
+
+            async fn get_onchain_balance(&self) -> Result<crate::OnchainBalanceResponse, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::get_onchain_balance(&this).await
+                }).await.unwrap()
+            }
+
+            async fn new_onchain_address(&self) -> Result<String, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::new_onchain_address(&this).await
+                }).await.unwrap()
+            }
+
+            async fn send_onchain(
+                &self,
+                address: String,
+                amount_sats: i64,
+                sat_per_vbyte: Option<i64>,
+            ) -> Result<crate::SendOnchainResponse, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::send_onchain(&this, address, amount_sats, sat_per_vbyte).await
+                }).await.unwrap()
+            }
+
+            async fn list_onchain_transactions(&self) -> Result<Vec<crate::OnchainTransaction>, crate::ApiError> {
+                let this = self.clone();
+                crate::TOKIO_RUNTIME.spawn(async move {
+                    <$node_type>::list_onchain_transactions(&this).await
                 }).await.unwrap()
             }
         }
@@ -182,6 +424,7 @@ pub mod phoenixd {
 pub mod cln {
     pub mod api;
     pub mod lib;
+    pub mod rune;
     pub mod types;
     pub use lib::{ClnConfig, ClnNode};
 }
@@ -189,6 +432,7 @@ pub mod cln {
 pub mod lnd {
     pub mod api;
     pub mod lib;
+    pub mod macaroon;
     pub mod types;
     pub use lib::{LndConfig, LndNode};
 }
@@ -197,6 +441,8 @@ pub mod nwc {
     pub mod api;
     pub mod lib;
     pub mod types;
+    #[cfg(test)]
+    mod test_relay;
     pub use lib::{NwcConfig, NwcNode};
 }
 
@@ -230,12 +476,22 @@ pub mod spark {
 
 pub mod lnurl;
 
+pub mod bolt12;
+
 pub mod types;
 pub use types::*;
 
 pub mod utils;
 pub use utils::*;
 
+mod websocket;
+
+pub mod subscription;
+pub use subscription::{Subscription, SubscriptionOutcome};
+
+pub mod events;
+pub use events::{InvoiceEvent, LightningNodeEvents, NodeEvent};
+
 pub mod database;
 pub use database::{Db, DbError, Payment};
 
@@ -345,3 +601,49 @@ pub async fn create_spark_node(config: spark::SparkConfig) -> Result<Arc<dyn Lig
 
 #[cfg(feature = "uniffi")]
 uniffi::setup_scaffolding!();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_from_message() {
+        let e = ApiError::from_message("Insufficient balance to pay invoice".to_string());
+        assert!(matches!(e, Some(ApiError::InsufficientBalance { .. })));
+
+        let e = ApiError::from_message("invoice expired at 1700000000".to_string());
+        assert!(matches!(e, Some(ApiError::InvoiceExpired { .. })));
+
+        let e = ApiError::from_message("unable to find a path to destination".to_string());
+        assert!(matches!(e, Some(ApiError::NoRoute { .. })));
+
+        // The raw message is kept on the variant
+        if let Some(ApiError::AlreadyPaid { reason }) =
+            ApiError::from_message("invoice is already paid".to_string())
+        {
+            assert_eq!(reason, "invoice is already paid");
+        } else {
+            panic!("expected AlreadyPaid");
+        }
+
+        assert!(ApiError::from_message("something else went wrong".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_api_error_from_http() {
+        let e = ApiError::from_http(reqwest::StatusCode::UNAUTHORIZED, None, "bad macaroon".to_string());
+        assert!(matches!(e, ApiError::Unauthorized { .. }));
+
+        let e = ApiError::from_http(reqwest::StatusCode::TOO_MANY_REQUESTS, Some(30), "slow down".to_string());
+        assert!(matches!(e, ApiError::RateLimited { retry_after: Some(30), .. }));
+        assert!(e.is_retryable());
+
+        let e = ApiError::from_http(reqwest::StatusCode::INTERNAL_SERVER_ERROR, None, "boom".to_string());
+        assert!(matches!(e, ApiError::Http { .. }));
+        assert!(!e.is_retryable());
+
+        let e = ApiError::from_http(reqwest::StatusCode::BAD_REQUEST, None, "payment is in flight".to_string());
+        assert!(matches!(e, ApiError::PaymentInFlight { .. }));
+        assert!(!e.is_retryable());
+    }
+}
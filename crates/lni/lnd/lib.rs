@@ -4,7 +4,10 @@ use napi_derive::napi;
 use crate::types::NodeInfo;
 use crate::{
     ApiError, CreateInvoiceParams, CreateOfferParams, ListTransactionsParams, LookupInvoiceParams,
-    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction,
+    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction, Channel, CloseChannelRequest,
+    CloseChannelResponse, OpenChannelRequest, OpenChannelResponse, UpdateChannelRequest,
+    OnchainBalanceResponse, OnchainTransaction, SendOnchainResponse, PayKeysendResponse, TLVRecord,
+    NodeCapabilities,
 };
 #[cfg(not(feature = "uniffi"))]
 use crate::LightningNode;
@@ -57,6 +60,10 @@ impl LndNode {
         crate::lnd::api::get_info(self.config.clone()).await
     }
 
+    pub async fn capabilities(&self) -> Result<NodeCapabilities, ApiError> {
+        crate::lnd::api::capabilities(self.config.clone()).await
+    }
+
     pub async fn create_invoice(&self, params: CreateInvoiceParams) -> Result<Transaction, ApiError> {
         crate::lnd::api::create_invoice(self.config.clone(), params).await
     }
@@ -65,8 +72,48 @@ impl LndNode {
         crate::lnd::api::pay_invoice(self.config.clone(), params).await
     }
 
+    pub async fn estimate_payment_fee(
+        &self,
+        invoice: String,
+        amount_msats: Option<i64>,
+    ) -> Result<crate::PaymentFeeEstimate, ApiError> {
+        crate::lnd::api::estimate_payment_fee(self.config.clone(), invoice, amount_msats).await
+    }
+
+    /// zbase32 signature of `message` by the node key, see `lnurl::MessageSigner`
+    pub async fn sign_message(&self, message: String) -> Result<String, ApiError> {
+        crate::lnd::api::sign_message(self.config.clone(), message).await
+    }
+
+    pub async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: crate::PayOptions,
+    ) -> Result<crate::PayResponse, ApiError> {
+        crate::lnurl::pay(self, destination, amount_msats, options).await
+    }
+
+    pub async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: crate::ZapRequestParams,
+    ) -> Result<crate::ZapResponse, ApiError> {
+        crate::lnurl::zap(self, destination, amount_msats, params).await
+    }
+
+    pub async fn pay_keysend(
+        &self,
+        destination_pubkey: String,
+        amount_msats: i64,
+        custom_records: Vec<TLVRecord>,
+    ) -> Result<PayKeysendResponse, ApiError> {
+        crate::lnd::api::pay_keysend(self.config.clone(), destination_pubkey, amount_msats, custom_records).await
+    }
+
     pub async fn create_offer(&self, _params: CreateOfferParams) -> Result<Offer, ApiError> {
-        Err(ApiError::Api { reason: "create_offer not implemented for LndNode".to_string() })
+        Err(ApiError::NotSupported { reason: "create_offer not implemented for LndNode".to_string() })
     }
 
     pub async fn get_offer(&self, search: Option<String>) -> Result<Offer, ApiError> {
@@ -100,6 +147,10 @@ impl LndNode {
         .await
     }
 
+    pub async fn lookup_payment(&self, payment_hash: String) -> Result<crate::OutgoingPayment, ApiError> {
+        crate::lnd::api::lookup_payment(self.config.clone(), payment_hash).await
+    }
+
     pub async fn list_transactions(
         &self,
         params: ListTransactionsParams,
@@ -113,22 +164,103 @@ impl LndNode {
         .await
     }
 
-    pub async fn decode(&self, str: String) -> Result<String, ApiError> {
+    pub async fn list_transactions_page(
+        &self,
+        params: ListTransactionsParams,
+    ) -> Result<crate::TransactionPage, ApiError> {
+        crate::lnd::api::list_transactions_page(self.config.clone(), params).await
+    }
+
+    pub async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, ApiError> {
         crate::lnd::api::decode(self.config.clone(), str).await
     }
 
-    pub async fn on_invoice_events(
+    pub fn on_invoice_events(
         &self,
         params: crate::types::OnInvoiceEventParams,
         callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-    ) {
-        crate::lnd::api::on_invoice_events(self.config.clone(), params, callback).await
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::invoice_events(callback, |callback| {
+            crate::lnd::api::on_invoice_events(config, params, callback)
+        })
+    }
+
+    pub fn on_payment_events(
+        &self,
+        params: crate::types::OnPaymentEventParams,
+        callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::payment_events(callback, |callback| {
+            crate::lnd::api::payment_events(config, params, callback)
+        })
+    }
+
+    pub fn subscribe_all(
+        &self,
+        params: crate::types::SubscribeAllParams,
+        callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::subscribe_all(callback, |callback| {
+            crate::lnd::api::subscribe_all(config, params, callback)
+        })
+    }
+
+    pub async fn list_channels(&self) -> Result<Vec<Channel>, ApiError> {
+        crate::lnd::api::list_channels(self.config.clone()).await
+    }
+
+    pub async fn open_channel(&self, params: OpenChannelRequest) -> Result<OpenChannelResponse, ApiError> {
+        crate::lnd::api::open_channel(self.config.clone(), params).await
+    }
+
+    pub async fn close_channel(&self, params: CloseChannelRequest) -> Result<CloseChannelResponse, ApiError> {
+        crate::lnd::api::close_channel(self.config.clone(), params).await
+    }
+
+    pub async fn update_channel_policy(&self, params: UpdateChannelRequest) -> Result<(), ApiError> {
+        crate::lnd::api::update_channel_policy(self.config.clone(), params).await
+    }
+
+    pub async fn get_onchain_balance(&self) -> Result<OnchainBalanceResponse, ApiError> {
+        crate::lnd::api::get_onchain_balance(self.config.clone()).await
+    }
+
+    pub async fn new_onchain_address(&self) -> Result<String, ApiError> {
+        crate::lnd::api::new_onchain_address(self.config.clone()).await
+    }
+
+    pub async fn send_onchain(
+        &self,
+        address: String,
+        amount_sats: i64,
+        sat_per_vbyte: Option<i64>,
+    ) -> Result<SendOnchainResponse, ApiError> {
+        crate::lnd::api::send_onchain(self.config.clone(), address, amount_sats, sat_per_vbyte).await
+    }
+
+    pub async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, ApiError> {
+        crate::lnd::api::list_onchain_transactions(self.config.clone()).await
     }
 }
 
 // Trait implementation for Rust consumers - uses the impl_lightning_node macro
 // Trait implementation for polymorphic access via Arc<dyn LightningNode>
 crate::impl_lightning_node!(LndNode);
+impl crate::LightningNodeEvents for LndNode {}
+
+// Lets the node derive LNURL-auth keys (LUD-13)
+#[async_trait::async_trait]
+impl crate::lnurl::MessageSigner for LndNode {
+    async fn sign_message(&self, message: String) -> Result<String, ApiError> {
+        let this = self.clone();
+        crate::TOKIO_RUNTIME.spawn(async move {
+            LndNode::sign_message(&this, message).await
+        }).await.unwrap()
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -404,6 +536,7 @@ mod tests {
             search: None,
             created_after: None,
             created_before: None,
+            cursor: None,
         };
         match NODE.list_transactions(params).await {
             Ok(txns) => {
@@ -419,6 +552,42 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_list_transactions_page() {
+        // walk two pages and make sure nothing comes back twice
+        let mut params = ListTransactionsParams {
+            from: 0,
+            limit: 5,
+            payment_hash: None,
+            search: None,
+            created_after: None,
+            created_before: None,
+            cursor: None,
+        };
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..2 {
+            let page = match NODE.list_transactions_page(params).await {
+                Ok(page) => page,
+                Err(e) => panic!("Failed to list transactions page: {:?}", e),
+            };
+            dbg!(&page.next_cursor);
+            assert!(page.transactions.len() <= 5);
+            for txn in &page.transactions {
+                assert!(seen.insert((txn.payment_hash.clone(), txn.created_at)), "Transaction repeated across pages");
+            }
+            let Some(next_cursor) = page.next_cursor else { break };
+            params = ListTransactionsParams {
+                from: 0,
+                limit: 5,
+                payment_hash: None,
+                search: None,
+                created_after: None,
+                created_before: None,
+                cursor: Some(next_cursor),
+            };
+        }
+    }
+
     #[tokio::test]
     async fn test_decode() {
         match NODE.decode(LND_TEST_PAYMENT_REQUEST.to_string()).await {
@@ -466,7 +635,7 @@ mod tests {
         };
 
         // Start the event listener
-        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).await;
+        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).wait().await;
 
         // Check if events were received
         let received_events = events.lock().unwrap();
@@ -476,4 +645,104 @@ mod tests {
             "Expected to receive at least one invoice event"
         );
     }
+
+    #[tokio::test]
+    async fn test_list_channels() {
+        match NODE.list_channels().await {
+            Ok(channels) => {
+                println!("channels: {:?}", channels);
+                for channel in channels.iter() {
+                    assert!(
+                        channel.local_balance <= channel.capacity,
+                        "Local balance should not exceed channel capacity"
+                    );
+                }
+            }
+            Err(e) => {
+                panic!("Failed to list channels: {:?}", e);
+            }
+        }
+    }
+    #[tokio::test]
+    async fn test_get_onchain_balance() {
+        match NODE.get_onchain_balance().await {
+            Ok(balance) => {
+                println!("onchain balance: {:?}", balance);
+                assert!(balance.spendable <= balance.total, "Spendable should not exceed total");
+            }
+            Err(e) => {
+                panic!("Failed to get onchain balance: {:?}", e);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_new_onchain_address() {
+        match NODE.new_onchain_address().await {
+            Ok(address) => {
+                println!("onchain address: {}", address);
+                assert!(!address.is_empty(), "Address should not be empty");
+            }
+            Err(e) => {
+                panic!("Failed to create onchain address: {:?}", e);
+            }
+        }
+    }
+    #[tokio::test]
+    async fn test_capabilities() {
+        match NODE.capabilities().await {
+            Ok(capabilities) => {
+                println!("capabilities: {:?}", capabilities);
+                assert!(capabilities.lookup_invoice, "Test credentials should be able to look up invoices");
+            }
+            Err(e) => {
+                panic!("Failed to get capabilities: {:?}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cursor_pages_ascending_listings() {
+        use crate::lnd::api::{newest_first, next_page, LndCursor};
+        let txn = |created_at: i64| crate::Transaction {
+            type_: "".to_string(),
+            status: crate::TransactionStatus::Settled,
+            direction: crate::Direction::Incoming,
+            invoice: "".to_string(),
+            preimage: "".to_string(),
+            payment_hash: created_at.to_string(),
+            amount_msats: 1000,
+            fees_paid: 0,
+            created_at,
+            expires_at: 0,
+            settled_at: 0,
+            description: "".to_string(),
+            description_hash: "".to_string(),
+            payer_note: None,
+            external_id: None,
+        };
+        // invoices at 10..50 with add_index 1..5, payments at 15, 25, 35 with payment_index 1..3
+        let invoices: Vec<(i64, i64)> = (1..=5).map(|i| (i, i * 10)).collect();
+        let payments: Vec<(i64, i64)> = (1..=3).map(|i| (i, i * 10 + 5)).collect();
+        // what lnd replies to a reversed listing: the newest `count` below the offset, ascending
+        let listing = |all: &[(i64, i64)], count: usize, offset: i64| -> Vec<(i64, crate::Transaction)> {
+            let older: Vec<_> = all.iter().filter(|(index, _)| offset == 0 || *index < offset).collect();
+            older[older.len().saturating_sub(count)..].iter().map(|(index, at)| (*index, txn(*at))).collect()
+        };
+
+        let mut cursor = LndCursor::default();
+        let mut seen = vec![];
+        let page = |cursor: &mut LndCursor| {
+            let invoices = newest_first(listing(&invoices, 3, cursor.invoice_offset));
+            let payments = newest_first(listing(&payments, 3, cursor.payment_offset));
+            next_page(cursor, invoices, payments, 3)
+        };
+        seen.extend(page(&mut cursor).into_iter().map(|t| t.created_at));
+        assert_eq!((cursor.invoice_offset, cursor.payment_offset), (4, 3));
+        seen.extend(page(&mut cursor).into_iter().map(|t| t.created_at));
+        assert_eq!((cursor.invoice_offset, cursor.payment_offset), (2, 2));
+        seen.extend(page(&mut cursor).into_iter().map(|t| t.created_at));
+        assert!(cursor.invoices_done && cursor.payments_done);
+        assert_eq!(seen, vec![50, 40, 35, 30, 25, 20, 15, 10]);
+    }
 }
@@ -0,0 +1,204 @@
+//! Read-only view of the permissions baked into an LND macaroon
+//!
+//! LND macaroons use the libmacaroons v2 binary format. The identifier is a
+//! version byte (3) followed by a protobuf `MacaroonId { nonce, storageId, ops }`
+//! where each op is an entity with its allowed actions, e.g. `invoices: [read, write]`.
+//! https://github.com/lightningnetwork/lnd/blob/master/macaroons/README.md
+//!
+//! The signature and first-party caveats are not checked, LND does that.
+
+use crate::ApiError;
+
+const MACAROON_V2: u8 = 2;
+const FIELD_EOS: u64 = 0;
+const FIELD_IDENTIFIER: u64 = 2;
+const LND_IDENTIFIER_VERSION: u8 = 3;
+// MacaroonId and Op protobuf field numbers
+const MACAROON_ID_OPS: u64 = 3;
+const OP_ENTITY: u64 = 1;
+const OP_ACTIONS: u64 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacaroonPermissions {
+    ops: Vec<(String, Vec<String>)>,
+}
+
+impl MacaroonPermissions {
+    /// Parse a hex encoded macaroon, as passed in `LndConfig::macaroon`
+    pub fn parse(macaroon_hex: &str) -> Result<MacaroonPermissions, ApiError> {
+        let bytes = hex::decode(macaroon_hex.trim())
+            .map_err(|e| ApiError::InvalidInput(format!("Invalid macaroon hex: {}", e)))?;
+        let identifier = read_identifier(&bytes)?;
+        if identifier.first() != Some(&LND_IDENTIFIER_VERSION) {
+            return Err(ApiError::InvalidInput(
+                "Unsupported macaroon identifier version".to_string(),
+            ));
+        }
+
+        let mut ops = vec![];
+        for (field, value) in read_protobuf(&identifier[1..])? {
+            if field == MACAROON_ID_OPS {
+                ops.push(read_op(value)?);
+            }
+        }
+        Ok(MacaroonPermissions { ops })
+    }
+
+    /// True when the macaroon grants `action` on `entity`, e.g. ("offchain", "write")
+    pub fn allows(&self, entity: &str, action: &str) -> bool {
+        self.ops
+            .iter()
+            .any(|(e, actions)| e == entity && actions.iter().any(|a| a == action))
+    }
+
+    /// Custom macaroons can grant single RPCs instead, e.g. "/lnrpc.Lightning/AddInvoice"
+    pub fn allows_uri(&self, uri: &str) -> bool {
+        self.allows("uri", uri)
+    }
+}
+
+fn read_identifier(bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+    if bytes.first() != Some(&MACAROON_V2) {
+        return Err(ApiError::InvalidInput(
+            "Only v2 binary macaroons are supported".to_string(),
+        ));
+    }
+    // The header section holds the optional location and the identifier
+    let mut pos = 1;
+    loop {
+        let field = read_varint(bytes, &mut pos)?;
+        if field == FIELD_EOS {
+            break;
+        }
+        let len = read_varint(bytes, &mut pos)? as usize;
+        let data = bytes
+            .get(pos..pos + len)
+            .ok_or_else(|| ApiError::InvalidInput("Truncated macaroon".to_string()))?;
+        pos += len;
+        if field == FIELD_IDENTIFIER {
+            return Ok(data.to_vec());
+        }
+    }
+    Err(ApiError::InvalidInput("Macaroon has no identifier".to_string()))
+}
+
+fn read_op(bytes: &[u8]) -> Result<(String, Vec<String>), ApiError> {
+    let mut entity = String::new();
+    let mut actions = vec![];
+    for (field, value) in read_protobuf(bytes)? {
+        let value = String::from_utf8_lossy(value).to_string();
+        match field {
+            OP_ENTITY => entity = value,
+            OP_ACTIONS => actions.push(value),
+            _ => {}
+        }
+    }
+    Ok((entity, actions))
+}
+
+// Length-delimited protobuf fields, other wire types are skipped
+fn read_protobuf(bytes: &[u8]) -> Result<Vec<(u64, &[u8])>, ApiError> {
+    let mut fields = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let key = read_varint(bytes, &mut pos)?;
+        let (field, wire_type) = (key >> 3, key & 0x7);
+        let len = match wire_type {
+            0 => {
+                read_varint(bytes, &mut pos)?;
+                continue;
+            }
+            1 => 8,
+            2 => read_varint(bytes, &mut pos)? as usize,
+            5 => 4,
+            _ => {
+                return Err(ApiError::InvalidInput(format!(
+                    "Unsupported protobuf wire type {} in macaroon",
+                    wire_type
+                )))
+            }
+        };
+        let data = bytes
+            .get(pos..pos + len)
+            .ok_or_else(|| ApiError::InvalidInput("Truncated macaroon identifier".to_string()))?;
+        pos += len;
+        if wire_type == 2 {
+            fields.push((field, data));
+        }
+    }
+    Ok(fields)
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, ApiError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| ApiError::InvalidInput("Truncated macaroon".to_string()))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(ApiError::InvalidInput("Varint too long in macaroon".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(out: &mut Vec<u8>, key: u8, data: &[u8]) {
+        out.push(key);
+        out.push(data.len() as u8);
+        out.extend_from_slice(data);
+    }
+
+    // Builds a minimal v2 macaroon with the given ops, no caveats
+    fn make_macaroon(ops: &[(&str, &[&str])]) -> String {
+        let mut id = vec![LND_IDENTIFIER_VERSION];
+        field(&mut id, 0x0a, &[1, 2, 3]); // nonce
+        field(&mut id, 0x12, b"0"); // storage id
+        for (entity, actions) in ops {
+            let mut op = vec![];
+            field(&mut op, 0x0a, entity.as_bytes());
+            for action in actions.iter() {
+                field(&mut op, 0x12, action.as_bytes());
+            }
+            field(&mut id, 0x1a, &op);
+        }
+
+        let mut mac = vec![MACAROON_V2];
+        field(&mut mac, 1, b"lnd");
+        field(&mut mac, 2, &id);
+        mac.push(0); // end of header
+        mac.push(0); // end of caveats
+        field(&mut mac, 6, &[0u8; 32]);
+        hex::encode(mac)
+    }
+
+    #[test]
+    fn test_invoice_macaroon() {
+        let mac = make_macaroon(&[("address", &["read", "write"]), ("invoices", &["read", "write"]), ("onchain", &["read"])]);
+        let perms = MacaroonPermissions::parse(&mac).unwrap();
+        assert!(perms.allows("invoices", "write"));
+        assert!(perms.allows("address", "write"));
+        assert!(perms.allows("onchain", "read"));
+        assert!(!perms.allows("offchain", "write"));
+        assert!(!perms.allows("onchain", "write"));
+    }
+
+    #[test]
+    fn test_uri_permissions() {
+        let mac = make_macaroon(&[("uri", &["/lnrpc.Lightning/AddInvoice"])]);
+        let perms = MacaroonPermissions::parse(&mac).unwrap();
+        assert!(perms.allows_uri("/lnrpc.Lightning/AddInvoice"));
+        assert!(!perms.allows_uri("/routerrpc.Router/SendPaymentV2"));
+    }
+
+    #[test]
+    fn test_invalid_macaroon() {
+        assert!(MacaroonPermissions::parse("zz").is_err());
+        assert!(MacaroonPermissions::parse("0201036c6e64").is_err()); // no identifier
+    }
+}
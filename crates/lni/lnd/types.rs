@@ -103,6 +103,55 @@ pub struct ListOffersResponse {
     pub offers: Vec<Offer>,
 }
 
+// /v1/payments, amounts are strings and unset fields may be missing
+#[derive(Debug, Deserialize)]
+pub struct ListPaymentsResponse {
+    #[serde(default)]
+    pub payments: Vec<LndPayment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LndPayment {
+    pub payment_hash: String,
+    pub value_msat: Option<String>, // excludes fees
+    pub fee_msat: Option<String>,
+    pub payment_preimage: Option<String>, // hex
+    pub payment_request: Option<String>,
+    pub status: Option<String>, // IN_FLIGHT, SUCCEEDED, FAILED or INITIATED
+    pub creation_date: Option<String>,
+    pub failure_reason: Option<String>,
+    pub htlcs: Option<Vec<PaymentHtlcAttempt>>,
+    pub payment_index: Option<String>,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/router/estimate-route-fee/
+#[derive(Debug, Deserialize)]
+pub struct EstimateRouteFeeResponse {
+    #[serde(default)]
+    pub routing_fee_msat: String,
+    #[serde(default)]
+    pub failure_reason: String,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/lightning/sign-message/
+#[derive(Debug, Deserialize)]
+pub struct SignMessageResponse {
+    pub signature: String, // zbase32
+}
+
+// One update of /v2/router/track/{hash}, the first one is the current state
+#[derive(Debug, Deserialize)]
+pub struct PaymentStreamMessage {
+    pub result: Option<LndPayment>,
+    pub error: Option<LndError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentHtlcAttempt {
+    pub status: Option<String>,
+    pub resolve_time_ns: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListInvoiceResponseWrapper {
     pub invoices: Vec<ListInvoiceResponse>,
@@ -110,6 +159,13 @@ pub struct ListInvoiceResponseWrapper {
     pub first_index_offset: Option<String>,
 }
 
+// grpc-gateway sends each message of a server stream as its own line
+#[derive(Debug, Deserialize)]
+pub struct InvoiceStreamMessage {
+    pub result: Option<ListInvoiceResponse>,
+    pub error: Option<LndError>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListInvoiceResponse {
     pub memo: Option<String>,
@@ -239,3 +295,202 @@ pub struct Amount {
     pub sat: Option<String>,
     pub msat: Option<String>,
 }
+
+// grpc-gateway error body, streaming endpoints wrap it as {"error": {...}}
+#[derive(Debug, Deserialize)]
+pub struct LndError {
+    pub code: Option<i64>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LndErrorWrapper {
+    pub error: LndError,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/lightning/list-channels/
+#[derive(Debug, Deserialize)]
+pub struct ListChannelsResponse {
+    #[serde(default)]
+    pub channels: Vec<LndChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LndChannel {
+    #[serde(default)]
+    pub active: bool,
+    pub remote_pubkey: String,
+    pub channel_point: String,
+    pub chan_id: String,
+    pub capacity: String,
+    pub local_balance: String,
+    pub remote_balance: String,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub initiator: bool,
+    pub local_constraints: Option<ChannelConstraints>,
+    pub remote_constraints: Option<ChannelConstraints>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelConstraints {
+    pub chan_reserve_sat: String,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/lightning/pending-channels/
+#[derive(Debug, Deserialize)]
+pub struct PendingChannelsResponse {
+    #[serde(default)]
+    pub total_limbo_balance: String,
+    #[serde(default)]
+    pub pending_open_channels: Vec<PendingOpenChannel>,
+    #[serde(default)]
+    pub pending_force_closing_channels: Vec<ClosingChannel>,
+    #[serde(default)]
+    pub waiting_close_channels: Vec<ClosingChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClosingChannel {
+    pub channel: PendingChannel,
+    #[serde(default)]
+    pub limbo_balance: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingOpenChannel {
+    pub channel: PendingChannel,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingChannel {
+    pub remote_node_pub: String,
+    pub channel_point: String,
+    pub capacity: String,
+    pub local_balance: String,
+    pub remote_balance: String,
+    pub local_chan_reserve_sat: String,
+    pub remote_chan_reserve_sat: String,
+    #[serde(default)]
+    pub initiator: String,
+    #[serde(default)]
+    pub private: bool,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/lightning/open-channel-sync/
+#[derive(Debug, Deserialize)]
+pub struct ChannelPoint {
+    pub funding_txid_bytes: Option<String>,
+    pub funding_txid_str: Option<String>,
+    #[serde(default)]
+    pub output_index: i64,
+}
+
+// first message of the CloseChannel stream
+// https://lightning.engineering/api-docs/api/lnd/lightning/close-channel/
+#[derive(Debug, Deserialize)]
+pub struct CloseStatusUpdateWrapper {
+    pub result: CloseStatusUpdate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloseStatusUpdate {
+    pub close_pending: Option<PendingUpdate>,
+    pub chan_close: Option<ChannelCloseUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingUpdate {
+    pub txid: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelCloseUpdate {
+    pub closing_txid: String,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/lightning/get-chan-info/
+#[derive(Debug, Deserialize)]
+pub struct ChannelEdge {
+    pub node1_pub: String,
+    pub node2_pub: String,
+    pub node1_policy: Option<RoutingPolicy>,
+    pub node2_policy: Option<RoutingPolicy>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoutingPolicy {
+    pub time_lock_delta: i64,
+    pub min_htlc: String,
+    pub fee_base_msat: String,
+    pub fee_rate_milli_msat: String,
+    pub max_htlc_msat: String,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/lightning/update-channel-policy/
+#[derive(Debug, Deserialize)]
+pub struct PolicyUpdateResponse {
+    #[serde(default)]
+    pub failed_updates: Vec<FailedUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FailedUpdate {
+    pub reason: String,
+    pub update_error: String,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/lightning/wallet-balance/
+#[derive(Debug, Deserialize)]
+pub struct WalletBalanceResponse {
+    #[serde(default)]
+    pub total_balance: String,
+    #[serde(default)]
+    pub confirmed_balance: String,
+    #[serde(default)]
+    pub locked_balance: String,
+    #[serde(default)]
+    pub reserved_balance_anchor_chan: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewAddressResponse {
+    pub address: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendCoinsResponse {
+    pub txid: String,
+}
+
+// https://lightning.engineering/api-docs/api/lnd/lightning/get-transactions/
+#[derive(Debug, Deserialize)]
+pub struct TransactionDetails {
+    #[serde(default)]
+    pub transactions: Vec<LndTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LndTransaction {
+    pub tx_hash: String,
+    pub amount: String, // signed, negative when sending
+    #[serde(default)]
+    pub num_confirmations: i64,
+    #[serde(default)]
+    pub block_height: i64,
+    #[serde(default)]
+    pub time_stamp: String,
+    #[serde(default)]
+    pub total_fees: String,
+    #[serde(default)]
+    pub output_details: Vec<OutputDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OutputDetail {
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub is_our_address: bool,
+}
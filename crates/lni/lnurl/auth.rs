@@ -0,0 +1,244 @@
+//! LNURL-auth (LUD-04): log in to a service by signing its `k1` with a key only used for that domain
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bip39::Mnemonic;
+use bitcoin::bip32::{ChildNumber, Xpriv};
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use bitcoin::secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use bitcoin::Network;
+#[cfg(feature = "napi_rs")]
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+
+use super::{callback_url, lnurl_get, lnurl_to_url, LnurlStatusResponse};
+use crate::ApiError;
+
+/// The message LUD-13 has a node sign to derive its LNURL-auth hashing key
+pub const HASHING_KEY_MESSAGE: &str = "DO NOT EVER SIGN THIS TEXT WITH YOUR PRIVATE KEYS! IT IS ONLY USED FOR DERIVATION OF LNURL-AUTH HASHING-KEY, DISCLOSING ITS SIGNATURE WILL COMPROMISE YOUR LNURL-AUTH IDENTITY AND MAY LEAD TO LOSS OF FUNDS!";
+
+/// Signs a message with a node's key, the way lnd and CLN's `signmessage` do. Used to derive
+/// LNURL-auth linking keys from a node instead of a mnemonic.
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+#[async_trait]
+pub trait MessageSigner: Send + Sync {
+    // The zbase32 signature of `message`
+    async fn sign_message(&self, message: String) -> Result<String, ApiError>;
+}
+
+/// Where LNURL-auth linking keys come from
+#[derive(Clone)]
+pub enum LnurlAuthSigner {
+    /// LUD-05, keys derived under m/138' of the BIP39 seed
+    Mnemonic { mnemonic: String, passphrase: Option<String> },
+    /// LUD-13, keys derived from the node's signature of `HASHING_KEY_MESSAGE`
+    Node(Arc<dyn MessageSigner>),
+}
+
+/// A login the service accepted
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LnurlAuthResponse {
+    pub domain: String,
+    pub linking_key: String,    // hex compressed public key, the identity the service sees
+    pub action: Option<String>, // register, login, link or auth when the service says
+}
+
+/// Log in with an LNURL-auth link (`lnurl1...` or `keyauth://`)
+///
+/// Derives the linking key for the link's domain from `signer`, signs `k1` with it and calls
+/// the service back. The same signer always shows the same key to a domain, and unrelated
+/// keys to different domains.
+pub async fn auth(lnurl: &str, signer: &LnurlAuthSigner) -> Result<LnurlAuthResponse, ApiError> {
+    let url = lnurl_to_url(lnurl)?;
+    let parsed = reqwest::Url::parse(&url).map_err(|e| ApiError::InvalidInput(format!("Invalid LNURL-auth url: {}", e)))?;
+    let param = |name: &str| parsed.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned());
+    if param("tag").as_deref() != Some("login") {
+        return Err(ApiError::InvalidInput("Not an LNURL-auth link, tag is not login".to_string()));
+    }
+    let k1 = param("k1")
+        .and_then(|k1| hex::decode(k1).ok())
+        .and_then(|k1| <[u8; 32]>::try_from(k1).ok())
+        .ok_or_else(|| ApiError::InvalidInput("LNURL-auth k1 must be 32 bytes of hex".to_string()))?;
+    let domain = parsed
+        .host_str()
+        .ok_or_else(|| ApiError::InvalidInput("LNURL-auth url has no domain".to_string()))?
+        .to_lowercase();
+
+    let key = linking_key(signer, &domain).await?;
+    let secp = Secp256k1::signing_only();
+    let signature = secp.sign_ecdsa(&Message::from_digest(k1), &key);
+    let linking_key = PublicKey::from_secret_key(&secp, &key).to_string();
+
+    let callback = callback_url(&url, &[("sig", hex::encode(signature.serialize_der())), ("key", linking_key.clone())])?;
+    let response: LnurlStatusResponse = lnurl_get(&callback, "LNURL-auth response").await?;
+    response.check("LNURL-auth callback")?;
+    Ok(LnurlAuthResponse {
+        domain,
+        linking_key,
+        action: param("action"),
+    })
+}
+
+/// `auth` with linking keys from a BIP39 mnemonic, e.g. one from `generate_mnemonic`
+#[cfg_attr(feature = "uniffi", uniffi::export(async_runtime = "tokio"))]
+pub async fn lnurl_auth_with_mnemonic(
+    lnurl: String,
+    mnemonic: String,
+    passphrase: Option<String>,
+) -> Result<LnurlAuthResponse, ApiError> {
+    auth(&lnurl, &LnurlAuthSigner::Mnemonic { mnemonic, passphrase }).await
+}
+
+/// `auth` with linking keys from a node's `sign_message`
+#[cfg_attr(feature = "uniffi", uniffi::export(async_runtime = "tokio"))]
+pub async fn lnurl_auth_with_signer(lnurl: String, signer: Arc<dyn MessageSigner>) -> Result<LnurlAuthResponse, ApiError> {
+    auth(&lnurl, &LnurlAuthSigner::Node(signer)).await
+}
+
+async fn linking_key(signer: &LnurlAuthSigner, domain: &str) -> Result<SecretKey, ApiError> {
+    match signer {
+        LnurlAuthSigner::Mnemonic { mnemonic, passphrase } => {
+            mnemonic_linking_key(mnemonic, passphrase.as_deref().unwrap_or_default(), domain)
+        }
+        LnurlAuthSigner::Node(signer) => {
+            // the zbase32 string as signmessage returns it is what gets hashed
+            let signature = signer.sign_message(HASHING_KEY_MESSAGE.to_string()).await?;
+            let hashing_key = sha256::Hash::hash(signature.as_bytes());
+            SecretKey::from_slice(&hmac_sha256(hashing_key.as_byte_array(), domain)).map_err(derivation_failed)
+        }
+    }
+}
+
+// LUD-05: the hashing key at m/138'/0 picks the linking key's path under m/138' per domain
+fn mnemonic_linking_key(mnemonic: &str, passphrase: &str, domain: &str) -> Result<SecretKey, ApiError> {
+    let mnemonic = Mnemonic::parse(mnemonic).map_err(|e| ApiError::InvalidInput(format!("Invalid mnemonic: {}", e)))?;
+    let root = Xpriv::new_master(Network::Bitcoin, &mnemonic.to_seed(passphrase)).map_err(derivation_failed)?;
+    let secp = Secp256k1::signing_only();
+    let derive = |path: &[ChildNumber]| root.derive_priv(&secp, &path).map(|key| key.private_key).map_err(derivation_failed);
+
+    let hashing_key = derive(&[ChildNumber::Hardened { index: 138 }, ChildNumber::Normal { index: 0 }])?;
+    derive(&linking_key_path(&hashing_key.secret_bytes(), domain))
+}
+
+// m/138' followed by the first 16 bytes of hmac(hashing key, domain) as four indexes
+fn linking_key_path(hashing_key: &[u8], domain: &str) -> Vec<ChildNumber> {
+    let material = hmac_sha256(hashing_key, domain);
+    let mut path = vec![ChildNumber::Hardened { index: 138 }];
+    path.extend(material[..16].chunks(4).map(|chunk| ChildNumber::from(u32::from_be_bytes(chunk.try_into().unwrap()))));
+    path
+}
+
+fn hmac_sha256(key: &[u8], domain: &str) -> [u8; 32] {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(key);
+    engine.input(domain.as_bytes());
+    hmac::Hmac::<sha256::Hash>::from_engine(engine).to_byte_array()
+}
+
+fn derivation_failed(e: impl std::fmt::Display) -> ApiError {
+    ApiError::Api {
+        reason: format!("Failed to derive LNURL-auth linking key: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lnurl::tests::{lnurl_service, query_param};
+    use bitcoin::secp256k1::ecdsa::Signature;
+    use std::str::FromStr;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    const K1: &str = "e2af6254a8df433264fa23f67eb8188635d15ce883e8fc020989d5f82ae6f11e";
+
+    struct FixedSigner;
+
+    #[async_trait]
+    impl MessageSigner for FixedSigner {
+        async fn sign_message(&self, message: String) -> Result<String, ApiError> {
+            assert_eq!(message, HASHING_KEY_MESSAGE);
+            Ok("d7r8fxbbf7wds1ek5anjmsskmxz6r3ox7ok4p6nx6ukdwsc5w6nz".to_string())
+        }
+    }
+
+    fn mnemonic(passphrase: Option<&str>) -> LnurlAuthSigner {
+        LnurlAuthSigner::Mnemonic {
+            mnemonic: MNEMONIC.to_string(),
+            passphrase: passphrase.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_linking_key_per_domain() {
+        for signer in [mnemonic(None), LnurlAuthSigner::Node(Arc::new(FixedSigner))] {
+            let site = linking_key(&signer, "site.com").await.unwrap();
+            assert_eq!(site, linking_key(&signer, "site.com").await.unwrap());
+            assert_ne!(site, linking_key(&signer, "other.com").await.unwrap());
+        }
+        let node = linking_key(&LnurlAuthSigner::Node(Arc::new(FixedSigner)), "site.com").await.unwrap();
+        assert_ne!(linking_key(&mnemonic(None), "site.com").await.unwrap(), node);
+        assert_ne!(
+            linking_key(&mnemonic(None), "site.com").await.unwrap(),
+            linking_key(&mnemonic(Some("extra")), "site.com").await.unwrap()
+        );
+
+        // LUD-05 test vector
+        let hashing_key = hex::decode("7d417a6a5e9a6a4a879aeaba11a11838764c8fa2b959c242d43dea682b3e409b").unwrap();
+        let path = linking_key_path(&hashing_key, "site.com");
+        // indexes from 2^31 up derive hardened whichever way they are written
+        let indexes: Vec<u32> = path.into_iter().map(u32::from).collect();
+        assert_eq!(indexes, vec![(1 << 31) + 138, 1588488367, 2659270754, 38110259, 4136336762]);
+        let key = linking_key(&mnemonic(None), "site.com").await.unwrap();
+        assert_eq!(
+            PublicKey::from_secret_key(&Secp256k1::new(), &key).to_string(),
+            "027da5d64331f61260eb8e2b356403446555f525bc7dc35b991ec1447e4f58991f"
+        );
+
+        let bad = LnurlAuthSigner::Mnemonic {
+            mnemonic: "not a mnemonic".to_string(),
+            passphrase: None,
+        };
+        assert!(matches!(linking_key(&bad, "site.com").await, Err(ApiError::InvalidInput(_))));
+    }
+
+    // The auth link points at the stand-in service's callback
+    async fn auth_link(query: &str, respond: serde_json::Value) -> (String, crate::lnurl::tests::Queries) {
+        let (lnurl, queries) = lnurl_service(serde_json::json!({}), move |_| respond.clone()).await;
+        let url = lnurl_to_url(&lnurl).unwrap().replace("/start", &format!("/callback?{}", query));
+        let lnurl = bech32::encode::<bech32::Bech32>(bech32::Hrp::parse("lnurl").unwrap(), url.as_bytes()).unwrap();
+        (lnurl, queries)
+    }
+
+    #[tokio::test]
+    async fn test_auth() {
+        let query = format!("tag=login&k1={}&action=register", K1);
+        let (lnurl, queries) = auth_link(&query, serde_json::json!({ "status": "OK" })).await;
+        let response = auth(&lnurl, &mnemonic(None)).await.unwrap();
+        assert_eq!(response.domain, "127.0.0.1");
+        assert_eq!(response.action.as_deref(), Some("register"));
+
+        let query = queries.lock().unwrap()[0].clone();
+        assert_eq!(query_param(&query, "k1").as_deref(), Some(K1));
+        assert_eq!(query_param(&query, "key"), Some(response.linking_key.clone()));
+        let key = PublicKey::from_str(&response.linking_key).unwrap();
+        let sig = Signature::from_der(&hex::decode(query_param(&query, "sig").unwrap()).unwrap()).unwrap();
+        let k1 = Message::from_digest(hex::decode(K1).unwrap().try_into().unwrap());
+        assert!(Secp256k1::verification_only().verify_ecdsa(&k1, &sig, &key).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_auth_refused() {
+        let refused = serde_json::json!({ "status": "ERROR", "reason": "Unknown k1" });
+        let (lnurl, _) = auth_link(&format!("tag=login&k1={}", K1), refused).await;
+        let result = auth(&lnurl, &mnemonic(None)).await;
+        assert!(matches!(result, Err(ApiError::LnurlError(reason)) if reason == "Unknown k1"));
+
+        let (lnurl, queries) = auth_link("tag=login&k1=abc", serde_json::json!({ "status": "OK" })).await;
+        assert!(matches!(auth(&lnurl, &mnemonic(None)).await, Err(ApiError::InvalidInput(_))));
+        let (lnurl, _) = auth_link(&format!("tag=withdrawRequest&k1={}", K1), serde_json::json!({})).await;
+        assert!(matches!(auth(&lnurl, &mnemonic(None)).await, Err(ApiError::InvalidInput(_))));
+        assert!(queries.lock().unwrap().is_empty());
+    }
+}
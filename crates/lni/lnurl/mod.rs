@@ -2,10 +2,31 @@
 //!
 //! Implements:
 //! - Lightning Address (user@domain) → LNURL-pay
-//! - LNURL-pay (lnurl1...) → BOLT11 invoice
+//! - LNURL-pay (lnurl1...) → BOLT11 invoice, checked against the metadata hash and amount
+//!   (LUD-06), with comments (LUD-12), payer data (LUD-18) and success actions (LUD-09, LUD-10)
+//! - `pay`, which sends to any of the above or a BOLT11 invoice / BOLT12 offer through a node
+//! - LNURL-withdraw (LUD-03), redeemed into an invoice created on a node
+//! - LNURL-auth (LUD-04), with linking keys from a mnemonic (LUD-05) or a node's signmessage (LUD-13)
+//! - NIP-57 zaps, an LNURL-pay carrying a signed nostr zap request
 
+pub mod auth;
+pub mod success_action;
+pub mod withdraw;
+pub mod zap;
+
+use std::future::Future;
+
+#[cfg(feature = "napi_rs")]
+use napi_derive::napi;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use crate::ApiError;
+use sha2::{Digest, Sha256};
+use crate::types::LightningNode;
+use crate::{ApiError, InvoiceType, PayInvoiceParams, PayInvoiceResponse, PayOptions, PayResponse};
+pub use auth::{auth, LnurlAuthResponse, LnurlAuthSigner, MessageSigner};
+pub use success_action::{LnurlSuccessAction, SuccessAction};
+pub use withdraw::{fetch_lnurl_withdraw, submit_withdraw_invoice, watch_withdrawal, withdraw, LnurlWithdrawResponse};
+pub use zap::{request_zap_invoice, zap, zap_request, LnurlZapInvoice};
 
 /// LNURL-pay response from the service
 #[derive(Debug, Deserialize)]
@@ -20,6 +41,46 @@ pub struct LnurlPayResponse {
     pub allows_nostr: Option<bool>,
     #[serde(default)]
     pub nostr_pubkey: Option<String>,
+    #[serde(default)]
+    pub comment_allowed: Option<i64>, // LUD-12, longest comment the service takes, unset means none
+    #[serde(default)]
+    pub payer_data: Option<PayerDataSpec>, // LUD-18
+}
+
+/// Payer details an LNURL-pay service asks for (LUD-18)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PayerDataSpec {
+    #[serde(default)]
+    pub name: Option<PayerDataField>,
+    #[serde(default)]
+    pub pubkey: Option<PayerDataField>,
+    #[serde(default)]
+    pub identifier: Option<PayerDataField>,
+    #[serde(default)]
+    pub email: Option<PayerDataField>,
+    #[serde(default)]
+    pub auth: Option<PayerDataField>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PayerDataField {
+    #[serde(default)]
+    pub mandatory: bool,
+}
+
+/// Payer details to send when a service asks for them (LUD-18), fields it didn't ask for are not sent
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayerData {
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub name: Option<String>,
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub pubkey: Option<String>,
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub identifier: Option<String>,
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub email: Option<String>,
 }
 
 /// Response when requesting invoice from callback
@@ -28,6 +89,15 @@ pub struct LnurlInvoiceResponse {
     pub pr: String,  // BOLT11 invoice
     #[serde(default)]
     pub routes: Option<Vec<serde_json::Value>>,
+    #[serde(default, rename = "successAction")]
+    pub success_action: Option<LnurlSuccessAction>,
+}
+
+/// An invoice from an LNURL-pay callback that matched the amount and metadata asked for
+#[derive(Debug, Clone)]
+pub struct LnurlPayInvoice {
+    pub invoice: String,
+    pub success_action: Option<LnurlSuccessAction>, // validated, `resolve` it with the preimage once paid
 }
 
 /// Error response from LNURL service
@@ -37,12 +107,33 @@ pub struct LnurlErrorResponse {
     pub reason: String,
 }
 
+// What callbacks answer when they only acknowledge a request, e.g. withdraw and auth
+#[derive(Debug, Deserialize)]
+pub(crate) struct LnurlStatusResponse {
+    status: String,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+impl LnurlStatusResponse {
+    // `what` names the callback in the error when it didn't answer OK
+    pub(crate) fn check(self, what: &str) -> Result<(), ApiError> {
+        if self.status.eq_ignore_ascii_case("OK") {
+            return Ok(());
+        }
+        Err(ApiError::LnurlError(
+            self.reason.unwrap_or_else(|| format!("{} answered {}", what, self.status)),
+        ))
+    }
+}
+
 /// Detect the type of payment destination
 #[derive(Debug, Clone, PartialEq)]
 pub enum PaymentDestination {
     Bolt11(String),
     Bolt12(String),
     LnurlPay(String),
+    LnurlWithdraw(String), // pays us, redeem it with `withdraw`
     LightningAddress { user: String, domain: String },
 }
 
@@ -74,13 +165,28 @@ impl PaymentDestination {
             return Ok(PaymentDestination::Bolt12(input.to_string()));
         }
         
-        // LNURL: lnurl1
+        // LUD-17 schemes say what they are
+        if lower.starts_with("lnurlw://") {
+            return Ok(PaymentDestination::LnurlWithdraw(input.to_string()));
+        }
+        if lower.starts_with("lnurlp://") {
+            return Ok(PaymentDestination::LnurlPay(input.to_string()));
+        }
+
+        // LNURL: lnurl1, a withdraw link usually carries its tag in the query (LUD-08)
         if lower.starts_with("lnurl1") {
+            let is_withdraw = decode_lnurl(input).is_ok_and(|url| {
+                reqwest::Url::parse(&url)
+                    .is_ok_and(|url| url.query_pairs().any(|(key, value)| key == "tag" && value == "withdrawRequest"))
+            });
+            if is_withdraw {
+                return Ok(PaymentDestination::LnurlWithdraw(input.to_string()));
+            }
             return Ok(PaymentDestination::LnurlPay(input.to_string()));
         }
         
         Err(ApiError::InvalidInput(format!(
-            "Unknown payment destination format. Expected: BOLT11 (lnbc...), BOLT12 (lno1...), LNURL (lnurl1...), or Lightning Address (user@domain)"
+            "Unknown payment destination format. Expected: BOLT11 (lnbc...), BOLT12 (lno1...), LNURL (lnurl1..., lnurlp://, lnurlw://), or Lightning Address (user@domain)"
         )))
     }
 }
@@ -107,8 +213,41 @@ pub fn decode_lnurl(lnurl: &str) -> Result<String, ApiError> {
         .map_err(|e| ApiError::InvalidInput(format!("LNURL contains invalid UTF-8: {}", e)))
 }
 
+/// Encode a URL as a bech32 LNURL
+pub fn encode_lnurl(url: &str) -> Result<String, ApiError> {
+    let hrp = bech32::Hrp::parse("lnurl").map_err(|e| ApiError::InvalidInput(e.to_string()))?;
+    bech32::encode::<bech32::Bech32>(hrp, url.as_bytes())
+        .map_err(|e| ApiError::InvalidInput(format!("Could not encode LNURL: {}", e)))
+}
+
+/// The URL behind a bech32 LNURL or a LUD-17 `lnurlp://`, `lnurlw://` or `keyauth://` link
+pub fn lnurl_to_url(lnurl: &str) -> Result<String, ApiError> {
+    let lnurl = lnurl.trim();
+    let Some((scheme, rest)) = lnurl.split_once("://") else {
+        return decode_lnurl(lnurl);
+    };
+    if !["lnurlp", "lnurlw", "lnurlc", "keyauth"].contains(&scheme.to_lowercase().as_str()) {
+        return Err(ApiError::InvalidInput(format!("Unknown LNURL scheme {}://", scheme)));
+    }
+    // onion services are plain http, everything else https
+    let host = rest.split(['/', '?']).next().unwrap_or_default().split(':').next().unwrap_or_default();
+    let scheme = if host.to_lowercase().ends_with(".onion") { "http" } else { "https" };
+    Ok(format!("{}://{}", scheme, rest))
+}
+
 /// Fetch LNURL-pay metadata from a URL
 pub async fn fetch_lnurl_pay(url: &str) -> Result<LnurlPayResponse, ApiError> {
+    let response: serde_json::Value = lnurl_get(url, "LNURL-pay response").await?;
+    // bech32 withdraw links mostly don't carry their tag in the url, only the response tells
+    if response["tag"] == "withdrawRequest" {
+        return Err(withdraw_not_payable());
+    }
+    serde_json::from_value(response).map_err(|e| ApiError::InvalidInput(format!("Invalid LNURL-pay response: {}", e)))
+}
+
+// GET an LNURL endpoint, a {"status": "ERROR"} reply becomes ApiError::LnurlError. `what`
+// names the response in error messages.
+pub(crate) async fn lnurl_get<T: DeserializeOwned>(url: &str, what: &str) -> Result<T, ApiError> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
@@ -119,12 +258,12 @@ pub async fn fetch_lnurl_pay(url: &str) -> Result<LnurlPayResponse, ApiError> {
         .header("Accept", "application/json")
         .send()
         .await
-        .map_err(|e| ApiError::NetworkError(format!("Failed to fetch LNURL: {}", e)))?;
+        .map_err(|e| ApiError::NetworkError(format!("Failed to fetch {}: {}", what, e)))?;
     
     let text = response
         .text()
         .await
-        .map_err(|e| ApiError::NetworkError(format!("Failed to read LNURL response: {}", e)))?;
+        .map_err(|e| ApiError::NetworkError(format!("Failed to read {}: {}", what, e)))?;
     
     // Check for error response
     if let Ok(error) = serde_json::from_str::<LnurlErrorResponse>(&text) {
@@ -134,46 +273,133 @@ pub async fn fetch_lnurl_pay(url: &str) -> Result<LnurlPayResponse, ApiError> {
     }
     
     serde_json::from_str(&text)
-        .map_err(|e| ApiError::InvalidInput(format!("Invalid LNURL-pay response: {} - {}", e, &text[..text.len().min(200)])))
+        .map_err(|e| ApiError::InvalidInput(format!("Invalid {}: {} - {}", what, e, &text[..text.len().min(200)])))
 }
 
 /// Request an invoice from LNURL-pay callback
+///
+/// The invoice comes back unchecked, `request_pay_invoice` is the one to use before paying.
 pub async fn request_invoice(callback_url: &str, amount_msats: i64) -> Result<String, ApiError> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| ApiError::NetworkError(e.to_string()))?;
-    
-    // Add amount to callback URL
-    let url = if callback_url.contains('?') {
-        format!("{}&amount={}", callback_url, amount_msats)
-    } else {
-        format!("{}?amount={}", callback_url, amount_msats)
+    let response = fetch_invoice(callback_url, &[("amount", amount_msats.to_string())]).await?;
+    Ok(response.pr)
+}
+
+/// Request an invoice for `amount_msats` and check it before anything is paid
+///
+/// The invoice must be for exactly `amount_msats` and its description hash must be sha256 of
+/// the metadata, with the payer data JSON appended when some was sent (LUD-18). `comment` is
+/// only sent to services that take comments (LUD-12) and must fit their limit.
+pub async fn request_pay_invoice(
+    lnurl_data: &LnurlPayResponse,
+    amount_msats: i64,
+    comment: Option<&str>,
+    payer_data: Option<&PayerData>,
+) -> Result<LnurlPayInvoice, ApiError> {
+    if lnurl_data.tag != "payRequest" {
+        return Err(ApiError::LnurlError(format!("Not an LNURL-pay service, tag is {}", lnurl_data.tag)));
+    }
+    check_sendable(lnurl_data, amount_msats)?;
+
+    let mut query = vec![("amount", amount_msats.to_string())];
+    if let Some(comment) = comment {
+        let allowed = lnurl_data.comment_allowed.unwrap_or(0);
+        if comment.chars().count() as i64 > allowed {
+            return Err(ApiError::InvalidInput(if allowed == 0 {
+                "This LNURL-pay service does not take comments".to_string()
+            } else {
+                format!("Comment is longer than the {} characters the service takes", allowed)
+            }));
+        }
+        query.push(("comment", comment.to_string()));
+    }
+    let payer_data = payer_data_json(lnurl_data.payer_data.as_ref(), payer_data)?;
+    if let Some(payer_data) = &payer_data {
+        query.push(("payerdata", payer_data.clone()));
+    }
+
+    let response = fetch_invoice(&lnurl_data.callback, &query).await?;
+    let hashed = format!("{}{}", lnurl_data.metadata, payer_data.unwrap_or_default());
+    check_invoice(&response.pr, amount_msats, &hashed, "the LNURL-pay metadata")?;
+    if let Some(action) = &response.success_action {
+        action.validate(&lnurl_data.callback)?;
+    }
+
+    Ok(LnurlPayInvoice {
+        invoice: response.pr,
+        success_action: response.success_action,
+    })
+}
+
+// An LNURL-pay invoice must be BOLT11 for the amount asked and its description hash sha256 of
+// `hashed`, `what` names what was hashed in the error
+fn check_invoice(invoice: &str, amount_msats: i64, hashed: &str, what: &str) -> Result<(), ApiError> {
+    let decoded = crate::decode_payment_request(invoice.to_string())?;
+    if !matches!(decoded.invoice_type, InvoiceType::Bolt11) {
+        return Err(ApiError::LnurlError("LNURL-pay service did not return a BOLT11 invoice".to_string()));
+    }
+    if decoded.amount_msats != Some(amount_msats) {
+        return Err(ApiError::LnurlError(format!(
+            "LNURL-pay service returned an invoice for {} msats, expected {} msats",
+            decoded.amount_msats.unwrap_or(0),
+            amount_msats
+        )));
+    }
+    let expected_hash = hex::encode(Sha256::digest(hashed.as_bytes()));
+    if decoded.description_hash.as_deref() != Some(expected_hash.as_str()) {
+        return Err(ApiError::LnurlError(format!("Invoice description hash does not match {}", what)));
+    }
+    Ok(())
+}
+
+// The LUD-18 payerdata JSON for the fields the service asked for, None when it asked for none
+fn payer_data_json(spec: Option<&PayerDataSpec>, payer_data: Option<&PayerData>) -> Result<Option<String>, ApiError> {
+    let Some(spec) = spec else {
+        return Ok(None);
     };
-    
-    let response = client
-        .get(&url)
-        .header("Accept", "application/json")
-        .send()
-        .await
-        .map_err(|e| ApiError::NetworkError(format!("Failed to request invoice: {}", e)))?;
-    
-    let text = response
-        .text()
-        .await
-        .map_err(|e| ApiError::NetworkError(format!("Failed to read invoice response: {}", e)))?;
-    
-    // Check for error response
-    if let Ok(error) = serde_json::from_str::<LnurlErrorResponse>(&text) {
-        if error.status == "ERROR" {
-            return Err(ApiError::LnurlError(error.reason));
+    if spec.auth.as_ref().is_some_and(|auth| auth.mandatory) {
+        return Err(ApiError::NotSupported {
+            reason: "LNURL-pay service requires LNURL-auth payer data".to_string(),
+        });
+    }
+    let payer_data = payer_data.cloned().unwrap_or_default();
+    let fields = [
+        ("name", &spec.name, payer_data.name),
+        ("pubkey", &spec.pubkey, payer_data.pubkey),
+        ("identifier", &spec.identifier, payer_data.identifier),
+        ("email", &spec.email, payer_data.email),
+    ];
+
+    let mut json = serde_json::Map::new();
+    for (key, requested, value) in fields {
+        let Some(requested) = requested else {
+            continue;
+        };
+        match value {
+            Some(value) => {
+                json.insert(key.to_string(), serde_json::Value::String(value));
+            }
+            None if requested.mandatory => {
+                return Err(ApiError::InvalidInput(format!("LNURL-pay service requires payer {}", key)));
+            }
+            None => {}
         }
     }
-    
-    let invoice_resp: LnurlInvoiceResponse = serde_json::from_str(&text)
-        .map_err(|e| ApiError::InvalidInput(format!("Invalid invoice response: {} - {}", e, &text[..text.len().min(200)])))?;
-    
-    Ok(invoice_resp.pr)
+    Ok((!json.is_empty()).then(|| serde_json::Value::Object(json).to_string()))
+}
+
+// GET the callback with `query` added to whatever query it already has
+pub(crate) fn callback_url(callback: &str, query: &[(&str, String)]) -> Result<String, ApiError> {
+    let query = serde_urlencoded::to_string(query)
+        .map_err(|e| ApiError::InvalidInput(format!("Invalid LNURL callback parameters: {}", e)))?;
+    Ok(if callback.contains('?') {
+        format!("{}&{}", callback, query)
+    } else {
+        format!("{}?{}", callback, query)
+    })
+}
+
+async fn fetch_invoice(callback: &str, query: &[(&str, String)]) -> Result<LnurlInvoiceResponse, ApiError> {
+    lnurl_get(&callback_url(callback, query)?, "invoice response").await
 }
 
 /// Resolve any payment destination to a BOLT11 invoice
@@ -206,21 +432,7 @@ pub async fn resolve_to_bolt11(
             let url = lightning_address_to_url(&user, &domain);
             let lnurl_data = fetch_lnurl_pay(&url).await?;
             
-            // Validate amount
-            if amount < lnurl_data.min_sendable {
-                return Err(ApiError::InvalidInput(format!(
-                    "Amount {} msats is below minimum {} msats",
-                    amount, lnurl_data.min_sendable
-                )));
-            }
-            if amount > lnurl_data.max_sendable {
-                return Err(ApiError::InvalidInput(format!(
-                    "Amount {} msats exceeds maximum {} msats",
-                    amount, lnurl_data.max_sendable
-                )));
-            }
-            
-            request_invoice(&lnurl_data.callback, amount).await
+            Ok(request_pay_invoice(&lnurl_data, amount, None, None).await?.invoice)
         }
         
         PaymentDestination::LnurlPay(lnurl) => {
@@ -228,25 +440,215 @@ pub async fn resolve_to_bolt11(
                 ApiError::InvalidInput("LNURL requires amount_msats".to_string())
             })?;
             
-            let url = decode_lnurl(&lnurl)?;
+            let url = lnurl_to_url(&lnurl)?;
             let lnurl_data = fetch_lnurl_pay(&url).await?;
             
-            // Validate amount
-            if amount < lnurl_data.min_sendable {
-                return Err(ApiError::InvalidInput(format!(
-                    "Amount {} msats is below minimum {} msats",
-                    amount, lnurl_data.min_sendable
-                )));
-            }
-            if amount > lnurl_data.max_sendable {
-                return Err(ApiError::InvalidInput(format!(
-                    "Amount {} msats exceeds maximum {} msats",
-                    amount, lnurl_data.max_sendable
-                )));
+            Ok(request_pay_invoice(&lnurl_data, amount, None, None).await?.invoice)
+        }
+
+        PaymentDestination::LnurlWithdraw(_) => Err(withdraw_not_payable()),
+    }
+}
+
+// LNURL-pay services only take amounts between min_sendable and max_sendable
+fn check_sendable(lnurl_data: &LnurlPayResponse, amount: i64) -> Result<(), ApiError> {
+    if amount < lnurl_data.min_sendable {
+        return Err(ApiError::InvalidInput(format!(
+            "Amount {} msats is below minimum {} msats",
+            amount, lnurl_data.min_sendable
+        )));
+    }
+    if amount > lnurl_data.max_sendable {
+        return Err(ApiError::InvalidInput(format!(
+            "Amount {} msats exceeds maximum {} msats",
+            amount, lnurl_data.max_sendable
+        )));
+    }
+    Ok(())
+}
+
+/// Pay any destination through `node`
+///
+/// - BOLT11: paid with `pay_invoice`, `amount_msats` is only needed for zero amount invoices
+/// - BOLT12: paid with `pay_offer`, `amount_msats` is only needed when the offer has no amount
+/// - Lightning Address / LNURL: resolved to a BOLT11 invoice for `amount_msats` with
+///   `request_pay_invoice`, then paid. `payer_note` goes along as the comment and the
+///   response carries the service's success action.
+pub async fn pay<N: LightningNode + ?Sized>(
+    node: &N,
+    destination: String,
+    amount_msats: Option<i64>,
+    options: PayOptions,
+) -> Result<PayResponse, ApiError> {
+    dispatch_pay(
+        &destination,
+        amount_msats,
+        options,
+        |params| node.pay_invoice(params),
+        |offer, amount, payer_note| node.pay_offer(offer, amount, payer_note),
+    )
+    .await
+}
+
+// The routing behind `pay`, with the node calls passed in so it can be tested without one
+async fn dispatch_pay<I, IFut, O, OFut>(
+    destination: &str,
+    amount_msats: Option<i64>,
+    options: PayOptions,
+    pay_invoice: I,
+    pay_offer: O,
+) -> Result<PayResponse, ApiError>
+where
+    I: FnOnce(PayInvoiceParams) -> IFut,
+    IFut: Future<Output = Result<PayInvoiceResponse, ApiError>>,
+    O: FnOnce(String, i64, Option<String>) -> OFut,
+    OFut: Future<Output = Result<PayInvoiceResponse, ApiError>>,
+{
+    if let Some(amount) = amount_msats {
+        if amount <= 0 {
+            return Err(ApiError::InvalidInput("amount_msats must be positive".to_string()));
+        }
+    }
+
+    let comment = options.payer_note.as_deref();
+    let payer_data = options.payer_data.as_ref();
+    let (destination_type, invoice, amount_msats, success_action) = match PaymentDestination::parse(destination)? {
+        PaymentDestination::Bolt12(offer) => {
+            let response = pay_bolt12(&offer, amount_msats, options, pay_offer).await?;
+            return Ok(pay_response("bolt12", offer, response));
+        }
+        PaymentDestination::Bolt11(invoice) => {
+            let decoded = crate::decode_payment_request(invoice.clone())?;
+            let amount_msats = match (decoded.amount_msats, amount_msats) {
+                (Some(own), Some(amount)) if own != amount => {
+                    return Err(ApiError::InvalidInput(format!(
+                        "Invoice is for {} msats, not {} msats",
+                        own, amount
+                    )))
+                }
+                (Some(_), _) => None,
+                (None, Some(amount)) => Some(amount),
+                (None, None) => {
+                    return Err(ApiError::InvalidInput(
+                        "Invoice has no amount, amount_msats is required".to_string(),
+                    ))
+                }
+            };
+            if let Some(parameter) = lnurl_only_option(&options) {
+                return Err(ApiError::UnsupportedParameter {
+                    parameter: parameter.to_string(),
+                    reason: "BOLT11 invoices carry no payer note or payer data".to_string(),
+                });
             }
-            
-            request_invoice(&lnurl_data.callback, amount).await
+            ("bolt11", invoice, amount_msats, None)
         }
+        PaymentDestination::LightningAddress { user, domain } => {
+            let amount = lnurl_amount(amount_msats, "Lightning Address")?;
+            let lnurl_data = fetch_lnurl_pay(&lightning_address_to_url(&user, &domain)).await?;
+            let resolved = request_pay_invoice(&lnurl_data, amount, comment, payer_data).await?;
+            ("lightning_address", resolved.invoice, None, resolved.success_action)
+        }
+        PaymentDestination::LnurlPay(lnurl) => {
+            let amount = lnurl_amount(amount_msats, "LNURL")?;
+            let lnurl_data = fetch_lnurl_pay(&lnurl_to_url(&lnurl)?).await?;
+            let resolved = request_pay_invoice(&lnurl_data, amount, comment, payer_data).await?;
+            ("lnurl", resolved.invoice, None, resolved.success_action)
+        }
+        PaymentDestination::LnurlWithdraw(_) => return Err(withdraw_not_payable()),
+    };
+
+    let response = pay_invoice(PayInvoiceParams {
+        invoice: invoice.clone(),
+        amount_msats,
+        fee_limit_msat: options.fee_limit_msat,
+        fee_limit_percentage: options.fee_limit_percentage,
+        timeout_seconds: options.timeout_seconds,
+        wait: options.wait,
+        ..Default::default()
+    })
+    .await?;
+    // the action is for a completed payment, one still in flight doesn't get it yet
+    let success_action = success_action
+        .filter(|_| response.status == crate::TransactionStatus::Settled)
+        .map(|action| action.resolve(&response.preimage));
+    Ok(PayResponse {
+        success_action,
+        ..pay_response(destination_type, invoice, response)
+    })
+}
+
+fn withdraw_not_payable() -> ApiError {
+    ApiError::InvalidInput("LNURL-withdraw links pay you, redeem them with lnurl::withdraw".to_string())
+}
+
+fn lnurl_amount(amount_msats: Option<i64>, kind: &str) -> Result<i64, ApiError> {
+    amount_msats.ok_or_else(|| ApiError::InvalidInput(format!("{} requires amount_msats", kind)))
+}
+
+// PayOptions fields only an LNURL-pay service takes, a payer note doubles as the LUD-12 comment
+fn lnurl_only_option(options: &PayOptions) -> Option<&'static str> {
+    if options.payer_note.is_some() {
+        Some("payer_note")
+    } else if options.payer_data.is_some() {
+        Some("payer_data")
+    } else {
+        None
+    }
+}
+
+async fn pay_bolt12<O, OFut>(
+    offer: &str,
+    amount_msats: Option<i64>,
+    options: PayOptions,
+    pay_offer: O,
+) -> Result<PayInvoiceResponse, ApiError>
+where
+    O: FnOnce(String, i64, Option<String>) -> OFut,
+    OFut: Future<Output = Result<PayInvoiceResponse, ApiError>>,
+{
+    // pay_offer has nowhere to put these, so refuse them rather than pay without them
+    let unsupported = [
+        ("fee_limit_msat", options.fee_limit_msat.is_some()),
+        ("fee_limit_percentage", options.fee_limit_percentage.is_some()),
+        ("timeout_seconds", options.timeout_seconds.is_some()),
+        ("payer_data", options.payer_data.is_some()),
+        ("wait", options.wait == Some(false)),
+    ];
+    if let Some((parameter, _)) = unsupported.iter().find(|(_, set)| *set) {
+        return Err(ApiError::UnsupportedParameter {
+            parameter: parameter.to_string(),
+            reason: "not supported when paying BOLT12 offers".to_string(),
+        });
+    }
+
+    let decoded = crate::bolt12::decode_offer(offer.to_string())?;
+    let amount = match (decoded.amount_msats, amount_msats) {
+        (Some(own), Some(amount)) if amount < own => {
+            return Err(ApiError::InvalidInput(format!(
+                "Offer asks for {} msats, {} msats is too little",
+                own, amount
+            )))
+        }
+        (_, Some(amount)) => amount,
+        (Some(own), None) => own,
+        (None, None) => {
+            return Err(ApiError::InvalidInput(
+                "Offer has no bitcoin amount, amount_msats is required".to_string(),
+            ))
+        }
+    };
+    pay_offer(offer.to_string(), amount, options.payer_note).await
+}
+
+fn pay_response(destination_type: &str, invoice: String, response: PayInvoiceResponse) -> PayResponse {
+    PayResponse {
+        destination_type: destination_type.to_string(),
+        invoice,
+        payment_hash: response.payment_hash,
+        preimage: response.preimage,
+        fee_msats: response.fee_msats,
+        status: response.status,
+        success_action: None,
     }
 }
 
@@ -265,25 +667,27 @@ pub async fn get_payment_info(
     
     match parsed {
         PaymentDestination::Bolt11(invoice) => {
-            // TODO: Could decode invoice to get amount
+            let decoded = crate::decode_payment_request(invoice)?;
             Ok(PaymentInfo {
                 destination_type: "bolt11".to_string(),
                 destination: destination.to_string(),
-                amount_msats,
+                // the invoice amount wins, `amount_msats` is only for zero amount invoices
+                amount_msats: decoded.amount_msats.or(amount_msats),
                 min_sendable_msats: None,
                 max_sendable_msats: None,
-                description: None,
+                description: decoded.description,
             })
         }
         
         PaymentDestination::Bolt12(offer) => {
+            let offer = crate::bolt12::decode_offer(offer)?;
             Ok(PaymentInfo {
                 destination_type: "bolt12".to_string(),
                 destination: destination.to_string(),
-                amount_msats,
+                amount_msats: offer.amount_msats.or(amount_msats),
                 min_sendable_msats: None,
                 max_sendable_msats: None,
-                description: None,
+                description: offer.description,
             })
         }
         
@@ -304,7 +708,7 @@ pub async fn get_payment_info(
         }
         
         PaymentDestination::LnurlPay(lnurl) => {
-            let url = decode_lnurl(&lnurl)?;
+            let url = lnurl_to_url(&lnurl)?;
             let lnurl_data = fetch_lnurl_pay(&url).await?;
             
             Ok(PaymentInfo {
@@ -316,6 +720,20 @@ pub async fn get_payment_info(
                 description: Some(lnurl_data.metadata),
             })
         }
+
+        // what can be withdrawn, min/max are the withdrawable range
+        PaymentDestination::LnurlWithdraw(lnurl) => {
+            let withdraw = fetch_lnurl_withdraw(&lnurl_to_url(&lnurl)?).await?;
+
+            Ok(PaymentInfo {
+                destination_type: "lnurl_withdraw".to_string(),
+                destination: destination.to_string(),
+                amount_msats,
+                min_sendable_msats: Some(withdraw.min_withdrawable),
+                max_sendable_msats: Some(withdraw.max_withdrawable),
+                description: Some(withdraw.default_description),
+            })
+        }
     }
 }
 
@@ -377,4 +795,291 @@ mod tests {
         let url = lightning_address_to_url("nick", "strike.me");
         assert_eq!(url, "https://strike.me/.well-known/lnurlp/nick");
     }
+
+    // BOLT 11 spec example for 250,000,000 msats
+    const COFFEE_INVOICE: &str = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+    const AMOUNTLESS_OFFER: &str = "lno1zcss9mk8y3wkklfvevcrszlmu23kfrxh49px20665dqwmn4p72pksese";
+
+    fn paid() -> PayInvoiceResponse {
+        PayInvoiceResponse {
+            payment_hash: "hash".to_string(),
+            preimage: "preimage".to_string(),
+            fee_msats: 1000,
+            status: crate::TransactionStatus::Settled,
+        }
+    }
+
+    async fn unreachable_offer(_: String, _: i64, _: Option<String>) -> Result<PayInvoiceResponse, ApiError> {
+        panic!("pay_offer called for a BOLT11 destination")
+    }
+
+    async fn unreachable_invoice(_: PayInvoiceParams) -> Result<PayInvoiceResponse, ApiError> {
+        panic!("pay_invoice called for a BOLT12 destination")
+    }
+
+    // How the stand-in service below treats the invoices it hands out
+    #[derive(Clone, Copy, PartialEq)]
+    enum Invoices {
+        Honest,
+        WrongAmount,
+        WrongHash,
+    }
+
+    pub(super) type Queries = std::sync::Arc<std::sync::Mutex<Vec<Vec<(String, String)>>>>;
+
+    // A local LNURL service: `/start` answers with `start`, its callback pointed back here, and
+    // `/callback` with whatever `respond` makes of the query. Returns the bech32 LNURL for
+    // `/start` and the callback queries seen so far.
+    pub(super) async fn lnurl_service<F>(mut start: serde_json::Value, respond: F) -> (String, Queries)
+    where
+        F: Fn(&[(String, String)]) -> serde_json::Value + Send + 'static,
+    {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        start["callback"] = serde_json::Value::String(format!("{}/callback", base));
+
+        let queries = Queries::default();
+        let seen = queries.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = vec![0; 4096];
+                let read = stream.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]);
+                let path = request.split(' ').nth(1).unwrap_or_default();
+                let body = if path == "/start" {
+                    start.to_string()
+                } else {
+                    let query = path.split_once('?').map(|(_, query)| query).unwrap_or_default();
+                    let query: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap();
+                    let body = respond(&query).to_string();
+                    seen.lock().unwrap().push(query);
+                    body
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let url = format!("{}/start", base);
+        let lnurl = bech32::encode::<bech32::Bech32>(bech32::Hrp::parse("lnurl").unwrap(), url.as_bytes()).unwrap();
+        (lnurl, queries)
+    }
+
+    pub(super) fn query_param(query: &[(String, String)], key: &str) -> Option<String> {
+        query.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+    }
+
+    // An LNURL-pay service whose callback signs an invoice per request. `extra` is merged into
+    // the pay response and `success_action` sent with every invoice.
+    async fn lnurl_pay_server(extra: serde_json::Value, success_action: serde_json::Value, invoices: Invoices) -> (String, Queries) {
+        let mut pay = serde_json::json!({
+            "tag": "payRequest",
+            "minSendable": 1000,
+            "maxSendable": 500_000_000,
+            "metadata": METADATA,
+        });
+        pay.as_object_mut().unwrap().extend(extra.as_object().cloned().unwrap_or_default());
+
+        lnurl_service(pay, move |query| {
+            let mut amount: u64 = query_param(query, "amount").unwrap().parse().unwrap();
+            let mut hashed = format!("{}{}", METADATA, query_param(query, "payerdata").unwrap_or_default());
+            match invoices {
+                Invoices::Honest => {}
+                Invoices::WrongAmount => amount += 1000,
+                Invoices::WrongHash => hashed = "something else".to_string(),
+            }
+            serde_json::json!({ "pr": signed_invoice(amount, &hashed), "successAction": success_action })
+        })
+        .await
+    }
+
+    const METADATA: &str = "[[\"text/plain\",\"coffee\"]]";
+
+    pub(super) fn signed_invoice(amount_msats: u64, hashed: &str) -> String {
+        use lightning_invoice::{Currency, InvoiceBuilder, PaymentSecret};
+        use nostr::hashes::{sha256, Hash};
+        use nostr::secp256k1::{Secp256k1, SecretKey};
+
+        let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        InvoiceBuilder::new(Currency::Bitcoin)
+            .description_hash(sha256::Hash::hash(hashed.as_bytes()))
+            .payment_hash(sha256::Hash::hash(&[0; 32]))
+            .payment_secret(PaymentSecret([0x22; 32]))
+            .amount_milli_satoshis(amount_msats)
+            .duration_since_epoch(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap())
+            .min_final_cltv_expiry_delta(144)
+            .build_signed(|message| Secp256k1::new().sign_ecdsa_recoverable(message, &key))
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_pay_bolt11() {
+        let options = PayOptions {
+            fee_limit_msat: Some(5000),
+            ..Default::default()
+        };
+        let response = dispatch_pay(
+            COFFEE_INVOICE,
+            Some(250_000_000),
+            options,
+            |params| async move {
+                assert_eq!(params.invoice, COFFEE_INVOICE);
+                assert_eq!(params.amount_msats, None); // the invoice carries it
+                assert_eq!(params.fee_limit_msat, Some(5000));
+                Ok(paid())
+            },
+            unreachable_offer,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.destination_type, "bolt11");
+        assert_eq!(response.invoice, COFFEE_INVOICE);
+        assert_eq!(response.fee_msats, 1000);
+
+        let wrong_amount = dispatch_pay(COFFEE_INVOICE, Some(1000), PayOptions::default(), unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(wrong_amount, Err(ApiError::InvalidInput(_))));
+
+        let note = PayOptions {
+            payer_note: Some("thanks".to_string()),
+            ..Default::default()
+        };
+        let with_note = dispatch_pay(COFFEE_INVOICE, None, note, unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(with_note, Err(ApiError::UnsupportedParameter { parameter, .. }) if parameter == "payer_note"));
+    }
+
+    #[tokio::test]
+    async fn test_pay_bolt12() {
+        let options = PayOptions {
+            payer_note: Some("thanks".to_string()),
+            ..Default::default()
+        };
+        let response = dispatch_pay(AMOUNTLESS_OFFER, Some(21_000), options, unreachable_invoice, |offer, amount, note| async move {
+            assert_eq!(offer, AMOUNTLESS_OFFER);
+            assert_eq!(amount, 21_000);
+            assert_eq!(note.as_deref(), Some("thanks"));
+            Ok(paid())
+        })
+        .await
+        .unwrap();
+        assert_eq!(response.destination_type, "bolt12");
+        assert_eq!(response.invoice, AMOUNTLESS_OFFER);
+
+        let no_amount = dispatch_pay(AMOUNTLESS_OFFER, None, PayOptions::default(), unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(no_amount, Err(ApiError::InvalidInput(_))));
+
+        let fee_limit = PayOptions {
+            fee_limit_msat: Some(10),
+            ..Default::default()
+        };
+        let with_fee_limit = dispatch_pay(AMOUNTLESS_OFFER, Some(21_000), fee_limit, unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(with_fee_limit, Err(ApiError::UnsupportedParameter { parameter, .. }) if parameter == "fee_limit_msat"));
+    }
+
+    #[tokio::test]
+    async fn test_pay_lnurl() {
+        let (lnurl, _) = lnurl_pay_server(serde_json::json!({}), serde_json::Value::Null, Invoices::Honest).await;
+        let response = dispatch_pay(
+            &lnurl,
+            Some(250_000_000),
+            PayOptions::default(),
+            |params| async move {
+                let decoded = crate::decode_payment_request(params.invoice).unwrap();
+                assert_eq!(decoded.amount_msats, Some(250_000_000));
+                assert_eq!(params.amount_msats, None);
+                Ok(paid())
+            },
+            unreachable_offer,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.destination_type, "lnurl");
+        assert!(response.invoice.starts_with("lnbc"));
+        assert_eq!(response.success_action, None);
+
+        let too_much = dispatch_pay(&lnurl, Some(600_000_000), PayOptions::default(), unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(too_much, Err(ApiError::InvalidInput(_))));
+        let no_amount = dispatch_pay(&lnurl, None, PayOptions::default(), unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(no_amount, Err(ApiError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_pay_untagged_withdraw_link() {
+        let withdraw = serde_json::json!({
+            "tag": "withdrawRequest",
+            "k1": "secret-k1",
+            "defaultDescription": "ATM withdrawal",
+            "minWithdrawable": 1000,
+            "maxWithdrawable": 50_000,
+        });
+        let (lnurl, _) = lnurl_service(withdraw, |_| serde_json::Value::Null).await;
+        assert!(matches!(PaymentDestination::parse(&lnurl), Ok(PaymentDestination::LnurlPay(_))));
+        let result = dispatch_pay(&lnurl, Some(21_000), PayOptions::default(), unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(result, Err(ApiError::InvalidInput(reason)) if reason.contains("LNURL-withdraw")));
+    }
+
+    #[tokio::test]
+    async fn test_lnurl_rejects_mismatched_invoices() {
+        for invoices in [Invoices::WrongAmount, Invoices::WrongHash] {
+            let (lnurl, queries) = lnurl_pay_server(serde_json::json!({}), serde_json::Value::Null, invoices).await;
+            let result = dispatch_pay(&lnurl, Some(21_000), PayOptions::default(), unreachable_invoice, unreachable_offer).await;
+            assert!(matches!(result, Err(ApiError::LnurlError(_))));
+            assert_eq!(queries.lock().unwrap().len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lnurl_comment_and_payer_data() {
+        let extra = serde_json::json!({
+            "commentAllowed": 10,
+            "payerData": { "name": { "mandatory": true }, "email": { "mandatory": false } },
+        });
+        let message = serde_json::json!({ "tag": "message", "message": "Thanks for the coffee" });
+        let (lnurl, queries) = lnurl_pay_server(extra, message, Invoices::Honest).await;
+
+        let options = |comment: &str, name: Option<&str>| PayOptions {
+            payer_note: Some(comment.to_string()),
+            payer_data: Some(PayerData {
+                name: name.map(str::to_string),
+                identifier: Some("not asked for".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let response = dispatch_pay(&lnurl, Some(21_000), options("hi", Some("Satoshi")), |_| async { Ok(paid()) }, unreachable_offer)
+            .await
+            .unwrap();
+        let action = response.success_action.unwrap();
+        assert_eq!(action.tag, "message");
+        assert_eq!(action.message.as_deref(), Some("Thanks for the coffee"));
+
+        // the honest service hashed the payer data it got, so the invoice matching proves it arrived intact
+        let query = queries.lock().unwrap()[0].clone();
+        assert!(query.contains(&("comment".to_string(), "hi".to_string())));
+        assert!(query.contains(&("payerdata".to_string(), r#"{"name":"Satoshi"}"#.to_string())));
+
+        let in_flight = || async {
+            Ok(PayInvoiceResponse {
+                preimage: String::new(),
+                status: crate::TransactionStatus::Pending,
+                ..paid()
+            })
+        };
+        let response = dispatch_pay(&lnurl, Some(21_000), options("hi", Some("Satoshi")), |_| in_flight(), unreachable_offer)
+            .await
+            .unwrap();
+        assert_eq!(response.success_action, None);
+
+        let long_comment = dispatch_pay(&lnurl, Some(21_000), options("far too long", Some("Satoshi")), unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(long_comment, Err(ApiError::InvalidInput(_))));
+        let no_name = dispatch_pay(&lnurl, Some(21_000), options("hi", None), unreachable_invoice, unreachable_offer).await;
+        assert!(matches!(no_name, Err(ApiError::InvalidInput(_))));
+        assert_eq!(queries.lock().unwrap().len(), 2);
+    }
 }
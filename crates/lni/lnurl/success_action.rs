@@ -0,0 +1,190 @@
+//! LNURL-pay success actions (LUD-09), including AES encrypted ones (LUD-10)
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+#[cfg(feature = "napi_rs")]
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+
+use crate::ApiError;
+
+type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+
+/// Success action as the service sends it with the invoice
+#[derive(Debug, Clone, Deserialize)]
+pub struct LnurlSuccessAction {
+    pub tag: String, // message, url or aes
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub ciphertext: Option<String>, // base64, AES-256-CBC keyed with the payment preimage
+    #[serde(default)]
+    pub iv: Option<String>, // base64, 16 bytes
+}
+
+/// What to show the payer once an LNURL payment went through
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SuccessAction {
+    pub tag: String,             // message, url or aes
+    pub message: Option<String>, // the message, or the decrypted aes plaintext
+    pub description: Option<String>,
+    pub url: Option<String>,
+}
+
+impl LnurlSuccessAction {
+    /// Checks the action against LUD-09 and LUD-10 before anything is paid, a url must be
+    /// on the same domain as the callback
+    pub fn validate(&self, callback: &str) -> Result<(), ApiError> {
+        let invalid = |reason: &str| Err(ApiError::LnurlError(format!("Invalid {} success action: {}", self.tag, reason)));
+        match self.tag.as_str() {
+            "message" => match &self.message {
+                Some(message) if message.chars().count() <= 144 => Ok(()),
+                Some(_) => invalid("message is longer than 144 characters"),
+                None => invalid("missing message"),
+            },
+            "url" => {
+                check_description(self.description.as_deref()).or_else(invalid)?;
+                let url = self.url.as_deref().unwrap_or_default();
+                let host = |u: &str| reqwest::Url::parse(u).ok().and_then(|u| u.host_str().map(str::to_lowercase));
+                match (host(url), host(callback)) {
+                    (Some(url_host), Some(callback_host)) if url_host == callback_host => Ok(()),
+                    _ => invalid("url is not on the callback's domain"),
+                }
+            }
+            "aes" => {
+                check_description(self.description.as_deref()).or_else(invalid)?;
+                let ciphertext = self.ciphertext.as_deref().unwrap_or_default();
+                if ciphertext.is_empty() || ciphertext.len() > 4096 {
+                    return invalid("ciphertext must be 1 to 4096 characters");
+                }
+                match self.iv.as_deref().map(base64::decode) {
+                    Some(Ok(iv)) if iv.len() == 16 => Ok(()),
+                    _ => invalid("iv must be 16 bytes of base64"),
+                }
+            }
+            _ => invalid("unknown tag"),
+        }
+    }
+
+    /// The action to hand back once the payment settled, `preimage` (hex) unlocks an aes message.
+    /// When it can't be decrypted the message is left empty.
+    pub fn resolve(self, preimage: &str) -> SuccessAction {
+        let message = match self.tag.as_str() {
+            "aes" => match (&self.ciphertext, &self.iv) {
+                (Some(ciphertext), Some(iv)) => decrypt_aes(ciphertext, iv, preimage).ok(),
+                _ => None,
+            },
+            _ => self.message,
+        };
+        SuccessAction {
+            tag: self.tag,
+            message,
+            description: self.description,
+            url: self.url,
+        }
+    }
+}
+
+fn check_description(description: Option<&str>) -> Result<(), &'static str> {
+    match description {
+        Some(description) if description.chars().count() <= 144 => Ok(()),
+        Some(_) => Err("description is longer than 144 characters"),
+        None => Err("missing description"),
+    }
+}
+
+/// Decrypts a LUD-10 aes success action with the hex payment preimage as key
+pub fn decrypt_aes(ciphertext: &str, iv: &str, preimage: &str) -> Result<String, ApiError> {
+    let key = hex::decode(preimage).map_err(|e| ApiError::InvalidInput(format!("Invalid preimage: {}", e)))?;
+    let iv = base64::decode(iv).map_err(|e| ApiError::LnurlError(format!("Invalid success action iv: {}", e)))?;
+    let mut buffer =
+        base64::decode(ciphertext).map_err(|e| ApiError::LnurlError(format!("Invalid success action ciphertext: {}", e)))?;
+    let plaintext = Aes256CbcDec::new_from_slices(&key, &iv)
+        .map_err(|_| ApiError::InvalidInput("AES key must be a 32 byte preimage and iv 16 bytes".to_string()))?
+        .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+        .map_err(|_| ApiError::LnurlError("Success action did not decrypt with the preimage".to_string()))?;
+    String::from_utf8(plaintext.to_vec())
+        .map_err(|e| ApiError::LnurlError(format!("Decrypted success action is not UTF-8: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::cipher::BlockEncryptMut;
+
+    const PREIMAGE: &str = "2a4d6c8e0f1a3b5c7d9e0f2a4b6c8d0e1f3a5b7c9d0e2f4a6b8c0d1e3f5a7b9c";
+
+    fn encrypt(plaintext: &str, iv: &[u8; 16]) -> String {
+        let key = hex::decode(PREIMAGE).unwrap();
+        let mut buffer = plaintext.as_bytes().to_vec();
+        buffer.resize(plaintext.len() / 16 * 16 + 16, 0);
+        let ciphertext = cbc::Encryptor::<aes::Aes256>::new_from_slices(&key, iv)
+            .unwrap()
+            .encrypt_padded_mut::<Pkcs7>(&mut buffer, plaintext.len())
+            .unwrap();
+        base64::encode(ciphertext)
+    }
+
+    fn action(tag: &str) -> LnurlSuccessAction {
+        LnurlSuccessAction {
+            tag: tag.to_string(),
+            message: None,
+            description: Some("Your voucher".to_string()),
+            url: None,
+            ciphertext: None,
+            iv: None,
+        }
+    }
+
+    #[test]
+    fn test_aes_success_action() {
+        let iv = [7u8; 16];
+        let aes = LnurlSuccessAction {
+            ciphertext: Some(encrypt("code: 1234-5678", &iv)),
+            iv: Some(base64::encode(iv)),
+            ..action("aes")
+        };
+        assert!(aes.validate("https://example.com/callback").is_ok());
+
+        let resolved = aes.clone().resolve(PREIMAGE);
+        assert_eq!(resolved.message.as_deref(), Some("code: 1234-5678"));
+        assert_eq!(resolved.description.as_deref(), Some("Your voucher"));
+
+        // without the right preimage the message stays locked
+        assert_eq!(aes.clone().resolve(&"00".repeat(32)).message, None);
+        assert_eq!(aes.resolve("").message, None);
+    }
+
+    #[test]
+    fn test_validate_success_action() {
+        let url = |url: &str| LnurlSuccessAction {
+            url: Some(url.to_string()),
+            ..action("url")
+        };
+        assert!(url("https://example.com/voucher").validate("https://example.com/callback").is_ok());
+        assert!(matches!(
+            url("https://elsewhere.com/voucher").validate("https://example.com/callback"),
+            Err(ApiError::LnurlError(_))
+        ));
+
+        let message = |message: &str| LnurlSuccessAction {
+            message: Some(message.to_string()),
+            ..action("message")
+        };
+        assert!(message("Thanks!").validate("https://example.com").is_ok());
+        assert!(message(&"x".repeat(145)).validate("https://example.com").is_err());
+
+        let bad_iv = LnurlSuccessAction {
+            ciphertext: Some("AAAA".to_string()),
+            iv: Some(base64::encode([0u8; 8])),
+            ..action("aes")
+        };
+        assert!(bad_iv.validate("https://example.com").is_err());
+        assert!(action("unknown").validate("https://example.com").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! LNURL-withdraw (LUD-03): the service pays an invoice we create on our own node
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use super::{callback_url, lnurl_get, lnurl_to_url, LnurlStatusResponse};
+use crate::types::{LightningNode, OnInvoiceEventCallback, OnInvoiceEventParams};
+use crate::{ApiError, CreateInvoiceParams, InvoiceType, Subscription, Transaction};
+
+/// LNURL-withdraw response from the service
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LnurlWithdrawResponse {
+    pub tag: String,
+    pub callback: String,
+    pub k1: String,
+    #[serde(default)]
+    pub default_description: String,
+    pub min_withdrawable: i64, // msats
+    pub max_withdrawable: i64, // msats
+}
+
+/// Fetch an LNURL-withdraw request, it must be tagged `withdrawRequest` with a usable range
+pub async fn fetch_lnurl_withdraw(url: &str) -> Result<LnurlWithdrawResponse, ApiError> {
+    let withdraw: LnurlWithdrawResponse = lnurl_get(url, "LNURL-withdraw response").await?;
+    if withdraw.tag != "withdrawRequest" {
+        return Err(ApiError::LnurlError(format!("Not an LNURL-withdraw service, tag is {}", withdraw.tag)));
+    }
+    if withdraw.max_withdrawable <= 0 || withdraw.min_withdrawable > withdraw.max_withdrawable {
+        return Err(ApiError::LnurlError(format!(
+            "LNURL-withdraw service offers nothing to withdraw ({} to {} msats)",
+            withdraw.min_withdrawable, withdraw.max_withdrawable
+        )));
+    }
+    Ok(withdraw)
+}
+
+/// Hand `invoice` to the service with its `k1`. OK only means the service took the invoice,
+/// the payment itself arrives afterwards.
+pub async fn submit_withdraw_invoice(withdraw: &LnurlWithdrawResponse, invoice: &str) -> Result<(), ApiError> {
+    let url = callback_url(&withdraw.callback, &[("k1", withdraw.k1.clone()), ("pr", invoice.to_string())])?;
+    let response: LnurlStatusResponse = lnurl_get(&url, "LNURL-withdraw callback response").await?;
+    response.check("LNURL-withdraw callback")
+}
+
+/// Redeem an LNURL-withdraw link (`lnurl1...` or `lnurlw://`) into `node`
+///
+/// Creates a BOLT11 invoice for `amount_msats`, or the most the link allows when None, and
+/// submits it to the service. Returns the invoice, pass it to `watch_withdrawal` or
+/// `lookup_invoice` to see the funds arrive.
+pub async fn withdraw<N: LightningNode + ?Sized>(
+    node: &N,
+    lnurl: &str,
+    amount_msats: Option<i64>,
+    description: Option<String>,
+) -> Result<Transaction, ApiError> {
+    withdraw_with(lnurl, amount_msats, description, |params| node.create_invoice(params)).await
+}
+
+/// Follows the invoice `withdraw` returned until the service's payment settles or
+/// `OnInvoiceEventParams::default()` gives up
+pub fn watch_withdrawal<N: LightningNode + ?Sized>(
+    node: &N,
+    invoice: &Transaction,
+    callback: Arc<dyn OnInvoiceEventCallback>,
+) -> Arc<Subscription> {
+    let params = OnInvoiceEventParams {
+        payment_hash: Some(invoice.payment_hash.clone()),
+        ..Default::default()
+    };
+    node.on_invoice_events(params, callback)
+}
+
+// `withdraw` with the node's create_invoice passed in so it can be tested without a node
+async fn withdraw_with<C, CFut>(
+    lnurl: &str,
+    amount_msats: Option<i64>,
+    description: Option<String>,
+    create_invoice: C,
+) -> Result<Transaction, ApiError>
+where
+    C: FnOnce(CreateInvoiceParams) -> CFut,
+    CFut: Future<Output = Result<Transaction, ApiError>>,
+{
+    let withdraw = fetch_lnurl_withdraw(&lnurl_to_url(lnurl)?).await?;
+    let amount = amount_msats.unwrap_or(withdraw.max_withdrawable);
+    if amount < withdraw.min_withdrawable || amount > withdraw.max_withdrawable {
+        return Err(ApiError::InvalidInput(format!(
+            "Amount {} msats is outside the {} to {} msats this link allows",
+            amount, withdraw.min_withdrawable, withdraw.max_withdrawable
+        )));
+    }
+
+    let invoice = create_invoice(CreateInvoiceParams {
+        invoice_type: Some(InvoiceType::Bolt11),
+        amount_msats: Some(amount),
+        description: Some(description.unwrap_or_else(|| withdraw.default_description.clone())),
+        ..Default::default()
+    })
+    .await?;
+    submit_withdraw_invoice(&withdraw, &invoice.invoice).await?;
+    Ok(invoice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lnurl::tests::{lnurl_service, query_param, signed_invoice};
+    use crate::lnurl::PaymentDestination;
+
+    fn withdraw_request() -> serde_json::Value {
+        serde_json::json!({
+            "tag": "withdrawRequest",
+            "k1": "secret-k1",
+            "defaultDescription": "ATM withdrawal",
+            "minWithdrawable": 1000,
+            "maxWithdrawable": 50_000,
+        })
+    }
+
+    fn invoice(params: &CreateInvoiceParams) -> Transaction {
+        let amount = params.amount_msats.unwrap();
+        Transaction {
+            type_: "incoming".to_string(),
+            status: crate::TransactionStatus::Pending,
+            direction: crate::Direction::Incoming,
+            invoice: signed_invoice(amount as u64, "withdrawal"),
+            description: params.description.clone().unwrap_or_default(),
+            description_hash: String::new(),
+            preimage: String::new(),
+            payment_hash: "hash".to_string(),
+            amount_msats: amount,
+            fees_paid: 0,
+            created_at: 0,
+            expires_at: 0,
+            settled_at: 0,
+            payer_note: None,
+            external_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_withdraw() {
+        let (lnurl, queries) = lnurl_service(withdraw_request(), |_| serde_json::json!({ "status": "OK" })).await;
+
+        let created = withdraw_with(&lnurl, None, None, |params| async move {
+            assert_eq!(params.amount_msats, Some(50_000)); // defaults to the most the link allows
+            assert_eq!(params.description.as_deref(), Some("ATM withdrawal"));
+            Ok(invoice(&params))
+        })
+        .await
+        .unwrap();
+
+        let query = queries.lock().unwrap()[0].clone();
+        assert_eq!(query_param(&query, "k1").as_deref(), Some("secret-k1"));
+        assert_eq!(query_param(&query, "pr"), Some(created.invoice));
+
+        let too_much = withdraw_with(&lnurl, Some(60_000), None, |_| async { panic!("invoice created for a bad amount") }).await;
+        assert!(matches!(too_much, Err(ApiError::InvalidInput(_))));
+        assert_eq!(queries.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_refused() {
+        let (lnurl, _) =
+            lnurl_service(withdraw_request(), |_| serde_json::json!({ "status": "ERROR", "reason": "Link already used" })).await;
+        let result = withdraw_with(&lnurl, Some(2000), Some("mine".to_string()), |params| async move { Ok(invoice(&params)) }).await;
+        assert!(matches!(result, Err(ApiError::LnurlError(reason)) if reason == "Link already used"));
+
+        // a pay link is not something to withdraw from
+        let pay = serde_json::json!({ "tag": "payRequest", "k1": "", "minWithdrawable": 0, "maxWithdrawable": 0 });
+        let (lnurl, _) = lnurl_service(pay, |_| serde_json::json!({ "status": "OK" })).await;
+        let result = withdraw_with(&lnurl, None, None, |params| async move { Ok(invoice(&params)) }).await;
+        assert!(matches!(result, Err(ApiError::LnurlError(_))));
+    }
+
+    #[test]
+    fn test_parse_lnurl_withdraw() {
+        let result = PaymentDestination::parse("lnurlw://atm.example.com/withdraw?k1=abc");
+        assert!(matches!(result, Ok(PaymentDestination::LnurlWithdraw(_))));
+
+        // LUD-08 fast withdraw links carry their tag
+        let url = "https://atm.example.com/w?tag=withdrawRequest&k1=abc";
+        let lnurl = bech32::encode::<bech32::Bech32>(bech32::Hrp::parse("lnurl").unwrap(), url.as_bytes()).unwrap();
+        assert!(matches!(PaymentDestination::parse(&lnurl), Ok(PaymentDestination::LnurlWithdraw(_))));
+
+        assert_eq!(
+            lnurl_to_url("lnurlw://atm.example.com/withdraw?k1=abc").unwrap(),
+            "https://atm.example.com/withdraw?k1=abc"
+        );
+        assert_eq!(lnurl_to_url("lnurlp://abc.onion/pay").unwrap(), "http://abc.onion/pay");
+        assert_eq!(lnurl_to_url(&lnurl).unwrap(), url);
+    }
+}
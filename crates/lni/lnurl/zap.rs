@@ -0,0 +1,273 @@
+//! NIP-57 zaps: an LNURL-pay carrying a signed kind 9734 zap request, which the service turns
+//! into a kind 9735 receipt once the invoice is paid
+
+use std::future::Future;
+
+use nostr::nips::nip01::Coordinate;
+use nostr::nips::nip57::{self, ZapRequestData};
+use nostr::{EventBuilder, EventId, JsonUtil, Keys, PublicKey, RelayUrl};
+
+use super::{
+    check_invoice, check_sendable, encode_lnurl, fetch_invoice, fetch_lnurl_pay, lightning_address_to_url, lnurl_to_url,
+    pay_response, LnurlPayResponse, LnurlSuccessAction, PaymentDestination,
+};
+use crate::types::LightningNode;
+use crate::{ApiError, PayInvoiceParams, PayInvoiceResponse, PayResponse, ZapRequestParams, ZapResponse};
+
+/// An invoice from a zap-enabled LNURL-pay service that commits to the zap request
+#[derive(Debug, Clone)]
+pub struct LnurlZapInvoice {
+    pub invoice: String,
+    pub zap_request: String,   // event JSON as sent, the invoice's description hash is sha256 of it
+    pub zapper_pubkey: String, // hex nostrPubkey of the service
+    pub success_action: Option<LnurlSuccessAction>, // validated, `resolve` it with the preimage once paid
+}
+
+/// Sign a kind 9734 zap request for `amount_msats` to the LNURL-pay service at `lnurl` (bech32)
+pub fn zap_request(lnurl: &str, amount_msats: i64, params: &ZapRequestParams) -> Result<nostr::Event, ApiError> {
+    if params.relays.is_empty() {
+        return Err(ApiError::InvalidInput("A zap request needs relays to publish the receipt to".to_string()));
+    }
+    let relays = params
+        .relays
+        .iter()
+        .map(|relay| RelayUrl::parse(relay).map_err(|e| invalid("relay", e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let data = ZapRequestData {
+        public_key: PublicKey::parse(&params.recipient_pubkey).map_err(|e| invalid("recipient pubkey", e))?,
+        relays,
+        message: params.comment.clone().unwrap_or_default(),
+        amount: Some(amount_msats as u64),
+        lnurl: Some(lnurl.to_string()),
+        event_id: params.event_id.as_deref().map(EventId::parse).transpose().map_err(|e| invalid("event id", e))?,
+        event_coordinate: params
+            .event_coordinate
+            .as_deref()
+            .map(Coordinate::parse)
+            .transpose()
+            .map_err(|e| invalid("event coordinate", e))?,
+    };
+
+    let signed = match &params.secret_key {
+        Some(secret_key) => {
+            let keys = Keys::parse(secret_key).map_err(|e| invalid("secret key", e))?;
+            EventBuilder::public_zap_request(data).sign_with_keys(&keys).map_err(|e| e.to_string())
+        }
+        None => nip57::anonymous_zap_request(data).map_err(|e| e.to_string()),
+    };
+    signed.map_err(|reason| ApiError::Api {
+        reason: format!("Failed to sign zap request: {}", reason),
+    })
+}
+
+/// Request an invoice for a zap from an LNURL-pay service that allows nostr
+///
+/// `lnurl` is the service's bech32 LNURL, it goes into the zap request's lnurl tag. The invoice
+/// must be for `amount_msats` with sha256 of the zap request as its description hash.
+pub async fn request_zap_invoice(
+    lnurl_data: &LnurlPayResponse,
+    lnurl: &str,
+    amount_msats: i64,
+    params: &ZapRequestParams,
+) -> Result<LnurlZapInvoice, ApiError> {
+    if lnurl_data.tag != "payRequest" {
+        return Err(ApiError::LnurlError(format!("Not an LNURL-pay service, tag is {}", lnurl_data.tag)));
+    }
+    if lnurl_data.allows_nostr != Some(true) {
+        return Err(ApiError::NotSupported {
+            reason: "LNURL-pay service does not accept zaps".to_string(),
+        });
+    }
+    let zapper_pubkey = lnurl_data
+        .nostr_pubkey
+        .as_deref()
+        .and_then(|pubkey| PublicKey::parse(pubkey).ok())
+        .ok_or_else(|| ApiError::LnurlError("LNURL-pay service allows nostr without a valid nostrPubkey".to_string()))?;
+    check_sendable(lnurl_data, amount_msats)?;
+
+    let zap_request = zap_request(lnurl, amount_msats, params)?.as_json();
+    let query = [
+        ("amount", amount_msats.to_string()),
+        ("nostr", zap_request.clone()),
+        ("lnurl", lnurl.to_string()),
+    ];
+    let response = fetch_invoice(&lnurl_data.callback, &query).await?;
+    check_invoice(&response.pr, amount_msats, &zap_request, "the zap request")?;
+    if let Some(action) = &response.success_action {
+        action.validate(&lnurl_data.callback)?;
+    }
+
+    Ok(LnurlZapInvoice {
+        invoice: response.pr,
+        zap_request,
+        zapper_pubkey: zapper_pubkey.to_hex(),
+        success_action: response.success_action,
+    })
+}
+
+/// Zap a Lightning Address or LNURL-pay link through `node` (NIP-57)
+///
+/// Signs the zap request with `params.secret_key`, or a throwaway key for an anonymous zap, has
+/// the service make an invoice committing to it and pays that with `pay_invoice`. The service
+/// then publishes a receipt signed by `zapper_pubkey` to `params.relays`.
+pub async fn zap<N: LightningNode + ?Sized>(
+    node: &N,
+    destination: String,
+    amount_msats: i64,
+    params: ZapRequestParams,
+) -> Result<ZapResponse, ApiError> {
+    zap_with(&destination, amount_msats, params, |params| node.pay_invoice(params)).await
+}
+
+// `zap` with the node's pay_invoice passed in so it can be tested without a node
+async fn zap_with<I, IFut>(
+    destination: &str,
+    amount_msats: i64,
+    params: ZapRequestParams,
+    pay_invoice: I,
+) -> Result<ZapResponse, ApiError>
+where
+    I: FnOnce(PayInvoiceParams) -> IFut,
+    IFut: Future<Output = Result<PayInvoiceResponse, ApiError>>,
+{
+    if amount_msats <= 0 {
+        return Err(ApiError::InvalidInput("amount_msats must be positive".to_string()));
+    }
+    let (destination_type, url) = match PaymentDestination::parse(destination)? {
+        PaymentDestination::LightningAddress { user, domain } => ("lightning_address", lightning_address_to_url(&user, &domain)),
+        PaymentDestination::LnurlPay(lnurl) => ("lnurl", lnurl_to_url(&lnurl)?),
+        _ => {
+            return Err(ApiError::InvalidInput(
+                "Only Lightning Addresses and LNURL-pay links can be zapped".to_string(),
+            ))
+        }
+    };
+    let lnurl_data = fetch_lnurl_pay(&url).await?;
+    let zap_invoice = request_zap_invoice(&lnurl_data, &encode_lnurl(&url)?, amount_msats, &params).await?;
+
+    let response = pay_invoice(PayInvoiceParams {
+        invoice: zap_invoice.invoice.clone(),
+        ..Default::default()
+    })
+    .await?;
+    let success_action = zap_invoice
+        .success_action
+        .filter(|_| response.status == crate::TransactionStatus::Settled)
+        .map(|action| action.resolve(&response.preimage));
+    Ok(ZapResponse {
+        zap_request: zap_invoice.zap_request,
+        zapper_pubkey: zap_invoice.zapper_pubkey,
+        payment: PayResponse {
+            success_action,
+            ..pay_response(destination_type, zap_invoice.invoice, response)
+        },
+    })
+}
+
+fn invalid(what: &str, e: impl std::fmt::Display) -> ApiError {
+    ApiError::InvalidInput(format!("Invalid zap request {}: {}", what, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lnurl::tests::{lnurl_service, query_param, signed_invoice};
+    use nostr::{Event, Kind, TagKind};
+
+    const ZAPPER: &str = "9630f464cca6a5147aa8a35f0bcdd3ce485324e732fd39e09233b1d848238f31";
+    const RECIPIENT: &str = "32e1827635450ebb3c5a7d12c1f8e7b2b514439ac10a67eef3d9fd9c5c68e245";
+
+    fn params(secret_key: Option<&str>) -> ZapRequestParams {
+        ZapRequestParams {
+            recipient_pubkey: RECIPIENT.to_string(),
+            relays: vec!["wss://relay.example.com".to_string()],
+            comment: Some("great post".to_string()),
+            event_id: Some("d".repeat(64)),
+            secret_key: secret_key.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn paid() -> PayInvoiceResponse {
+        PayInvoiceResponse {
+            payment_hash: "hash".to_string(),
+            preimage: "preimage".to_string(),
+            fee_msats: 0,
+            status: crate::TransactionStatus::Settled,
+        }
+    }
+
+    // A zap-enabled service, `hashed` picks what its invoices commit to from the zap request
+    async fn zap_service(allows_nostr: bool, hashed: fn(String) -> String) -> (String, crate::lnurl::tests::Queries) {
+        let pay = serde_json::json!({
+            "tag": "payRequest",
+            "minSendable": 1000,
+            "maxSendable": 500_000_000,
+            "metadata": "[[\"text/plain\",\"zap me\"]]",
+            "allowsNostr": allows_nostr,
+            "nostrPubkey": ZAPPER,
+        });
+        lnurl_service(pay, move |query| {
+            let amount = query_param(query, "amount").unwrap().parse().unwrap();
+            serde_json::json!({ "pr": signed_invoice(amount, &hashed(query_param(query, "nostr").unwrap())) })
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_zap() {
+        let keys = Keys::generate();
+        let secret_key = keys.secret_key().to_secret_hex();
+        let (lnurl, queries) = zap_service(true, |zap_request| zap_request).await;
+
+        let response = zap_with(&lnurl, 21_000, params(Some(&secret_key)), |params| async move {
+            assert_eq!(params.amount_msats, None);
+            Ok(paid())
+        })
+        .await
+        .unwrap();
+        assert_eq!(response.zapper_pubkey, ZAPPER);
+        assert_eq!(response.payment.destination_type, "lnurl");
+
+        let query = queries.lock().unwrap()[0].clone();
+        assert_eq!(query_param(&query, "nostr"), Some(response.zap_request.clone()));
+        assert_eq!(query_param(&query, "lnurl"), Some(lnurl.clone()));
+        let event = Event::from_json(&response.zap_request).unwrap();
+        assert!(event.verify().is_ok());
+        assert_eq!(event.kind, Kind::ZapRequest);
+        assert_eq!(event.pubkey, keys.public_key());
+        assert_eq!(event.content, "great post");
+        let tag = |kind: &str| event.tags.find(TagKind::custom(kind)).and_then(|tag| tag.content()).map(str::to_string);
+        assert_eq!(tag("p").as_deref(), Some(RECIPIENT));
+        assert_eq!(tag("amount").as_deref(), Some("21000"));
+        assert_eq!(tag("lnurl"), Some(lnurl));
+        assert_eq!(tag("relays").as_deref(), Some("wss://relay.example.com"));
+        assert_eq!(tag("e"), Some("d".repeat(64)));
+        assert!(tag("anon").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_zap_checks() {
+        // the invoice must commit to the zap request
+        let (lnurl, _) = zap_service(true, |_| "something else".to_string()).await;
+        let result = zap_with(&lnurl, 21_000, params(None), |_| async { panic!("paid an invoice that was not for the zap") }).await;
+        assert!(matches!(result, Err(ApiError::LnurlError(_))));
+
+        let (lnurl, queries) = zap_service(false, |zap_request| zap_request).await;
+        let result = zap_with(&lnurl, 21_000, params(None), |_| async { Ok(paid()) }).await;
+        assert!(matches!(result, Err(ApiError::NotSupported { .. })));
+        assert!(queries.lock().unwrap().is_empty());
+
+        let no_relays = ZapRequestParams {
+            relays: Vec::new(),
+            ..params(None)
+        };
+        assert!(matches!(zap_request("lnurl1", 1000, &no_relays), Err(ApiError::InvalidInput(_))));
+        let result = zap_with("lnbc10u1ptest", 1000, params(None), |_| async { Ok(paid()) }).await;
+        assert!(matches!(result, Err(ApiError::InvalidInput(_))));
+
+        // without a secret key the zap is anonymous
+        let event = zap_request("lnurl1", 1000, &params(None)).unwrap();
+        assert!(event.tags.find(TagKind::custom("anon")).is_some());
+    }
+}
@@ -1,6 +1,9 @@
-use crate::{ApiError, CreateInvoiceParams, PayInvoiceParams, Offer, Transaction, PayInvoiceResponse, NodeInfo, ListTransactionsParams};
+use crate::{ApiError, DecodedPaymentRequest, CreateInvoiceParams, PayInvoiceParams, Offer, OutgoingPayment, Transaction, PayInvoiceResponse, PayKeysendResponse, TLVRecord, NodeCapabilities, NodeInfo, ListTransactionsParams, TransactionPage, TransactionStatus, Direction};
 use crate::nwc::NwcConfig;
-use crate::types::{OnInvoiceEventParams, OnInvoiceEventCallback};
+use crate::types::{
+    AccountEvent, AccountEventType, OnAccountEventParams, OnInvoiceEventCallback, OnInvoiceEventParams, SubscribeAllCallback,
+    SubscribeAllParams,
+};
 use nwc::prelude::*;
 use std::str::FromStr;
 use std::time::Duration;
@@ -17,12 +20,31 @@ async fn create_nwc_client(config: &NwcConfig) -> Result<NWC, ApiError> {
     Ok(nwc)
 }
 
+// Map NIP-47 error codes into structured errors, `context` describes what we were doing
+// https://github.com/nostr-protocol/nips/blob/master/47.md#error-codes
+fn nwc_error(e: nwc::Error, context: &str) -> ApiError {
+    let reason = format!("{}: {}", context, e);
+    match e {
+        nwc::Error::Timeout => ApiError::Timeout { reason },
+        nwc::Error::NIP47(nip47::Error::ErrorCode(err)) => match err.code {
+            ErrorCode::RateLimited => ApiError::RateLimited { retry_after: None, reason },
+            ErrorCode::NotImplemented => ApiError::NotSupported { reason },
+            ErrorCode::InsufficientBalance | ErrorCode::QuotaExceeded => {
+                ApiError::InsufficientBalance { reason }
+            }
+            ErrorCode::Restricted | ErrorCode::Unauthorized => ApiError::Unauthorized { reason },
+            _ => ApiError::from_message(reason.clone()).unwrap_or(ApiError::Api { reason }),
+        },
+        _ => ApiError::from_message(reason.clone()).unwrap_or(ApiError::Api { reason }),
+    }
+}
+
 pub async fn get_info(config: NwcConfig) -> Result<NodeInfo, ApiError> {
         let nwc = create_nwc_client(&config).await?;
         
         // Get balance first
         let balance = nwc.get_balance().await
-            .map_err(|e| ApiError::Api { reason: format!("Failed to get balance: {}", e) })?;
+            .map_err(|e| nwc_error(e, "Failed to get balance"))?;
         
         // Try to get more info using get_info method if available
         let info_result = nwc.get_info().await;
@@ -88,10 +110,12 @@ pub async fn create_invoice(config: NwcConfig, params: CreateInvoiceParams) -> R
     };
     
     let response = nwc.make_invoice(request).await
-        .map_err(|e| ApiError::Api { reason: format!("Failed to create invoice: {}", e) })?;
+        .map_err(|e| nwc_error(e, "Failed to create invoice"))?;
     
     Ok(Transaction {
         type_: "incoming".to_string(),
+        status: TransactionStatus::Pending,
+        direction: Direction::Incoming,
         invoice: response.invoice,
         description: params.description.unwrap_or_default(),
         description_hash: "".to_string(),
@@ -107,40 +131,103 @@ pub async fn create_invoice(config: NwcConfig, params: CreateInvoiceParams) -> R
     })
 }
 
-pub async fn pay_invoice(config: NwcConfig, params: PayInvoiceParams) -> Result<PayInvoiceResponse, ApiError> {
+// NIP-47 pay_invoice only carries an amount
+pub(crate) const PAY_PARAMS: crate::PayParamSupport = crate::PayParamSupport {
+    amount: true,
+    ..crate::PayParamSupport::NONE
+};
+
+pub async fn pay_invoice(
+    config: NwcConfig,
+    params: PayInvoiceParams,
+) -> Result<PayInvoiceResponse, ApiError> {
+    crate::check_pay_params(&params, "NWC", PAY_PARAMS)?;
+    if params.wait == Some(false) {
+        // the wallet only answers pay_invoice once the payment is final
+        let invoice = params.invoice.clone();
+        let timeout_seconds = params.timeout_seconds;
+        let lookup_config = config.clone();
+        return crate::dispatch_payment(&invoice, timeout_seconds, send_payment(config, params), |payment_hash| {
+            lookup_payment(lookup_config.clone(), payment_hash)
+        })
+        .await;
+    }
+    send_payment(config, params).await
+}
+
+async fn send_payment(
+    config: NwcConfig,
+    params: PayInvoiceParams,
+) -> Result<PayInvoiceResponse, ApiError> {
     let nwc = create_nwc_client(&config).await?;
     
-    let request = PayInvoiceRequest::new(params.invoice);
+    let mut request = PayInvoiceRequest::new(params.invoice);
+    request.amount = params.amount_msats.map(|amount| amount as u64);
     
     let response = nwc.pay_invoice(request).await
-        .map_err(|e| ApiError::Api { reason: format!("Failed to pay invoice: {}", e) })?;
-    
-    // Compute payment hash from preimage (payment_hash = SHA256(preimage))
-    let payment_hash = if !response.preimage.is_empty() {
-        let preimage_bytes = hex::decode(&response.preimage)
-            .map_err(|e| ApiError::Api { reason: format!("Invalid preimage hex: {}", e) })?;
-        let mut hasher = Sha256::new();
-        hasher.update(preimage_bytes);
-        hex::encode(hasher.finalize())
-    } else {
-        "".to_string()
-    };
+        .map_err(|e| nwc_error(e, "Failed to pay invoice"))?;
     
     Ok(PayInvoiceResponse {
-        payment_hash,
+        payment_hash: payment_hash_from_preimage(&response.preimage)?,
+        preimage: response.preimage,
+        fee_msats: 0, // Not available in response
+        status: TransactionStatus::Settled,
+    })
+}
+
+pub async fn pay_keysend(
+    config: NwcConfig,
+    destination_pubkey: String,
+    amount_msats: i64,
+    custom_records: Vec<TLVRecord>,
+) -> Result<PayKeysendResponse, ApiError> {
+    crate::validate_tlv_records(&custom_records)?;
+    let nwc = create_nwc_client(&config).await?;
+
+    let request = PayKeysendRequest {
+        id: None,
+        amount: amount_msats as u64,
+        pubkey: destination_pubkey,
+        preimage: None, // let the wallet generate it
+        tlv_records: custom_records
+            .into_iter()
+            .map(|r| KeysendTLVRecord {
+                tlv_type: r.type_ as u64,
+                value: r.value,
+            })
+            .collect(),
+    };
+
+    let response = nwc.pay_keysend(request).await
+        .map_err(|e| nwc_error(e, "Failed to pay keysend"))?;
+
+    Ok(PayKeysendResponse {
+        payment_hash: payment_hash_from_preimage(&response.preimage)?,
         preimage: response.preimage,
         fee_msats: 0, // Not available in response
     })
 }
 
+// NIP-47 pay responses only carry the preimage, payment_hash = SHA256(preimage)
+fn payment_hash_from_preimage(preimage: &str) -> Result<String, ApiError> {
+    if preimage.is_empty() {
+        return Ok("".to_string());
+    }
+    let preimage_bytes = hex::decode(preimage)
+        .map_err(|e| ApiError::Api { reason: format!("Invalid preimage hex: {}", e) })?;
+    let mut hasher = Sha256::new();
+    hasher.update(preimage_bytes);
+    Ok(hex::encode(hasher.finalize()))
+}
+
 pub async fn get_offer(_config: &NwcConfig, _search: Option<String>) -> Result<Offer, ApiError> {
     // NWC doesn't support offers/BOLT12 yet
-    Err(ApiError::Api { reason: "NWC does not support offers (BOLT12) yet".to_string() })
+    Err(ApiError::NotSupported { reason: "NWC does not support offers (BOLT12) yet".to_string() })
 }
 
 pub async fn list_offers(_config: &NwcConfig, _search: Option<String>) -> Result<Vec<Offer>, ApiError> {
     // NWC doesn't support offers/BOLT12 yet
-    Err(ApiError::Api { reason: "NWC does not support offers (BOLT12) yet".to_string() })
+    Err(ApiError::NotSupported { reason: "NWC does not support offers (BOLT12) yet".to_string() })
 }
 
 pub async fn pay_offer(
@@ -150,7 +237,7 @@ pub async fn pay_offer(
     _payer_note: Option<String>,
 ) -> Result<PayInvoiceResponse, ApiError> {
     // NWC doesn't support offers/BOLT12 yet
-    Err(ApiError::Api { reason: "NWC does not support offers (BOLT12) yet".to_string() })
+    Err(ApiError::NotSupported { reason: "NWC does not support offers (BOLT12) yet".to_string() })
 }
 
 pub async fn lookup_invoice(
@@ -166,9 +253,11 @@ pub async fn lookup_invoice(
     };
     
     let response = nwc.lookup_invoice(request).await
-        .map_err(|e| ApiError::Api { reason: format!("Failed to lookup invoice: {}", e) })?;
+        .map_err(|e| nwc_error(e, "Failed to lookup invoice"))?;
     
     Ok(Transaction {
+        status: transaction_status(&response),
+        direction: direction(response.transaction_type.as_ref()),
         type_: match response.transaction_type {
             Some(t) => format!("{:?}", t).to_lowercase(),
             None => "unknown".to_string(),
@@ -188,6 +277,17 @@ pub async fn lookup_invoice(
     })
 }
 
+// NIP-47 lookup_invoice also answers for payments we sent
+pub async fn lookup_payment(config: NwcConfig, payment_hash: String) -> Result<OutgoingPayment, ApiError> {
+    let txn = lookup_invoice(config, Some(payment_hash.clone()), None).await?;
+    if txn.direction != Direction::Outgoing {
+        return Err(ApiError::Api {
+            reason: format!("Payment not found for payment hash {}", payment_hash),
+        });
+    }
+    Ok(OutgoingPayment::from_transaction(txn, None))
+}
+
 pub async fn list_transactions(config: NwcConfig, params: ListTransactionsParams) -> Result<Vec<Transaction>, ApiError> {
     let nwc = create_nwc_client(&config).await?;
     
@@ -201,43 +301,92 @@ pub async fn list_transactions(config: NwcConfig, params: ListTransactionsParams
     };
     
     let response = nwc.list_transactions(request).await
-        .map_err(|e| ApiError::Api { reason: format!("Failed to list transactions: {}", e) })?;
+        .map_err(|e| nwc_error(e, "Failed to list transactions"))?;
     
-    let mut transactions = Vec::new();
-    for tx in response {
-        transactions.push(Transaction {
-            type_: match tx.transaction_type {
-                Some(t) => format!("{:?}", t).to_lowercase(),
-                None => "unknown".to_string(),
-            },
-            invoice: tx.invoice.unwrap_or_default(),
-            description: tx.description.unwrap_or_default(),
-            description_hash: "".to_string(),
-            preimage: tx.preimage.unwrap_or_default(),
-            payment_hash: tx.payment_hash,
-            amount_msats: tx.amount as i64,
-            fees_paid: tx.fees_paid as i64,
-            created_at: tx.created_at.as_u64() as i64,
-            expires_at: tx.expires_at.map(|t| t.as_u64() as i64).unwrap_or(0),
-            settled_at: tx.settled_at.map(|t| t.as_u64() as i64).unwrap_or(0),
-            payer_note: None,
-            external_id: None,
-        });
+    Ok(response.into_iter().map(nwc_transaction).collect())
+}
+
+pub async fn list_transactions_page(
+    config: NwcConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    crate::search_page(params, |params| transactions_page(config.clone(), params)).await
+}
+
+// NIP-47 pages with until/offset, the window's `until` keeps the offsets from shifting
+async fn transactions_page(config: NwcConfig, params: ListTransactionsParams) -> Result<TransactionPage, ApiError> {
+    let limit = crate::page_limit(params.limit)?;
+    let window = crate::WindowCursor::from_params(params.cursor.as_deref())?;
+    let nwc = create_nwc_client(&config).await?;
+
+    let request = ListTransactionsRequest {
+        from: None,
+        until: Some(Timestamp::from(window.until as u64)),
+        limit: Some(limit as u64),
+        offset: Some(window.offset as u64),
+        unpaid: None,
+        transaction_type: None,
+    };
+    let response = nwc.list_transactions(request).await
+        .map_err(|e| nwc_error(e, "Failed to list transactions"))?;
+    let count = response.len();
+
+    let transactions: Vec<Transaction> = response.into_iter().map(nwc_transaction).collect();
+    Ok(TransactionPage {
+        transactions,
+        next_cursor: window.next(count, limit),
+    })
+}
+
+fn nwc_transaction(tx: LookupInvoiceResponse) -> Transaction {
+    Transaction {
+        status: transaction_status(&tx),
+        direction: direction(tx.transaction_type.as_ref()),
+        type_: match tx.transaction_type {
+            Some(t) => format!("{:?}", t).to_lowercase(),
+            None => "unknown".to_string(),
+        },
+        invoice: tx.invoice.unwrap_or_default(),
+        description: tx.description.unwrap_or_default(),
+        description_hash: "".to_string(),
+        preimage: tx.preimage.unwrap_or_default(),
+        payment_hash: tx.payment_hash,
+        amount_msats: tx.amount as i64,
+        fees_paid: tx.fees_paid as i64,
+        created_at: tx.created_at.as_u64() as i64,
+        expires_at: tx.expires_at.map(|t| t.as_u64() as i64).unwrap_or(0),
+        settled_at: tx.settled_at.map(|t| t.as_u64() as i64).unwrap_or(0),
+        payer_note: None,
+        external_id: None,
     }
-    
-    Ok(transactions)
 }
 
-pub async fn decode(_config: NwcConfig, str: String) -> Result<String, ApiError> {
-    // NWC doesn't have a decode method, just return the input
-    Ok(str)
+// NIP-47 has no explicit state, only settled_at/expires_at timestamps
+fn transaction_status(tx: &LookupInvoiceResponse) -> TransactionStatus {
+    if tx.settled_at.is_some() {
+        TransactionStatus::Settled
+    } else {
+        TransactionStatus::unpaid(tx.expires_at.map(|t| t.as_u64() as i64).unwrap_or(0))
+    }
+}
+
+// Wallets may omit the type, in which case we assume it is an invoice we created
+fn direction(transaction_type: Option<&TransactionType>) -> Direction {
+    match transaction_type {
+        Some(TransactionType::Outgoing) => Direction::Outgoing,
+        _ => Direction::Incoming,
+    }
+}
+
+pub async fn decode(_config: NwcConfig, str: String) -> Result<DecodedPaymentRequest, ApiError> {
+    crate::decode_payment_request(str)
 }
 
 // Core logic shared with other implementations - processes lookup result and determines status
 fn process_invoice_lookup_result(transaction_result: Result<Transaction, ApiError>) -> (String, Option<Transaction>) {
     match transaction_result {
         Ok(transaction) => {
-            if transaction.settled_at > 0 {
+            if transaction.status == TransactionStatus::Settled {
                 ("settled".to_string(), Some(transaction))
             } else {
                 ("pending".to_string(), Some(transaction))
@@ -302,13 +451,167 @@ pub async fn poll_invoice_events<F>(
     }
 }
 
+// Wallets advertising `payment_received` in get_info push NIP-47 notifications, so we wait
+// on those instead of sending lookup_invoice requests over the relays. The nwc client only
+// subscribes to the NIP-04 kind (23196), which every notifying wallet still publishes.
+pub async fn invoice_events<F>(config: &NwcConfig, params: OnInvoiceEventParams, mut callback: F)
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(params.max_polling_sec.max(0) as u64);
+
+    if let Some(payment_hash) = params.payment_hash.as_deref() {
+        if let Ok(nwc) = notification_client(config, &["payment_received"]).await {
+            let notified = wait_for_payment(&nwc, config, payment_hash, deadline, &mut callback).await;
+            nwc.shutdown().await;
+            if notified.is_ok() {
+                return;
+            }
+        }
+    }
+
+    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+    let params = OnInvoiceEventParams {
+        max_polling_sec: remaining.as_secs() as i64,
+        ..params
+    };
+    poll_invoice_events(config, params, callback).await;
+}
+
+// Client subscribed to wallet notifications, Err unless the wallet sends one of `wanted`
+async fn notification_client(config: &NwcConfig, wanted: &[&str]) -> Result<NotificationClient, ApiError> {
+    let nwc = create_nwc_client(config).await?;
+    let info = nwc.get_info().await
+        .map_err(|e| nwc_error(e, "Failed to get info"))?;
+    if !info.notifications.iter().any(|n| wanted.contains(&n.as_str())) {
+        return Err(ApiError::NotSupported {
+            reason: "Wallet does not send NIP-47 notifications".to_string(),
+        });
+    }
+    let nwc = NotificationClient(Some(nwc));
+    nwc.subscribe_to_notifications().await
+        .map_err(|e| nwc_error(e, "Failed to subscribe to notifications"))?;
+    Ok(nwc)
+}
+
+// Disconnects the relays when dropped too, so a cancelled watcher doesn't leave them open
+struct NotificationClient(Option<NWC>);
+
+impl NotificationClient {
+    async fn shutdown(mut self) {
+        if let Some(nwc) = self.0.take() {
+            nwc.shutdown().await;
+        }
+    }
+}
+
+impl std::ops::Deref for NotificationClient {
+    type Target = NWC;
+
+    fn deref(&self) -> &NWC {
+        self.0.as_ref().expect("only taken by shutdown")
+    }
+}
+
+impl Drop for NotificationClient {
+    fn drop(&mut self) {
+        if let (Some(nwc), Ok(runtime)) = (self.0.take(), tokio::runtime::Handle::try_current()) {
+            runtime.spawn(nwc.shutdown());
+        }
+    }
+}
+
+// Ok once a final status was reported, Err hands over to polling
+async fn wait_for_payment<F>(
+    nwc: &NWC,
+    config: &NwcConfig,
+    payment_hash: &str,
+    deadline: tokio::time::Instant,
+    callback: &mut F,
+) -> Result<(), ApiError>
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let received = std::sync::Mutex::new(None);
+    let handled = nwc.handle_notifications(|notification| {
+        let received = &received;
+        async move {
+            if let NotificationResult::PaymentReceived(payment) = notification.notification {
+                if payment.payment_hash == payment_hash {
+                    *received.lock().unwrap() = Some(payment);
+                    return Ok(true);
+                }
+            }
+            Ok::<_, Box<dyn std::error::Error>>(false)
+        }
+    });
+    tokio::pin!(handled);
+
+    // The handler is polled first so it is listening before the lookup, a payment landing
+    // while we look it up is then caught by one or the other
+    let lookup = lookup_invoice(config.clone(), Some(payment_hash.to_string()), None);
+    let handled_early = tokio::select! {
+        biased;
+        result = &mut handled => Some(result),
+        txn = lookup => {
+            let txn = txn?;
+            if txn.status == TransactionStatus::Settled {
+                callback("success".to_string(), Some(txn));
+                return Ok(());
+            }
+            callback("pending".to_string(), Some(txn));
+            None
+        }
+    };
+    let handled = match handled_early {
+        Some(result) => Ok(result),
+        None => tokio::time::timeout_at(deadline, handled).await,
+    };
+
+    match handled {
+        Err(_) => {
+            callback("failure".to_string(), None);
+            Ok(())
+        }
+        Ok(Err(e)) => Err(nwc_error(e, "Failed to handle notifications")),
+        Ok(Ok(())) => match received.lock().unwrap().take() {
+            Some(payment) => {
+                callback("success".to_string(), Some(notification_transaction(payment, Direction::Incoming)));
+                Ok(())
+            }
+            None => Err(ApiError::NetworkError("Relay pool shut down".to_string())),
+        },
+    }
+}
+
+fn notification_transaction(payment: PaymentNotification, direction: Direction) -> Transaction {
+    let transaction_type = payment.transaction_type.or(Some(match direction {
+        Direction::Outgoing => TransactionType::Outgoing,
+        Direction::Incoming => TransactionType::Incoming,
+    }));
+    nwc_transaction(LookupInvoiceResponse {
+        transaction_type,
+        invoice: Some(payment.invoice),
+        description: payment.description,
+        description_hash: payment.description_hash,
+        preimage: Some(payment.preimage),
+        payment_hash: payment.payment_hash,
+        amount: payment.amount,
+        fees_paid: payment.fees_paid,
+        created_at: payment.created_at,
+        expires_at: payment.expires_at,
+        settled_at: Some(payment.settled_at),
+        metadata: payment.metadata,
+    })
+}
+
 // Async version for direct async use
 pub async fn on_invoice_events(
     config: NwcConfig,
     params: OnInvoiceEventParams,
     callback: std::sync::Arc<dyn OnInvoiceEventCallback>,
 ) {
-    poll_invoice_events(&config, params, move |status, tx| match status.as_str() {
+    invoice_events(&config, params, move |status, tx| match status.as_str() {
         "success" => callback.success(tx),
         "pending" => callback.pending(tx),
         "failure" => callback.failure(tx),
@@ -317,3 +620,252 @@ pub async fn on_invoice_events(
     .await;
 }
 
+// Every payment on the connection, reported as "payment_received" or "payment_sent". Comes
+// from NIP-47 notifications when the wallet sends them, otherwise from diffing
+// list_transactions every `polling_delay_sec`. Returns after `max_polling_sec`.
+pub async fn account_events<F>(config: &NwcConfig, params: OnAccountEventParams, mut callback: F)
+where
+    F: FnMut(String, Transaction),
+{
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(params.max_polling_sec.max(0) as u64);
+
+    if let Ok(nwc) = notification_client(config, &["payment_received", "payment_sent"]).await {
+        let callback = std::sync::Mutex::new(&mut callback);
+        let handled = nwc.handle_notifications(|notification| {
+            let callback = &callback;
+            async move {
+                let (event, txn) = match notification.notification {
+                    NotificationResult::PaymentReceived(payment) => {
+                        ("payment_received", notification_transaction(payment, Direction::Incoming))
+                    }
+                    NotificationResult::PaymentSent(payment) => {
+                        ("payment_sent", notification_transaction(payment, Direction::Outgoing))
+                    }
+                };
+                (callback.lock().unwrap())(event.to_string(), txn);
+                Ok::<_, Box<dyn std::error::Error>>(false)
+            }
+        });
+        let finished = tokio::time::timeout_at(deadline, handled).await;
+        nwc.shutdown().await;
+        // Only fall through to polling if the relays went away before the deadline
+        if !matches!(finished, Ok(Err(_)) | Ok(Ok(()))) {
+            return;
+        }
+    }
+
+    poll_account_events(config, params.polling_delay_sec, deadline, callback).await;
+}
+
+// Reports transactions that settled since the previous listing. Each poll pages back to
+// the watermark, the newest transaction of the previous listing or the oldest one that was
+// still pending in it, so nothing is dropped however many arrive in between.
+async fn poll_account_events<F>(config: &NwcConfig, polling_delay_sec: i64, deadline: tokio::time::Instant, mut callback: F)
+where
+    F: FnMut(String, Transaction),
+{
+    let polling_delay = Duration::from_secs(polling_delay_sec.max(1) as u64);
+    let mut seen = std::collections::HashSet::new();
+    // the first listing only sets the watermark
+    let mut watermark = list_back_to(config, None).await.ok().map(|txns| {
+        seen = settled_hashes(&txns);
+        next_watermark(&txns, 0)
+    });
+    while tokio::time::Instant::now() + polling_delay < deadline {
+        tokio::time::sleep(polling_delay).await;
+        let Ok(txns) = list_back_to(config, watermark).await else {
+            continue;
+        };
+        for txn in txns.iter().filter(|t| t.status == TransactionStatus::Settled) {
+            if watermark.is_none() || seen.contains(&txn.payment_hash) {
+                continue;
+            }
+            let event = match txn.direction {
+                Direction::Outgoing => "payment_sent",
+                Direction::Incoming => "payment_received",
+            };
+            callback(event.to_string(), txn.clone());
+        }
+        seen = settled_hashes(&txns);
+        watermark = Some(next_watermark(&txns, watermark.unwrap_or(0)));
+    }
+}
+
+// Newest first, down to the first transaction created before `watermark`. Only the newest
+// page without one.
+async fn list_back_to(config: &NwcConfig, watermark: Option<i64>) -> Result<Vec<Transaction>, ApiError> {
+    let mut txns = vec![];
+    let mut cursor = None;
+    loop {
+        let page = list_transactions_page(
+            config.clone(),
+            ListTransactionsParams {
+                from: 0,
+                limit: 50,
+                payment_hash: None,
+                search: None,
+                created_after: None,
+                created_before: None,
+                cursor,
+            },
+        )
+        .await?;
+        let reached = match watermark {
+            Some(watermark) => page.transactions.iter().any(|t| t.created_at < watermark),
+            None => true,
+        };
+        txns.extend(
+            page.transactions
+                .into_iter()
+                .filter(|t| watermark.is_none_or(|watermark| t.created_at >= watermark)),
+        );
+        cursor = page.next_cursor;
+        if reached || cursor.is_none() {
+            return Ok(txns);
+        }
+    }
+}
+
+fn settled_hashes(txns: &[Transaction]) -> std::collections::HashSet<String> {
+    txns.iter()
+        .filter(|t| t.status == TransactionStatus::Settled)
+        .map(|t| t.payment_hash.clone())
+        .collect()
+}
+
+// Invoices past their expiry won't settle anymore and don't hold the watermark back
+fn next_watermark(txns: &[Transaction], previous: i64) -> i64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let newest = txns.iter().map(|t| t.created_at).max().unwrap_or(previous);
+    txns.iter()
+        .filter(|t| t.status == TransactionStatus::Pending && (t.expires_at == 0 || t.expires_at > now))
+        .map(|t| t.created_at)
+        .fold(newest, i64::min)
+}
+
+// Every settled invoice and sent payment from the wallet's NIP-47 notifications. On a resume,
+// and whenever the relays have to be reconnected, what settled while not listening is listed
+// first. NIP-47 has no notification for failed payments, those only show up in that listing.
+// Wallets without notifications are diffed with events::poll_all. The resume index is the
+// unix time of the event, as for poll_all.
+pub async fn subscribe_all(
+    config: NwcConfig,
+    params: SubscribeAllParams,
+    callback: &dyn SubscribeAllCallback,
+) -> Result<(), ApiError> {
+    let mut since = match params.resume_from.as_deref() {
+        Some(index) => Some(crate::events::parse_resume_index::<1>(index)?[0]),
+        None => None,
+    };
+    let mut backoff = crate::events::Backoff::new();
+    loop {
+        let nwc = match notification_client(&config, &["payment_received", "payment_sent"]).await {
+            Ok(nwc) => nwc,
+            Err(ApiError::NotSupported { .. }) => {
+                let node = crate::nwc::NwcNode::new(config.clone());
+                let params = SubscribeAllParams {
+                    resume_from: since.map(|since| since.to_string()),
+                    ..params
+                };
+                return crate::events::poll_all(&node, params, callback).await;
+            }
+            Err(e) => {
+                backoff.retry(e).await?;
+                continue;
+            }
+        };
+        // Subscribed already, so nothing settling from here on is missed
+        if let Some(resume_from) = since {
+            match settled_since(&config, resume_from).await {
+                Ok(events) => {
+                    for (event_type, txn) in events {
+                        since = Some(crate::events::event_time(&txn));
+                        callback.event(AccountEvent {
+                            event_type,
+                            resume_index: crate::events::event_time(&txn).to_string(),
+                            transaction: txn,
+                        });
+                    }
+                }
+                Err(e) => {
+                    backoff.retry(e).await?;
+                    continue;
+                }
+            }
+        }
+        backoff.reset();
+        since = Some(since.unwrap_or_else(crate::events::now_secs));
+
+        let latest = std::sync::Mutex::new(since);
+        let handled = nwc.handle_notifications(|notification| {
+            let latest = &latest;
+            async move {
+                let (event_type, txn) = match notification.notification {
+                    NotificationResult::PaymentReceived(payment) => (
+                        AccountEventType::InvoiceSettled,
+                        notification_transaction(payment, Direction::Incoming),
+                    ),
+                    NotificationResult::PaymentSent(payment) => (
+                        AccountEventType::PaymentSent,
+                        notification_transaction(payment, Direction::Outgoing),
+                    ),
+                };
+                let time = crate::events::event_time(&txn);
+                *latest.lock().unwrap() = Some(time);
+                callback.event(AccountEvent {
+                    event_type,
+                    resume_index: time.to_string(),
+                    transaction: txn,
+                });
+                Ok::<_, Box<dyn std::error::Error>>(false)
+            }
+        });
+        let dropped = match handled.await {
+            Ok(()) => ApiError::NetworkError("Relay pool shut down".to_string()),
+            Err(e) => nwc_error(e, "Failed to handle notifications"),
+        };
+        nwc.shutdown().await;
+        since = *latest.lock().unwrap();
+        backoff.retry(dropped).await?;
+    }
+}
+
+// What finished at or after `since`, oldest first
+async fn settled_since(config: &NwcConfig, since: i64) -> Result<Vec<(AccountEventType, Transaction)>, ApiError> {
+    let txns = list_back_to(config, Some(since - crate::events::RESUME_LOOKBACK_SECS)).await?;
+    Ok(crate::events::account_transactions(
+        &mut std::collections::HashMap::new(),
+        txns,
+        since,
+    ))
+}
+
+// Taken from the methods the wallet advertises for this connection
+pub async fn capabilities(config: NwcConfig) -> Result<NodeCapabilities, ApiError> {
+    let nwc = create_nwc_client(&config).await?;
+    let info = nwc.get_info().await
+        .map_err(|e| nwc_error(e, "Failed to get info"))?;
+    let has = |method: &str| info.methods.iter().any(|m| m == method);
+
+    Ok(NodeCapabilities {
+        bolt11_receive: has("make_invoice"),
+        bolt11_send: has("pay_invoice"),
+        bolt12_receive: false,
+        bolt12_send: false,
+        keysend: has("pay_keysend"),
+        onchain_receive: false,
+        onchain_send: false,
+        onchain_balance: false,
+        hold_invoices: false,
+        description_hash_invoices: false,
+        zero_amount_invoices: false, // make_invoice requires an amount
+        lookup_invoice: has("lookup_invoice"),
+        list_transactions: has("list_transactions"),
+        channel_management: false,
+        native_event_streaming: !info.notifications.is_empty(),
+        fiat: false,
+    })
+}
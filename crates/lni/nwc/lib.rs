@@ -4,7 +4,10 @@ use napi_derive::napi;
 use crate::types::NodeInfo;
 use crate::{
     ApiError, CreateInvoiceParams, CreateOfferParams, ListTransactionsParams, LookupInvoiceParams,
-    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction,
+    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction, Channel, CloseChannelRequest,
+    CloseChannelResponse, OpenChannelRequest, OpenChannelResponse, UpdateChannelRequest,
+    OnchainBalanceResponse, OnchainTransaction, SendOnchainResponse, PayKeysendResponse, TLVRecord,
+    NodeCapabilities,
 };
 #[cfg(not(feature = "uniffi"))]
 use crate::LightningNode;
@@ -56,6 +59,10 @@ impl NwcNode {
         crate::nwc::api::get_info(self.config.clone()).await
     }
 
+    pub async fn capabilities(&self) -> Result<NodeCapabilities, ApiError> {
+        crate::nwc::api::capabilities(self.config.clone()).await
+    }
+
     pub async fn create_invoice(&self, params: CreateInvoiceParams) -> Result<Transaction, ApiError> {
         crate::nwc::api::create_invoice(self.config.clone(), params).await
     }
@@ -64,8 +71,43 @@ impl NwcNode {
         crate::nwc::api::pay_invoice(self.config.clone(), params).await
     }
 
+    pub async fn estimate_payment_fee(
+        &self,
+        _invoice: String,
+        _amount_msats: Option<i64>,
+    ) -> Result<crate::PaymentFeeEstimate, ApiError> {
+        Err(ApiError::NotSupported { reason: "NIP-47 has no fee estimate method".to_string() })
+    }
+
+    pub async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: crate::PayOptions,
+    ) -> Result<crate::PayResponse, ApiError> {
+        crate::lnurl::pay(self, destination, amount_msats, options).await
+    }
+
+    pub async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: crate::ZapRequestParams,
+    ) -> Result<crate::ZapResponse, ApiError> {
+        crate::lnurl::zap(self, destination, amount_msats, params).await
+    }
+
+    pub async fn pay_keysend(
+        &self,
+        destination_pubkey: String,
+        amount_msats: i64,
+        custom_records: Vec<TLVRecord>,
+    ) -> Result<PayKeysendResponse, ApiError> {
+        crate::nwc::api::pay_keysend(self.config.clone(), destination_pubkey, amount_msats, custom_records).await
+    }
+
     pub async fn create_offer(&self, _params: CreateOfferParams) -> Result<Offer, ApiError> {
-        Err(ApiError::Api { reason: "create_offer not implemented for NwcNode".to_string() })
+        Err(ApiError::NotSupported { reason: "create_offer not implemented for NwcNode".to_string() })
     }
 
     pub async fn get_offer(&self, search: Option<String>) -> Result<Offer, ApiError> {
@@ -89,6 +131,10 @@ impl NwcNode {
         crate::nwc::api::lookup_invoice(self.config.clone(), params.payment_hash, params.search).await
     }
 
+    pub async fn lookup_payment(&self, payment_hash: String) -> Result<crate::OutgoingPayment, ApiError> {
+        crate::nwc::api::lookup_payment(self.config.clone(), payment_hash).await
+    }
+
     pub async fn list_transactions(
         &self,
         params: ListTransactionsParams,
@@ -96,16 +142,87 @@ impl NwcNode {
         crate::nwc::api::list_transactions(self.config.clone(), params).await
     }
 
-    pub async fn decode(&self, str: String) -> Result<String, ApiError> {
+    pub async fn list_transactions_page(
+        &self,
+        params: ListTransactionsParams,
+    ) -> Result<crate::TransactionPage, ApiError> {
+        crate::nwc::api::list_transactions_page(self.config.clone(), params).await
+    }
+
+    pub async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, ApiError> {
         crate::nwc::api::decode(self.config.clone(), str).await
     }
 
-    pub async fn on_invoice_events(
+    pub fn on_invoice_events(
         &self,
         params: crate::types::OnInvoiceEventParams,
         callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-    ) {
-        crate::nwc::api::on_invoice_events(self.config.clone(), params, callback).await
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::invoice_events(callback, |callback| {
+            crate::nwc::api::on_invoice_events(config, params, callback)
+        })
+    }
+
+    pub fn on_payment_events(
+        &self,
+        params: crate::types::OnPaymentEventParams,
+        callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No payment feed, the payment is looked up every polling_delay_sec
+        let node = self.clone();
+        crate::Subscription::payment_events(callback, |callback| async move {
+            let payment_hash = params.payment_hash.clone();
+            crate::poll_payment_events(|| node.lookup_payment(payment_hash.clone()), params, callback.as_ref()).await
+        })
+    }
+
+    pub fn subscribe_all(
+        &self,
+        params: crate::types::SubscribeAllParams,
+        callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::subscribe_all(callback, |callback| async move {
+            crate::nwc::api::subscribe_all(config, params, callback.as_ref()).await
+        })
+    }
+
+    pub async fn list_channels(&self) -> Result<Vec<Channel>, ApiError> {
+        Err(ApiError::NotSupported { reason: "NWC does not expose channel management".to_string() })
+    }
+
+    pub async fn open_channel(&self, _params: OpenChannelRequest) -> Result<OpenChannelResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "NWC does not expose channel management".to_string() })
+    }
+
+    pub async fn close_channel(&self, _params: CloseChannelRequest) -> Result<CloseChannelResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "NWC does not expose channel management".to_string() })
+    }
+
+    pub async fn update_channel_policy(&self, _params: UpdateChannelRequest) -> Result<(), ApiError> {
+        Err(ApiError::NotSupported { reason: "NWC does not expose channel management".to_string() })
+    }
+
+    pub async fn get_onchain_balance(&self) -> Result<OnchainBalanceResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "NWC does not expose an onchain wallet".to_string() })
+    }
+
+    pub async fn new_onchain_address(&self) -> Result<String, ApiError> {
+        Err(ApiError::NotSupported { reason: "NWC does not expose an onchain wallet".to_string() })
+    }
+
+    pub async fn send_onchain(
+        &self,
+        _address: String,
+        _amount_sats: i64,
+        _sat_per_vbyte: Option<i64>,
+    ) -> Result<SendOnchainResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "NWC does not expose an onchain wallet".to_string() })
+    }
+
+    pub async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, ApiError> {
+        Err(ApiError::NotSupported { reason: "NWC does not expose an onchain wallet".to_string() })
     }
 }
 
@@ -113,6 +230,24 @@ impl NwcNode {
 // Trait implementation for polymorphic access via Arc<dyn LightningNode>
 crate::impl_lightning_node!(NwcNode);
 
+// Account events come from NIP-47 notifications when the wallet sends them
+impl crate::LightningNodeEvents for NwcNode {
+    fn account_events(
+        &self,
+        params: crate::types::OnAccountEventParams,
+    ) -> impl futures_util::Stream<Item = crate::NodeEvent> + Send + 'static {
+        let config = self.config.clone();
+        crate::events::spawn_stream(move |tx| async move {
+            crate::nwc::api::account_events(&config, params, |event, txn| {
+                if let Some(event) = crate::events::account_event(&event, txn) {
+                    let _ = tx.send(event);
+                }
+            })
+            .await
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::InvoiceType;
@@ -235,6 +370,7 @@ mod tests {
             search: None,
             created_after: None,
             created_before: None,
+            cursor: None,
         };
         match NODE.list_transactions(params).await {
             Ok(txns) => {
@@ -297,7 +433,7 @@ mod tests {
         };
 
         // Start the event listener
-        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).await;
+        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).wait().await;
 
         // Check if events were received
         let received_events = events.lock().unwrap();
@@ -0,0 +1,414 @@
+// In-process stand-in for a relay and the wallet service behind it. It speaks just enough
+// NIP-01 and NIP-47 (get_info, lookup_invoice, list_transactions, notifications) to test
+// event handling without a real wallet.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use nwc::prelude::*;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::nwc::NwcConfig;
+use crate::types::{OnAccountEventParams, OnInvoiceEventParams};
+use crate::Transaction;
+
+const PAYMENT_HASH: &str = "8a8e5a3c0a2a8a5b2a8d2f1fa1b0c7e9e34c1f7b0a8e4c0c5f5a2d3b4c5d6e7f";
+const PREIMAGE: &str = "2a4d6c8e0f1a3b5c7d9e0f2a4b6c8d0e1f3a5b7c9d0e2f4a6b8c0d1e3f5a7b9c";
+
+type Subscription = (SubscriptionId, Filter, mpsc::UnboundedSender<String>);
+
+struct WalletStandIn {
+    wallet: Keys,
+    client: Keys,
+    notifications: Vec<String>,
+    subscriptions: Mutex<Vec<Subscription>>,
+    requests: Mutex<Vec<String>>,
+    transactions: Mutex<Vec<serde_json::Value>>,
+}
+
+impl WalletStandIn {
+    async fn start(notifications: &[&str]) -> (Arc<Self>, NwcConfig) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+        let relay = Arc::new(Self {
+            wallet: Keys::generate(),
+            client: Keys::generate(),
+            notifications: notifications.iter().map(|n| n.to_string()).collect(),
+            subscriptions: Mutex::new(Vec::new()),
+            requests: Mutex::new(Vec::new()),
+            transactions: Mutex::new(Vec::new()),
+        });
+
+        let server = relay.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let server = server.clone();
+                tokio::spawn(async move {
+                    if let Ok(ws) = tokio_tungstenite::accept_async(stream).await {
+                        server.serve(ws).await;
+                    }
+                });
+            }
+        });
+
+        let uri = NostrWalletConnectURI::new(
+            relay.wallet.public_key(),
+            vec![RelayUrl::parse(&format!("ws://{}", addr)).unwrap()],
+            relay.client.secret_key().clone(),
+            None,
+        );
+        let config = NwcConfig {
+            nwc_uri: uri.to_string(),
+            ..Default::default()
+        };
+        (relay, config)
+    }
+
+    async fn serve(&self, ws: tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>) {
+        let (mut sink, mut stream) = ws.split();
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        tokio::spawn(async move {
+            while let Some(text) = rx.recv().await {
+                if sink.send(Message::text(text)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(Ok(message)) = stream.next().await {
+            let Message::Text(text) = message else {
+                continue;
+            };
+            let Ok(message) = ClientMessage::from_json(text.as_str()) else {
+                continue;
+            };
+            match message {
+                ClientMessage::Req { subscription_id, filter } => {
+                    let subscription_id = subscription_id.into_owned();
+                    let _ = tx.send(RelayMessage::eose(subscription_id.clone()).as_json());
+                    self.subscriptions.lock().unwrap().push((subscription_id, filter.into_owned(), tx.clone()));
+                }
+                ClientMessage::Close(subscription_id) => {
+                    self.subscriptions.lock().unwrap().retain(|(id, _, _)| id != subscription_id.as_ref());
+                }
+                ClientMessage::Event(event) => {
+                    let _ = tx.send(RelayMessage::ok(event.id, true, "").as_json());
+                    if event.kind == Kind::WalletConnectRequest {
+                        self.respond(&event);
+                    }
+                }
+                _ => {}
+            }
+        }
+        // the client went away, its subscriptions go with it
+        self.subscriptions.lock().unwrap().retain(|(_, _, sender)| !sender.same_channel(&tx));
+    }
+
+    fn respond(&self, request: &Event) {
+        let json = nip04::decrypt(self.wallet.secret_key(), &request.pubkey, &request.content).unwrap();
+        let request_json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let method = request_json["method"].as_str().unwrap_or_default().to_string();
+        self.requests.lock().unwrap().push(method.clone());
+
+        let result = match method.as_str() {
+            "get_info" => serde_json::json!({
+                "alias": "stand-in",
+                "methods": ["get_info", "lookup_invoice", "list_transactions"],
+                "notifications": self.notifications,
+            }),
+            "lookup_invoice" => unpaid_invoice(),
+            "list_transactions" => self.list_transactions(&request_json["params"]),
+            _ => serde_json::json!({}),
+        };
+        let response = serde_json::json!({ "result_type": method, "result": result });
+        let content = nip04::encrypt(self.wallet.secret_key(), &request.pubkey, response.to_string()).unwrap();
+        let event = EventBuilder::new(Kind::WalletConnectResponse, content)
+            .tag(Tag::public_key(request.pubkey))
+            .tag(Tag::event(request.id))
+            .sign_with_keys(&self.wallet)
+            .unwrap();
+        self.broadcast(&event);
+    }
+
+    // Newest first, paged with until/offset/limit like a wallet service does
+    fn list_transactions(&self, params: &serde_json::Value) -> serde_json::Value {
+        let until = params["until"].as_u64().unwrap_or(u64::MAX);
+        let mut transactions: Vec<_> = self
+            .transactions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t["created_at"].as_u64().unwrap_or_default() <= until)
+            .cloned()
+            .collect();
+        transactions.sort_by_key(|t| std::cmp::Reverse(t["created_at"].as_u64()));
+        let page: Vec<_> = transactions
+            .into_iter()
+            .skip(params["offset"].as_u64().unwrap_or(0) as usize)
+            .take(params["limit"].as_u64().unwrap_or(u64::MAX) as usize)
+            .collect();
+        serde_json::json!({ "transactions": page })
+    }
+
+    fn notify(&self, notification_type: &str, payment_hash: &str) {
+        let notification = serde_json::json!({
+            "notification_type": notification_type,
+            "notification": {
+                "type": if notification_type == "payment_sent" { "outgoing" } else { "incoming" },
+                "invoice": "lnbc1standin",
+                "description": "stand-in",
+                "preimage": PREIMAGE,
+                "payment_hash": payment_hash,
+                "amount": 21000,
+                "fees_paid": 0,
+                "created_at": Timestamp::now().as_u64() - 60,
+                "settled_at": Timestamp::now().as_u64(),
+            },
+        });
+        let content =
+            nip04::encrypt(self.wallet.secret_key(), &self.client.public_key(), notification.to_string()).unwrap();
+        let event = EventBuilder::new(Kind::WalletConnectNotification, content)
+            .tag(Tag::public_key(self.client.public_key()))
+            .sign_with_keys(&self.wallet)
+            .unwrap();
+        self.broadcast(&event);
+    }
+
+    fn broadcast(&self, event: &Event) {
+        for (id, filter, tx) in self.subscriptions.lock().unwrap().iter() {
+            if filter.match_event(event, MatchEventOptions::new()) {
+                let _ = tx.send(RelayMessage::event(id.clone(), event.clone()).as_json());
+            }
+        }
+    }
+
+    // Resolves once the client subscribed to notifications and sent `after` (if any)
+    async fn wait_for_listener(&self, after: Option<&str>) {
+        for _ in 0..100 {
+            let subscribed = self.subscriptions.lock().unwrap().iter().any(|(_, filter, _)| {
+                filter.kinds.as_ref().is_some_and(|kinds| kinds.contains(&Kind::WalletConnectNotification))
+            });
+            let requested = after.is_none_or(|method| self.requests.lock().unwrap().iter().any(|m| m == method));
+            if subscribed && requested {
+                // let the response reach the client before notifying
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("client never subscribed to notifications");
+    }
+}
+
+fn unpaid_invoice() -> serde_json::Value {
+    serde_json::json!({
+        "type": "incoming",
+        "invoice": "lnbc1standin",
+        "payment_hash": PAYMENT_HASH,
+        "amount": 21000,
+        "fees_paid": 0,
+        "created_at": Timestamp::now().as_u64() - 60,
+        "expires_at": Timestamp::now().as_u64() + 3600,
+    })
+}
+
+#[tokio::test]
+async fn test_invoice_events_from_notifications() {
+    let (relay, config) = WalletStandIn::start(&["payment_received"]).await;
+    let events = Arc::new(Mutex::new(Vec::<(String, Option<Transaction>)>::new()));
+
+    let recorded = events.clone();
+    let params = OnInvoiceEventParams {
+        payment_hash: Some(PAYMENT_HASH.to_string()),
+        polling_delay_sec: 60, // a poll would blow the deadline, so success must come from the notification
+        max_polling_sec: 20,
+        ..Default::default()
+    };
+    let watcher = tokio::spawn(async move {
+        crate::nwc::api::invoice_events(&config, params, move |status, txn| {
+            recorded.lock().unwrap().push((status, txn));
+        })
+        .await;
+    });
+
+    relay.wait_for_listener(Some("lookup_invoice")).await;
+    relay.notify("payment_received", &"00".repeat(32)); // someone else's invoice
+    relay.notify("payment_received", PAYMENT_HASH);
+    tokio::time::timeout(Duration::from_secs(10), watcher).await.unwrap().unwrap();
+
+    let events = events.lock().unwrap();
+    let statuses: Vec<&str> = events.iter().map(|(status, _)| status.as_str()).collect();
+    assert_eq!(statuses, vec!["pending", "success"]);
+    let txn = events[1].1.as_ref().unwrap();
+    assert_eq!(txn.payment_hash, PAYMENT_HASH);
+    assert_eq!(txn.preimage, PREIMAGE);
+    assert_eq!(txn.status, crate::TransactionStatus::Settled);
+}
+
+#[tokio::test]
+async fn test_invoice_events_without_notifications_polls() {
+    let (relay, config) = WalletStandIn::start(&[]).await;
+    let mut statuses = Vec::new();
+    let params = OnInvoiceEventParams {
+        payment_hash: Some(PAYMENT_HASH.to_string()),
+        polling_delay_sec: 1,
+        max_polling_sec: 2,
+        ..Default::default()
+    };
+    crate::nwc::api::invoice_events(&config, params, |status, _| statuses.push(status)).await;
+
+    assert_eq!(statuses.first().map(String::as_str), Some("pending"));
+    assert_eq!(statuses.last().map(String::as_str), Some("failure"));
+    let subscribed = relay.subscriptions.lock().unwrap().iter().any(|(_, filter, _)| {
+        filter.kinds.as_ref().is_some_and(|kinds| kinds.contains(&Kind::WalletConnectNotification))
+    });
+    assert!(!subscribed);
+}
+
+#[tokio::test]
+async fn test_account_events_from_notifications() {
+    let (relay, config) = WalletStandIn::start(&["payment_received", "payment_sent"]).await;
+    let events = Arc::new(Mutex::new(Vec::<(String, Transaction)>::new()));
+
+    let recorded = events.clone();
+    let params = OnAccountEventParams {
+        polling_delay_sec: 60,
+        max_polling_sec: 3,
+    };
+    let watcher = tokio::spawn(async move {
+        crate::nwc::api::account_events(&config, params, move |event, txn| {
+            recorded.lock().unwrap().push((event, txn));
+        })
+        .await;
+    });
+
+    relay.wait_for_listener(None).await;
+    relay.notify("payment_received", PAYMENT_HASH);
+    relay.notify("payment_sent", &"11".repeat(32));
+    watcher.await.unwrap();
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].0, "payment_received");
+    assert_eq!(events[0].1.direction, crate::Direction::Incoming);
+    assert_eq!(events[1].0, "payment_sent");
+    assert_eq!(events[1].1.direction, crate::Direction::Outgoing);
+}
+
+#[tokio::test]
+async fn test_account_events_polling_pages_back() {
+    let (relay, config) = WalletStandIn::start(&[]).await;
+    let events = Arc::new(Mutex::new(Vec::<(String, Transaction)>::new()));
+
+    let recorded = events.clone();
+    let params = OnAccountEventParams {
+        polling_delay_sec: 0, // taken as 1s rather than spinning
+        max_polling_sec: 3,
+    };
+    let watcher = tokio::spawn(async move {
+        crate::nwc::api::account_events(&config, params, move |event, txn| {
+            recorded.lock().unwrap().push((event, txn));
+        })
+        .await;
+    });
+
+    for _ in 0..100 {
+        if relay.requests.lock().unwrap().iter().any(|m| m == "list_transactions") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    // more than a page of payments arrive between two polls
+    let now = Timestamp::now().as_u64();
+    relay.transactions.lock().unwrap().extend((0..60u64).map(|i| {
+        serde_json::json!({
+            "type": "incoming",
+            "invoice": "lnbc1standin",
+            "payment_hash": format!("{:064x}", i),
+            "preimage": PREIMAGE,
+            "amount": 21000,
+            "fees_paid": 0,
+            "created_at": now - i,
+            "settled_at": now,
+        })
+    }));
+    watcher.await.unwrap();
+
+    assert_eq!(events.lock().unwrap().len(), 60);
+    let listings = relay.requests.lock().unwrap().iter().filter(|m| *m == "list_transactions").count();
+    assert!(listings < 10, "polled {} times", listings);
+}
+
+#[tokio::test]
+async fn test_subscribe_all_from_notifications() {
+    use crate::types::{AccountEvent, AccountEventType, SubscribeAllCallback, SubscribeAllParams};
+
+    struct Recorder(Mutex<Vec<AccountEvent>>);
+    impl SubscribeAllCallback for Recorder {
+        fn event(&self, event: AccountEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+        fn error(&self, _reason: String) {}
+    }
+
+    let (relay, config) = WalletStandIn::start(&["payment_received", "payment_sent"]).await;
+    let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+    let recorded = recorder.clone();
+    let feed = tokio::spawn(async move {
+        crate::nwc::api::subscribe_all(config, SubscribeAllParams::default(), recorded.as_ref()).await
+    });
+
+    relay.wait_for_listener(None).await;
+    relay.notify("payment_received", PAYMENT_HASH);
+    relay.notify("payment_sent", &"11".repeat(32));
+    for _ in 0..100 {
+        if recorder.0.lock().unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    feed.abort();
+
+    let events = recorder.0.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event_type, AccountEventType::InvoiceSettled);
+    assert_eq!(events[1].event_type, AccountEventType::PaymentSent);
+    assert_eq!(events[0].transaction.payment_hash, PAYMENT_HASH);
+    assert!(events[0].resume_index.parse::<i64>().is_ok());
+    // no transaction listing without a resume index, notifications only
+    assert!(!relay.requests.lock().unwrap().iter().any(|m| m == "list_transactions"));
+}
+
+#[tokio::test]
+async fn test_account_events_stream() {
+    use crate::{LightningNodeEvents, NodeEvent};
+
+    let (relay, config) = WalletStandIn::start(&["payment_received", "payment_sent"]).await;
+    let node = crate::nwc::NwcNode::new(config);
+    let mut events = Box::pin(node.account_events(OnAccountEventParams {
+        polling_delay_sec: 60,
+        max_polling_sec: 10,
+    }));
+
+    relay.wait_for_listener(None).await;
+    relay.notify("payment_sent", &"11".repeat(32));
+    let event = tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap();
+    assert!(matches!(event, Some(NodeEvent::PaymentSent(txn)) if txn.payment_hash == "11".repeat(32)));
+
+    // dropping the stream stops the watcher and disconnects from the relay
+    drop(events);
+    for _ in 0..50 {
+        let subscribed = relay.subscriptions.lock().unwrap().iter().any(|(_, filter, _)| {
+            filter.kinds.as_ref().is_some_and(|kinds| kinds.contains(&Kind::WalletConnectNotification))
+        });
+        if !subscribed {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("notification subscription outlived the stream");
+}
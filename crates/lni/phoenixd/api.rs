@@ -1,21 +1,25 @@
 use super::types::{
     Bolt11Req, Bolt11Resp, Bolt12Req, InfoResponse, InvoiceResponse, OutgoingPaymentResponse,
-    PayResponse, PhoenixPayInvoiceResp,
+    PayResponse, PhoenixPayFailure, PhoenixPayInvoiceResp, WebsocketEvent,
 };
 use super::PhoenixdConfig;
 use crate::ListTransactionsParams;
 use crate::{
-    phoenixd::types::GetBalanceResponse, ApiError, CreateOfferParams, InvoiceType, NodeInfo, Offer, OnInvoiceEventCallback,
-    OnInvoiceEventParams, PayInvoiceParams, PayInvoiceResponse, Transaction,
+    phoenixd::types::GetBalanceResponse, retry_after_secs, ApiError, Channel, CloseChannelRequest, CloseChannelResponse, DecodedPaymentRequest, CreateOfferParams, Direction, InvoiceType, NodeCapabilities, NodeInfo, Offer,
+    OnInvoiceEventCallback, OnInvoiceEventParams, OnchainBalanceResponse, OnchainTransaction, OpenChannelRequest, OpenChannelResponse,
+    OutgoingPayment,
+    PayInvoiceParams, PayInvoiceResponse, SendOnchainResponse, Transaction, TransactionPage, TransactionStatus,
+    UpdateChannelRequest,
 };
 use lightning_invoice::Bolt11Invoice;
+use serde::{Deserialize, Serialize};
 use serde_urlencoded;
 use std::str::FromStr;
 use std::time::Duration;
 use tokio::time::sleep;
+use crate::websocket;
 
 // TODO
-// list_channels
 // get_balance
 
 // https://phoenix.acinq.co/server/api
@@ -55,6 +59,26 @@ fn client(config: &PhoenixdConfig) -> reqwest::Client {
     client_builder.build().unwrap_or_else(|_| reqwest::Client::new())
 }
 
+// phoenixd answers errors with a plain text body, so only the status and message are available
+async fn error_from_response(response: reqwest::Response) -> ApiError {
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    ApiError::from_http(status, retry_after, body.trim().to_string())
+}
+
+// Failed payments come back as a 200 with a `reason`, anything else unparseable is a Json error
+fn pay_error(response_text: &str, parse_error: serde_json::Error) -> ApiError {
+    match serde_json::from_str::<PhoenixPayFailure>(response_text) {
+        Ok(failure) => ApiError::from_message(failure.reason.clone()).unwrap_or(ApiError::Api {
+            reason: format!("Payment failed: {}", failure.reason),
+        }),
+        Err(_) => ApiError::Json {
+            reason: format!("Failed to parse pay response: {} {}", parse_error, response_text),
+        },
+    }
+}
+
 pub async fn get_info(config: PhoenixdConfig) -> Result<NodeInfo, ApiError> {
     let info_url = format!("{}/getinfo", config.url);
     let client = client(&config);
@@ -64,9 +88,10 @@ pub async fn get_info(config: PhoenixdConfig) -> Result<NodeInfo, ApiError> {
         .basic_auth("", Some(config.password.clone()))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "get_info"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     let response_text = response.text().await.map_err(|e| ApiError::Http {
         reason: e.to_string(),
     })?;
@@ -79,9 +104,10 @@ pub async fn get_info(config: PhoenixdConfig) -> Result<NodeInfo, ApiError> {
         .basic_auth("", Some(config.password.clone()))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "get_info"))?;
+    if !balance_response.status().is_success() {
+        return Err(error_from_response(balance_response).await);
+    }
     let balance_response_text = balance_response
         .text()
         .await
@@ -138,9 +164,10 @@ pub async fn create_invoice(
                 .form(&bolt11_req)
                 .send()
                 .await
-                .map_err(|e| ApiError::Http {
-                    reason: e.to_string(),
-                })?;
+                .map_err(|e| ApiError::from_reqwest(e, "create_invoice"))?;
+            if !response.status().is_success() {
+                return Err(error_from_response(response).await);
+            }
 
             // println!("Status: {}", response.status());
 
@@ -157,6 +184,8 @@ pub async fn create_invoice(
 
             Ok(Transaction {
                 type_: "incoming".to_string(),
+                status: TransactionStatus::Pending,
+                direction: Direction::Incoming,
                 invoice: bolt11_resp.serialized,
                 preimage: "".to_string(),
                 payment_hash: bolt11_resp.payment_hash,
@@ -185,9 +214,10 @@ pub async fn create_invoice(
                 .form(&bolt12_req)
                 .send()
                 .await
-                .map_err(|e| ApiError::Http {
-                    reason: e.to_string(),
-                })?;
+                .map_err(|e| ApiError::from_reqwest(e, "create_invoice"))?;
+            if !response.status().is_success() {
+                return Err(error_from_response(response).await);
+            }
 
             // println!("Status: {}", response.status());
 
@@ -199,6 +229,8 @@ pub async fn create_invoice(
 
             Ok(Transaction {
                 type_: "incoming".to_string(),
+                status: TransactionStatus::Pending,
+                direction: Direction::Incoming,
                 invoice: invoice_str.to_string(),
                 preimage: "".to_string(),
                 payment_hash: "".to_string(),
@@ -216,9 +248,33 @@ pub async fn create_invoice(
     }
 }
 
+// /payinvoice takes an amount and nothing else
+pub(crate) const PAY_PARAMS: crate::PayParamSupport = crate::PayParamSupport {
+    amount: true,
+    ..crate::PayParamSupport::NONE
+};
+
 pub async fn pay_invoice(
     config: PhoenixdConfig,
     invoice_params: PayInvoiceParams,
+) -> Result<PayInvoiceResponse, ApiError> {
+    crate::check_pay_params(&invoice_params, "Phoenixd", PAY_PARAMS)?;
+    if invoice_params.wait == Some(false) {
+        // /payinvoice only answers once the payment is final
+        let invoice = invoice_params.invoice.clone();
+        let timeout_seconds = invoice_params.timeout_seconds;
+        let lookup_config = config.clone();
+        return crate::dispatch_payment(&invoice, timeout_seconds, send_payment(config, invoice_params), |payment_hash| {
+            lookup_payment(lookup_config.clone(), payment_hash)
+        })
+        .await;
+    }
+    send_payment(config, invoice_params).await
+}
+
+async fn send_payment(
+    config: PhoenixdConfig,
+    invoice_params: PayInvoiceParams,
 ) -> Result<PayInvoiceResponse, ApiError> {
     let client = client(&config);
     let req_url = format!("{}/payinvoice", config.url);
@@ -236,29 +292,28 @@ pub async fn pay_invoice(
         .form(&params)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "pay_invoice"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     // println!("Status: {}", response.status());
     let response_text = response.text().await.map_err(|e| ApiError::Http {
         reason: e.to_string(),
     })?;
     let pay_invoice_resp: PhoenixPayInvoiceResp =
-        serde_json::from_str(&response_text).map_err(|e| ApiError::Json {
-            reason: format!("Failed to parse pay_invoice response: {}", e),
-        })?;
+        serde_json::from_str(&response_text).map_err(|e| pay_error(&response_text, e))?;
 
     Ok(PayInvoiceResponse {
         payment_hash: pay_invoice_resp.payment_hash,
         preimage: pay_invoice_resp.preimage,
         fee_msats: pay_invoice_resp.routing_fee_sat * 1000,
+        status: TransactionStatus::Settled,
     })
 }
 
-// TODO decode - bolt11 invoice (lnbc) bolt12 invoice (lni) or bolt12 offer (lno)
-// Not supported by Phoenixd api so maybe we can use ldk to decode the bolt12 offer?
-pub fn decode(str: String) -> Result<String, ApiError> {
-    Ok(str)
+// Not supported by the Phoenixd api, decoded locally instead
+pub fn decode(str: String) -> Result<DecodedPaymentRequest, ApiError> {
+    crate::decode_payment_request(str)
 }
 
 // Create a new BOLT12 offer
@@ -282,9 +337,10 @@ pub async fn create_offer(
         .form(&bolt12_req)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "create_offer"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
 
     let offer_str = response.text().await.map_err(|e| ApiError::Http {
         reason: e.to_string(),
@@ -310,9 +366,10 @@ pub async fn get_offer(config: PhoenixdConfig) -> Result<Offer, ApiError> {
         .basic_auth("", Some(config.password.clone()))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "get_offer"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     let offer_str = response.text().await.map_err(|e| ApiError::Http {
         reason: e.to_string(),
     })?;
@@ -345,25 +402,23 @@ pub async fn pay_offer(
         ])
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "pay_offer"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     let response_text = response.text().await.map_err(|e| ApiError::Http {
         reason: e.to_string(),
     })?;
     let response_text = response_text.as_str();
     let pay_resp: PayResponse = match serde_json::from_str(&response_text) {
         Ok(resp) => resp,
-        Err(_e) => {
-            return Err(ApiError::Json {
-                reason: response_text.to_string(),
-            })
-        }
+        Err(e) => return Err(pay_error(response_text, e)),
     };
     Ok(PayInvoiceResponse {
         payment_hash: pay_resp.payment_hash,
         preimage: pay_resp.preimage,
         fee_msats: pay_resp.routing_fee_sat * 1000,
+        status: TransactionStatus::Settled,
     })
 }
 
@@ -389,9 +444,10 @@ pub async fn lookup_invoice(
         .basic_auth("", Some(config.password.clone()))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "lookup_invoice"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
     let response_text = response.text().await.map_err(|e| ApiError::Http {
         reason: e.to_string(),
     })?;
@@ -405,23 +461,35 @@ pub async fn lookup_invoice(
             0
         };
 
+    let decoded_invoice = inv
+        .invoice
+        .as_ref()
+        .and_then(|invoice_str| Bolt11Invoice::from_str(invoice_str).ok());
+
     // Determine the amount: use received_sat if paid, otherwise decode from invoice
     let amount_msats = if inv.received_sat > 0 {
         inv.received_sat * 1000
-    } else if let Some(invoice_str) = &inv.invoice {
-        // Try to decode the invoice to get the amount
-        match Bolt11Invoice::from_str(invoice_str) {
-            Ok(decoded_invoice) => {
-                decoded_invoice.amount_milli_satoshis().unwrap_or(0) as i64
-            }
-            Err(_) => 0
-        }
+    } else if let Some(decoded_invoice) = &decoded_invoice {
+        decoded_invoice.amount_milli_satoshis().unwrap_or(0) as i64
     } else {
         0
     };
 
+    let status = if inv.is_paid {
+        TransactionStatus::Settled
+    } else {
+        let invoice_expires_at = decoded_invoice
+            .as_ref()
+            .and_then(|i| i.expires_at())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        TransactionStatus::unpaid(invoice_expires_at)
+    };
+
     let txn = Transaction {
         type_: "incoming".to_string(),
+        status,
+        direction: Direction::Incoming,
         invoice: inv.invoice.unwrap_or_default(),
         preimage: inv.preimage,
         payment_hash: inv.payment_hash,
@@ -442,161 +510,244 @@ pub async fn list_transactions(
     config: PhoenixdConfig,
     params: ListTransactionsParams,
 ) -> Result<Vec<Transaction>, ApiError> {
-    let client = client(&config);
-
-    // 1) Build query for incoming transactions
-    let mut incoming_params = vec![];
+    // Both lists take the same query
+    let mut query = vec![];
     if params.from != 0 {
-        incoming_params.push(("from", (params.from * 1000).to_string()));
+        query.push(("from", (params.from * 1000).to_string()));
     }
     if params.limit != 0 {
-        incoming_params.push(("limit", params.limit.to_string()));
+        query.push(("limit", params.limit.to_string()));
     }
-    incoming_params.push(("all", "false".to_string())); // do not return payments that have failed
+    query.push(("all", "false".to_string())); // do not return payments that have failed
+
+    let mut transactions = fetch_incoming(&config, &query).await?;
+    transactions.extend(fetch_outgoing(&config, &query).await?);
+
+    transactions.retain(|txn| {
+        // Only include if search matches payment_hash or payer_note
+        let search_match = params.search.as_ref().is_none_or(|search| {
+            txn.payment_hash == *search || txn.payer_note.as_ref() == Some(search)
+        });
+        // Outgoing payments without a hash yet can't be ruled out
+        let hash_match = params.payment_hash.as_ref().is_none_or(|payment_hash| {
+            txn.payment_hash == *payment_hash
+                || (txn.direction == Direction::Outgoing && txn.payment_hash.is_empty())
+        });
+        search_match && hash_match
+    });
+
+    // Sort by created date descending
+    transactions.sort_by_key(|t| std::cmp::Reverse(t.created_at));
 
-    // Build the final incoming URL with query
-    let incoming_query = serde_urlencoded::to_string(&incoming_params).unwrap();
+    Ok(transactions)
+}
+
+// Phoenixd pages with offsets, which would shift as payments come in. The first page
+// pins `until` (millis) so later pages look at the same window.
+#[derive(Debug, Serialize, Deserialize)]
+struct PhoenixdCursor {
+    until: i64,
+    incoming_offset: i64,
+    outgoing_offset: i64,
+    incoming_done: bool,
+    outgoing_done: bool,
+}
+
+pub async fn list_transactions_page(
+    config: PhoenixdConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    crate::search_page(params, |params| transactions_page(config.clone(), params)).await
+}
+
+async fn transactions_page(
+    config: PhoenixdConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    let limit = crate::page_limit(params.limit)?;
+    let mut cursor: PhoenixdCursor = match params.cursor.as_deref() {
+        Some(cursor) => crate::decode_cursor(cursor)?,
+        None => PhoenixdCursor {
+            until: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or(0),
+            incoming_offset: 0,
+            outgoing_offset: 0,
+            incoming_done: false,
+            outgoing_done: false,
+        },
+    };
+    let query = |offset: i64| {
+        vec![
+            ("to", cursor.until.to_string()),
+            ("offset", offset.to_string()),
+            ("limit", limit.to_string()),
+            ("all", "false".to_string()),
+        ]
+    };
+
+    let mut incoming = if cursor.incoming_done {
+        vec![]
+    } else {
+        fetch_incoming(&config, &query(cursor.incoming_offset)).await?
+    };
+    let mut outgoing = if cursor.outgoing_done {
+        vec![]
+    } else {
+        fetch_outgoing(&config, &query(cursor.outgoing_offset)).await?
+    };
+    incoming.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+    outgoing.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+    let (incoming_len, outgoing_len) = (incoming.len(), outgoing.len());
+
+    let (transactions, taken) = crate::merge_newest_first(vec![incoming, outgoing], limit);
+    cursor.incoming_offset += taken[0] as i64;
+    cursor.outgoing_offset += taken[1] as i64;
+    cursor.incoming_done |= incoming_len < limit && taken[0] == incoming_len;
+    cursor.outgoing_done |= outgoing_len < limit && taken[1] == outgoing_len;
+
+    Ok(TransactionPage {
+        transactions,
+        next_cursor: if cursor.incoming_done && cursor.outgoing_done {
+            None
+        } else {
+            Some(crate::encode_cursor(&cursor))
+        },
+    })
+}
+
+async fn fetch_incoming(config: &PhoenixdConfig, query: &[(&str, String)]) -> Result<Vec<Transaction>, ApiError> {
+    let client = client(config);
+    let incoming_query = serde_urlencoded::to_string(query).unwrap_or_default();
     let incoming_url = format!("{}/payments/incoming?{}", config.url, incoming_query);
 
-    // Fetch incoming transactions
     let incoming_resp = client
         .get(&incoming_url)
         .basic_auth("", Some(config.password.clone()))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "list_transactions"))?;
+    if !incoming_resp.status().is_success() {
+        return Err(error_from_response(incoming_resp).await);
+    }
     let incoming_text = incoming_resp
         .text()
         .await
         .map_err(|e| ApiError::Http {
             reason: e.to_string(),
         })?;
-    let incoming_text = incoming_text.as_str();
-    let incoming_payments: Vec<InvoiceResponse> = serde_json::from_str(&incoming_text).unwrap();
-
-    let mut transactions: Vec<Transaction> = vec![];
+    let incoming_payments: Vec<InvoiceResponse> = serde_json::from_str(&incoming_text)?;
 
-    for inc_payment in incoming_payments {
-        let settled_at = if inc_payment.completed_at.unwrap_or(0) != 0 && inc_payment.is_paid {
-            Some((inc_payment.completed_at.unwrap_or(0) / 1000) as i64)
-        } else {
-            None
-        };
-        if let Some(ref search) = params.search {
-            let hash_match = inc_payment.payment_hash == *search;
-            let note_match = inc_payment
-                .payer_note
-                .as_ref()
-                .map_or(false, |note| note == search);
-            // Only include if search matches payment_hash or payer_note
-            if !(hash_match || note_match) {
-                continue;
+    let transactions = incoming_payments
+        .into_iter()
+        .map(|inc_payment| {
+            let settled_at = if inc_payment.completed_at.unwrap_or(0) != 0 && inc_payment.is_paid {
+                Some(inc_payment.completed_at.unwrap_or(0) / 1000)
+            } else {
+                None
+            };
+            Transaction {
+                type_: "incoming".to_string(),
+                status: if inc_payment.is_paid {
+                    TransactionStatus::Settled
+                } else {
+                    TransactionStatus::Pending
+                },
+                direction: Direction::Incoming,
+                invoice: "".to_string(), // TODO
+                preimage: inc_payment.preimage,
+                payment_hash: inc_payment.payment_hash,
+                amount_msats: inc_payment.received_sat * 1000,
+                fees_paid: inc_payment.fees * 1000,
+                created_at: inc_payment.created_at / 1000,
+                expires_at: 0, // TODO
+                settled_at: settled_at.unwrap_or(0),
+                description: "".to_string(),
+                description_hash: "".to_string(),
+                payer_note: Some(inc_payment.payer_note.unwrap_or("".to_string())),
+                external_id: Some(inc_payment.external_id.unwrap_or("".to_string())),
             }
-        }
-        if params.payment_hash.is_some()
-            && inc_payment.payment_hash != params.payment_hash.clone().unwrap()
-        {
-            continue;
-        }
-        transactions.push(Transaction {
-            type_: "incoming".to_string(),
-            invoice: "".to_string(), // TODO
-            preimage: inc_payment.preimage,
-            payment_hash: inc_payment.payment_hash,
-            amount_msats: inc_payment.received_sat * 1000,
-            fees_paid: inc_payment.fees * 1000,
-            created_at: (inc_payment.created_at / 1000) as i64,
-            expires_at: 0, // TODO
-            settled_at: settled_at.unwrap_or(0),
-            description: "".to_string(),
-            description_hash: "".to_string(),
-            payer_note: Some(inc_payment.payer_note.unwrap_or("".to_string())),
-            external_id: Some(inc_payment.external_id.unwrap_or("".to_string())),
-        });
-    }
+        })
+        .collect();
+    Ok(transactions)
+}
 
-    // 2) Build query for outgoing transactions
-    let mut outgoing_params = vec![];
-    if params.from != 0 {
-        outgoing_params.push(("from", (params.from * 1000).to_string()));
-    }
-    if params.limit != 0 {
-        outgoing_params.push(("limit", params.limit.to_string()));
+pub async fn lookup_payment(config: PhoenixdConfig, payment_hash: String) -> Result<OutgoingPayment, ApiError> {
+    let url = format!("{}/payments/outgoingbyhash/{}", config.url, payment_hash);
+    let response = client(&config)
+        .get(&url)
+        .basic_auth("", Some(config.password.clone()))
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "lookup_payment"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
     }
-    outgoing_params.push(("all", "false".to_string())); // do not return payments that have failed
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let payment: OutgoingPaymentResponse = serde_json::from_str(&response_text)?;
+    Ok(OutgoingPayment::from_transaction(outgoing_transaction(payment), None))
+}
 
-    // Build the final outgoing URL with query
-    let outgoing_query = serde_urlencoded::to_string(&outgoing_params).unwrap();
+async fn fetch_outgoing(config: &PhoenixdConfig, query: &[(&str, String)]) -> Result<Vec<Transaction>, ApiError> {
+    let client = client(config);
+    let outgoing_query = serde_urlencoded::to_string(query).unwrap_or_default();
     let outgoing_url = format!("{}/payments/outgoing?{}", config.url, outgoing_query);
 
-    // Fetch outgoing transactions
     let outgoing_resp = client
         .get(&outgoing_url)
         .basic_auth("", Some(config.password.clone()))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "list_transactions"))?;
+    if !outgoing_resp.status().is_success() {
+        return Err(error_from_response(outgoing_resp).await);
+    }
     let outgoing_text = outgoing_resp
         .text()
         .await
         .map_err(|e| ApiError::Http {
             reason: e.to_string(),
         })?;
-    let outgoing_text = outgoing_text.as_str();
-    let outgoing_payments: Vec<OutgoingPaymentResponse> =
-        serde_json::from_str(&outgoing_text).unwrap();
-
-    // Convert outgoing payments into "outgoing" Transaction
-    for payment in outgoing_payments {
-        let settled_at = if payment.completed_at != 0 {
-            Some((payment.completed_at / 1000) as i64)
-        } else {
-            None
-        };
-        if let Some(ref search) = params.search {
-            let hash_match = payment.payment_hash == Some(search.clone());
-            let note_match = payment
-                .payer_note
-                .as_ref()
-                .map_or(false, |note| note == search);
-            // Only include if search matches payment_hash or payer_note
-            if !(hash_match || note_match) {
-                continue;
-            }
-        }
-        if params.payment_hash.is_some()
-            && payment.payment_hash.is_some()
-            && payment.payment_hash.clone().unwrap() != params.payment_hash.clone().unwrap()
-        {
-            continue;
-        }
-        transactions.push(Transaction {
-            type_: "outgoing".to_string(),
-            invoice: "".to_string(), // TODO
-            preimage: payment.preimage.unwrap_or("".to_string()),
-            payment_hash: payment.payment_hash.unwrap_or("".to_string()),
-            amount_msats: payment.sent * 1000,
-            fees_paid: payment.fees * 1000,
-            created_at: (payment.created_at / 1000) as i64,
-            expires_at: 0, // TODO
-            settled_at: settled_at.unwrap_or(0),
-            description: "".to_string(),
-            description_hash: "".to_string(),
-            payer_note: Some(payment.payer_note.unwrap_or("".to_string())),
-            external_id: Some(payment.external_id.unwrap_or("".to_string())),
-        });
-    }
-
-    // Sort by created date descending
-    transactions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    let outgoing_payments: Vec<OutgoingPaymentResponse> = serde_json::from_str(&outgoing_text)?;
 
+    let transactions = outgoing_payments.into_iter().map(outgoing_transaction).collect();
     Ok(transactions)
 }
 
+fn outgoing_transaction(payment: OutgoingPaymentResponse) -> Transaction {
+    let settled_at = if payment.completed_at != 0 {
+        Some(payment.completed_at / 1000)
+    } else {
+        None
+    };
+    // completedAt is also set when a payment fails, isPaid tells them apart
+    let outgoing_status = match (payment.completed_at != 0, payment.is_paid) {
+        (true, true) => TransactionStatus::Settled,
+        (true, false) => TransactionStatus::Failed,
+        _ => TransactionStatus::Pending,
+    };
+    Transaction {
+        type_: "outgoing".to_string(),
+        status: outgoing_status,
+        direction: Direction::Outgoing,
+        invoice: "".to_string(), // TODO
+        preimage: payment.preimage.unwrap_or("".to_string()),
+        payment_hash: payment.payment_hash.unwrap_or("".to_string()),
+        amount_msats: payment.sent * 1000,
+        fees_paid: payment.fees * 1000,
+        created_at: payment.created_at / 1000,
+        expires_at: 0, // TODO
+        settled_at: settled_at.unwrap_or(0),
+        description: "".to_string(),
+        description_hash: "".to_string(),
+        payer_note: Some(payment.payer_note.unwrap_or("".to_string())),
+        external_id: Some(payment.external_id.unwrap_or("".to_string())),
+    }
+}
+
 // Core logic shared by both implementations
 pub async fn poll_invoice_events<F>(
     config: PhoenixdConfig,
@@ -622,6 +773,7 @@ pub async fn poll_invoice_events<F>(
                 search: params.search.clone(),
                 created_after: None,
                 created_before: None,
+                cursor: None,
             },
         ).await {
             Ok(transactions) => {
@@ -629,7 +781,7 @@ pub async fn poll_invoice_events<F>(
                     ("pending".to_string(), None)
                 } else {
                     let transaction = transactions[0].clone();
-                    if transaction.settled_at > 0 {
+                    if transaction.status == TransactionStatus::Settled {
                         ("settled".to_string(), Some(transaction))
                     } else {
                         ("pending".to_string(), Some(transaction))
@@ -657,15 +809,342 @@ pub async fn poll_invoice_events<F>(
     }
 }
 
+// Settlements are pushed on phoenixd's /websocket feed as payment_received messages. The
+// socket is reopened if it drops; polling takes over when it can't be opened at all, which
+// includes nodes reached through a SOCKS5 proxy.
+pub async fn invoice_events<F>(config: PhoenixdConfig, params: OnInvoiceEventParams, mut callback: F)
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(params.max_polling_sec.max(0) as u64);
+
+    let proxied = config.socks5_proxy.as_ref().is_some_and(|p| !p.is_empty());
+    if !proxied && stream_invoice_events(&config, &params, deadline, &mut callback).await.is_ok() {
+        return;
+    }
+
+    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+    let params = OnInvoiceEventParams {
+        max_polling_sec: remaining.as_secs() as i64,
+        ..params
+    };
+    poll_invoice_events(config, params, callback).await;
+}
+
+// Ok once a final status was reported, Err hands over to polling
+async fn stream_invoice_events<F>(
+    config: &PhoenixdConfig,
+    params: &OnInvoiceEventParams,
+    deadline: tokio::time::Instant,
+    callback: &mut F,
+) -> Result<(), ApiError>
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let url = format!("{}/websocket", websocket::ws_url(&config.url));
+    let auth = format!("Basic {}", base64::encode(format!(":{}", config.password)));
+    let mut connected = false;
+    let mut attempts = 0;
+    loop {
+        let mut ws = match websocket::connect(&url, &[("authorization", auth.clone())]).await {
+            Ok(ws) => ws,
+            Err(e) => {
+                attempts += 1;
+                if !connected || attempts > websocket::MAX_RECONNECTS {
+                    return Err(e);
+                }
+                if tokio::time::Instant::now() + websocket::reconnect_delay(attempts) >= deadline {
+                    callback("failure".to_string(), None);
+                    return Ok(());
+                }
+                sleep(websocket::reconnect_delay(attempts)).await;
+                continue;
+            }
+        };
+        connected = true;
+        attempts = 0;
+
+        // Checked on every (re)connect so a payment received while disconnected isn't missed
+        if report_invoice_state(config, params, callback).await? {
+            return Ok(());
+        }
+        loop {
+            let text = match tokio::time::timeout_at(deadline, websocket::next_text(&mut ws)).await {
+                Err(_) => {
+                    callback("failure".to_string(), None);
+                    return Ok(());
+                }
+                Ok(Ok(Some(text))) => text,
+                Ok(_) => break,
+            };
+            let event: WebsocketEvent = match serde_json::from_str(&text) {
+                Ok(event) => event,
+                Err(_) => continue,
+            };
+            if event.type_ != "payment_received" {
+                continue;
+            }
+            if let (Some(wanted), Some(got)) = (&params.payment_hash, &event.payment_hash) {
+                if wanted != got {
+                    continue;
+                }
+            }
+            if report_invoice_state(config, params, callback).await? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// Reports success (true) once the invoice is settled, pending otherwise
+async fn report_invoice_state<F>(
+    config: &PhoenixdConfig,
+    params: &OnInvoiceEventParams,
+    callback: &mut F,
+) -> Result<bool, ApiError>
+where
+    F: FnMut(String, Option<Transaction>),
+{
+    let transaction = list_transactions(
+        config.clone(),
+        ListTransactionsParams {
+            from: 0,
+            limit: 2500, // TODO remove hardcoded limit
+            payment_hash: params.payment_hash.clone(),
+            search: params.search.clone(),
+            created_after: None,
+            created_before: None,
+            cursor: None,
+        },
+    )
+    .await?
+    .into_iter()
+    .next();
+    match transaction {
+        Some(txn) if txn.status == TransactionStatus::Settled => {
+            callback("success".to_string(), Some(txn));
+            Ok(true)
+        }
+        txn => {
+            callback("pending".to_string(), txn);
+            Ok(false)
+        }
+    }
+}
+
 pub async fn on_invoice_events(
     config: PhoenixdConfig,
     params: OnInvoiceEventParams,
     callback: std::sync::Arc<dyn OnInvoiceEventCallback>,
 ) {
-    poll_invoice_events(config, params, move |status, tx| match status.as_str() {
+    invoice_events(config, params, move |status, tx| match status.as_str() {
         "success" => callback.success(tx),
         "pending" => callback.pending(tx),
         "failure" => callback.failure(tx),
         _ => {}
     }).await;
 }
+
+// Channels come from /getinfo, phoenixd only ever has channels with the ACINQ LSP
+pub async fn list_channels(config: PhoenixdConfig) -> Result<Vec<Channel>, ApiError> {
+    let info_url = format!("{}/getinfo", config.url);
+    let client = client(&config);
+    let response = client
+        .get(&info_url)
+        .basic_auth("", Some(config.password.clone()))
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "list_channels"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let raw: serde_json::Value = serde_json::from_str(&response_text)?;
+    let info: InfoResponse = serde_json::from_value(raw.clone())?;
+    let raw_channels = raw["channels"].as_array().cloned().unwrap_or_default();
+
+    Ok(info
+        .channels
+        .into_iter()
+        .zip(raw_channels)
+        .map(|(c, raw)| Channel {
+            local_balance: c.balance_sat * 1000,
+            local_spendable_balance: c.balance_sat * 1000,
+            remote_balance: c.inbound_liquidity_sat * 1000,
+            capacity: c.capacity_sat * 1000,
+            id: c.channel_id,
+            short_channel_id: None,
+            remote_pubkey: "".to_string(),
+            funding_tx_id: c.funding_tx_id,
+            funding_tx_vout: 0,
+            active: c.state == "Normal",
+            public: false,
+            internal_channel: raw.to_string(),
+            confirmations: 0,
+            confirmations_required: 0,
+            forwarding_fee_base_msat: 0,
+            unspendable_punishment_reserve: 0,
+            counterparty_unspendable_punishment_reserve: 0,
+            error: "".to_string(),
+            is_outbound: false,
+        })
+        .collect())
+}
+
+pub async fn open_channel(
+    _config: PhoenixdConfig,
+    _params: OpenChannelRequest,
+) -> Result<OpenChannelResponse, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Phoenixd opens channels automatically with its LSP".to_string(),
+    })
+}
+
+// Mutual close only, phoenixd needs the address and feerate to build the closing tx
+pub async fn close_channel(
+    config: PhoenixdConfig,
+    params: CloseChannelRequest,
+) -> Result<CloseChannelResponse, ApiError> {
+    if params.force {
+        return Err(ApiError::NotSupported {
+            reason: "Phoenixd does not support force closing channels".to_string(),
+        });
+    }
+    let address = params.address.clone().ok_or_else(|| {
+        ApiError::InvalidInput("address is required to close a Phoenixd channel".to_string())
+    })?;
+    let feerate = params.sat_per_vbyte.ok_or_else(|| {
+        ApiError::InvalidInput("sat_per_vbyte is required to close a Phoenixd channel".to_string())
+    })?;
+    let client = client(&config);
+    let req_url = format!("{}/closechannel", config.url);
+    let form = vec![
+        ("channelId", params.channel_id.clone()),
+        ("address", address),
+        ("feerateSatByte", feerate.to_string()),
+    ];
+    let response = client
+        .post(&req_url)
+        .basic_auth("", Some(config.password.clone()))
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "close_channel"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    // responds with the closing txid as plain text
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let txid = response_text.trim().to_string();
+    Ok(CloseChannelResponse {
+        closing_tx_id: if txid.is_empty() { None } else { Some(txid) },
+    })
+}
+
+pub async fn update_channel_policy(
+    _config: PhoenixdConfig,
+    _params: UpdateChannelRequest,
+) -> Result<(), ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Phoenixd does not forward payments, channel policy is not configurable".to_string(),
+    })
+}
+
+// Phoenixd has no onchain wallet of its own, funds are spliced in and out of the channel
+pub async fn get_onchain_balance(_config: PhoenixdConfig) -> Result<OnchainBalanceResponse, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Phoenixd does not keep an onchain balance".to_string(),
+    })
+}
+
+// Address of the wallet that receives funds when channels are closed
+pub async fn new_onchain_address(config: PhoenixdConfig) -> Result<String, ApiError> {
+    let req_url = format!("{}/getfinaladdress", config.url);
+    let client = client(&config);
+    let response = client
+        .get(&req_url)
+        .basic_auth("", Some(config.password.clone()))
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "new_onchain_address"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let address = response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    Ok(address.trim().to_string())
+}
+
+// Splices funds out of the channel, phoenixd needs an explicit feerate
+pub async fn send_onchain(
+    config: PhoenixdConfig,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+) -> Result<SendOnchainResponse, ApiError> {
+    let feerate = sat_per_vbyte.ok_or_else(|| {
+        ApiError::InvalidInput("sat_per_vbyte is required to send onchain with Phoenixd".to_string())
+    })?;
+    let client = client(&config);
+    let req_url = format!("{}/sendtoaddress", config.url);
+    let form = vec![
+        ("amountSat", amount_sats.to_string()),
+        ("address", address),
+        ("feerateSatByte", feerate.to_string()),
+    ];
+    let response = client
+        .post(&req_url)
+        .basic_auth("", Some(config.password.clone()))
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "send_onchain"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    // responds with the splice txid as plain text
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let txid = response_text.trim().to_string();
+    Ok(SendOnchainResponse {
+        txid: if txid.is_empty() { None } else { Some(txid) },
+        external_id: None,
+        fee_sats: None,
+    })
+}
+
+pub async fn list_onchain_transactions(
+    _config: PhoenixdConfig,
+) -> Result<Vec<OnchainTransaction>, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Phoenixd does not expose onchain transaction history".to_string(),
+    })
+}
+
+// Channels are managed by the LSP, only listing and closing is exposed
+pub async fn capabilities(_config: PhoenixdConfig) -> Result<NodeCapabilities, ApiError> {
+    Ok(NodeCapabilities {
+        bolt11_receive: true,
+        bolt11_send: true,
+        bolt12_receive: true,
+        bolt12_send: true,
+        keysend: false,
+        onchain_receive: true,
+        onchain_send: true,
+        onchain_balance: false,
+        hold_invoices: false,
+        description_hash_invoices: false,
+        zero_amount_invoices: false,
+        lookup_invoice: true,
+        list_transactions: true,
+        channel_management: false,
+        native_event_streaming: true,
+        fiat: false,
+    })
+}
@@ -3,7 +3,10 @@ use napi_derive::napi;
 
 use crate::{
     phoenixd::api::*, ApiError, ListTransactionsParams, PayInvoiceParams, PayInvoiceResponse,
-    Transaction, CreateOfferParams
+    Transaction, CreateOfferParams, Channel, CloseChannelRequest, CloseChannelResponse,
+    OpenChannelRequest, OpenChannelResponse, UpdateChannelRequest,
+    OnchainBalanceResponse, OnchainTransaction, SendOnchainResponse, PayKeysendResponse, TLVRecord,
+    NodeCapabilities,
 };
 #[cfg(not(feature = "uniffi"))]
 use crate::LightningNode;
@@ -58,6 +61,10 @@ impl PhoenixdNode {
         crate::phoenixd::api::get_info(self.config.clone()).await
     }
 
+    pub async fn capabilities(&self) -> Result<NodeCapabilities, ApiError> {
+        crate::phoenixd::api::capabilities(self.config.clone()).await
+    }
+
     pub async fn create_invoice(&self, params: CreateInvoiceParams) -> Result<Transaction, ApiError> {
         create_invoice(
             self.config.clone(),
@@ -73,6 +80,41 @@ impl PhoenixdNode {
         pay_invoice(self.config.clone(), params).await
     }
 
+    pub async fn estimate_payment_fee(
+        &self,
+        _invoice: String,
+        _amount_msats: Option<i64>,
+    ) -> Result<crate::PaymentFeeEstimate, ApiError> {
+        Err(ApiError::NotSupported { reason: "Phoenixd has no fee quote, its trampoline fee is only known once paid".to_string() })
+    }
+
+    pub async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: crate::PayOptions,
+    ) -> Result<crate::PayResponse, ApiError> {
+        crate::lnurl::pay(self, destination, amount_msats, options).await
+    }
+
+    pub async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: crate::ZapRequestParams,
+    ) -> Result<crate::ZapResponse, ApiError> {
+        crate::lnurl::zap(self, destination, amount_msats, params).await
+    }
+
+    pub async fn pay_keysend(
+        &self,
+        _destination_pubkey: String,
+        _amount_msats: i64,
+        _custom_records: Vec<TLVRecord>,
+    ) -> Result<PayKeysendResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Phoenixd does not support keysend payments".to_string() })
+    }
+
     pub async fn create_offer(&self, params: CreateOfferParams) -> Result<Offer, ApiError> {
         crate::phoenixd::api::create_offer(self.config.clone(), params).await
     }
@@ -104,6 +146,10 @@ impl PhoenixdNode {
         ).await
     }
 
+    pub async fn lookup_payment(&self, payment_hash: String) -> Result<crate::OutgoingPayment, ApiError> {
+        crate::phoenixd::api::lookup_payment(self.config.clone(), payment_hash).await
+    }
+
     pub async fn list_transactions(
         &self,
         params: ListTransactionsParams,
@@ -111,22 +157,95 @@ impl PhoenixdNode {
         crate::phoenixd::api::list_transactions(self.config.clone(), params).await
     }
 
-    pub async fn decode(&self, _str: String) -> Result<String, ApiError> {
-        Ok("".to_string())
+    pub async fn list_transactions_page(
+        &self,
+        params: ListTransactionsParams,
+    ) -> Result<crate::TransactionPage, ApiError> {
+        crate::phoenixd::api::list_transactions_page(self.config.clone(), params).await
     }
 
-    pub async fn on_invoice_events(
+    pub async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, ApiError> {
+        crate::phoenixd::api::decode(str)
+    }
+
+    pub fn on_invoice_events(
         &self,
         params: crate::types::OnInvoiceEventParams,
         callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-    ) {
-        crate::phoenixd::api::on_invoice_events(self.config.clone(), params, callback).await
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::invoice_events(callback, |callback| {
+            crate::phoenixd::api::on_invoice_events(config, params, callback)
+        })
+    }
+
+    pub fn on_payment_events(
+        &self,
+        params: crate::types::OnPaymentEventParams,
+        callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No payment feed, the payment is looked up every polling_delay_sec
+        let node = self.clone();
+        crate::Subscription::payment_events(callback, |callback| async move {
+            let payment_hash = params.payment_hash.clone();
+            crate::poll_payment_events(|| node.lookup_payment(payment_hash.clone()), params, callback.as_ref()).await
+        })
+    }
+
+    pub fn subscribe_all(
+        &self,
+        params: crate::types::SubscribeAllParams,
+        callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No account feed, changes are picked up by diffing list_transactions
+        let node = self.clone();
+        crate::Subscription::subscribe_all(callback, |callback| async move {
+            crate::events::poll_all(&node, params, callback.as_ref()).await
+        })
+    }
+
+    pub async fn list_channels(&self) -> Result<Vec<Channel>, ApiError> {
+        crate::phoenixd::api::list_channels(self.config.clone()).await
+    }
+
+    pub async fn open_channel(&self, params: OpenChannelRequest) -> Result<OpenChannelResponse, ApiError> {
+        crate::phoenixd::api::open_channel(self.config.clone(), params).await
+    }
+
+    pub async fn close_channel(&self, params: CloseChannelRequest) -> Result<CloseChannelResponse, ApiError> {
+        crate::phoenixd::api::close_channel(self.config.clone(), params).await
+    }
+
+    pub async fn update_channel_policy(&self, params: UpdateChannelRequest) -> Result<(), ApiError> {
+        crate::phoenixd::api::update_channel_policy(self.config.clone(), params).await
+    }
+
+    pub async fn get_onchain_balance(&self) -> Result<OnchainBalanceResponse, ApiError> {
+        crate::phoenixd::api::get_onchain_balance(self.config.clone()).await
+    }
+
+    pub async fn new_onchain_address(&self) -> Result<String, ApiError> {
+        crate::phoenixd::api::new_onchain_address(self.config.clone()).await
+    }
+
+    pub async fn send_onchain(
+        &self,
+        address: String,
+        amount_sats: i64,
+        sat_per_vbyte: Option<i64>,
+    ) -> Result<SendOnchainResponse, ApiError> {
+        crate::phoenixd::api::send_onchain(self.config.clone(), address, amount_sats, sat_per_vbyte).await
+    }
+
+    pub async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, ApiError> {
+        crate::phoenixd::api::list_onchain_transactions(self.config.clone()).await
     }
 }
 
 // Trait implementation for Rust consumers - uses the impl_lightning_node macro
 // Trait implementation for polymorphic access via Arc<dyn LightningNode>
 crate::impl_lightning_node!(PhoenixdNode);
+impl crate::LightningNodeEvents for PhoenixdNode {}
 
 #[cfg(test)]
 mod tests {
@@ -322,6 +441,7 @@ mod tests {
             search: None,
             created_after: None,
             created_before: None,
+            cursor: None,
         };
         match NODE.list_transactions(params).await {
             Ok(txns) => {
@@ -373,6 +493,24 @@ mod tests {
             ..Default::default()
         };
         let callback = OnInvoiceEventCallback {};
-        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).await;
+        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).wait().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_channels() {
+        match NODE.list_channels().await {
+            Ok(channels) => {
+                println!("channels: {:?}", channels);
+                for channel in channels.iter() {
+                    assert!(
+                        channel.local_balance <= channel.capacity,
+                        "Local balance should not exceed channel capacity"
+                    );
+                }
+            }
+            Err(e) => {
+                panic!("Failed to list channels: {:?}", e);
+            }
+        }
     }
 }
@@ -149,4 +149,20 @@ pub struct PhoenixPayInvoiceResp {
     pub payment_hash: String,
     #[serde(rename = "paymentPreimage")]
     pub preimage: String,
-}
\ No newline at end of file
+}
+// Returned with a 200 when phoenixd gives up on a payment, e.g. {"paymentId": "..", "reason": "no route to recipient"}
+#[derive(Debug, Deserialize)]
+pub struct PhoenixPayFailure {
+    #[serde(rename = "paymentId")]
+    pub payment_id: Option<String>,
+    pub reason: String,
+}
+
+// Message on the /websocket feed, only payment_received is acted on
+#[derive(Debug, Deserialize)]
+pub struct WebsocketEvent {
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(rename = "paymentHash")]
+    pub payment_hash: Option<String>,
+}
@@ -4,18 +4,33 @@ use std::sync::Arc;
 use breez_sdk_spark::{
     BreezSdk, EventListener, GetInfoRequest, GetPaymentRequest, ListPaymentsRequest,
     PaymentDetails, PaymentStatus, PaymentType, PrepareSendPaymentRequest, ReceivePaymentMethod,
-    ReceivePaymentRequest, SdkEvent, SendPaymentRequest,
+    ReceivePaymentRequest, SdkEvent, SendPaymentMethod, SendPaymentRequest,
 };
 use tokio::sync::RwLock;
 
 use crate::types::NodeInfo;
 use crate::{
-    ApiError, CreateInvoiceParams, InvoiceType, Offer, OnInvoiceEventCallback,
-    OnInvoiceEventParams, PayInvoiceParams, PayInvoiceResponse, Transaction,
+    AccountEvent, AccountEventType, ApiError, DecodedPaymentRequest, CreateInvoiceParams, Direction, InvoiceType, NodeCapabilities, Offer, OnInvoiceEventCallback,
+    ListTransactionsParams, OnInvoiceEventParams, OutgoingPayment, PayInvoiceParams, PayInvoiceResponse, PaymentFeeEstimate, Transaction,
+    SubscribeAllCallback, SubscribeAllParams, TransactionPage, TransactionStatus,
 };
 
 // ── Helpers ──────────────────────────────────────────────────────────
 
+// The SDK error variants aren't stable across releases, so classify by message
+fn sdk_error(e: impl std::fmt::Display) -> ApiError {
+    let reason = e.to_string();
+    ApiError::from_message(reason.clone()).unwrap_or(ApiError::Api { reason })
+}
+
+fn payment_status(status: &PaymentStatus) -> TransactionStatus {
+    match status {
+        PaymentStatus::Completed => TransactionStatus::Settled,
+        PaymentStatus::Failed => TransactionStatus::Failed,
+        PaymentStatus::Pending => TransactionStatus::Pending,
+    }
+}
+
 struct PaymentInfo {
     invoice: String,
     payment_hash: String,
@@ -93,11 +108,15 @@ fn payment_to_transaction(payment: &breez_sdk_spark::Payment) -> Option<Transact
         }
     };
 
+    let direction = match payment.payment_type {
+        PaymentType::Send => Direction::Outgoing,
+        PaymentType::Receive => Direction::Incoming,
+    };
+
     Some(Transaction {
-        type_: match payment.payment_type {
-            PaymentType::Send => "outgoing".to_string(),
-            PaymentType::Receive => "incoming".to_string(),
-        },
+        type_: direction.as_str().to_string(),
+        status: payment_status(&payment.status),
+        direction,
         invoice,
         preimage,
         payment_hash,
@@ -141,9 +160,7 @@ pub async fn get_info(sdk: Arc<BreezSdk>, network: &str) -> Result<NodeInfo, Api
             ensure_synced: Some(true),
         })
         .await
-        .map_err(|e| ApiError::Api {
-            reason: e.to_string(),
-        })?;
+        .map_err(sdk_error)?;
 
     Ok(NodeInfo {
         alias: "Spark Node".to_string(),
@@ -177,9 +194,7 @@ pub async fn create_invoice(
                     },
                 })
                 .await
-                .map_err(|e| ApiError::Api {
-                    reason: e.to_string(),
-                })?;
+                .map_err(sdk_error)?;
 
             let now = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -188,6 +203,8 @@ pub async fn create_invoice(
 
             Ok(Transaction {
                 type_: "incoming".to_string(),
+                status: TransactionStatus::Pending,
+                direction: Direction::Incoming,
                 invoice: response.payment_request.clone(),
                 preimage: "".to_string(),
                 payment_hash: extract_payment_hash(&response.payment_request).unwrap_or_default(),
@@ -202,16 +219,43 @@ pub async fn create_invoice(
                 external_id: None,
             })
         }
-        InvoiceType::Bolt12 => Err(ApiError::Api {
+        InvoiceType::Bolt12 => Err(ApiError::NotSupported {
             reason: "Bolt12 not yet implemented for Spark".to_string(),
         }),
     }
 }
 
+// prepare_send_payment quotes the lightning fee before anything is sent
+pub(crate) const PAY_PARAMS: crate::PayParamSupport = crate::PayParamSupport {
+    amount: true,
+    fee_limit: true,
+    ..crate::PayParamSupport::NONE
+};
+
 /// Pay an invoice using Spark SDK
 pub async fn pay_invoice(
     sdk: Arc<BreezSdk>,
     invoice_params: PayInvoiceParams,
+    cache: Arc<RwLock<HashMap<String, String>>>,
+) -> Result<PayInvoiceResponse, ApiError> {
+    let fee_limit_msat = crate::check_pay_params(&invoice_params, "Spark", PAY_PARAMS)?;
+    if invoice_params.wait == Some(false) {
+        // send_payment only returns once the payment is final
+        let invoice = invoice_params.invoice.clone();
+        let timeout_seconds = invoice_params.timeout_seconds;
+        let pay = send_payment(sdk.clone(), invoice_params, fee_limit_msat);
+        return crate::dispatch_payment(&invoice, timeout_seconds, pay, |payment_hash| {
+            lookup_payment(sdk.clone(), payment_hash, cache.clone())
+        })
+        .await;
+    }
+    send_payment(sdk, invoice_params, fee_limit_msat).await
+}
+
+async fn send_payment(
+    sdk: Arc<BreezSdk>,
+    invoice_params: PayInvoiceParams,
+    fee_limit_msat: Option<i64>,
 ) -> Result<PayInvoiceResponse, ApiError> {
     // Prepare the payment first
     let prepare_response = sdk
@@ -221,9 +265,21 @@ pub async fn pay_invoice(
             token_identifier: None,
         })
         .await
-        .map_err(|e| ApiError::Api {
-            reason: e.to_string(),
-        })?;
+        .map_err(sdk_error)?;
+
+    if let (Some(fee_limit_msat), SendPaymentMethod::Bolt11Invoice { lightning_fee_sats, .. }) =
+        (fee_limit_msat, &prepare_response.payment_method)
+    {
+        let fee_msats = (*lightning_fee_sats as i64) * 1000;
+        if fee_msats > fee_limit_msat {
+            return Err(ApiError::NoRoute {
+                reason: format!(
+                    "Spark prepared a {} msat lightning fee, above the {} msat fee limit",
+                    fee_msats, fee_limit_msat
+                ),
+            });
+        }
+    }
 
     // Execute the payment
     let response = sdk
@@ -233,9 +289,7 @@ pub async fn pay_invoice(
             idempotency_key: None,
         })
         .await
-        .map_err(|e| ApiError::Api {
-            reason: e.to_string(),
-        })?;
+        .map_err(sdk_error)?;
 
     let (payment_hash, preimage) = match response.payment.details {
         Some(PaymentDetails::Lightning {
@@ -257,9 +311,41 @@ pub async fn pay_invoice(
         payment_hash,
         preimage,
         fee_msats: (response.payment.fees as i64) * 1000,
+        status: payment_status(&response.payment.status),
     })
 }
 
+/// Lightning fee quoted by prepare_send_payment, which is what send_payment above pays
+pub async fn estimate_payment_fee(
+    sdk: Arc<BreezSdk>,
+    invoice: String,
+    amount_msats: Option<i64>,
+) -> Result<PaymentFeeEstimate, ApiError> {
+    let (decoded, amount) = crate::estimate_target(&invoice, amount_msats)?;
+    let prepared = sdk
+        .prepare_send_payment(PrepareSendPaymentRequest {
+            payment_request: invoice,
+            amount: decoded.amount_msats.is_none().then_some((amount / 1000) as u128),
+            token_identifier: None,
+        })
+        .await
+        .map_err(sdk_error);
+
+    crate::no_route_estimate(prepared.and_then(|prepared| match prepared.payment_method {
+        SendPaymentMethod::Bolt11Invoice { lightning_fee_sats, .. } => {
+            let fee_msats = (lightning_fee_sats as i64) * 1000;
+            Ok(PaymentFeeEstimate {
+                min_fee_msats: fee_msats,
+                max_fee_msats: fee_msats,
+                route_found: true,
+            })
+        }
+        _ => Err(ApiError::InvalidInput(
+            "Spark did not prepare a lightning payment for this invoice".to_string(),
+        )),
+    }))
+}
+
 // ── Lookup ───────────────────────────────────────────────────────────
 
 /// O(1) lookup by payment ID via get_payment
@@ -298,9 +384,7 @@ async fn scan_for_hash(
                 ..Default::default()
             })
             .await
-            .map_err(|e| ApiError::Api {
-                reason: e.to_string(),
-            })?;
+            .map_err(sdk_error)?;
 
         if payments.payments.is_empty() {
             break;
@@ -389,6 +473,22 @@ pub async fn lookup_invoice(
     })
 }
 
+/// Lookup a payment we sent, by payment hash. Same tiers as `lookup_invoice`,
+/// which finds payments in either direction.
+pub async fn lookup_payment(
+    sdk: Arc<BreezSdk>,
+    payment_hash: String,
+    cache: Arc<RwLock<HashMap<String, String>>>,
+) -> Result<OutgoingPayment, ApiError> {
+    let txn = lookup_invoice(sdk, Some(payment_hash.clone()), None, None, None, cache).await?;
+    if txn.direction != Direction::Outgoing {
+        return Err(ApiError::Api {
+            reason: format!("Payment not found for payment hash {}", payment_hash),
+        });
+    }
+    Ok(OutgoingPayment::from_transaction(txn, None))
+}
+
 // ── List Transactions ────────────────────────────────────────────────
 
 /// List transactions from Spark SDK with optional date filters
@@ -415,9 +515,7 @@ pub async fn list_transactions(
             ..Default::default()
         })
         .await
-        .map_err(|e| ApiError::Api {
-            reason: e.to_string(),
-        })?;
+        .map_err(sdk_error)?;
 
     let mut transactions = Vec::new();
 
@@ -432,28 +530,66 @@ pub async fn list_transactions(
     Ok(transactions)
 }
 
-// ── Decode / Offers ──────────────────────────────────────────────────
+pub async fn list_transactions_page(
+    sdk: Arc<BreezSdk>,
+    params: ListTransactionsParams,
+    cache: Arc<RwLock<HashMap<String, String>>>,
+) -> Result<TransactionPage, ApiError> {
+    crate::search_page(params, |params| {
+        transactions_page(sdk.clone(), params, cache.clone())
+    })
+    .await
+}
 
-/// Decode a payment request using the SDK's parse functionality
-pub async fn decode(sdk: Arc<BreezSdk>, input: String) -> Result<String, ApiError> {
-    match sdk.parse(&input).await {
-        Ok(parsed) => Ok(format!("{:?}", parsed)),
-        Err(e) => Err(ApiError::Api {
-            reason: format!("Failed to decode input: {}", e),
-        }),
+/// Cursor page over the payments up to the window's `until`, so new ones can't shift the offset
+async fn transactions_page(
+    sdk: Arc<BreezSdk>,
+    params: ListTransactionsParams,
+    cache: Arc<RwLock<HashMap<String, String>>>,
+) -> Result<TransactionPage, ApiError> {
+    let limit = crate::page_limit(params.limit)?;
+    let window = crate::WindowCursor::from_params(params.cursor.as_deref())?;
+
+    let payments = sdk
+        .list_payments(ListPaymentsRequest {
+            offset: Some(u32::try_from(window.offset.max(0)).unwrap_or(u32::MAX)),
+            limit: Some(u32::try_from(limit).unwrap_or(u32::MAX)),
+            to_timestamp: u64::try_from(window.until).ok(),
+            ..Default::default()
+        })
+        .await
+        .map_err(sdk_error)?;
+
+    let mut transactions = Vec::new();
+    for payment in &payments.payments {
+        cache_payment(payment, &cache).await;
+        if let Some(txn) = payment_to_transaction(payment) {
+            transactions.push(txn);
+        }
     }
+    Ok(TransactionPage {
+        transactions,
+        next_cursor: window.next(payments.payments.len(), limit),
+    })
+}
+
+// ── Decode / Offers ──────────────────────────────────────────────────
+
+/// Decode a payment request locally, the same way as every other node
+pub async fn decode(_sdk: Arc<BreezSdk>, input: String) -> Result<DecodedPaymentRequest, ApiError> {
+    crate::decode_payment_request(input)
 }
 
 /// Get offer (not implemented for Spark yet)
 pub fn get_offer(_search: Option<String>) -> Result<Offer, ApiError> {
-    Err(ApiError::Api {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 offers not yet implemented for Spark".to_string(),
     })
 }
 
 /// List offers (not implemented for Spark yet)
 pub fn list_offers(_search: Option<String>) -> Result<Vec<Offer>, ApiError> {
-    Err(ApiError::Api {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 offers not yet implemented for Spark".to_string(),
     })
 }
@@ -464,7 +600,7 @@ pub fn pay_offer(
     _amount_msats: i64,
     _payer_note: Option<String>,
 ) -> Result<PayInvoiceResponse, ApiError> {
-    Err(ApiError::Api {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 offers not yet implemented for Spark".to_string(),
     })
 }
@@ -516,7 +652,7 @@ pub async fn on_invoice_events(
     )
     .await
     {
-        if txn.settled_at > 0 {
+        if txn.status == TransactionStatus::Settled {
             callback.success(Some(txn));
             return;
         }
@@ -563,7 +699,7 @@ pub async fn on_invoice_events(
             )
             .await
             {
-                if txn.settled_at > 0 {
+                if txn.status == TransactionStatus::Settled {
                     callback.success(Some(txn));
                     return;
                 }
@@ -573,6 +709,106 @@ pub async fn on_invoice_events(
     }
 }
 
+/// EventListener that forwards finished payments in both directions, `true` for failures
+struct AccountEventListener {
+    tx: tokio::sync::mpsc::UnboundedSender<(breez_sdk_spark::Payment, bool)>,
+}
+
+#[async_trait::async_trait]
+impl EventListener for AccountEventListener {
+    async fn on_event(&self, event: SdkEvent) {
+        let forwarded = match event {
+            SdkEvent::PaymentSucceeded { payment } => (payment, false),
+            SdkEvent::PaymentFailed { payment } => (payment, true),
+            _ => return,
+        };
+        let _ = self.tx.send(forwarded);
+    }
+}
+
+/// Removes the listener when the feed ends, also when its subscription is cancelled mid-await
+struct RegisteredListener {
+    sdk: Arc<BreezSdk>,
+    id: String,
+}
+
+impl Drop for RegisteredListener {
+    fn drop(&mut self) {
+        let sdk = self.sdk.clone();
+        let id = std::mem::take(&mut self.id);
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                sdk.remove_event_listener(&id).await;
+            });
+        }
+    }
+}
+
+/// Every finished payment from the SDK's event listener. The resume index is the unix time
+/// of the payment, on resume the payments listed since then are reported before live ones.
+pub async fn subscribe_all(
+    sdk: Arc<BreezSdk>,
+    params: SubscribeAllParams,
+    callback: Arc<dyn SubscribeAllCallback>,
+    cache: Arc<RwLock<HashMap<String, String>>>,
+) -> Result<(), ApiError> {
+    let since = match params.resume_from.as_deref() {
+        Some(index) => Some(crate::events::parse_resume_index::<1>(index)?[0]),
+        None => None,
+    };
+
+    // Listening before the catch-up listing so nothing falls in between
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let _listener = RegisteredListener {
+        id: sdk.add_event_listener(Box::new(AccountEventListener { tx })).await,
+        sdk: sdk.clone(),
+    };
+
+    let mut caught_up = std::collections::HashSet::new();
+    if let Some(since) = since {
+        let txns = list_transactions(
+            sdk.clone(),
+            0,
+            500,
+            None,
+            Some(since - crate::events::RESUME_LOOKBACK_SECS),
+            None,
+            cache.clone(),
+        )
+        .await?;
+        let mut seen = HashMap::new();
+        for (event_type, txn) in crate::events::account_transactions(&mut seen, txns, since) {
+            caught_up.insert(txn.payment_hash.clone());
+            callback.event(AccountEvent {
+                event_type,
+                resume_index: crate::events::event_time(&txn).to_string(),
+                transaction: txn,
+            });
+        }
+    }
+
+    while let Some((payment, failed)) = rx.recv().await {
+        cache_payment(&payment, &cache).await;
+        let Some(txn) = payment_to_transaction(&payment) else {
+            continue;
+        };
+        if caught_up.remove(&txn.payment_hash) {
+            continue;
+        }
+        let event_type = match (failed, txn.direction) {
+            (true, _) => AccountEventType::PaymentFailed,
+            (false, Direction::Outgoing) => AccountEventType::PaymentSent,
+            (false, Direction::Incoming) => AccountEventType::InvoiceSettled,
+        };
+        callback.event(AccountEvent {
+            event_type,
+            resume_index: crate::events::event_time(&txn).to_string(),
+            transaction: txn,
+        });
+    }
+    Ok(())
+}
+
 /// Extract payment hash from a BOLT11 invoice
 fn extract_payment_hash(invoice: &str) -> Option<String> {
     use lightning_invoice::Bolt11Invoice;
@@ -582,3 +818,24 @@ fn extract_payment_hash(invoice: &str) -> Option<String> {
         .ok()
         .map(|inv| format!("{:x}", inv.payment_hash()))
 }
+
+pub async fn capabilities(_sdk: Arc<BreezSdk>) -> Result<NodeCapabilities, ApiError> {
+    Ok(NodeCapabilities {
+        bolt11_receive: true,
+        bolt11_send: true,
+        bolt12_receive: false,
+        bolt12_send: false,
+        keysend: false,
+        onchain_receive: false,
+        onchain_send: false,
+        onchain_balance: false,
+        hold_invoices: false,
+        description_hash_invoices: false,
+        zero_amount_invoices: true,
+        lookup_invoice: true,
+        list_transactions: true,
+        channel_management: false,
+        native_event_streaming: false,
+        fiat: false,
+    })
+}
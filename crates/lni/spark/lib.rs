@@ -11,8 +11,11 @@ use tokio::sync::RwLock;
 
 use crate::types::NodeInfo;
 use crate::{
-    ApiError, CreateInvoiceParams, CreateOfferParams, ListTransactionsParams,
-    LookupInvoiceParams, Offer, PayInvoiceParams, PayInvoiceResponse, Transaction,
+    ApiError, CreateInvoiceParams, CreateOfferParams, ListTransactionsParams, LookupInvoiceParams,
+    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction, Channel, CloseChannelRequest,
+    CloseChannelResponse, OpenChannelRequest, OpenChannelResponse, UpdateChannelRequest,
+    OnchainBalanceResponse, OnchainTransaction, SendOnchainResponse, PayKeysendResponse, TLVRecord,
+    NodeCapabilities,
 };
 #[cfg(not(feature = "uniffi"))]
 use crate::LightningNode;
@@ -157,16 +160,55 @@ impl SparkNode {
         crate::spark::api::get_info(self.sdk.clone(), network).await
     }
 
+    pub async fn capabilities(&self) -> Result<NodeCapabilities, ApiError> {
+        crate::spark::api::capabilities(self.sdk.clone()).await
+    }
+
     pub async fn create_invoice(&self, params: CreateInvoiceParams) -> Result<Transaction, ApiError> {
         crate::spark::api::create_invoice(self.sdk.clone(), params).await
     }
 
     pub async fn pay_invoice(&self, params: PayInvoiceParams) -> Result<PayInvoiceResponse, ApiError> {
-        crate::spark::api::pay_invoice(self.sdk.clone(), params).await
+        crate::spark::api::pay_invoice(self.sdk.clone(), params, self.spark_transactions_cache.clone()).await
+    }
+
+    pub async fn estimate_payment_fee(
+        &self,
+        invoice: String,
+        amount_msats: Option<i64>,
+    ) -> Result<crate::PaymentFeeEstimate, ApiError> {
+        crate::spark::api::estimate_payment_fee(self.sdk.clone(), invoice, amount_msats).await
+    }
+
+    pub async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: crate::PayOptions,
+    ) -> Result<crate::PayResponse, ApiError> {
+        crate::lnurl::pay(self, destination, amount_msats, options).await
+    }
+
+    pub async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: crate::ZapRequestParams,
+    ) -> Result<crate::ZapResponse, ApiError> {
+        crate::lnurl::zap(self, destination, amount_msats, params).await
+    }
+
+    pub async fn pay_keysend(
+        &self,
+        _destination_pubkey: String,
+        _amount_msats: i64,
+        _custom_records: Vec<TLVRecord>,
+    ) -> Result<PayKeysendResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Keysend is not available for SparkNode".to_string() })
     }
 
     pub async fn create_offer(&self, _params: CreateOfferParams) -> Result<Offer, ApiError> {
-        Err(ApiError::Api {
+        Err(ApiError::NotSupported {
             reason: "create_offer not yet implemented for SparkNode".to_string(),
         })
     }
@@ -183,6 +225,10 @@ impl SparkNode {
         .await
     }
 
+    pub async fn lookup_payment(&self, payment_hash: String) -> Result<crate::OutgoingPayment, ApiError> {
+        crate::spark::api::lookup_payment(self.sdk.clone(), payment_hash, self.spark_transactions_cache.clone()).await
+    }
+
     pub async fn list_transactions(
         &self,
         params: ListTransactionsParams,
@@ -199,21 +245,57 @@ impl SparkNode {
         .await
     }
 
-    pub async fn decode(&self, str: String) -> Result<String, ApiError> {
+    pub async fn list_transactions_page(
+        &self,
+        params: ListTransactionsParams,
+    ) -> Result<crate::TransactionPage, ApiError> {
+        crate::spark::api::list_transactions_page(
+            self.sdk.clone(),
+            params,
+            self.spark_transactions_cache.clone(),
+        )
+        .await
+    }
+
+    pub async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, ApiError> {
         crate::spark::api::decode(self.sdk.clone(), str).await
     }
 
-    pub async fn on_invoice_events(
+    pub fn on_invoice_events(
         &self,
         params: crate::types::OnInvoiceEventParams,
         callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-    ) {
-        crate::spark::api::on_invoice_events(
-            self.sdk.clone(),
-            params,
-            callback,
-            self.spark_transactions_cache.clone(),
-        ).await
+    ) -> std::sync::Arc<crate::Subscription> {
+        let sdk = self.sdk.clone();
+        let cache = self.spark_transactions_cache.clone();
+        crate::Subscription::invoice_events(callback, |callback| {
+            crate::spark::api::on_invoice_events(sdk, params, callback, cache)
+        })
+    }
+
+    pub fn on_payment_events(
+        &self,
+        params: crate::types::OnPaymentEventParams,
+        callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No payment feed, the payment is looked up every polling_delay_sec
+        let node = self.clone();
+        crate::Subscription::payment_events(callback, |callback| async move {
+            let payment_hash = params.payment_hash.clone();
+            crate::poll_payment_events(|| node.lookup_payment(payment_hash.clone()), params, callback.as_ref()).await
+        })
+    }
+
+    pub fn subscribe_all(
+        &self,
+        params: crate::types::SubscribeAllParams,
+        callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        let sdk = self.sdk.clone();
+        let cache = self.spark_transactions_cache.clone();
+        crate::Subscription::subscribe_all(callback, |callback| {
+            crate::spark::api::subscribe_all(sdk, params, callback, cache)
+        })
     }
 
     pub async fn get_offer(&self, search: Option<String>) -> Result<Offer, ApiError> {
@@ -232,11 +314,50 @@ impl SparkNode {
     ) -> Result<PayInvoiceResponse, ApiError> {
         crate::spark::api::pay_offer(offer, amount_msats, payer_note)
     }
+
+    pub async fn list_channels(&self) -> Result<Vec<Channel>, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for SparkNode".to_string() })
+    }
+
+    pub async fn open_channel(&self, _params: OpenChannelRequest) -> Result<OpenChannelResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for SparkNode".to_string() })
+    }
+
+    pub async fn close_channel(&self, _params: CloseChannelRequest) -> Result<CloseChannelResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for SparkNode".to_string() })
+    }
+
+    pub async fn update_channel_policy(&self, _params: UpdateChannelRequest) -> Result<(), ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for SparkNode".to_string() })
+    }
+
+    pub async fn get_onchain_balance(&self) -> Result<OnchainBalanceResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Onchain wallet is not available for SparkNode".to_string() })
+    }
+
+    pub async fn new_onchain_address(&self) -> Result<String, ApiError> {
+        Err(ApiError::NotSupported { reason: "Onchain wallet is not available for SparkNode".to_string() })
+    }
+
+    pub async fn send_onchain(
+        &self,
+        _address: String,
+        _amount_sats: i64,
+        _sat_per_vbyte: Option<i64>,
+    ) -> Result<SendOnchainResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Onchain wallet is not available for SparkNode".to_string() })
+    }
+
+    pub async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, ApiError> {
+        Err(ApiError::NotSupported { reason: "Onchain wallet is not available for SparkNode".to_string() })
+    }
 }
 
 // Trait implementation for polymorphic access via Arc<dyn LightningNode>
 crate::impl_lightning_node!(SparkNode);
 
+impl crate::LightningNodeEvents for SparkNode {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -402,6 +523,7 @@ mod tests {
             search: None,
             created_after: None,
             created_before: None,
+            cursor: None,
         };
 
         match node.list_transactions(params).await {
@@ -491,7 +613,7 @@ mod tests {
         let test_invoice = "lnbc1..."; // You can put a valid invoice here for testing
         match node.decode(test_invoice.to_string()).await {
             Ok(decoded) => {
-                println!("Decoded: {}", decoded);
+                println!("Decoded: {:?}", decoded);
             }
             Err(e) => {
                 println!("Decode error (may be expected for invalid input): {:?}", e);
@@ -528,7 +650,7 @@ mod tests {
             ..Default::default()
         };
         let callback = TestInvoiceEventCallback;
-        node.on_invoice_events(params, Arc::new(callback)).await;
+        node.on_invoice_events(params, Arc::new(callback)).wait().await;
         let _ = node.disconnect().await;
     }
 
@@ -9,12 +9,34 @@ use super::types::*;
 use super::SpeedConfig;
 use crate::types::NodeInfo;
 use crate::{
-    ApiError, CreateInvoiceParams, InvoiceType, Offer, OnInvoiceEventCallback, OnInvoiceEventParams,
-    PayInvoiceParams, PayInvoiceResponse, Transaction,
+    retry_after_secs, ApiError, DecodedPaymentRequest, CreateInvoiceParams, Direction, InvoiceType, NodeCapabilities, Offer, OnInvoiceEventCallback,
+    OnInvoiceEventParams, OnchainBalanceResponse, OnchainTransaction, OutgoingPayment, PayInvoiceParams, PayInvoiceResponse,
+    ListTransactionsParams, SendOnchainResponse, Transaction, TransactionPage, TransactionStatus,
 };
 
 // Docs: https://apidocs.tryspeed.com/
 
+// Map a Speed error body into structured errors, using the error type first and the message as fallback
+fn speed_error(status: reqwest::StatusCode, retry_after: Option<i64>, body: String) -> ApiError {
+    let detail = match serde_json::from_str::<SpeedError>(&body) {
+        Ok(err) => err.error,
+        Err(_) => return ApiError::from_http(status, retry_after, body),
+    };
+    let reason = format!("{} ({}): {}", detail.r#type, detail.code, detail.message);
+    match detail.r#type.as_str() {
+        "authentication_error" | "permission_error" => ApiError::Unauthorized { reason },
+        "rate_limit_error" => ApiError::RateLimited { retry_after, reason },
+        _ => ApiError::from_http(status, retry_after, reason),
+    }
+}
+
+async fn error_from_response(response: reqwest::Response) -> ApiError {
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    speed_error(status, retry_after, body)
+}
+
 fn client(config: &SpeedConfig) -> reqwest::Client {
     let mut headers = reqwest::header::HeaderMap::new();
 
@@ -91,16 +113,10 @@ pub async fn get_info(config: &SpeedConfig) -> Result<NodeInfo, ApiError> {
         .get(&format!("{}/balances", get_base_url(config)))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "get_info"))?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(ApiError::Http {
-            reason: format!("HTTP {} - {}", status, error_text),
-        });
+        return Err(error_from_response(response).await);
     }
 
     let balance_response: SpeedBalanceResponse = response.json().await.map_err(|e| ApiError::Json {
@@ -152,16 +168,10 @@ pub async fn create_invoice(
                 .json(&request)
                 .send()
                 .await
-                .map_err(|e| ApiError::Http {
-                    reason: e.to_string(),
-                })?;
+                .map_err(|e| ApiError::from_reqwest(e, "create_invoice"))?;
 
             if !response.status().is_success() {
-                let status = response.status();
-                let error_text = response.text().await.unwrap_or_default();
-                return Err(ApiError::Http {
-                    reason: format!("HTTP {} - {}", status, error_text),
-                });
+                return Err(error_from_response(response).await);
             }
 
             let payment: SpeedCreatePaymentResponse =
@@ -199,6 +209,8 @@ pub async fn create_invoice(
 
             Ok(Transaction {
                 type_: "incoming".to_string(),
+                status: transaction_status(&payment.payment.status),
+                direction: Direction::Incoming,
                 invoice: lightning_invoice.to_string(),
                 preimage: "".to_string(), // Not available in Speed API
                 payment_hash,
@@ -219,16 +231,23 @@ pub async fn create_invoice(
                 external_id: Some(payment.payment.id),
             })
         }
-        InvoiceType::Bolt12 => Err(ApiError::Json {
+        InvoiceType::Bolt12 => Err(ApiError::NotSupported {
             reason: "Bolt12 not implemented for Speed".to_string(),
         }),
     }
 }
 
+// /send takes an amount and nothing else
+pub(crate) const PAY_PARAMS: crate::PayParamSupport = crate::PayParamSupport {
+    amount: true,
+    ..crate::PayParamSupport::NONE
+};
+
 pub async fn pay_invoice(
     config: &SpeedConfig,
     invoice_params: PayInvoiceParams,
 ) -> Result<PayInvoiceResponse, ApiError> {
+    crate::check_pay_params(&invoice_params, "Speed", PAY_PARAMS)?;
     let client = client(config);
 
     // Extract amount from invoice or use provided amount
@@ -269,16 +288,10 @@ pub async fn pay_invoice(
         .json(&request)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "pay_invoice"))?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(ApiError::Http {
-            reason: format!("HTTP {} - {}", status, error_text),
-        });
+        return Err(error_from_response(response).await);
     }
 
     let send_response: SpeedSendResponse = response.json().await.map_err(|e| ApiError::Json {
@@ -295,16 +308,53 @@ pub async fn pay_invoice(
         payment_hash,
         preimage: "".to_string(), // Not available in Speed send response
         fee_msats: (send_response.speed_fee.amount * 1000) as i64,
+        status: transaction_status(&send_response.status),
     })
 }
 
-pub async fn decode(_config: &SpeedConfig, str: String) -> Result<String, ApiError> {
-    // Speed doesn't have a decode endpoint, return raw string
-    Ok(str)
+// Speed charges its own fee for a send and routes it itself, /send/calculate-fee quotes that
+// fee for the invoice. It is a single figure, so min and max are the same.
+pub async fn estimate_payment_fee(
+    config: &SpeedConfig,
+    invoice: String,
+    amount_msats: Option<i64>,
+) -> Result<crate::PaymentFeeEstimate, ApiError> {
+    let (_, amount) = crate::estimate_target(&invoice, amount_msats)?;
+    let request = SpeedSendFeeRequest {
+        amount: amount as f64 / 1000.0,
+        currency: "SATS".to_string(),
+        target_currency: "SATS".to_string(),
+        withdraw_method: "lightning".to_string(),
+        withdraw_request: invoice,
+    };
+
+    let response = client(config)
+        .post(format!("{}/send/calculate-fee", get_base_url(config)))
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "estimate_payment_fee"))?;
+    if !response.status().is_success() {
+        return crate::no_route_estimate(Err(error_from_response(response).await));
+    }
+
+    let fee_response: SpeedSendFeeResponse = response.json().await.map_err(|e| ApiError::Json {
+        reason: format!("error decoding response body: {}", e),
+    })?;
+    let fee_msats = fee_response.speed_fee.amount * 1000;
+    Ok(crate::PaymentFeeEstimate {
+        min_fee_msats: fee_msats,
+        max_fee_msats: fee_msats,
+        route_found: true,
+    })
+}
+
+pub async fn decode(_config: &SpeedConfig, str: String) -> Result<DecodedPaymentRequest, ApiError> {
+    crate::decode_payment_request(str)
 }
 
 pub async fn get_offer(_config: &SpeedConfig, _search: Option<String>) -> Result<Offer, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Speed".to_string(),
     })
 }
@@ -313,7 +363,7 @@ pub async fn list_offers(
     _config: &SpeedConfig,
     _search: Option<String>,
 ) -> Result<Vec<Offer>, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Speed".to_string(),
     })
 }
@@ -324,7 +374,7 @@ pub async fn create_offer(
     _description: Option<String>,
     _expiry: Option<i64>,
 ) -> Result<Transaction, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Speed".to_string(),
     })
 }
@@ -335,7 +385,7 @@ pub async fn fetch_invoice_from_offer(
     _amount_msats: i64,
     _payer_note: Option<String>,
 ) -> Result<crate::cln::types::FetchInvoiceResponse, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Speed".to_string(),
     })
 }
@@ -346,7 +396,7 @@ pub async fn pay_offer(
     _amount_msats: i64,
     _payer_note: Option<String>,
 ) -> Result<PayInvoiceResponse, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Speed".to_string(),
     })
 }
@@ -369,16 +419,10 @@ async fn fetch_send_transactions(
         .json(&request)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "pay_offer"))?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(ApiError::Http {
-            reason: format!("HTTP {} - {}", status, error_text),
-        });
+        return Err(error_from_response(response).await);
     }
 
     let filter_response: SpeedSendFilterResponse = response.json().await.map_err(|e| ApiError::Json {
@@ -420,6 +464,8 @@ fn convert_send_to_transaction(send_tx: SpeedSendResponse) -> Transaction {
 
     Transaction {
         type_: type_.to_string(),
+        status: transaction_status(&send_tx.status),
+        direction: Direction::Outgoing,
         invoice: send_tx.withdraw_request.clone(),
         preimage: "".to_string(), // Not available in send response
         payment_hash,
@@ -435,6 +481,17 @@ fn convert_send_to_transaction(send_tx: SpeedSendResponse) -> Transaction {
     }
 }
 
+// Speed statuses are lowercase: "unpaid", "pending", "paid", "failed", "expired", "cancelled"
+fn transaction_status(status: &str) -> TransactionStatus {
+    match status {
+        "paid" => TransactionStatus::Settled,
+        "failed" => TransactionStatus::Failed,
+        "expired" => TransactionStatus::Expired,
+        "cancelled" => TransactionStatus::Cancelled,
+        _ => TransactionStatus::Pending,
+    }
+}
+
 pub async fn lookup_invoice(
     config: &SpeedConfig,
     payment_hash: Option<String>,
@@ -495,6 +552,20 @@ pub async fn lookup_invoice(
     }
 }
 
+pub async fn lookup_payment(config: &SpeedConfig, payment_hash: String) -> Result<OutgoingPayment, ApiError> {
+    // /send/filter can't filter by payment hash, sends are matched client-side
+    let statuses = ["unpaid", "pending", "paid", "failed"].map(String::from).to_vec();
+    let payment = fetch_send_transactions(config, Some(statuses), None)
+        .await?
+        .into_iter()
+        .map(convert_send_to_transaction)
+        .find(|t| t.payment_hash == payment_hash)
+        .ok_or_else(|| ApiError::Api {
+            reason: format!("Payment not found for payment hash {}", payment_hash),
+        })?;
+    Ok(OutgoingPayment::from_transaction(payment, None))
+}
+
 pub async fn list_transactions(
     config: &SpeedConfig,
     _from: i64,
@@ -532,6 +603,35 @@ pub async fn list_transactions(
     Ok(transactions)
 }
 
+pub async fn list_transactions_page(
+    config: &SpeedConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    crate::search_page(params, |params| transactions_page(config, params)).await
+}
+
+// Speed returns the whole history at once, so the window is applied here
+async fn transactions_page(
+    config: &SpeedConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    let limit = crate::page_limit(params.limit)?;
+    let window = crate::WindowCursor::from_params(params.cursor.as_deref())?;
+    let status_filter = Some(vec!["unpaid".to_string(), "paid".to_string(), "failed".to_string()]);
+    let send_transactions = fetch_send_transactions(config, status_filter, None).await?;
+
+    let transactions: Vec<Transaction> = send_transactions
+        .into_iter()
+        .map(convert_send_to_transaction)
+        .collect();
+    let transactions = crate::window_page(transactions, &window, limit);
+    let next_cursor = window.next(transactions.len(), limit);
+    Ok(TransactionPage {
+        transactions,
+        next_cursor,
+    })
+}
+
 // Core logic shared by both implementations
 pub async fn poll_invoice_events<F>(config: &SpeedConfig, params: OnInvoiceEventParams, mut callback: F)
 where
@@ -553,7 +653,7 @@ where
             params.search.clone(),
         ).await {
             Ok(transaction) => {
-                if transaction.settled_at > 0 {
+                if transaction.status == TransactionStatus::Settled {
                     ("success".to_string(), Some(transaction))
                 } else {
                     ("pending".to_string(), Some(transaction))
@@ -583,3 +683,88 @@ pub async fn on_invoice_events(
         "failure" | _ => callback.failure(tx),
     }).await;
 }
+
+pub async fn get_onchain_balance(_config: &SpeedConfig) -> Result<OnchainBalanceResponse, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Speed does not keep a separate onchain balance".to_string(),
+    })
+}
+
+// Speed only hands out onchain addresses as part of an amount-bound payment
+pub async fn new_onchain_address(_config: &SpeedConfig) -> Result<String, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Speed does not support reusable onchain addresses".to_string(),
+    })
+}
+
+// Speed picks the fee rate, `sat_per_vbyte` is ignored
+pub async fn send_onchain(
+    config: &SpeedConfig,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+) -> Result<SendOnchainResponse, ApiError> {
+    crate::refuse_fee_rate(sat_per_vbyte, "Speed")?;
+    let client = client(config);
+
+    let request = SpeedPayInvoiceRequest {
+        amount: amount_sats as f64,
+        currency: "SATS".to_string(),
+        target_currency: "SATS".to_string(),
+        withdraw_method: "onchain".to_string(),
+        withdraw_request: address,
+        note: Some("LNI onchain payment".to_string()),
+        external_id: None,
+    };
+
+    let response = client
+        .post(format!("{}/send", get_base_url(config)))
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "send_onchain"))?;
+
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+
+    let send_response: SpeedSendResponse = response.json().await.map_err(|e| ApiError::Json {
+        reason: format!("error decoding response body: {}", e),
+    })?;
+
+    Ok(SendOnchainResponse {
+        // The txid is not part of the send response, Speed broadcasts asynchronously
+        txid: None,
+        external_id: Some(send_response.id),
+        fee_sats: Some(send_response.speed_fee.amount as i64),
+    })
+}
+
+pub async fn list_onchain_transactions(
+    _config: &SpeedConfig,
+) -> Result<Vec<OnchainTransaction>, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Onchain transaction history not implemented for Speed".to_string(),
+    })
+}
+
+pub async fn capabilities(_config: &SpeedConfig) -> Result<NodeCapabilities, ApiError> {
+    Ok(NodeCapabilities {
+        bolt11_receive: true,
+        bolt11_send: true,
+        bolt12_receive: false,
+        bolt12_send: false,
+        keysend: false,
+        onchain_receive: false,
+        onchain_send: true,
+        onchain_balance: false,
+        hold_invoices: false,
+        description_hash_invoices: false,
+        zero_amount_invoices: false,
+        lookup_invoice: true,
+        list_transactions: true,
+        channel_management: false,
+        native_event_streaming: false,
+        fiat: false,
+    })
+}
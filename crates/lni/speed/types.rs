@@ -73,6 +73,21 @@ pub struct SpeedPayInvoiceRequest {
     pub external_id: Option<String>,
 }
 
+// Request for Speed's send fee calculation, the same destination and amount /send gets
+#[derive(Debug, Serialize)]
+pub struct SpeedSendFeeRequest {
+    pub amount: f64,
+    pub currency: String,
+    pub target_currency: String,
+    pub withdraw_method: String,
+    pub withdraw_request: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpeedSendFeeResponse {
+    pub speed_fee: SpeedSendFee,
+}
+
 // Response for balance endpoint
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SpeedBalanceResponse {
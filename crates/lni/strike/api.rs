@@ -4,17 +4,21 @@ use std::str::FromStr;
 use lightning_invoice::Bolt11Invoice;
 
 use super::types::{
-    Amount, CreateReceiveRequestRequest, PaymentExecutionResponse, PaymentQuoteRequest,
-    PaymentQuoteResponse, PaymentsResponse, ReceiveRequestBolt11,
-    StrikeReceiveRequestResponse, StrikeReceivesWithCountResponse,
+    Amount, CreateReceiveRequestRequest, ErrorResponse, OnchainPaymentExecutionResponse,
+    OnchainPaymentQuoteRequest, OnchainPaymentQuoteResponse, OnchainPaymentTier,
+    PaymentExecutionResponse, PaymentQuoteAmount, PaymentQuoteRequest, PaymentQuoteResponse,
+    PaymentsResponse, ReceiveRequestBolt11, ReceiveRequestOnchain, StrikeReceiveRequestResponse,
+    StrikeReceivesWithCountResponse,
 };
 use super::StrikeConfig;
 use crate::types::NodeInfo;
 use crate::{
-    ApiError, CreateInvoiceParams, InvoiceType, Offer, OnInvoiceEventCallback, OnInvoiceEventParams,
-    PayInvoiceParams, PayInvoiceResponse, Transaction,
+    retry_after_secs, ApiError, DecodedPaymentRequest, CreateInvoiceParams, Direction, InvoiceType, NodeCapabilities, Offer, OnInvoiceEventCallback,
+    OnInvoiceEventParams, OnchainBalanceResponse, OnchainTransaction, OutgoingPayment, PayInvoiceParams, PayInvoiceResponse,
+    PaymentFeeEstimate, ListTransactionsParams, SendOnchainResponse, Transaction, TransactionPage, TransactionStatus,
 };
 use reqwest::header;
+use serde::{Deserialize, Serialize};
 
 // Docs
 // https://docs.strike.me/api/
@@ -74,6 +78,42 @@ fn get_base_url(config: &StrikeConfig) -> &str {
     config.base_url.as_deref().unwrap_or("https://api.strike.me/v1")
 }
 
+// Map Strike error codes into structured errors, `context` describes what we were doing
+// https://docs.strike.me/api/errors
+fn strike_error(
+    status: reqwest::StatusCode,
+    retry_after: Option<i64>,
+    body: String,
+    context: &str,
+) -> ApiError {
+    let (code, message) = match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(err) => (err.data.code, err.data.message),
+        Err(_) => (String::new(), body),
+    };
+    let reason = if code.is_empty() {
+        format!("{}: {}", context, message)
+    } else {
+        format!("{}: {} {}", context, code, message)
+    };
+    match code.as_str() {
+        "UNAUTHORIZED" | "FORBIDDEN" | "INVALID_SCOPE" => ApiError::Unauthorized { reason },
+        "RATE_LIMIT_EXCEEDED" | "TOO_MANY_ATTEMPTS" => ApiError::RateLimited { retry_after, reason },
+        "BALANCE_TOO_LOW" | "INSUFFICIENT_BALANCE" => ApiError::InsufficientBalance { reason },
+        "PAYMENT_PROCESSING" => ApiError::PaymentInFlight { reason },
+        c if c.contains("EXPIRED") => ApiError::InvoiceExpired { reason },
+        c if c.contains("ROUTE") => ApiError::NoRoute { reason },
+        c if c.contains("ALREADY_PAID") || c.contains("DUPLICATE") => ApiError::AlreadyPaid { reason },
+        _ => ApiError::from_http(status, retry_after, reason),
+    }
+}
+
+async fn error_from_response(response: reqwest::Response, context: &str) -> ApiError {
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    strike_error(status, retry_after, body, context)
+}
+
 pub async fn get_info(config: StrikeConfig) -> Result<NodeInfo, ApiError> {
     let client = async_client(&config);
 
@@ -82,9 +122,7 @@ pub async fn get_info(config: StrikeConfig) -> Result<NodeInfo, ApiError> {
         .get(&format!("{}/balances", get_base_url(&config)))
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "get_info"))?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -161,19 +199,10 @@ pub async fn create_invoice(
                 .json(&create_request)
                 .send()
                 .await
-                .map_err(|e| ApiError::Http {
-                    reason: e.to_string(),
-                })?;
+                .map_err(|e| ApiError::from_reqwest(e, "create_invoice"))?;
 
             if !response.status().is_success() {
-                let status = response.status();
-                let error_text = response.text().await.unwrap_or_default();
-                return Err(ApiError::Http {
-                    reason: format!(
-                        "Failed to create receive request: {} - {}",
-                        status, error_text
-                    ),
-                });
+                return Err(error_from_response(response, "Failed to create receive request").await);
             }
 
             let response_text = response.text().await.unwrap();
@@ -194,6 +223,8 @@ pub async fn create_invoice(
 
             Ok(Transaction {
                 type_: "incoming".to_string(),
+                status: TransactionStatus::Pending,
+                direction: Direction::Incoming,
                 invoice: bolt11_info.invoice,
                 preimage: "".to_string(),
                 payment_hash: bolt11_info.payment_hash,
@@ -212,54 +243,42 @@ pub async fn create_invoice(
                 external_id: Some(receive_request_resp.receive_request_id),
             })
         }
-        InvoiceType::Bolt12 => Err(ApiError::Json {
+        InvoiceType::Bolt12 => Err(ApiError::NotSupported {
             reason: "Bolt12 not implemented for Strike".to_string(),
         }),
     }
 }
 
+// Payment quotes take an amount and state the fee before anything is sent
+pub(crate) const PAY_PARAMS: crate::PayParamSupport = crate::PayParamSupport {
+    amount: true,
+    fee_limit: true,
+    ..crate::PayParamSupport::NONE
+};
+
 pub async fn pay_invoice(
     config: StrikeConfig,
     invoice_params: PayInvoiceParams,
 ) -> Result<PayInvoiceResponse, ApiError> {
+    let fee_limit_msat = crate::check_pay_params(&invoice_params, "Strike", PAY_PARAMS)?;
     let client = async_client(&config);
 
     // Create payment quote first
-    let quote_url = format!("{}/payment-quotes/lightning", get_base_url(&config));
-    let quote_request = PaymentQuoteRequest {
-        ln_invoice: invoice_params.invoice.clone(),
-        source_currency: "BTC".to_string(),
-        amount: invoice_params
-            .amount_msats
-            .map(|amt| super::types::PaymentQuoteAmount {
-                amount: format!("{:.8}", amt as f64 / 100_000_000_000.0),
-                currency: "BTC".to_string(),
-            }),
-    };
-
-    let quote_response = client
-        .post(&quote_url)
-        .json(&quote_request)
-        .send()
-        .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
-
-    if !quote_response.status().is_success() {
-        let status = quote_response.status();
-        let error_text = quote_response.text().await.unwrap_or_default();
-        return Err(ApiError::Http {
-            reason: format!(
-                "Failed to create payment quote: {} - {}",
-                status, error_text
-            ),
-        });
+    let quote_resp = create_payment_quote(&config, &invoice_params.invoice, invoice_params.amount_msats).await?;
+
+    // the quote fixes the fee, so an expensive one is simply never executed
+    if let Some(fee_limit_msat) = fee_limit_msat {
+        let quoted_fee_msats = quoted_fee_msats(&quote_resp);
+        if quoted_fee_msats > fee_limit_msat {
+            return Err(ApiError::NoRoute {
+                reason: format!(
+                    "Strike quoted a {} msat network fee, above the {} msat fee limit",
+                    quoted_fee_msats, fee_limit_msat
+                ),
+            });
+        }
     }
 
-    let quote_text = quote_response.text().await.unwrap();
-    let quote_resp: PaymentQuoteResponse = serde_json::from_str(&quote_text)?;
-
     // Execute the payment quote
     let execute_url = format!(
         "{}/payment-quotes/{}/execute",
@@ -269,16 +288,10 @@ pub async fn pay_invoice(
         .patch(&execute_url)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "pay_invoice"))?;
 
     if !execute_response.status().is_success() {
-        let status = execute_response.status();
-        let error_text = execute_response.text().await.unwrap_or_default();
-        return Err(ApiError::Http {
-            reason: format!("Failed to execute payment: {} - {}", status, error_text),
-        });
+        return Err(error_from_response(execute_response, "Failed to execute payment").await);
     }
 
     let execute_text = execute_response.text().await.unwrap();
@@ -295,16 +308,10 @@ pub async fn pay_invoice(
         .get(&payment_url)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "pay_invoice"))?;
 
     if !payment_response.status().is_success() {
-        let status = payment_response.status();
-        let error_text = payment_response.text().await.unwrap_or_default();
-        return Err(ApiError::Http {
-            reason: format!("Failed to get payment details: {} - {}", status, error_text),
-        });
+        return Err(error_from_response(payment_response, "Failed to get payment details").await);
     }
 
     let payment_text = payment_response.text().await.unwrap();
@@ -333,16 +340,74 @@ pub async fn pay_invoice(
         payment_hash, // Extract from BOLT11 invoice
         preimage: "".to_string(), // Strike doesn't expose preimage
         fee_msats,
+        status: transaction_status(&payment_resp.state),
     })
 }
 
-pub fn decode(_config: &StrikeConfig, str: String) -> Result<String, ApiError> {
-    // Strike doesn't have a decode endpoint, return raw string
-    Ok(str)
+async fn create_payment_quote(
+    config: &StrikeConfig,
+    invoice: &str,
+    amount_msats: Option<i64>,
+) -> Result<PaymentQuoteResponse, ApiError> {
+    let client = async_client(config);
+    let quote_url = format!("{}/payment-quotes/lightning", get_base_url(config));
+    let quote_request = PaymentQuoteRequest {
+        ln_invoice: invoice.to_string(),
+        source_currency: "BTC".to_string(),
+        amount: amount_msats.map(|amt| super::types::PaymentQuoteAmount {
+            amount: format!("{:.8}", amt as f64 / 100_000_000_000.0),
+            currency: "BTC".to_string(),
+        }),
+    };
+
+    let quote_response = client
+        .post(&quote_url)
+        .json(&quote_request)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "Failed to create payment quote"))?;
+
+    if !quote_response.status().is_success() {
+        return Err(error_from_response(quote_response, "Failed to create payment quote").await);
+    }
+
+    let quote_text = quote_response.text().await.map_err(|e| ApiError::Http {
+        reason: format!("Failed to read payment quote: {}", e),
+    })?;
+    Ok(serde_json::from_str(&quote_text)?)
+}
+
+// Quotes are in BTC since that is the source currency
+fn quoted_fee_msats(quote: &PaymentQuoteResponse) -> i64 {
+    let fee = quote.lightning_network_fee.amount.parse::<f64>().unwrap_or(0.0);
+    (fee * 100_000_000_000.0).round() as i64
+}
+
+// An unexecuted quote expires on its own, so asking for one commits to nothing
+pub async fn estimate_payment_fee(
+    config: StrikeConfig,
+    invoice: String,
+    amount_msats: Option<i64>,
+) -> Result<PaymentFeeEstimate, ApiError> {
+    let (decoded, amount) = crate::estimate_target(&invoice, amount_msats)?;
+    let quote_amount = decoded.amount_msats.is_none().then_some(amount);
+    let quote = create_payment_quote(&config, &invoice, quote_amount).await;
+    crate::no_route_estimate(quote.map(|quote| {
+        let fee_msats = quoted_fee_msats(&quote);
+        PaymentFeeEstimate {
+            min_fee_msats: fee_msats,
+            max_fee_msats: fee_msats,
+            route_found: true,
+        }
+    }))
+}
+
+pub fn decode(_config: &StrikeConfig, str: String) -> Result<DecodedPaymentRequest, ApiError> {
+    crate::decode_payment_request(str)
 }
 
 pub fn get_offer(_config: &StrikeConfig, _search: Option<String>) -> Result<Offer, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Strike".to_string(),
     })
 }
@@ -351,7 +416,7 @@ pub async fn list_offers(
     _config: &StrikeConfig,
     _search: Option<String>,
 ) -> Result<Vec<Offer>, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Strike".to_string(),
     })
 }
@@ -362,7 +427,7 @@ pub fn create_offer(
     _description: Option<String>,
     _expiry: Option<i64>,
 ) -> Result<Transaction, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Strike".to_string(),
     })
 }
@@ -373,7 +438,7 @@ pub fn fetch_invoice_from_offer(
     _amount_msats: i64,
     _payer_note: Option<String>,
 ) -> Result<crate::cln::types::FetchInvoiceResponse, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Strike".to_string(),
     })
 }
@@ -384,7 +449,7 @@ pub fn pay_offer(
     _amount_msats: i64,
     _payer_note: Option<String>,
 ) -> Result<PayInvoiceResponse, ApiError> {
-    Err(ApiError::Json {
+    Err(ApiError::NotSupported {
         reason: "Bolt12 not implemented for Strike".to_string(),
     })
 }
@@ -409,9 +474,7 @@ pub async fn lookup_invoice(
         .get(&receives_url)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| ApiError::from_reqwest(e, "lookup_invoice"))?;
 
     if response.status() == reqwest::StatusCode::NOT_FOUND {
         return Err(ApiError::Json {
@@ -420,11 +483,7 @@ pub async fn lookup_invoice(
     }
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(ApiError::Http {
-            reason: format!("Failed to get receives: {} - {}", status, error_text),
-        });
+        return Err(error_from_response(response, "Failed to get receives").await);
     }
 
     let response_text = response.text().await.unwrap();
@@ -455,6 +514,8 @@ pub async fn lookup_invoice(
 
     Ok(Transaction {
         type_: "incoming".to_string(),
+        status: transaction_status(&receive.state),
+        direction: Direction::Incoming,
         invoice: lightning_info.invoice,
         preimage: lightning_info.preimage,
         payment_hash: lightning_info.payment_hash,
@@ -483,94 +544,312 @@ pub async fn lookup_invoice(
     })
 }
 
+// Receives and payments share the same state names
+// Strike has no lookup by payment hash for payments, so recent payments are scanned for it
+// and its current state read from /payments/{id}
+pub async fn lookup_payment(config: StrikeConfig, payment_hash: String) -> Result<OutgoingPayment, ApiError> {
+    const PAGE_SIZE: i64 = 100;
+    const MAX_PAGES: i64 = 10;
+
+    let mut found = None;
+    for page in 0..MAX_PAGES {
+        let rows = fetch_payments(&config, page * PAGE_SIZE, PAGE_SIZE).await?;
+        let last_page = (rows.len() as i64) < PAGE_SIZE;
+        found = rows
+            .into_iter()
+            .find(|(_, txn)| txn.as_ref().is_some_and(|txn| txn.payment_hash == payment_hash));
+        if found.is_some() || last_page {
+            break;
+        }
+    }
+    let Some((payment_id, Some(mut txn))) = found else {
+        return Err(ApiError::Api {
+            reason: format!("Payment not found for payment hash {}", payment_hash),
+        });
+    };
+
+    let payment_url = format!("{}/payments/{}", get_base_url(&config), payment_id);
+    let response = async_client(&config)
+        .get(&payment_url)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "lookup_payment"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response, "Failed to get payment details").await);
+    }
+    let payment_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let payment: PaymentExecutionResponse = serde_json::from_str(&payment_text)?;
+
+    txn.status = transaction_status(&payment.state);
+    if let Some(lightning) = payment.lightning.filter(|l| l.network_fee.currency == "BTC") {
+        txn.fees_paid = (lightning.network_fee.amount.parse::<f64>().unwrap_or(0.0) * 100_000_000_000.0) as i64;
+    }
+    if txn.status == TransactionStatus::Settled && txn.settled_at == 0 {
+        txn.settled_at = payment
+            .completed
+            .as_deref()
+            .and_then(|dt| chrono::DateTime::parse_from_rfc3339(dt).ok())
+            .map(|dt| dt.timestamp())
+            .unwrap_or(0);
+    }
+    Ok(OutgoingPayment::from_transaction(txn, Some(payment.result)))
+}
+
+fn transaction_status(state: &str) -> TransactionStatus {
+    match state {
+        "COMPLETED" => TransactionStatus::Settled,
+        "FAILED" => TransactionStatus::Failed,
+        "EXPIRED" => TransactionStatus::Expired,
+        "CANCELLED" => TransactionStatus::Cancelled,
+        _ => TransactionStatus::Pending,
+    }
+}
+
 pub async fn list_transactions(
     config: StrikeConfig,
     from: i64,
     limit: i64,
     _search: Option<String>,
 ) -> Result<Vec<Transaction>, ApiError> {
-    let client = async_client(&config);
+    let mut transactions: Vec<Transaction> = fetch_receives(&config, from, limit)
+        .await?
+        .into_iter()
+        .filter_map(|(_, txn)| txn)
+        .collect();
+    let payments = fetch_payments(&config, from, limit).await?;
+    transactions.extend(payments.into_iter().filter_map(|(_, txn)| txn));
+
+    // Sort by created date descending
+    transactions.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+
+    Ok(transactions)
+}
+
+// Strike only pages with $skip, which shifts when new transactions arrive. Each list
+// remembers the id of the last row returned, so the next page can find where it left off.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct SkipPosition {
+    pub skip: i64,
+    pub last_id: Option<String>,
+    pub done: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StrikeCursor {
+    receives: SkipPosition,
+    payments: SkipPosition,
+}
+
+// A row of a listing: its id and the transaction, None for rows that aren't lightning
+pub(crate) type StrikeRow = (String, Option<Transaction>);
+
+pub async fn list_transactions_page(
+    config: StrikeConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    crate::search_page(params, |params| transactions_page(config.clone(), params)).await
+}
+
+async fn transactions_page(
+    config: StrikeConfig,
+    params: ListTransactionsParams,
+) -> Result<TransactionPage, ApiError> {
+    let limit = crate::page_limit(params.limit)?;
+    let mut cursor: StrikeCursor = match params.cursor.as_deref() {
+        Some(cursor) => crate::decode_cursor(cursor)?,
+        None => StrikeCursor::default(),
+    };
+
+    let (receives, receives_exhausted) = if cursor.receives.done {
+        (vec![], true)
+    } else {
+        rows_after(&cursor.receives, limit, |skip, top| fetch_receives(&config, skip, top)).await?
+    };
+    let (payments, payments_exhausted) = if cursor.payments.done {
+        (vec![], true)
+    } else {
+        rows_after(&cursor.payments, limit, |skip, top| fetch_payments(&config, skip, top)).await?
+    };
+    let (receive_rows, receives) = split_rows(receives);
+    let (payment_rows, payments) = split_rows(payments);
+
+    let (transactions, taken) = crate::merge_newest_first(vec![receives, payments], limit);
+    advance(&mut cursor.receives, &receive_rows, taken[0], receives_exhausted);
+    advance(&mut cursor.payments, &payment_rows, taken[1], payments_exhausted);
+
+    Ok(TransactionPage {
+        transactions,
+        next_cursor: if cursor.receives.done && cursor.payments.done {
+            None
+        } else {
+            Some(crate::encode_cursor(&cursor))
+        },
+    })
+}
+
+// The rows after `position` with their skip, `limit` of them unless the listing ran out,
+// and whether it did
+pub(crate) async fn rows_after<F, Fut>(
+    position: &SkipPosition,
+    limit: usize,
+    fetch: F,
+) -> Result<(Vec<(i64, StrikeRow)>, bool), ApiError>
+where
+    F: Fn(i64, i64) -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<StrikeRow>, ApiError>>,
+{
+    let mut start = position.skip;
+    if let Some(last_id) = &position.last_id {
+        // New rows push the last one we returned further down, look for it from where it was
+        let mut skip = (position.skip - 1).max(0);
+        loop {
+            let rows = fetch(skip, limit as i64 + 1).await?;
+            if let Some(i) = rows.iter().position(|(id, _)| id == last_id) {
+                start = skip + i as i64 + 1;
+                break;
+            }
+            if rows.len() <= limit {
+                // it is gone, carry on from where it was
+                break;
+            }
+            skip += rows.len() as i64;
+        }
+    }
 
-    // Get receives (incoming) using the receives endpoint similar to lookup_invoice
+    let rows = fetch(start, limit as i64).await?;
+    let exhausted = rows.len() < limit;
+    let rows = rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| (start + i as i64, row))
+        .collect();
+    Ok((rows, exhausted))
+}
+
+// Every fetched row's skip, id and whether it is lightning, and the lightning transactions
+pub(crate) fn split_rows(rows: Vec<(i64, StrikeRow)>) -> (Vec<(i64, String, bool)>, Vec<Transaction>) {
+    let mut positions = vec![];
+    let mut transactions = vec![];
+    for (skip, (id, txn)) in rows {
+        positions.push((skip, id, txn.is_some()));
+        transactions.extend(txn);
+    }
+    (positions, transactions)
+}
+
+// Moves past the `taken` lightning rows and the other rows up to the next lightning one,
+// so a window without any lightning rows is still stepped over
+pub(crate) fn advance(position: &mut SkipPosition, rows: &[(i64, String, bool)], taken: usize, exhausted: bool) {
+    let end = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, _, lightning))| *lightning)
+        .nth(taken)
+        .map_or(rows.len(), |(i, _)| i);
+    if end > 0 {
+        let (skip, id, _) = &rows[end - 1];
+        position.skip = skip + 1;
+        position.last_id = Some(id.clone());
+    }
+    position.done |= exhausted && end == rows.len();
+}
+
+async fn fetch_receives(config: &StrikeConfig, skip: i64, top: i64) -> Result<Vec<StrikeRow>, ApiError> {
+    let client = async_client(config);
     let receives_url = format!(
         "{}/receive-requests/receives?$skip={}&$top={}",
-        get_base_url(&config), from, limit
+        get_base_url(config), skip, top
     );
-    let receives_response =
-        client
-            .get(&receives_url)
-            .send()
-            .await
-            .map_err(|e| ApiError::Http {
-                reason: e.to_string(),
-            })?;
-
-    let mut transactions: Vec<Transaction> = Vec::new();
+    let receives_response = client
+        .get(&receives_url)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "list_transactions"))?;
+    if !receives_response.status().is_success() {
+        return Err(error_from_response(receives_response, "list_transactions").await);
+    }
+    let receives_text = receives_response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let receives_resp: StrikeReceivesWithCountResponse =
+        serde_json::from_str(&receives_text).map_err(|e| ApiError::Json {
+            reason: format!("Failed to parse receives response: {} - Response: {}", e, receives_text),
+        })?;
 
-    if receives_response.status().is_success() {
-        let receives_text = receives_response.text().await.unwrap();
-        let receives_resp: StrikeReceivesWithCountResponse =
-            serde_json::from_str(&receives_text).map_err(|e| ApiError::Json {
-                reason: format!("Failed to parse receives response: {} - Response: {}", e, receives_text),
-            })?;
+    let rows = receives_resp
+        .items
+        .into_iter()
+        .map(|receive| {
+            let id = receive.receive_id.clone();
+            let Some(lightning_info) = receive.lightning else {
+                return (id, None);
+            };
+            // Convert amount to millisatoshis
+            let amount_msats = if receive.amount_received.currency == "BTC" {
+                let btc_amount = receive.amount_received.amount.parse::<f64>().unwrap_or(0.0);
+                (btc_amount * 100_000_000_000.0) as i64
+            } else {
+                0
+            };
 
-        for receive in receives_resp.items {
-            if let Some(lightning_info) = receive.lightning {
-                // Convert amount to millisatoshis
-                let amount_msats = if receive.amount_received.currency == "BTC" {
-                    let btc_amount = receive.amount_received.amount.parse::<f64>().unwrap_or(0.0);
-                    (btc_amount * 100_000_000_000.0) as i64
+            let txn = Transaction {
+                type_: "incoming".to_string(),
+                status: transaction_status(&receive.state),
+                direction: Direction::Incoming,
+                invoice: lightning_info.invoice,
+                preimage: lightning_info.preimage,
+                payment_hash: lightning_info.payment_hash,
+                amount_msats,
+                fees_paid: 0,
+                created_at: chrono::DateTime::parse_from_rfc3339(&receive.created)
+                    .map(|dt| dt.timestamp())
+                    .unwrap_or(0),
+                expires_at: 0, // Not available in receives response
+                settled_at: if receive.state == "COMPLETED" {
+                    receive.completed
+                        .as_ref()
+                        .and_then(|dt| chrono::DateTime::parse_from_rfc3339(dt).ok())
+                        .map(|dt| dt.timestamp())
+                        .unwrap_or(0)
                 } else {
                     0
-                };
-
-                transactions.push(Transaction {
-                    type_: "incoming".to_string(),
-                    invoice: lightning_info.invoice,
-                    preimage: lightning_info.preimage,
-                    payment_hash: lightning_info.payment_hash,
-                    amount_msats,
-                    fees_paid: 0,
-                    created_at: chrono::DateTime::parse_from_rfc3339(&receive.created)
-                        .map(|dt| dt.timestamp())
-                        .unwrap_or(0),
-                    expires_at: 0, // Not available in receives response
-                    settled_at: if receive.state == "COMPLETED" {
-                        receive.completed
-                            .as_ref()
-                            .and_then(|dt| chrono::DateTime::parse_from_rfc3339(dt).ok())
-                            .map(|dt| dt.timestamp())
-                            .unwrap_or(0)
-                    } else {
-                        0
-                    },
-                    description: lightning_info.description.unwrap_or_else(|| {
-                        // If no description, use description_hash if available
-                        lightning_info.description_hash.clone().unwrap_or_default()
-                    }),
-                    description_hash: lightning_info.description_hash.clone().unwrap_or_default(),
-                    payer_note: Some("".to_string()),
-                    external_id: Some(receive.receive_request_id),
-                });
-            }
-        }
-    }
+                },
+                description: lightning_info.description.unwrap_or_else(|| {
+                    // If no description, use description_hash if available
+                    lightning_info.description_hash.clone().unwrap_or_default()
+                }),
+                description_hash: lightning_info.description_hash.clone().unwrap_or_default(),
+                payer_note: Some("".to_string()),
+                external_id: Some(receive.receive_request_id),
+            };
+            (id, Some(txn))
+        })
+        .collect();
+    Ok(rows)
+}
 
-    // Get payments (outgoing)
-    let payments_url = format!("{}/payments?skip={}&top={}", get_base_url(&config), from, limit);
+async fn fetch_payments(config: &StrikeConfig, skip: i64, top: i64) -> Result<Vec<StrikeRow>, ApiError> {
+    let client = async_client(config);
+    let payments_url = format!("{}/payments?skip={}&top={}", get_base_url(config), skip, top);
     let payments_response = client
         .get(&payments_url)
         .send()
         .await
-        .map_err(|e| ApiError::Http {
-            reason: e.to_string(),
-        })?;
-
-    if payments_response.status().is_success() {
-        let payments_text = payments_response.text().await.unwrap();
-        let payments_resp: PaymentsResponse = serde_json::from_str(&payments_text)?;
+        .map_err(|e| ApiError::from_reqwest(e, "list_transactions"))?;
+    if !payments_response.status().is_success() {
+        return Err(error_from_response(payments_response, "list_transactions").await);
+    }
+    let payments_text = payments_response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let payments_resp: PaymentsResponse = serde_json::from_str(&payments_text)?;
 
-        for payment in payments_resp.data {
+    let rows = payments_resp
+        .data
+        .into_iter()
+        .map(|payment| {
             let amount_msats = if payment.amount.currency == "BTC" {
                 let btc_amount = payment.amount.amount.parse::<f64>().unwrap_or(0.0);
                 (btc_amount * 100_000_000_000.0) as i64
@@ -578,23 +857,18 @@ pub async fn list_transactions(
                 0
             };
 
-            let fee_msats = if let Some(lightning) = &payment.lightning {
-                if let Some(network_fee) = &lightning.network_fee {
+            let fee_msats = match payment.lightning.as_ref().and_then(|l| l.network_fee.as_ref()) {
+                Some(network_fee) if network_fee.currency == "BTC" => {
                     let fee_amount = network_fee.amount.parse::<f64>().unwrap_or(0.0);
-                    if network_fee.currency == "BTC" {
-                        (fee_amount * 100_000_000_000.0) as i64
-                    } else {
-                        0
-                    }
-                } else {
-                    0
+                    (fee_amount * 100_000_000_000.0) as i64
                 }
-            } else {
-                0
+                _ => 0,
             };
 
-            transactions.push(Transaction {
+            let txn = Transaction {
                 type_: "outgoing".to_string(),
+                status: transaction_status(&payment.state),
+                direction: Direction::Outgoing,
                 invoice: payment
                     .lightning
                     .as_ref()
@@ -625,15 +899,12 @@ pub async fn list_transactions(
                 description: payment.description.unwrap_or_default(),
                 description_hash: "".to_string(),
                 payer_note: Some("".to_string()),
-                external_id: Some(payment.id),
-            });
-        }
-    }
-
-    // Sort by created date descending
-    transactions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-
-    Ok(transactions)
+                external_id: Some(payment.id.clone()),
+            };
+            (payment.id, Some(txn))
+        })
+        .collect();
+    Ok(rows)
 }
 
 // Core logic shared by both implementations
@@ -657,7 +928,7 @@ where
             params.search.clone(),
         ).await {
             Ok(transaction) => {
-                if transaction.settled_at > 0 {
+                if transaction.status == TransactionStatus::Settled {
                     ("settled".to_string(), Some(transaction))
                 } else {
                     ("pending".to_string(), Some(transaction))
@@ -695,3 +966,152 @@ pub async fn on_invoice_events(
         _ => {}
     }).await;
 }
+
+// On-chain funds land in the same account balance as lightning, see get_info
+pub async fn get_onchain_balance(_config: StrikeConfig) -> Result<OnchainBalanceResponse, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Strike does not keep a separate onchain balance".to_string(),
+    })
+}
+
+pub async fn new_onchain_address(config: StrikeConfig) -> Result<String, ApiError> {
+    let client = async_client(&config);
+    let req_url = format!("{}/receive-requests", get_base_url(&config));
+    let create_request = CreateReceiveRequestRequest {
+        bolt11: None,
+        onchain: Some(ReceiveRequestOnchain { amount: None }),
+        target_currency: Some("BTC".to_string()),
+    };
+    let response = client
+        .post(&req_url)
+        .json(&create_request)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "new_onchain_address"))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response, "Failed to create onchain receive request").await);
+    }
+    let response_text = response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let receive_request_resp: StrikeReceiveRequestResponse = serde_json::from_str(&response_text)?;
+    receive_request_resp
+        .onchain
+        .map(|onchain| onchain.address)
+        .ok_or_else(|| ApiError::Json {
+            reason: "No onchain information in receive request response".to_string(),
+        })
+}
+
+// Strike picks the fee rate, `sat_per_vbyte` is ignored and the cheapest tier is used
+pub async fn send_onchain(
+    config: StrikeConfig,
+    address: String,
+    amount_sats: i64,
+    sat_per_vbyte: Option<i64>,
+) -> Result<SendOnchainResponse, ApiError> {
+    crate::refuse_fee_rate(sat_per_vbyte, "Strike")?;
+    let client = async_client(&config);
+    let mut quote_request = OnchainPaymentQuoteRequest {
+        btc_address: address,
+        source_currency: "BTC".to_string(),
+        amount: PaymentQuoteAmount {
+            amount: format!("{:.8}", amount_sats as f64 / 100_000_000.0),
+            currency: "BTC".to_string(),
+        },
+        onchain_tier_id: None,
+    };
+
+    let tiers_url = format!("{}/payment-quotes/onchain/tiers", get_base_url(&config));
+    let tiers_response = client
+        .post(&tiers_url)
+        .json(&quote_request)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "send_onchain"))?;
+    if !tiers_response.status().is_success() {
+        return Err(error_from_response(tiers_response, "Failed to get onchain payment tiers").await);
+    }
+    let tiers_text = tiers_response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let tiers: Vec<OnchainPaymentTier> = serde_json::from_str(&tiers_text)?;
+    quote_request.onchain_tier_id = tiers
+        .into_iter()
+        .min_by(|a, b| {
+            let fee = |t: &OnchainPaymentTier| t.estimated_fee.amount.parse::<f64>().unwrap_or(f64::MAX);
+            fee(a).total_cmp(&fee(b))
+        })
+        .map(|tier| tier.id);
+
+    let quote_url = format!("{}/payment-quotes/onchain", get_base_url(&config));
+    let quote_response = client
+        .post(&quote_url)
+        .json(&quote_request)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "send_onchain"))?;
+    if !quote_response.status().is_success() {
+        return Err(error_from_response(quote_response, "Failed to create onchain payment quote").await);
+    }
+    let quote_text = quote_response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let quote_resp: OnchainPaymentQuoteResponse = serde_json::from_str(&quote_text)?;
+
+    let execute_url = format!(
+        "{}/payment-quotes/{}/execute",
+        get_base_url(&config), quote_resp.payment_quote_id
+    );
+    let execute_response = client
+        .patch(&execute_url)
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(e, "send_onchain"))?;
+    if !execute_response.status().is_success() {
+        return Err(error_from_response(execute_response, "Failed to execute onchain payment").await);
+    }
+    let execute_text = execute_response.text().await.map_err(|e| ApiError::Http {
+        reason: e.to_string(),
+    })?;
+    let execute_resp: OnchainPaymentExecutionResponse = serde_json::from_str(&execute_text)?;
+
+    Ok(SendOnchainResponse {
+        // broadcast asynchronously, the txid shows up on /payments/{id} later
+        txid: None,
+        external_id: Some(execute_resp.payment_id),
+        fee_sats: quote_resp
+            .total_fee
+            .filter(|fee| fee.currency == "BTC")
+            .map(|fee| (fee.amount.parse::<f64>().unwrap_or(0.0) * 100_000_000.0).round() as i64),
+    })
+}
+
+pub async fn list_onchain_transactions(
+    _config: StrikeConfig,
+) -> Result<Vec<OnchainTransaction>, ApiError> {
+    Err(ApiError::NotSupported {
+        reason: "Onchain transaction history not implemented for Strike".to_string(),
+    })
+}
+
+pub async fn capabilities(_config: StrikeConfig) -> Result<NodeCapabilities, ApiError> {
+    Ok(NodeCapabilities {
+        bolt11_receive: true,
+        bolt11_send: true,
+        bolt12_receive: false,
+        bolt12_send: false,
+        keysend: false,
+        onchain_receive: true,
+        onchain_send: true,
+        onchain_balance: false,
+        hold_invoices: false,
+        description_hash_invoices: true,
+        zero_amount_invoices: true,
+        lookup_invoice: true,
+        list_transactions: true,
+        channel_management: false,
+        native_event_streaming: false,
+        fiat: true,
+    })
+}
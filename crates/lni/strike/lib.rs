@@ -4,7 +4,10 @@ use napi_derive::napi;
 use crate::types::NodeInfo;
 use crate::{
     ApiError, CreateInvoiceParams, CreateOfferParams, ListTransactionsParams, LookupInvoiceParams,
-    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction,
+    Offer, PayInvoiceParams, PayInvoiceResponse, Transaction, Channel, CloseChannelRequest,
+    CloseChannelResponse, OpenChannelRequest, OpenChannelResponse, UpdateChannelRequest,
+    OnchainBalanceResponse, OnchainTransaction, SendOnchainResponse, PayKeysendResponse, TLVRecord,
+    NodeCapabilities,
 };
 #[cfg(not(feature = "uniffi"))]
 use crate::LightningNode;
@@ -59,6 +62,10 @@ impl StrikeNode {
         crate::strike::api::get_info(self.config.clone()).await
     }
 
+    pub async fn capabilities(&self) -> Result<NodeCapabilities, ApiError> {
+        crate::strike::api::capabilities(self.config.clone()).await
+    }
+
     pub async fn create_invoice(&self, params: CreateInvoiceParams) -> Result<Transaction, ApiError> {
         crate::strike::api::create_invoice(self.config.clone(), params).await
     }
@@ -67,8 +74,43 @@ impl StrikeNode {
         crate::strike::api::pay_invoice(self.config.clone(), params).await
     }
 
+    pub async fn estimate_payment_fee(
+        &self,
+        invoice: String,
+        amount_msats: Option<i64>,
+    ) -> Result<crate::PaymentFeeEstimate, ApiError> {
+        crate::strike::api::estimate_payment_fee(self.config.clone(), invoice, amount_msats).await
+    }
+
+    pub async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: crate::PayOptions,
+    ) -> Result<crate::PayResponse, ApiError> {
+        crate::lnurl::pay(self, destination, amount_msats, options).await
+    }
+
+    pub async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: crate::ZapRequestParams,
+    ) -> Result<crate::ZapResponse, ApiError> {
+        crate::lnurl::zap(self, destination, amount_msats, params).await
+    }
+
+    pub async fn pay_keysend(
+        &self,
+        _destination_pubkey: String,
+        _amount_msats: i64,
+        _custom_records: Vec<TLVRecord>,
+    ) -> Result<PayKeysendResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Keysend is not available for custodial StrikeNode".to_string() })
+    }
+
     pub async fn create_offer(&self, _params: CreateOfferParams) -> Result<Offer, ApiError> {
-        Err(ApiError::Api { reason: "create_offer not implemented for StrikeNode".to_string() })
+        Err(ApiError::NotSupported { reason: "create_offer not implemented for StrikeNode".to_string() })
     }
 
     pub async fn lookup_invoice(
@@ -85,6 +127,10 @@ impl StrikeNode {
         .await
     }
 
+    pub async fn lookup_payment(&self, payment_hash: String) -> Result<crate::OutgoingPayment, ApiError> {
+        crate::strike::api::lookup_payment(self.config.clone(), payment_hash).await
+    }
+
     pub async fn list_transactions(
         &self,
         params: ListTransactionsParams,
@@ -98,7 +144,14 @@ impl StrikeNode {
         .await
     }
 
-    pub async fn decode(&self, str: String) -> Result<String, ApiError> {
+    pub async fn list_transactions_page(
+        &self,
+        params: ListTransactionsParams,
+    ) -> Result<crate::TransactionPage, ApiError> {
+        crate::strike::api::list_transactions_page(self.config.clone(), params).await
+    }
+
+    pub async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, ApiError> {
         crate::strike::api::decode(&self.config, str)
     }
 
@@ -119,17 +172,83 @@ impl StrikeNode {
         crate::strike::api::pay_offer(&self.config, offer, amount_msats, payer_note)
     }
 
-    pub async fn on_invoice_events(
+    pub fn on_invoice_events(
         &self,
         params: crate::types::OnInvoiceEventParams,
         callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-    ) {
-        crate::strike::api::on_invoice_events(self.config.clone(), params, callback).await
+    ) -> std::sync::Arc<crate::Subscription> {
+        let config = self.config.clone();
+        crate::Subscription::invoice_events(callback, |callback| {
+            crate::strike::api::on_invoice_events(config, params, callback)
+        })
+    }
+
+    pub fn on_payment_events(
+        &self,
+        params: crate::types::OnPaymentEventParams,
+        callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No payment feed, the payment is looked up every polling_delay_sec
+        let node = self.clone();
+        crate::Subscription::payment_events(callback, |callback| async move {
+            let payment_hash = params.payment_hash.clone();
+            crate::poll_payment_events(|| node.lookup_payment(payment_hash.clone()), params, callback.as_ref()).await
+        })
+    }
+
+    pub fn subscribe_all(
+        &self,
+        params: crate::types::SubscribeAllParams,
+        callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+    ) -> std::sync::Arc<crate::Subscription> {
+        // No account feed, changes are picked up by diffing list_transactions
+        let node = self.clone();
+        crate::Subscription::subscribe_all(callback, |callback| async move {
+            crate::events::poll_all(&node, params, callback.as_ref()).await
+        })
+    }
+
+    pub async fn list_channels(&self) -> Result<Vec<Channel>, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for custodial StrikeNode".to_string() })
+    }
+
+    pub async fn open_channel(&self, _params: OpenChannelRequest) -> Result<OpenChannelResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for custodial StrikeNode".to_string() })
+    }
+
+    pub async fn close_channel(&self, _params: CloseChannelRequest) -> Result<CloseChannelResponse, ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for custodial StrikeNode".to_string() })
+    }
+
+    pub async fn update_channel_policy(&self, _params: UpdateChannelRequest) -> Result<(), ApiError> {
+        Err(ApiError::NotSupported { reason: "Channel management is not available for custodial StrikeNode".to_string() })
+    }
+
+    pub async fn get_onchain_balance(&self) -> Result<OnchainBalanceResponse, ApiError> {
+        crate::strike::api::get_onchain_balance(self.config.clone()).await
+    }
+
+    pub async fn new_onchain_address(&self) -> Result<String, ApiError> {
+        crate::strike::api::new_onchain_address(self.config.clone()).await
+    }
+
+    pub async fn send_onchain(
+        &self,
+        address: String,
+        amount_sats: i64,
+        sat_per_vbyte: Option<i64>,
+    ) -> Result<SendOnchainResponse, ApiError> {
+        crate::strike::api::send_onchain(self.config.clone(), address, amount_sats, sat_per_vbyte).await
+    }
+
+    pub async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, ApiError> {
+        crate::strike::api::list_onchain_transactions(self.config.clone()).await
     }
 }
 
 // Trait implementation for polymorphic access via Arc<dyn LightningNode>
 crate::impl_lightning_node!(StrikeNode);
+impl crate::LightningNodeEvents for StrikeNode {}
 
 #[cfg(test)]
 mod tests {
@@ -271,6 +390,7 @@ mod tests {
             search: None,
             created_after: None,
             created_before: None,
+            cursor: None,
         };
         match NODE.list_transactions(params).await {
             Ok(txns) => {
@@ -336,7 +456,7 @@ mod tests {
         };
 
         // Start the event listener
-        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).await;
+        NODE.on_invoice_events(params, std::sync::Arc::new(callback)).wait().await;
 
         // Check that some events were captured
         let events_guard = events.lock().unwrap();
@@ -377,4 +497,49 @@ mod tests {
             node_with_proxy.config.socks5_proxy, node_with_proxy.config.accept_invalid_certs
         );
     }
+
+    #[tokio::test]
+    async fn test_skip_cursor_steps_over_non_lightning_rows() {
+        use crate::strike::api::{advance, rows_after, split_rows, SkipPosition, StrikeRow};
+        // three on-chain receives, then a lightning one and another on-chain one
+        let listing: Vec<StrikeRow> = (0..5)
+            .map(|i| {
+                let txn = (i == 3).then(|| Transaction {
+                    type_: "incoming".to_string(),
+                    status: crate::TransactionStatus::Settled,
+                    direction: crate::Direction::Incoming,
+                    invoice: "".to_string(),
+                    preimage: "".to_string(),
+                    payment_hash: "ln".to_string(),
+                    amount_msats: 1000,
+                    fees_paid: 0,
+                    created_at: 100,
+                    expires_at: 0,
+                    settled_at: 100,
+                    description: "".to_string(),
+                    description_hash: "".to_string(),
+                    payer_note: None,
+                    external_id: None,
+                });
+                (format!("r{}", i), txn)
+            })
+            .collect();
+        let fetch = |skip: i64, top: i64| {
+            let rows = listing.iter().skip(skip as usize).take(top as usize).cloned().collect();
+            async move { Ok(rows) }
+        };
+
+        let mut position = SkipPosition::default();
+        let (rows, exhausted) = rows_after(&position, 3, fetch).await.unwrap();
+        let (rows, transactions) = split_rows(rows);
+        assert!(transactions.is_empty());
+        advance(&mut position, &rows, 0, exhausted);
+        assert_eq!((position.skip, position.last_id.as_deref(), position.done), (3, Some("r2"), false));
+
+        let (rows, exhausted) = rows_after(&position, 3, fetch).await.unwrap();
+        let (rows, transactions) = split_rows(rows);
+        assert_eq!(transactions.len(), 1);
+        advance(&mut position, &rows, 1, exhausted);
+        assert_eq!((position.skip, position.done), (5, true));
+    }
 }
@@ -207,6 +207,40 @@ pub struct PaymentExecutionResponse {
     pub lightning: Option<LightningDetails>,
 }
 
+// https://docs.strike.me/api/get-on-chain-payment-tiers
+#[derive(Debug, Deserialize)]
+pub struct OnchainPaymentTier {
+    pub id: String,
+    #[serde(rename = "estimatedFee")]
+    pub estimated_fee: Amount,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OnchainPaymentQuoteRequest {
+    #[serde(rename = "btcAddress")]
+    pub btc_address: String,
+    #[serde(rename = "sourceCurrency")]
+    pub source_currency: String,
+    pub amount: PaymentQuoteAmount,
+    #[serde(rename = "onchainTierId", skip_serializing_if = "Option::is_none")]
+    pub onchain_tier_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnchainPaymentQuoteResponse {
+    #[serde(rename = "paymentQuoteId")]
+    pub payment_quote_id: String,
+    #[serde(rename = "totalFee")]
+    pub total_fee: Option<Amount>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnchainPaymentExecutionResponse {
+    #[serde(rename = "paymentId")]
+    pub payment_id: String,
+    pub state: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PaymentExecution {
     pub payment_id: String,
@@ -0,0 +1,266 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "napi_rs")]
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tokio::task::AbortHandle;
+
+use crate::types::{OnInvoiceEventCallback, OnPaymentEventCallback, SubscribeAllCallback};
+use crate::{ApiError, OutgoingPayment, Transaction};
+
+/// How a subscription ended, the callbacks already carried the details
+#[cfg_attr(feature = "napi_rs", napi(string_enum))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(feature = "napi_rs"), derive(Clone, Copy))]
+pub enum SubscriptionOutcome {
+    Success,   // the watched invoice settled
+    Failure,   // timed out or the backend gave up
+    Cancelled, // cancel() was called first
+    TimedOut,  // the watched payment was still in flight when max_polling_sec passed
+}
+
+/// Handle to a running event watcher. Dropping it leaves the watcher running, call
+/// `cancel()` to stop it.
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+#[derive(Debug)]
+pub struct Subscription {
+    task: AbortHandle,
+    outcome: watch::Sender<Option<SubscriptionOutcome>>,
+}
+
+impl Subscription {
+    /// Runs `watcher` on the shared runtime, its output becomes the completion result
+    pub fn spawn<F>(watcher: F) -> Arc<Self>
+    where
+        F: Future<Output = SubscriptionOutcome> + Send + 'static,
+    {
+        let (outcome, _) = watch::channel(None);
+        let finished = outcome.clone();
+        let task = crate::TOKIO_RUNTIME.spawn(async move {
+            finish(&finished, watcher.await);
+        });
+        Arc::new(Self {
+            task: task.abort_handle(),
+            outcome,
+        })
+    }
+
+    /// Spawns an invoice watcher, the outcome follows its last success or failure callback
+    pub fn invoice_events<F, Fut>(callback: Arc<dyn OnInvoiceEventCallback>, watch: F) -> Arc<Self>
+    where
+        F: FnOnce(Arc<dyn OnInvoiceEventCallback>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let recorder = OutcomeRecorder::new(callback);
+        let watcher = watch(recorder.clone());
+        Self::recorded(recorder, watcher)
+    }
+
+    /// Same as `invoice_events` for a payment watcher
+    pub fn payment_events<F, Fut>(callback: Arc<dyn OnPaymentEventCallback>, watch: F) -> Arc<Self>
+    where
+        F: FnOnce(Arc<dyn OnPaymentEventCallback>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let recorder = OutcomeRecorder::new(callback);
+        let watcher = watch(recorder.clone());
+        Self::recorded(recorder, watcher)
+    }
+
+    fn recorded<C, Fut>(recorder: Arc<OutcomeRecorder<C>>, watcher: Fut) -> Arc<Self>
+    where
+        C: ?Sized + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self::spawn(async move {
+            watcher.await;
+            let last = recorder.last.lock().map(|last| *last);
+            last.unwrap_or(SubscriptionOutcome::Failure)
+        })
+    }
+
+    /// Spawns an account feed, it runs until cancelled unless it hits an error retrying won't fix
+    pub fn subscribe_all<F, Fut>(callback: Arc<dyn SubscribeAllCallback>, watch: F) -> Arc<Self>
+    where
+        F: FnOnce(Arc<dyn SubscribeAllCallback>) -> Fut,
+        Fut: Future<Output = Result<(), ApiError>> + Send + 'static,
+    {
+        let watcher = watch(callback.clone());
+        Self::spawn(async move {
+            match watcher.await {
+                Ok(()) => SubscriptionOutcome::Success,
+                Err(e) => {
+                    callback.error(e.to_string());
+                    SubscriptionOutcome::Failure
+                }
+            }
+        })
+    }
+}
+
+#[cfg_attr(feature = "uniffi", uniffi::export(async_runtime = "tokio"))]
+impl Subscription {
+    /// Stops the watcher, no callbacks fire afterwards. A no-op once it has finished.
+    pub fn cancel(&self) {
+        self.task.abort();
+        finish(&self.outcome, SubscriptionOutcome::Cancelled);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.outcome.borrow().is_none()
+    }
+
+    /// Resolves once the watcher finished or was cancelled
+    pub async fn wait(&self) -> SubscriptionOutcome {
+        let mut outcome = self.outcome.subscribe();
+        // the sender lives in self, so waiting can't fail while we are borrowed
+        let current = outcome.wait_for(Option::is_some).await.map(|current| *current);
+        current.ok().flatten().unwrap_or(SubscriptionOutcome::Failure)
+    }
+}
+
+// First result wins, so cancel() racing a finishing watcher keeps whichever came first
+fn finish(outcome: &watch::Sender<Option<SubscriptionOutcome>>, result: SubscriptionOutcome) {
+    outcome.send_if_modified(|current| {
+        if current.is_some() {
+            return false;
+        }
+        *current = Some(result);
+        true
+    });
+}
+
+// Passes callbacks through and remembers the last final status
+struct OutcomeRecorder<C: ?Sized> {
+    inner: Arc<C>,
+    last: Mutex<SubscriptionOutcome>,
+}
+
+impl<C: ?Sized> OutcomeRecorder<C> {
+    fn new(inner: Arc<C>) -> Arc<Self> {
+        Arc::new(Self {
+            inner,
+            last: Mutex::new(SubscriptionOutcome::Failure),
+        })
+    }
+
+    fn record(&self, outcome: SubscriptionOutcome) {
+        *self.last.lock().unwrap() = outcome;
+    }
+}
+
+impl OnInvoiceEventCallback for OutcomeRecorder<dyn OnInvoiceEventCallback> {
+    fn success(&self, transaction: Option<Transaction>) {
+        self.record(SubscriptionOutcome::Success);
+        self.inner.success(transaction);
+    }
+
+    fn pending(&self, transaction: Option<Transaction>) {
+        self.inner.pending(transaction);
+    }
+
+    fn failure(&self, transaction: Option<Transaction>) {
+        self.record(SubscriptionOutcome::Failure);
+        self.inner.failure(transaction);
+    }
+}
+
+impl OnPaymentEventCallback for OutcomeRecorder<dyn OnPaymentEventCallback> {
+    fn success(&self, payment: Option<OutgoingPayment>) {
+        self.record(SubscriptionOutcome::Success);
+        self.inner.success(payment);
+    }
+
+    fn pending(&self, payment: Option<OutgoingPayment>) {
+        // a payment watcher stops after a pending update only when it ran out of time
+        self.record(SubscriptionOutcome::TimedOut);
+        self.inner.pending(payment);
+    }
+
+    fn failure(&self, payment: Option<OutgoingPayment>) {
+        self.record(SubscriptionOutcome::Failure);
+        self.inner.failure(payment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl OnInvoiceEventCallback for Recorder {
+        fn success(&self, _: Option<Transaction>) {
+            self.0.lock().unwrap().push("success".to_string());
+        }
+        fn pending(&self, _: Option<Transaction>) {
+            self.0.lock().unwrap().push("pending".to_string());
+        }
+        fn failure(&self, _: Option<Transaction>) {
+            self.0.lock().unwrap().push("failure".to_string());
+        }
+    }
+
+    impl OnPaymentEventCallback for Recorder {
+        fn success(&self, _: Option<OutgoingPayment>) {
+            self.0.lock().unwrap().push("success".to_string());
+        }
+        fn pending(&self, _: Option<OutgoingPayment>) {
+            self.0.lock().unwrap().push("pending".to_string());
+        }
+        fn failure(&self, _: Option<OutgoingPayment>) {
+            self.0.lock().unwrap().push("failure".to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription_completes() {
+        let events = Arc::new(Recorder(Mutex::new(Vec::new())));
+        let subscription = Subscription::invoice_events(events.clone(), |callback| async move {
+            callback.pending(None);
+            callback.success(None);
+        });
+        assert_eq!(subscription.wait().await, SubscriptionOutcome::Success);
+        assert!(!subscription.is_active());
+        // cancelling a finished subscription keeps its result
+        subscription.cancel();
+        assert_eq!(subscription.wait().await, SubscriptionOutcome::Success);
+        assert_eq!(*events.0.lock().unwrap(), vec!["pending", "success"]);
+    }
+
+    #[tokio::test]
+    async fn test_payment_still_in_flight_times_out() {
+        let events = Arc::new(Recorder(Mutex::new(Vec::new())));
+        let subscription = Subscription::payment_events(events.clone(), |callback| async move {
+            callback.pending(None);
+            callback.pending(None);
+        });
+        assert_eq!(subscription.wait().await, SubscriptionOutcome::TimedOut);
+        assert_eq!(*events.0.lock().unwrap(), vec!["pending", "pending"]);
+    }
+
+    #[tokio::test]
+    async fn test_subscription_cancel() {
+        let events = Arc::new(Recorder(Mutex::new(Vec::new())));
+        let subscription = Subscription::invoice_events(events.clone(), |callback| async move {
+            loop {
+                callback.pending(None);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(subscription.is_active());
+        subscription.cancel();
+        assert!(!subscription.is_active());
+        assert_eq!(subscription.wait().await, SubscriptionOutcome::Cancelled);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let seen = events.0.lock().unwrap().len();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(events.0.lock().unwrap().len(), seen);
+    }
+}
@@ -21,8 +21,36 @@ pub enum LightningNodeEnum {
 #[async_trait]
 pub trait LightningNode: Send + Sync {
     async fn get_info(&self) -> Result<crate::NodeInfo, crate::ApiError>;
+    async fn capabilities(&self) -> Result<NodeCapabilities, crate::ApiError>;
     async fn create_invoice(&self, params: CreateInvoiceParams) -> Result<Transaction, crate::ApiError>;
     async fn pay_invoice(&self, params: PayInvoiceParams) -> Result<PayInvoiceResponse, crate::ApiError>;
+    // Pays a BOLT11 invoice, BOLT12 offer, LNURL or Lightning Address, `amount_msats` is
+    // required unless the destination carries its own amount
+    async fn pay(
+        &self,
+        destination: String,
+        amount_msats: Option<i64>,
+        options: PayOptions,
+    ) -> Result<PayResponse, crate::ApiError>;
+    // Zaps a Lightning Address or LNURL-pay whose service allows nostr (NIP-57)
+    async fn zap(
+        &self,
+        destination: String,
+        amount_msats: i64,
+        params: ZapRequestParams,
+    ) -> Result<ZapResponse, crate::ApiError>;
+    // What paying the invoice would cost, `amount_msats` is for zero amount invoices
+    async fn estimate_payment_fee(
+        &self,
+        invoice: String,
+        amount_msats: Option<i64>,
+    ) -> Result<PaymentFeeEstimate, crate::ApiError>;
+    async fn pay_keysend(
+        &self,
+        destination_pubkey: String,
+        amount_msats: i64,
+        custom_records: Vec<TLVRecord>,
+    ) -> Result<PayKeysendResponse, crate::ApiError>;
     async fn create_offer(&self, params: CreateOfferParams) -> Result<Offer, crate::ApiError>;
     async fn get_offer(&self, search: Option<String>) -> Result<Offer, crate::ApiError>;
     async fn list_offers(&self, search: Option<String>) -> Result<Vec<Offer>, crate::ApiError>;
@@ -33,16 +61,47 @@ pub trait LightningNode: Send + Sync {
         payer_note: Option<String>,
     ) -> Result<PayInvoiceResponse, crate::ApiError>;
     async fn lookup_invoice(&self, params: LookupInvoiceParams) -> Result<crate::Transaction, crate::ApiError>;
+    async fn lookup_payment(&self, payment_hash: String) -> Result<OutgoingPayment, crate::ApiError>;
     async fn list_transactions(
         &self,
         params: ListTransactionsParams,
     ) -> Result<Vec<crate::Transaction>, crate::ApiError>;
-    async fn decode(&self, str: String) -> Result<String, crate::ApiError>;
-    async fn on_invoice_events(
+    async fn list_transactions_page(
+        &self,
+        params: ListTransactionsParams,
+    ) -> Result<TransactionPage, crate::ApiError>;
+    async fn decode(&self, str: String) -> Result<crate::DecodedPaymentRequest, crate::ApiError>;
+    // Returns right away, the watcher runs until settled, timed out or cancelled
+    fn on_invoice_events(
         &self,
         params: crate::types::OnInvoiceEventParams,
         callback: std::sync::Arc<dyn crate::types::OnInvoiceEventCallback>,
-    );
+    ) -> std::sync::Arc<crate::Subscription>;
+    // Follows a payment we sent until it settles, fails, times out or is cancelled
+    fn on_payment_events(
+        &self,
+        params: crate::types::OnPaymentEventParams,
+        callback: std::sync::Arc<dyn crate::types::OnPaymentEventCallback>,
+    ) -> std::sync::Arc<crate::Subscription>;
+    // Every incoming settlement and outgoing completion or failure until cancelled
+    fn subscribe_all(
+        &self,
+        params: crate::types::SubscribeAllParams,
+        callback: std::sync::Arc<dyn crate::types::SubscribeAllCallback>,
+    ) -> std::sync::Arc<crate::Subscription>;
+    async fn list_channels(&self) -> Result<Vec<Channel>, crate::ApiError>;
+    async fn open_channel(&self, params: OpenChannelRequest) -> Result<OpenChannelResponse, crate::ApiError>;
+    async fn close_channel(&self, params: CloseChannelRequest) -> Result<CloseChannelResponse, crate::ApiError>;
+    async fn update_channel_policy(&self, params: UpdateChannelRequest) -> Result<(), crate::ApiError>;
+    async fn get_onchain_balance(&self) -> Result<OnchainBalanceResponse, crate::ApiError>;
+    async fn new_onchain_address(&self) -> Result<String, crate::ApiError>;
+    async fn send_onchain(
+        &self,
+        address: String,
+        amount_sats: i64,
+        sat_per_vbyte: Option<i64>,
+    ) -> Result<SendOnchainResponse, crate::ApiError>;
+    async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, crate::ApiError>;
 }
 
 #[cfg_attr(feature = "napi_rs", napi(string_enum))]
@@ -56,13 +115,16 @@ pub enum InvoiceType {
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TLVRecord {
     #[serde(rename = "type")]
-    pub type_: i64,
+    pub type_: i64, // custom records use types >= 65536
     // hex-encoded value
     pub value: String,
 }
+
+/// TLV type carrying the preimage of a keysend payment
+pub const KEYSEND_PREIMAGE_TLV_TYPE: i64 = 5482373484;
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[derive(Debug, Serialize, Deserialize)]
@@ -101,11 +163,60 @@ impl Default for NodeInfo {
     }
 }
 
+/// Lifecycle state of an invoice or payment, normalized across backends
+#[cfg_attr(feature = "napi_rs", napi(string_enum))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(feature = "napi_rs"), derive(Clone, Copy))]
+pub enum TransactionStatus {
+    Pending,
+    Settled,
+    Failed,
+    Expired,
+    Cancelled,
+}
+
+impl TransactionStatus {
+    pub fn is_final(&self) -> bool {
+        !matches!(self, TransactionStatus::Pending)
+    }
+
+    /// Status of an unpaid invoice given its absolute expiry (unix seconds, 0 = unknown)
+    pub fn unpaid(expires_at: i64) -> Self {
+        if expires_at > 0 && expires_at < chrono::Utc::now().timestamp() {
+            TransactionStatus::Expired
+        } else {
+            TransactionStatus::Pending
+        }
+    }
+}
+
+#[cfg_attr(feature = "napi_rs", napi(string_enum))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(feature = "napi_rs"), derive(Clone, Copy))]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+impl Direction {
+    /// Legacy string form used by `Transaction::type_`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Incoming => "incoming",
+            Direction::Outgoing => "outgoing",
+        }
+    }
+}
+
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Transaction {
-    pub type_: String,
+    pub type_: String, // "incoming" or "outgoing", kept for compatibility; prefer `direction`
+    pub status: TransactionStatus,
+    pub direction: Direction,
     pub invoice: String,
     pub description: String,
     pub description_hash: String,
@@ -115,11 +226,55 @@ pub struct Transaction {
     pub fees_paid: i64,
     pub created_at: i64,
     pub expires_at: i64,
-    pub settled_at: i64, // 0 means not paid yet TODO maybe add status field
+    pub settled_at: i64, // 0 means not paid yet, see `status`
     pub payer_note: Option<String>,  // used in bolt12 (on phoenixd)
     pub external_id: Option<String>, // used in bolt11 (on phoenixd)
 }
 
+// Result of `LightningNode::decode`, decoded locally so it is the same on every backend
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DecodedPaymentRequest {
+    pub invoice_type: InvoiceType,
+    pub invoice: String,
+    pub network: String,           // "bitcoin", "testnet", "signet" or "regtest"
+    pub amount_msats: Option<i64>, // None for zero amount invoices
+    pub payment_hash: String,
+    pub payment_secret: Option<String>,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub payee_pubkey: String,
+    pub created_at: i64,
+    pub expiry: i64,     // seconds after created_at
+    pub expires_at: i64, // created_at + expiry
+    pub min_final_cltv_expiry_delta: i64,
+    pub route_hints: Vec<RouteHint>,
+    pub features: Vec<i64>, // feature bits set in the invoice (BOLT 9)
+    pub payer_note: Option<String>,                 // BOLT12 invoices only
+    pub offer: Option<crate::bolt12::Bolt12Offer>, // set for BOLT12 offers and invoices
+}
+
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RouteHint {
+    pub hops: Vec<RouteHintHop>,
+}
+
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RouteHintHop {
+    pub pubkey: String,
+    pub short_channel_id: String, // "blockxtxxoutput"
+    pub fee_base_msat: i64,
+    pub fee_proportional_millionths: i64,
+    pub cltv_expiry_delta: i64,
+    pub htlc_minimum_msat: Option<i64>,
+    pub htlc_maximum_msat: Option<i64>,
+}
+
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[derive(Debug, Serialize, Deserialize)]
@@ -131,12 +286,14 @@ pub struct NodeConnectionInfo {
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Channel {
-    pub local_balance: i64,
-    pub local_spendable_balance: i64,
-    pub remote_balance: i64,
-    pub id: String,
+    pub local_balance: i64,           // msats
+    pub local_spendable_balance: i64, // msats, local balance minus our reserve
+    pub remote_balance: i64,          // msats
+    pub capacity: i64,                // msats
+    pub id: String, // backend channel id, accepted by close_channel and update_channel_policy
+    pub short_channel_id: Option<String>, // "blockxtxxout", None until confirmed
     pub remote_pubkey: String,
     pub funding_tx_id: String,
     pub funding_tx_vout: i64,
@@ -146,8 +303,8 @@ pub struct Channel {
     pub confirmations: i64,
     pub confirmations_required: i64,
     pub forwarding_fee_base_msat: i64,
-    pub unspendable_punishment_reserve: i64,
-    pub counterparty_unspendable_punishment_reserve: i64,
+    pub unspendable_punishment_reserve: i64, // sats
+    pub counterparty_unspendable_punishment_reserve: i64, // sats
     pub error: String,
     pub is_outbound: bool,
 }
@@ -177,6 +334,9 @@ pub struct OpenChannelRequest {
     pub pubkey: String,
     pub amount_msats: i64,
     pub public: bool,
+    pub host: Option<String>, // "host:port", connects to the peer first when set
+    pub push_msats: Option<i64>,
+    pub sat_per_vbyte: Option<i64>,
 }
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
@@ -184,6 +344,8 @@ pub struct OpenChannelRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OpenChannelResponse {
     pub funding_tx_id: String,
+    pub funding_tx_vout: i64,
+    pub channel_id: Option<String>,
 }
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
@@ -192,7 +354,9 @@ pub struct OpenChannelResponse {
 pub struct CloseChannelRequest {
     pub channel_id: String,
     pub node_id: String,
-    pub force: bool,
+    pub force: bool, // unilateral close instead of a cooperative one
+    pub address: Option<String>, // on-chain address to send our funds to
+    pub sat_per_vbyte: Option<i64>,
 }
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
@@ -203,12 +367,18 @@ pub struct UpdateChannelRequest {
     pub node_id: String,
     pub forwarding_fee_base_msat: i64,
     pub max_dust_htlc_exposure_from_fee_rate_multiplier: i64,
+    pub fee_rate_ppm: Option<i64>,
+    pub cltv_expiry_delta: Option<i64>,
+    pub min_htlc_msat: Option<i64>,
+    pub max_htlc_msat: Option<i64>,
 }
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[derive(Debug, Serialize, Deserialize)]
-pub struct CloseChannelResponse {}
+pub struct CloseChannelResponse {
+    pub closing_tx_id: Option<String>,
+}
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
@@ -225,14 +395,37 @@ pub struct PendingBalanceDetails {
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OnchainBalanceResponse {
-    pub spendable: i64,
-    pub total: i64,
-    pub reserved: i64,
-    pub pending_balances_from_channel_closures: i64,
+    pub spendable: i64, // sats, confirmed and not reserved
+    pub total: i64,     // sats, including unconfirmed
+    pub reserved: i64,  // sats, locked or kept back for anchor channels
+    pub pending_balances_from_channel_closures: i64, // sats
     pub pending_balances_details: Vec<PendingBalanceDetails>,
     pub internal_balances: String, // serde_json::Value,
 }
 
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OnchainTransaction {
+    pub txid: String,
+    pub direction: Direction,
+    pub amount_sats: i64,
+    pub fee_sats: i64,
+    pub confirmations: i64,
+    pub block_height: i64,
+    pub created_at: i64,
+    pub address: Option<String>,
+}
+
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SendOnchainResponse {
+    pub txid: Option<String>,        // None when a custodial backend broadcasts later
+    pub external_id: Option<String>, // custodial payment id
+    pub fee_sats: Option<i64>,
+}
+
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[derive(Debug, Serialize, Deserialize)]
@@ -262,13 +455,121 @@ pub struct PayInvoiceResponse {
     pub payment_hash: String,
     pub preimage: String,
     pub fee_msats: i64,
+    pub status: TransactionStatus, // Settled, or Pending while the payment is still in flight
+}
+
+/// Options for `LightningNode::pay`, the fee and timeout fields work as in `PayInvoiceParams`
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayOptions {
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub fee_limit_msat: Option<i64>,
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub fee_limit_percentage: Option<f64>,
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub timeout_seconds: Option<i64>,
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub payer_note: Option<String>, // sent with BOLT12 offers, and as the comment to LNURL-pay services
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub payer_data: Option<crate::lnurl::PayerData>, // LNURL-pay only, sent when the service asks for it
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub wait: Option<bool>,
+}
+
+/// Result of `LightningNode::pay`, the same whatever the destination was
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayResponse {
+    pub destination_type: String, // bolt11, bolt12, lnurl or lightning_address
+    pub invoice: String,          // what got paid: the offer, or the BOLT11 invoice LNURL resolved to
+    pub payment_hash: String,
+    pub preimage: String,
+    pub fee_msats: i64,
+    pub status: TransactionStatus,
+    pub success_action: Option<crate::lnurl::SuccessAction>, // LNURL-pay only, aes messages come decrypted
+}
+
+/// The NIP-57 zap request `LightningNode::zap` signs and sends along with the payment
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZapRequestParams {
+    pub recipient_pubkey: String, // hex or npub of who gets zapped
+    pub relays: Vec<String>,      // where the service publishes the zap receipt
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub comment: Option<String>,
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub event_id: Option<String>, // hex or note1 of the event being zapped
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub event_coordinate: Option<String>, // kind:pubkey:d-tag of the addressable event being zapped
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub secret_key: Option<String>, // hex or nsec to sign with, None sends an anonymous zap
+}
+
+/// Result of `LightningNode::zap`
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZapResponse {
+    pub zap_request: String,   // the signed kind 9734 event JSON, the receipt's description
+    pub zapper_pubkey: String, // the service's nostrPubkey, the one that signs the kind 9735 receipt
+    pub payment: PayResponse,
+}
+
+/// Fee the node expects to pay for an invoice, see `LightningNode::estimate_payment_fee`.
+/// Every backend quotes a single fee for now, so this is a point estimate with min == max.
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PaymentFeeEstimate {
+    pub min_fee_msats: i64,
+    pub max_fee_msats: i64,
+    pub route_found: bool, // false when no route was found, the fees are 0 then
+}
+
+/// An outgoing payment as the node reports it right now, see `LightningNode::lookup_payment`.
+/// Use it to find out how a `pay_invoice` that timed out or errored actually ended.
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OutgoingPayment {
+    pub payment_hash: String,
+    pub status: TransactionStatus, // Pending while in flight, then Settled or Failed
+    pub amount_msats: i64,         // excludes fees
+    pub fee_msats: i64,
+    pub preimage: String,               // empty until settled, some backends never expose it
+    pub failure_reason: Option<String>, // set when Failed and the backend says why
+    pub invoice: String,
+    pub created_at: i64,
+    pub settled_at: i64,
+}
+
+impl OutgoingPayment {
+    pub fn from_transaction(txn: Transaction, failure_reason: Option<String>) -> Self {
+        let failed = txn.status == TransactionStatus::Failed;
+        Self {
+            payment_hash: txn.payment_hash,
+            status: txn.status,
+            amount_msats: txn.amount_msats,
+            fee_msats: txn.fees_paid,
+            preimage: txn.preimage,
+            failure_reason: failure_reason.filter(|reason| failed && !reason.is_empty()),
+            invoice: txn.invoice,
+            created_at: txn.created_at,
+            settled_at: txn.settled_at,
+        }
+    }
 }
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PayKeysendResponse {
-    pub fee: i64,
+    pub payment_hash: String,
+    pub preimage: String,
+    pub fee_msats: i64,
 }
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
@@ -289,6 +590,31 @@ pub struct PaymentFailedEventProperties {
     pub reason: String,
 }
 
+/// What a node can do through LNI, so callers can hide unsupported actions up front.
+/// LND and CLN narrow this down from the macaroon or rune, NWC from the methods the
+/// wallet advertises in `get_info`.
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeCapabilities {
+    pub bolt11_receive: bool,
+    pub bolt11_send: bool,
+    pub bolt12_receive: bool,
+    pub bolt12_send: bool,
+    pub keysend: bool,
+    pub onchain_receive: bool,
+    pub onchain_send: bool,
+    pub onchain_balance: bool, // also covers list_onchain_transactions
+    pub hold_invoices: bool,
+    pub description_hash_invoices: bool,
+    pub zero_amount_invoices: bool,
+    pub lookup_invoice: bool,
+    pub list_transactions: bool,
+    pub channel_management: bool,
+    pub native_event_streaming: bool, // invoice events are pushed instead of polled
+    pub fiat: bool,                   // the account holds fiat balances
+}
+
 pub const DEFAULT_INVOICE_EXPIRY: i64 = 86400;
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
@@ -303,6 +629,19 @@ pub struct ListTransactionsParams {
     pub created_after: Option<i64>,   // unix seconds
     #[cfg_attr(feature = "uniffi", uniffi(default = None))]
     pub created_before: Option<i64>,  // unix seconds
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub cursor: Option<String>, // next_cursor of the previous page, see list_transactions_page
+}
+
+/// A page of transactions, newest first. Pass `next_cursor` back in
+/// `ListTransactionsParams::cursor` for the next page; it is None after the last one.
+/// Pages don't shift when new transactions arrive in between.
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionPage {
+    pub transactions: Vec<Transaction>,
+    pub next_cursor: Option<String>,
 }
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
@@ -335,7 +674,7 @@ pub struct CreateInvoiceParams {
     #[cfg_attr(feature = "uniffi", uniffi(default = None))]
     pub description: Option<String>,
     #[cfg_attr(feature = "uniffi", uniffi(default = None))]
-    pub description_hash: Option<String>,
+    pub description_hash: Option<String>, // hex, CLN also needs the description it is the hash of
     #[cfg_attr(feature = "uniffi", uniffi(default = None))]
     pub expiry: Option<i64>,
     #[cfg_attr(feature = "uniffi", uniffi(default = None))]
@@ -407,12 +746,13 @@ impl Default for CreateOfferParams {
 
 #[cfg_attr(feature = "napi_rs", napi(object))]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PayInvoiceParams {
+    // A backend applies every field that is set or fails with ApiError::UnsupportedParameter
     pub invoice: String,
     pub fee_limit_msat: Option<i64>, // mutually exclusive with fee_limit_percentage (only set one or the other)
     pub fee_limit_percentage: Option<f64>, // mutually exclusive with fee_limit_msat
-    pub timeout_seconds: Option<i64>,
+    pub timeout_seconds: Option<i64>, // how long the node keeps trying, unset uses the backend default
     pub amount_msats: Option<i64>, // used the specify the amount for zero amount invoices
 
     pub max_parts: Option<i64>, // The maximum number of partial payments that may be use to complete the full amount.
@@ -420,6 +760,13 @@ pub struct PayInvoiceParams {
     pub last_hop_pubkey: Option<String>,
     pub allow_self_payment: Option<bool>, // circular payments
     pub is_amp: Option<bool>,             // enable atomic multipath payments
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub custom_records: Option<Vec<TLVRecord>>, // extra TLVs for the final hop, only LND sends them with invoices
+    // false returns once the payment is dispatched with status Pending, follow it with
+    // lookup_payment or on_payment_events. Waits for the final result by default, a payment
+    // still in flight when the node stops waiting is ApiError::PaymentInFlight.
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub wait: Option<bool>,
 }
 impl Default for PayInvoiceParams {
     fn default() -> Self {
@@ -427,7 +774,7 @@ impl Default for PayInvoiceParams {
             invoice: "".to_string(),
             fee_limit_msat: None,
             fee_limit_percentage: None, // 0.2% is a sensible default
-            timeout_seconds: Some(60),  // default to 60 seconds timeout
+            timeout_seconds: None,      // LND and CLN give up after 60 seconds
             amount_msats: None,
 
             max_parts: None,
@@ -435,6 +782,8 @@ impl Default for PayInvoiceParams {
             last_hop_pubkey: None,
             allow_self_payment: None, // allow self (circurlar) payments
             is_amp: None,
+            custom_records: None,
+            wait: None,
         }
     }
 }
@@ -468,3 +817,92 @@ impl Default for OnInvoiceEventParams {
         }
     }
 }
+
+// Same shape as OnInvoiceEventCallback, for a payment we sent. Lookup errors are reported
+// as pending(None): the payment may still go through, so they are no reason to pay again.
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait OnPaymentEventCallback: Send + Sync {
+    fn success(&self, payment: Option<OutgoingPayment>);
+    fn pending(&self, payment: Option<OutgoingPayment>);
+    fn failure(&self, payment: Option<OutgoingPayment>);
+}
+
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[derive(Debug, Clone)]
+pub struct OnPaymentEventParams {
+    pub payment_hash: String,
+    pub polling_delay_sec: i64, // only used when the backend can't push updates
+    pub max_polling_sec: i64,
+}
+impl Default for OnPaymentEventParams {
+    fn default() -> Self {
+        Self {
+            payment_hash: "".to_string(),
+            polling_delay_sec: 5,
+            max_polling_sec: 60,
+        }
+    }
+}
+
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[derive(Debug, Clone)]
+pub struct OnAccountEventParams {
+    pub polling_delay_sec: i64, // only used when the backend can't push events
+    pub max_polling_sec: i64,
+}
+impl Default for OnAccountEventParams {
+    fn default() -> Self {
+        Self {
+            polling_delay_sec: 5,
+            max_polling_sec: 60,
+        }
+    }
+}
+
+#[cfg_attr(feature = "napi_rs", napi(string_enum))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(feature = "napi_rs"), derive(Clone, Copy))]
+pub enum AccountEventType {
+    InvoiceSettled, // incoming payment received
+    PaymentSent,    // outgoing payment completed
+    PaymentFailed,  // outgoing payment failed for good
+}
+
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountEvent {
+    pub event_type: AccountEventType,
+    pub transaction: Transaction,
+    // Opaque, store it once the event is handled and pass it as `resume_from` after a
+    // restart. Delivery is at-least-once, events right at the index can repeat.
+    pub resume_index: String,
+}
+
+// Every event on the node, see `LightningNode::subscribe_all`
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait SubscribeAllCallback: Send + Sync {
+    fn event(&self, event: AccountEvent);
+    // The feed gave up, e.g. the credentials lost access. Transient errors are retried.
+    fn error(&self, reason: String);
+}
+
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[cfg_attr(feature = "napi_rs", napi(object))]
+#[derive(Debug, Clone)]
+pub struct SubscribeAllParams {
+    #[cfg_attr(feature = "uniffi", uniffi(default = None))]
+    pub resume_from: Option<String>, // resume_index of the last handled event, None starts from now
+    pub polling_delay_sec: i64,      // only used when the backend can't push events
+}
+impl Default for SubscribeAllParams {
+    fn default() -> Self {
+        Self {
+            resume_from: None,
+            polling_delay_sec: 5,
+        }
+    }
+}
@@ -1,6 +1,13 @@
-use lightning_invoice::Bolt11Invoice;
+use crate::{
+    ApiError, DecodedPaymentRequest, InvoiceType, ListTransactionsParams, OnPaymentEventCallback, OnPaymentEventParams,
+    OutgoingPayment, PayInvoiceParams, PayInvoiceResponse, PaymentFeeEstimate, RouteHint, RouteHintHop, TLVRecord,
+    Transaction, TransactionPage, TransactionStatus, KEYSEND_PREIMAGE_TLV_TYPE,
+};
+use lightning_invoice::{Bolt11Invoice, Bolt11InvoiceDescriptionRef};
 use std::error::Error;
+use std::future::Future;
 use std::str::FromStr;
+use std::time::Duration;
 
 pub fn calculate_fee_msats(
     bolt11: &str,
@@ -34,3 +41,891 @@ pub fn calculate_fee_msats(
 
     Ok(fee_msats)
 }
+
+/// Decode a payment request locally, without asking the backend.
+/// Accepts an optional `lightning:` prefix and any case.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn decode_payment_request(input: String) -> Result<DecodedPaymentRequest, ApiError> {
+    let trimmed = input.trim();
+    let lower = trimmed.to_lowercase();
+    let request = lower.strip_prefix("lightning:").unwrap_or(&lower);
+
+    if crate::bolt12::is_bolt12(request) {
+        return crate::bolt12::decode(request);
+    }
+    if request.starts_with("ln") {
+        return decode_bolt11(request);
+    }
+    Err(ApiError::InvalidInput(format!(
+        "Unrecognized payment request: {}",
+        trimmed
+    )))
+}
+
+fn decode_bolt11(invoice_str: &str) -> Result<DecodedPaymentRequest, ApiError> {
+    let invoice = Bolt11Invoice::from_str(invoice_str)
+        .map_err(|e| ApiError::InvalidInput(format!("Failed to parse BOLT11 invoice: {}", e)))?;
+
+    let (description, description_hash) = match invoice.description() {
+        Bolt11InvoiceDescriptionRef::Direct(d) => (Some(d.to_string()), None),
+        Bolt11InvoiceDescriptionRef::Hash(h) => (None, Some(format!("{:x}", h.0))),
+    };
+
+    let route_hints = invoice
+        .route_hints()
+        .into_iter()
+        .map(|hint| RouteHint {
+            hops: hint
+                .0
+                .into_iter()
+                .map(|hop| RouteHintHop {
+                    pubkey: hop.src_node_id.to_string(),
+                    short_channel_id: format_short_channel_id(hop.short_channel_id),
+                    fee_base_msat: hop.fees.base_msat as i64,
+                    fee_proportional_millionths: hop.fees.proportional_millionths as i64,
+                    cltv_expiry_delta: hop.cltv_expiry_delta as i64,
+                    htlc_minimum_msat: hop.htlc_minimum_msat.map(|m| m as i64),
+                    htlc_maximum_msat: hop.htlc_maximum_msat.map(|m| m as i64),
+                })
+                .collect(),
+        })
+        .collect();
+
+    // le_flags is little endian, byte 0 holds bits 0-7
+    let features = invoice
+        .features()
+        .map(|f| {
+            f.le_flags()
+                .iter()
+                .enumerate()
+                .flat_map(|(i, byte)| {
+                    (0..8)
+                        .filter(move |bit| byte & (1 << bit) != 0)
+                        .map(move |bit| (i * 8 + bit) as i64)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let created_at = invoice.duration_since_epoch().as_secs() as i64;
+    let expiry = invoice.expiry_time().as_secs() as i64;
+
+    Ok(DecodedPaymentRequest {
+        invoice_type: InvoiceType::Bolt11,
+        invoice: invoice_str.to_string(),
+        network: invoice.network().to_string(),
+        amount_msats: invoice.amount_milli_satoshis().map(|a| a as i64),
+        payment_hash: format!("{:x}", invoice.payment_hash()),
+        payment_secret: Some(hex::encode(invoice.payment_secret().0)),
+        description,
+        description_hash,
+        payee_pubkey: invoice.get_payee_pub_key().to_string(),
+        created_at,
+        expiry,
+        expires_at: created_at + expiry,
+        min_final_cltv_expiry_delta: invoice.min_final_cltv_expiry_delta() as i64,
+        route_hints,
+        features,
+        payer_note: None,
+        offer: None,
+    })
+}
+
+// Short channel ids are shown as block x tx index x output index
+pub(crate) fn format_short_channel_id(scid: u64) -> String {
+    format!("{}x{}x{}", scid >> 40, (scid >> 16) & 0xFF_FFFF, scid & 0xFFFF)
+}
+
+/// Check custom TLV records before sending them: types must be in the custom range
+/// and values hex-encoded.
+pub(crate) fn validate_tlv_records(records: &[TLVRecord]) -> Result<(), ApiError> {
+    for record in records {
+        if record.type_ < 65536 || record.type_ == KEYSEND_PREIMAGE_TLV_TYPE {
+            return Err(ApiError::InvalidInput(format!(
+                "TLV type {} is reserved, custom records must use types >= 65536",
+                record.type_
+            )));
+        }
+        if hex::decode(&record.value).is_err() {
+            return Err(ApiError::InvalidInput(format!(
+                "TLV record {} value is not valid hex",
+                record.type_
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Interleave incoming and outgoing transactions newest first, then apply `from` and
+/// `limit` to the merged list. A `limit` of 0 returns everything after `from`.
+pub(crate) fn paginate_transactions(mut transactions: Vec<Transaction>, from: i64, limit: i64) -> Vec<Transaction> {
+    transactions.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+    let rest = transactions.into_iter().skip(from.max(0) as usize);
+    if limit > 0 {
+        rest.take(limit as usize).collect()
+    } else {
+        rest.collect()
+    }
+}
+
+/// Cursors are opaque to callers, each backend serializes its own position into one
+pub(crate) fn encode_cursor<T: serde::Serialize>(position: &T) -> String {
+    let json = serde_json::to_vec(position).unwrap_or_default();
+    base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+}
+
+pub(crate) fn decode_cursor<T: serde::de::DeserializeOwned>(cursor: &str) -> Result<T, ApiError> {
+    base64::decode_config(cursor.trim(), base64::URL_SAFE_NO_PAD)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| ApiError::InvalidInput("Invalid or foreign transaction cursor".to_string()))
+}
+
+// Cursor pages need a limit, otherwise there is nothing to page
+pub(crate) fn page_limit(limit: i64) -> Result<usize, ApiError> {
+    if limit <= 0 {
+        return Err(ApiError::InvalidInput(
+            "limit must be greater than 0 when paging with a cursor".to_string(),
+        ));
+    }
+    Ok(limit as usize)
+}
+
+/// Merge lists that are each already newest first, taking at most `limit` in total.
+/// Unlike a sort this never reorders within a list, so each list's position only moves
+/// past what was returned. Returns the page and how many were taken from each list.
+pub(crate) fn merge_newest_first(lists: Vec<Vec<Transaction>>, limit: usize) -> (Vec<Transaction>, Vec<usize>) {
+    let mut taken = vec![0; lists.len()];
+    let mut iters: Vec<_> = lists.into_iter().map(|list| list.into_iter().peekable()).collect();
+    let mut page = vec![];
+    while page.len() < limit {
+        let newest = iters
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, iter)| iter.peek().map(|txn| (i, txn.created_at)))
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)));
+        let Some((i, _)) = newest else { break };
+        if let Some(txn) = iters[i].next() {
+            page.push(txn);
+            taken[i] += 1;
+        }
+    }
+    (page, taken)
+}
+
+/// Position for backends without a native cursor. The first page pins `until` so
+/// transactions arriving later can't shift the offsets of the pages after it.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub(crate) struct WindowCursor {
+    pub until: i64, // unix seconds
+    pub offset: i64,
+}
+
+impl WindowCursor {
+    pub fn from_params(cursor: Option<&str>) -> Result<WindowCursor, ApiError> {
+        match cursor {
+            Some(cursor) => decode_cursor(cursor),
+            None => Ok(WindowCursor {
+                until: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0),
+                offset: 0,
+            }),
+        }
+    }
+
+    /// The cursor after a page of `count`, or None when the window ran out
+    pub fn next(&self, count: usize, limit: usize) -> Option<String> {
+        if count < limit {
+            return None;
+        }
+        Some(encode_cursor(&WindowCursor {
+            until: self.until,
+            offset: self.offset + count as i64,
+        }))
+    }
+}
+
+/// One page of a window for backends that can only list everything at once
+pub(crate) fn window_page(
+    mut transactions: Vec<Transaction>,
+    window: &WindowCursor,
+    limit: usize,
+) -> Vec<Transaction> {
+    transactions.retain(|txn| txn.created_at <= window.until);
+    transactions.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+    transactions
+        .into_iter()
+        .skip(window.offset.max(0) as usize)
+        .take(limit)
+        .collect()
+}
+
+/// A page of `search` matches. The backend's unfiltered pages are read and filtered until
+/// `limit` matches are in hand or the listing runs out, each read asking only for as many
+/// as are still missing so the page never overshoots its cursor.
+pub(crate) async fn search_page<F, Fut>(
+    params: ListTransactionsParams,
+    fetch_page: F,
+) -> Result<TransactionPage, ApiError>
+where
+    F: Fn(ListTransactionsParams) -> Fut,
+    Fut: Future<Output = Result<TransactionPage, ApiError>>,
+{
+    let Some(search) = params.search.clone() else {
+        return fetch_page(params).await;
+    };
+    let limit = page_limit(params.limit)?;
+    let mut transactions = vec![];
+    let mut cursor = params.cursor.clone();
+    loop {
+        let page = fetch_page(ListTransactionsParams {
+            from: params.from,
+            limit: (limit - transactions.len()) as i64,
+            payment_hash: params.payment_hash.clone(),
+            search: None,
+            created_after: params.created_after,
+            created_before: params.created_before,
+            cursor,
+        })
+        .await?;
+        transactions.extend(
+            page.transactions
+                .into_iter()
+                .filter(|txn| transaction_matches_search(txn, &search)),
+        );
+        cursor = page.next_cursor;
+        if cursor.is_none() || transactions.len() >= limit {
+            return Ok(TransactionPage {
+                transactions,
+                next_cursor: cursor,
+            });
+        }
+    }
+}
+
+// Same fields the CLN sql search looks at: payment hash, description and payer note
+pub(crate) fn transaction_matches_search(txn: &Transaction, search: &str) -> bool {
+    txn.payment_hash == search
+        || txn.description == search
+        || txn.payer_note.as_deref() == Some(search)
+}
+
+/// The `PayInvoiceParams` fields a backend applies, `check_pay_params` refuses the others
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PayParamSupport {
+    pub amount: bool,
+    pub fee_limit: bool,
+    pub timeout: bool,
+    pub max_parts: bool,
+    pub first_hop: bool,
+    pub last_hop: bool,
+    pub self_payment: bool,
+    pub amp: bool,
+    pub custom_records: bool,
+}
+
+impl PayParamSupport {
+    pub const NONE: Self = Self {
+        amount: false,
+        fee_limit: false,
+        timeout: false,
+        max_parts: false,
+        first_hop: false,
+        last_hop: false,
+        self_payment: false,
+        amp: false,
+        custom_records: false,
+    };
+}
+
+/// Validation every backend runs before paying an invoice. Returns the fee limit in msats,
+/// `fee_limit_percentage` is taken of the invoice amount (or `amount_msats` when it has none).
+pub(crate) fn check_pay_params(
+    params: &PayInvoiceParams,
+    backend: &str,
+    support: PayParamSupport,
+) -> Result<Option<i64>, ApiError> {
+    if params.fee_limit_msat.is_some() && params.fee_limit_percentage.is_some() {
+        return Err(ApiError::InvalidInput(
+            "Set either fee_limit_msat or fee_limit_percentage, not both".to_string(),
+        ));
+    }
+
+    // flags only count when they ask for something beyond the default
+    let requested = [
+        ("amount_msats", params.amount_msats.is_some(), support.amount),
+        ("fee_limit_msat", params.fee_limit_msat.is_some(), support.fee_limit),
+        ("fee_limit_percentage", params.fee_limit_percentage.is_some(), support.fee_limit),
+        ("timeout_seconds", params.timeout_seconds.is_some(), support.timeout),
+        ("max_parts", params.max_parts.is_some(), support.max_parts),
+        ("first_hop_pubkey", params.first_hop_pubkey.is_some(), support.first_hop),
+        ("last_hop_pubkey", params.last_hop_pubkey.is_some(), support.last_hop),
+        ("allow_self_payment", params.allow_self_payment == Some(true), support.self_payment),
+        ("is_amp", params.is_amp == Some(true), support.amp),
+        ("custom_records", params.custom_records.as_ref().is_some_and(|r| !r.is_empty()), support.custom_records),
+    ];
+    if let Some((parameter, _, _)) = requested.iter().find(|(_, set, supported)| *set && !supported) {
+        return Err(ApiError::UnsupportedParameter {
+            parameter: parameter.to_string(),
+            reason: format!("{} can't apply {} to invoice payments", backend, parameter),
+        });
+    }
+
+    if params.amount_msats.is_some_and(|amount| amount <= 0) {
+        return Err(ApiError::InvalidInput("amount_msats must be positive".to_string()));
+    }
+    if params.timeout_seconds.is_some_and(|timeout| timeout <= 0) {
+        return Err(ApiError::InvalidInput("timeout_seconds must be positive".to_string()));
+    }
+    if params.max_parts.is_some_and(|parts| parts < 1) {
+        return Err(ApiError::InvalidInput("max_parts must be at least 1".to_string()));
+    }
+    for pubkey in [&params.first_hop_pubkey, &params.last_hop_pubkey].into_iter().flatten() {
+        if hex::decode(pubkey).map_or(true, |bytes| bytes.len() != 33) {
+            return Err(ApiError::InvalidInput(format!("Invalid node pubkey: {}", pubkey)));
+        }
+    }
+
+    match (params.fee_limit_msat, params.fee_limit_percentage) {
+        (Some(fee_limit), _) if fee_limit < 0 => Err(ApiError::InvalidInput(
+            "fee_limit_msat can't be negative".to_string(),
+        )),
+        (Some(fee_limit), _) => Ok(Some(fee_limit)),
+        (None, Some(percentage)) if !percentage.is_finite() || percentage < 0.0 => Err(
+            ApiError::InvalidInput("fee_limit_percentage must be a positive number".to_string()),
+        ),
+        (None, Some(percentage)) => calculate_fee_msats(
+            &params.invoice,
+            percentage,
+            params.amount_msats.map(|amount| amount as u64),
+        )
+        .map(|fee| Some(fee as i64))
+        .map_err(|e| ApiError::InvalidInput(e.to_string())),
+        (None, None) => Ok(None),
+    }
+}
+
+/// For backends that pick the on-chain fee themselves, a fee rate the caller set is refused
+/// rather than dropped
+pub(crate) fn refuse_fee_rate(sat_per_vbyte: Option<i64>, backend: &str) -> Result<(), ApiError> {
+    match sat_per_vbyte {
+        Some(_) => Err(ApiError::UnsupportedParameter {
+            parameter: "sat_per_vbyte".to_string(),
+            reason: format!("{} picks the fee for on-chain sends", backend),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The 32 bytes a hex `description_hash` from `CreateInvoiceParams` stands for
+pub(crate) fn description_hash_bytes(description_hash: &str) -> Result<Vec<u8>, ApiError> {
+    hex::decode(description_hash)
+        .ok()
+        .filter(|hash| hash.len() == 32)
+        .ok_or_else(|| ApiError::InvalidInput(format!("Invalid description_hash, expected 32 hex bytes: {}", description_hash)))
+}
+
+/// The invoice and the amount a fee estimate is for, `amount_msats` only counts when the
+/// invoice has no amount of its own
+pub(crate) fn estimate_target(
+    invoice: &str,
+    amount_msats: Option<i64>,
+) -> Result<(DecodedPaymentRequest, i64), ApiError> {
+    let decoded = decode_payment_request(invoice.to_string())?;
+    if !matches!(decoded.invoice_type, InvoiceType::Bolt11) {
+        return Err(ApiError::InvalidInput("Fee estimates need a BOLT11 invoice".to_string()));
+    }
+    let amount = decoded.amount_msats.or(amount_msats).filter(|amount| *amount > 0).ok_or_else(|| {
+        ApiError::InvalidInput("Zero amount invoice requires amount_msats".to_string())
+    })?;
+    Ok((decoded, amount))
+}
+
+/// No route is an answer for a fee estimate, not an error: a zero point estimate with
+/// `route_found` false
+pub(crate) fn no_route_estimate(
+    estimate: Result<PaymentFeeEstimate, ApiError>,
+) -> Result<PaymentFeeEstimate, ApiError> {
+    match estimate {
+        Err(ApiError::NoRoute { .. }) => Ok(PaymentFeeEstimate {
+            min_fee_msats: 0,
+            max_fee_msats: 0,
+            route_found: false,
+        }),
+        estimate => estimate,
+    }
+}
+
+/// Payment watcher for backends that can't push payment updates: looks the payment up
+/// every `polling_delay_sec` until it is final or `max_polling_sec` passed. An in-flight payment
+/// is never reported as failed, the last update it gets is `pending`.
+pub(crate) async fn poll_payment_events<L, Fut>(
+    mut lookup: L,
+    params: OnPaymentEventParams,
+    callback: &dyn OnPaymentEventCallback,
+) where
+    L: FnMut() -> Fut,
+    Fut: Future<Output = Result<OutgoingPayment, ApiError>>,
+{
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(params.max_polling_sec.max(0) as u64);
+    let delay = Duration::from_secs(params.polling_delay_sec.max(1) as u64);
+    loop {
+        match lookup().await {
+            Ok(payment) if payment.status == TransactionStatus::Settled => return callback.success(Some(payment)),
+            Ok(payment) if payment.status.is_final() => return callback.failure(Some(payment)),
+            Ok(payment) => callback.pending(Some(payment)),
+            // not known yet or a transient error, neither means it failed
+            Err(_) => callback.pending(None),
+        }
+        if tokio::time::Instant::now() + delay > deadline {
+            return;
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// `pay_invoice` with `wait: false` for backends whose pay call only returns once the payment
+/// is final. The call keeps running on the shared runtime, this returns as soon as `lookup`
+/// sees the payment, or with the call's own result if that comes first. When neither shows up
+/// within `timeout_seconds` (60 if unset) the payment is reported pending, it may still go out.
+pub(crate) async fn dispatch_payment<P, L, Fut>(
+    invoice: &str,
+    timeout_seconds: Option<i64>,
+    pay: P,
+    mut lookup: L,
+) -> Result<PayInvoiceResponse, ApiError>
+where
+    P: Future<Output = Result<PayInvoiceResponse, ApiError>> + Send + 'static,
+    L: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<OutgoingPayment, ApiError>>,
+{
+    let payment_hash = decode_payment_request(invoice.to_string())?.payment_hash;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_seconds.unwrap_or(60).max(0) as u64);
+    let mut pay = crate::TOKIO_RUNTIME.spawn(pay);
+    loop {
+        tokio::select! {
+            result = &mut pay => {
+                return result.unwrap_or_else(|e| Err(ApiError::Api {
+                    reason: format!("Payment task failed: {}", e),
+                }));
+            }
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+        }
+        // a failed payment with this hash can be an earlier attempt, the call reports on ours
+        if let Ok(payment) = lookup(payment_hash.clone()).await {
+            if payment.status != TransactionStatus::Failed {
+                return Ok(PayInvoiceResponse {
+                    payment_hash: payment.payment_hash,
+                    preimage: payment.preimage,
+                    fee_msats: payment.fee_msats,
+                    status: payment.status,
+                });
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Ok(PayInvoiceResponse {
+                payment_hash,
+                preimage: String::new(),
+                fee_msats: 0,
+                status: TransactionStatus::Pending,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BOLT 11 spec example: "Please send $3 for a cup of coffee to the same peer, within one minute"
+    const COFFEE_INVOICE: &str = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+
+    #[test]
+    fn test_decode_bolt11() {
+        let decoded = decode_payment_request(COFFEE_INVOICE.to_string()).unwrap();
+        assert!(matches!(decoded.invoice_type, InvoiceType::Bolt11));
+        assert_eq!(decoded.network, "bitcoin");
+        assert_eq!(decoded.amount_msats, Some(250_000_000));
+        assert_eq!(
+            decoded.payment_hash,
+            "0001020304050607080900010203040506070809000102030405060708090102"
+        );
+        assert_eq!(decoded.description.as_deref(), Some("1 cup coffee"));
+        assert_eq!(decoded.description_hash, None);
+        assert_eq!(
+            decoded.payee_pubkey,
+            "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad"
+        );
+        assert_eq!(decoded.created_at, 1496314658);
+        assert_eq!(decoded.expiry, 60);
+        assert_eq!(decoded.expires_at, 1496314658 + 60);
+        assert!(decoded.features.contains(&8)); // var_onion_optin required
+        assert!(decoded.features.contains(&14)); // payment_secret required
+    }
+
+    #[test]
+    fn test_decode_uppercase_with_prefix() {
+        let input = format!("lightning:{}", COFFEE_INVOICE.to_uppercase());
+        let decoded = decode_payment_request(input).unwrap();
+        assert_eq!(decoded.amount_msats, Some(250_000_000));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(matches!(
+            decode_payment_request("lnbc1invalid".to_string()),
+            Err(ApiError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            decode_payment_request("hello".to_string()),
+            Err(ApiError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_short_channel_id() {
+        // 700000x1234x1
+        let scid = (700000u64 << 40) | (1234u64 << 16) | 1;
+        assert_eq!(format_short_channel_id(scid), "700000x1234x1");
+    }
+
+    fn txn(payment_hash: &str, created_at: i64, direction: crate::Direction) -> Transaction {
+        Transaction {
+            type_: "".to_string(),
+            status: crate::TransactionStatus::Settled,
+            direction,
+            invoice: "".to_string(),
+            preimage: "".to_string(),
+            payment_hash: payment_hash.to_string(),
+            amount_msats: 1000,
+            fees_paid: 0,
+            created_at,
+            expires_at: 0,
+            settled_at: 0,
+            description: "".to_string(),
+            description_hash: "".to_string(),
+            payer_note: None,
+            external_id: None,
+        }
+    }
+
+    #[test]
+    fn test_paginate_transactions() {
+        use crate::Direction::{Incoming, Outgoing};
+        let merged = vec![
+            txn("in1", 100, Incoming),
+            txn("in2", 300, Incoming),
+            txn("out1", 200, Outgoing),
+            txn("out2", 400, Outgoing),
+        ];
+        let hashes = |txns: Vec<Transaction>| txns.into_iter().map(|t| t.payment_hash).collect::<Vec<_>>();
+
+        assert_eq!(hashes(paginate_transactions(merged.clone(), 0, 0)), vec!["out2", "in2", "out1", "in1"]);
+        assert_eq!(hashes(paginate_transactions(merged.clone(), 1, 2)), vec!["in2", "out1"]);
+        assert!(paginate_transactions(merged, 10, 5).is_empty());
+    }
+
+    #[test]
+    fn test_merge_newest_first() {
+        use crate::Direction::{Incoming, Outgoing};
+        let incoming = vec![txn("in2", 300, Incoming), txn("in1", 100, Incoming)];
+        let outgoing = vec![txn("out2", 400, Outgoing), txn("out1", 200, Outgoing)];
+        let (page, taken) = merge_newest_first(vec![incoming.clone(), outgoing.clone()], 3);
+        let hashes: Vec<_> = page.into_iter().map(|t| t.payment_hash).collect();
+        assert_eq!(hashes, vec!["out2", "in2", "out1"]);
+        assert_eq!(taken, vec![1, 2]);
+
+        let (page, taken) = merge_newest_first(vec![incoming, vec![]], 10);
+        assert_eq!(page.len(), 2);
+        assert_eq!(taken, vec![2, 0]);
+    }
+
+    #[test]
+    fn test_window_cursor() {
+        use crate::Direction::Incoming;
+        let window = WindowCursor { until: 300, offset: 0 };
+        let cursor = window.next(2, 2).unwrap();
+        let next: WindowCursor = decode_cursor(&cursor).unwrap();
+        assert_eq!((next.until, next.offset), (300, 2));
+        assert!(window.next(1, 2).is_none());
+        assert!(decode_cursor::<WindowCursor>("not a cursor").is_err());
+
+        // a transaction arriving after the first page doesn't shift the second one
+        let txns = vec![
+            txn("new", 400, Incoming),
+            txn("c", 300, Incoming),
+            txn("b", 200, Incoming),
+            txn("a", 100, Incoming),
+        ];
+        let page: Vec<_> = window_page(txns, &next, 2).into_iter().map(|t| t.payment_hash).collect();
+        assert_eq!(page, vec!["a"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_page() {
+        use crate::Direction::Incoming;
+        // one match in every third transaction, listed a window at a time
+        let txns: Vec<_> = (0..9)
+            .map(|i| Transaction {
+                description: if i % 3 == 0 { "coffee".to_string() } else { "".to_string() },
+                ..txn(&format!("h{}", i), 900 - i as i64 * 100, Incoming)
+            })
+            .collect();
+        let fetch_page = |params: ListTransactionsParams| {
+            let txns = txns.clone();
+            async move {
+                let window = WindowCursor::from_params(params.cursor.as_deref())?;
+                let window = WindowCursor { until: 1000, ..window };
+                let limit = page_limit(params.limit)?;
+                let page = window_page(txns, &window, limit);
+                Ok(TransactionPage {
+                    next_cursor: window.next(page.len(), limit),
+                    transactions: page,
+                })
+            }
+        };
+        let params = |cursor| ListTransactionsParams {
+            from: 0,
+            limit: 2,
+            payment_hash: None,
+            search: Some("coffee".to_string()),
+            created_after: None,
+            created_before: None,
+            cursor,
+        };
+        let hashes = |page: &TransactionPage| page.transactions.iter().map(|t| t.payment_hash.clone()).collect::<Vec<_>>();
+
+        let first = search_page(params(None), fetch_page).await.unwrap();
+        assert_eq!(hashes(&first), vec!["h0", "h3"]);
+        let second = search_page(params(first.next_cursor), fetch_page).await.unwrap();
+        assert_eq!(hashes(&second), vec!["h6"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_validate_tlv_records() {
+        let record = |type_: i64, value: &str| TLVRecord {
+            type_,
+            value: value.to_string(),
+        };
+        assert!(validate_tlv_records(&[record(7629169, "deadbeef")]).is_ok());
+        assert!(validate_tlv_records(&[record(34349334, "not hex")]).is_err());
+        assert!(validate_tlv_records(&[record(3, "00")]).is_err());
+        assert!(validate_tlv_records(&[record(KEYSEND_PREIMAGE_TLV_TYPE, "00")]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_poll_payment_events() {
+        use std::sync::Mutex;
+
+        struct Recorder(Mutex<Vec<String>>);
+        impl OnPaymentEventCallback for Recorder {
+            fn success(&self, payment: Option<OutgoingPayment>) {
+                let preimage = payment.map(|p| p.preimage).unwrap_or_default();
+                self.0.lock().unwrap().push(format!("success {}", preimage));
+            }
+            fn pending(&self, payment: Option<OutgoingPayment>) {
+                self.0.lock().unwrap().push(format!("pending {}", payment.is_some()));
+            }
+            fn failure(&self, _: Option<OutgoingPayment>) {
+                self.0.lock().unwrap().push("failure".to_string());
+            }
+        }
+
+        let params = OnPaymentEventParams {
+            payment_hash: "aa".repeat(32),
+            polling_delay_sec: 1,
+            max_polling_sec: 10,
+        };
+        // not found yet, in flight, then settled
+        let mut lookups = 0;
+        let events = Recorder(Mutex::new(vec![]));
+        poll_payment_events(
+            || {
+                lookups += 1;
+                let mut payment = txn("aa", 100, crate::Direction::Outgoing);
+                payment.status = if lookups < 3 { TransactionStatus::Pending } else { TransactionStatus::Settled };
+                payment.preimage = "bb".to_string();
+                let result = if lookups == 1 {
+                    Err(ApiError::Api { reason: "Payment not found".to_string() })
+                } else {
+                    Ok(OutgoingPayment::from_transaction(payment, None))
+                };
+                async move { result }
+            },
+            params.clone(),
+            &events,
+        )
+        .await;
+        assert_eq!(*events.0.lock().unwrap(), vec!["pending false", "pending true", "success bb"]);
+
+        // still in flight when the time is up
+        let events = Recorder(Mutex::new(vec![]));
+        let params = OnPaymentEventParams { max_polling_sec: 1, ..params };
+        poll_payment_events(
+            || {
+                let mut payment = txn("aa", 100, crate::Direction::Outgoing);
+                payment.status = TransactionStatus::Pending;
+                async move { Ok(OutgoingPayment::from_transaction(payment, None)) }
+            },
+            params,
+            &events,
+        )
+        .await;
+        assert_eq!(*events.0.lock().unwrap(), vec!["pending true"]);
+    }
+
+    #[test]
+    fn test_outgoing_payment_failure_reason() {
+        let mut payment = txn("aa", 100, crate::Direction::Outgoing);
+        let reason = Some("FAILURE_REASON_NO_ROUTE".to_string());
+        assert_eq!(OutgoingPayment::from_transaction(payment.clone(), reason.clone()).failure_reason, None);
+        payment.status = TransactionStatus::Failed;
+        assert_eq!(OutgoingPayment::from_transaction(payment, reason.clone()).failure_reason, reason);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_payment() {
+        // the pay call hangs on a hold invoice, an earlier failed attempt shows up before ours
+        let mut lookups = 0;
+        let response = dispatch_payment(COFFEE_INVOICE, None, std::future::pending(), |payment_hash| {
+            lookups += 1;
+            let mut payment = txn(&payment_hash, 100, crate::Direction::Outgoing);
+            payment.status = if lookups == 1 { TransactionStatus::Failed } else { TransactionStatus::Pending };
+            async move { Ok(OutgoingPayment::from_transaction(payment, None)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(lookups, 2);
+        assert_eq!(response.status, TransactionStatus::Pending);
+        assert_eq!(response.payment_hash, "0001020304050607080900010203040506070809000102030405060708090102");
+
+        // the call's own error wins when it fails before the payment shows up
+        let pay = async { Err(ApiError::NoRoute { reason: "no route".to_string() }) };
+        let result = dispatch_payment(COFFEE_INVOICE, None, pay, |_| async {
+            Err::<OutgoingPayment, _>(ApiError::Api { reason: "not found".to_string() })
+        })
+        .await;
+        assert!(matches!(result, Err(ApiError::NoRoute { .. })));
+
+        // neither the call nor the lookup gets anywhere before the timeout
+        let response = dispatch_payment(COFFEE_INVOICE, Some(2), std::future::pending(), |_| async {
+            Err::<OutgoingPayment, _>(ApiError::Api { reason: "not found".to_string() })
+        })
+        .await
+        .unwrap();
+        assert_eq!(response.status, TransactionStatus::Pending);
+        assert_eq!(response.payment_hash, "0001020304050607080900010203040506070809000102030405060708090102");
+    }
+
+    #[test]
+    fn test_check_pay_params() {
+        let params = |fee_limit_msat, fee_limit_percentage| PayInvoiceParams {
+            invoice: COFFEE_INVOICE.to_string(),
+            fee_limit_msat,
+            fee_limit_percentage,
+            ..Default::default()
+        };
+        let all = PayParamSupport { fee_limit: true, ..PayParamSupport::NONE };
+        assert_eq!(check_pay_params(&params(None, None), "test", all).unwrap(), None);
+        assert_eq!(check_pay_params(&params(Some(5000), None), "test", all).unwrap(), Some(5000));
+        // 1% of the invoice's 250000 sats
+        assert_eq!(check_pay_params(&params(None, Some(1.0)), "test", all).unwrap(), Some(2_500_000));
+        for invalid in [params(Some(5000), Some(1.0)), params(Some(-1), None), params(None, Some(f64::NAN))] {
+            assert!(matches!(check_pay_params(&invalid, "test", all), Err(ApiError::InvalidInput(_))));
+        }
+        let bad_hop = PayInvoiceParams { first_hop_pubkey: Some("02abcd".to_string()), ..params(None, None) };
+        let hops = PayParamSupport { first_hop: true, ..PayParamSupport::NONE };
+        assert!(matches!(check_pay_params(&bad_hop, "test", hops), Err(ApiError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_description_hash_bytes() {
+        assert_eq!(description_hash_bytes(&"ab".repeat(32)).unwrap(), vec![0xab; 32]);
+        assert!(matches!(description_hash_bytes("abcd"), Err(ApiError::InvalidInput(_))));
+        assert!(matches!(description_hash_bytes(&"zz".repeat(32)), Err(ApiError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_refuse_fee_rate() {
+        assert!(refuse_fee_rate(None, "test").is_ok());
+        assert!(matches!(
+            refuse_fee_rate(Some(2), "test"),
+            Err(ApiError::UnsupportedParameter { parameter, .. }) if parameter == "sat_per_vbyte"
+        ));
+    }
+
+    #[test]
+    fn test_estimate_target() {
+        // the invoice's own amount wins over amount_msats
+        let (decoded, amount) = estimate_target(COFFEE_INVOICE, Some(1000)).unwrap();
+        assert_eq!(amount, 250_000_000);
+        assert_eq!(decoded.payee_pubkey, "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad");
+        assert!(matches!(estimate_target("lnbc1invalid", None), Err(ApiError::InvalidInput(_))));
+
+        let no_route = no_route_estimate(Err(ApiError::NoRoute { reason: "no path".to_string() })).unwrap();
+        assert_eq!(no_route, PaymentFeeEstimate { min_fee_msats: 0, max_fee_msats: 0, route_found: false });
+        assert!(no_route_estimate(Err(ApiError::Unauthorized { reason: "bad key".to_string() })).is_err());
+    }
+
+    // Every backend either takes a PayInvoiceParams field or refuses it up front. The nodes
+    // point nowhere, so a field that got past validation fails with a network error instead.
+    #[tokio::test]
+    async fn test_pay_params_conformance() {
+        use crate::LightningNode;
+        type Backend = (&'static str, PayParamSupport, Option<Box<dyn LightningNode>>);
+
+        let url = "http://127.0.0.1:9".to_string();
+        let backends: Vec<Backend> = vec![
+            ("LND", crate::lnd::api::PAY_PARAMS, Some(Box::new(crate::lnd::LndNode::new(crate::lnd::LndConfig { url: url.clone(), ..Default::default() })))),
+            ("CLN", crate::cln::api::PAY_PARAMS, Some(Box::new(crate::cln::ClnNode::new(crate::cln::ClnConfig { url: url.clone(), ..Default::default() })))),
+            ("Phoenixd", crate::phoenixd::api::PAY_PARAMS, Some(Box::new(crate::phoenixd::PhoenixdNode::new(crate::phoenixd::PhoenixdConfig { url: url.clone(), ..Default::default() })))),
+            ("NWC", crate::nwc::api::PAY_PARAMS, Some(Box::new(crate::nwc::NwcNode::new(crate::nwc::NwcConfig::default())))),
+            ("Blink", crate::blink::api::PAY_PARAMS, Some(Box::new(crate::blink::BlinkNode::new(crate::blink::BlinkConfig { base_url: Some(url.clone()), ..Default::default() })))),
+            ("Strike", crate::strike::api::PAY_PARAMS, Some(Box::new(crate::strike::StrikeNode::new(crate::strike::StrikeConfig { base_url: Some(url.clone()), ..Default::default() })))),
+            ("Speed", crate::speed::api::PAY_PARAMS, Some(Box::new(crate::speed::SpeedNode::new(crate::speed::SpeedConfig { base_url: Some(url.clone()), ..Default::default() })))),
+            // a Spark node needs a live SDK, so only its declared support is checked
+            ("Spark", crate::spark::api::PAY_PARAMS, None),
+        ];
+
+        let base = PayInvoiceParams { invoice: COFFEE_INVOICE.to_string(), ..Default::default() };
+        let pubkey = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad".to_string();
+        let fields = [
+            ("amount_msats", PayInvoiceParams { amount_msats: Some(1000), ..base.clone() }),
+            ("fee_limit_msat", PayInvoiceParams { fee_limit_msat: Some(1000), ..base.clone() }),
+            ("fee_limit_percentage", PayInvoiceParams { fee_limit_percentage: Some(1.0), ..base.clone() }),
+            ("timeout_seconds", PayInvoiceParams { timeout_seconds: Some(30), ..base.clone() }),
+            ("max_parts", PayInvoiceParams { max_parts: Some(2), ..base.clone() }),
+            ("first_hop_pubkey", PayInvoiceParams { first_hop_pubkey: Some(pubkey.clone()), ..base.clone() }),
+            ("last_hop_pubkey", PayInvoiceParams { last_hop_pubkey: Some(pubkey.clone()), ..base.clone() }),
+            ("allow_self_payment", PayInvoiceParams { allow_self_payment: Some(true), ..base.clone() }),
+            ("is_amp", PayInvoiceParams { is_amp: Some(true), ..base.clone() }),
+            ("custom_records", PayInvoiceParams {
+                custom_records: Some(vec![TLVRecord { type_: 696969, value: "01".to_string() }]),
+                ..base.clone()
+            }),
+        ];
+        let conflicting = PayInvoiceParams { fee_limit_msat: Some(1000), fee_limit_percentage: Some(1.0), ..base.clone() };
+
+        for (backend, support, node) in &backends {
+            assert!(check_pay_params(&base, backend, *support).is_ok(), "{} rejects default params", backend);
+            assert!(matches!(check_pay_params(&conflicting, backend, *support), Err(ApiError::InvalidInput(_))));
+            for (field, params) in &fields {
+                let checked = check_pay_params(params, backend, *support);
+                let refused = matches!(&checked, Err(ApiError::UnsupportedParameter { parameter, .. }) if parameter == field);
+                assert!(refused || checked.is_ok(), "{} {}: {:?}", backend, field, checked);
+
+                let Some(node) = node else { continue };
+                let paid = node.pay_invoice(params.clone()).await;
+                let node_refused = matches!(&paid, Err(ApiError::UnsupportedParameter { parameter, .. }) if parameter == field);
+                assert_eq!(node_refused, refused, "{} pay_invoice with {}: {:?}", backend, field, paid);
+            }
+            if let Some(node) = node {
+                let paid = node.pay_invoice(conflicting.clone()).await;
+                assert!(matches!(paid, Err(ApiError::InvalidInput(_))), "{}: {:?}", backend, paid);
+            }
+        }
+    }
+}
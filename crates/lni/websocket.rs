@@ -0,0 +1,82 @@
+//! Websocket plumbing for backends that push events (Phoenixd, Blink). Each backend
+//! runs its own event loop on top of this and reconnects a few times before giving
+//! up and polling instead.
+
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::ApiError;
+
+pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Consecutive failed connects before falling back to polling
+pub(crate) const MAX_RECONNECTS: u32 = 3;
+
+/// Wait before reconnect attempt `attempt` (1 based): 1s, 2s, 4s
+pub(crate) fn reconnect_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.saturating_sub(1).min(5))
+}
+
+/// http(s) base url to its ws(s) counterpart
+pub(crate) fn ws_url(http_url: &str) -> String {
+    if let Some(rest) = http_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = http_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        http_url.to_string()
+    }
+}
+
+/// Open a socket, `headers` are lowercase names such as "authorization"
+pub(crate) async fn connect(url: &str, headers: &[(&'static str, String)]) -> Result<WsStream, ApiError> {
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| ApiError::InvalidInput(format!("Invalid websocket url {}: {}", url, e)))?;
+    for (name, value) in headers {
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ApiError::InvalidInput(format!("Invalid {} header: {}", name, e)))?;
+        request.headers_mut().insert(HeaderName::from_static(name), value);
+    }
+    let (ws, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| ApiError::NetworkError(format!("Websocket connect to {} failed: {}", url, e)))?;
+    Ok(ws)
+}
+
+/// Next text frame, None once the socket is closed. Pings are answered by tungstenite.
+pub(crate) async fn next_text(ws: &mut WsStream) -> Result<Option<String>, ApiError> {
+    while let Some(message) = ws.next().await {
+        match message.map_err(|e| ApiError::NetworkError(format!("Websocket error: {}", e)))? {
+            Message::Text(text) => return Ok(Some(text.to_string())),
+            Message::Close(_) => return Ok(None),
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+pub(crate) async fn send_json(ws: &mut WsStream, value: &serde_json::Value) -> Result<(), ApiError> {
+    ws.send(Message::text(value.to_string()))
+        .await
+        .map_err(|e| ApiError::NetworkError(format!("Websocket send failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ws_url() {
+        assert_eq!(ws_url("https://api.blink.sv/graphql"), "wss://api.blink.sv/graphql");
+        assert_eq!(ws_url("http://127.0.0.1:9740"), "ws://127.0.0.1:9740");
+        assert_eq!(reconnect_delay(1), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(3), Duration::from_secs(4));
+    }
+}